@@ -1,80 +1,87 @@
 use crate::api::model::ApiWrapper;
 use crate::config;
+use crate::core::schema_watch;
+use crate::core::stats::{self, LOSSY_UTF8_RESPONSES};
 use crate::error::{AppError, AppResult};
 use crate::logging::{log, LogLevel};
-use bytes::Bytes;
-use reqwest::{header::HeaderValue, Client, Method, Response, StatusCode};
+use crate::model::common::LangCode;
+use bytes::{Bytes, BytesMut};
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    Client, Method, Response, StatusCode,
+};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use tokio::time::sleep;
 
+/// Request header carrying the wanted language; the API echoes the language it served.
+const LANGUAGE_HEADER: &str = "x-rpc-language";
+
 #[derive(Clone)]
 pub struct ApiClient {
     client: Client,
+    base_url: String,
 }
 
 impl ApiClient {
+    /// A client for the process-wide base URL (see `config::set_api_base_url`).
     pub fn new() -> AppResult<Self> {
+        Self::with_base_url(config::api_base_url())
+    }
+
+    pub fn with_base_url(base_url: &str) -> AppResult<Self> {
         let client = Client::builder()
             .timeout(Duration::from_secs(config::HTTP_TIMEOUT_SECONDS))
             .connect_timeout(Duration::from_secs(config::HTTP_CONNECT_TIMEOUT))
             .build()
             .map_err(AppError::from)?;
-        Ok(ApiClient { client })
+        Ok(ApiClient {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
     }
 
     pub async fn fetch<T>(
         &self,
         method: Method,
         endpoint_key: &'static str,
-        lang: &str,
+        lang: &LangCode,
         params: Option<&HashMap<String, String>>,
         payload: Option<&Value>,
     ) -> AppResult<T>
     where
         T: DeserializeOwned,
     {
-        let url = config::API_ENDPOINTS.get(endpoint_key).ok_or_else(|| {
-            AppError::ConfigError(format!("Invalid endpoint key: {}", endpoint_key))
-        })?;
-
-        let bytes = self
-            .fetch_internal(method, url, lang, params, payload, endpoint_key)
+        let (headers, bytes, wrapper) = self
+            .fetch_internal(method, endpoint_key, lang, params, payload, |bytes| {
+                parse_wrapper::<T>(bytes, endpoint_key, lang)
+            })
             .await?;
-
-        let wrapper: ApiWrapper<T> = serde_json::from_slice(&bytes).map_err(|e| {
-            let snippet_len = bytes.len().min(200);
-            let snippet = String::from_utf8_lossy(&bytes[..snippet_len]);
-            log(
-                LogLevel::Error,
-                &format!(
-                    "Fail parse API wrapper for {} [{}] Type {}: {}. Snippet: '{}'",
-                    endpoint_key,
-                    lang,
-                    std::any::type_name::<T>(),
-                    e,
-                    snippet
-                ),
-            );
-
-            AppError::from(e)
-        })?;
+        check_served_language(&headers, endpoint_key, lang)?;
 
         if wrapper.retcode != 0 {
             if wrapper.retcode == 100010 {
-                return Err(AppError::api_error(100010, "Not Found", endpoint_key, lang));
+                return Err(AppError::api_error(
+                    100010,
+                    "Not Found",
+                    endpoint_key,
+                    lang.as_str(),
+                ));
             }
 
             return Err(AppError::api_error(
                 wrapper.retcode,
                 wrapper.message,
                 endpoint_key,
-                lang,
+                lang.as_str(),
             ));
         }
 
+        schema_watch::observe(endpoint_key, lang, params, payload, &bytes);
+
         wrapper.data.ok_or_else(|| {
             AppError::response_invalid(
                 format!(
@@ -82,32 +89,41 @@ impl ApiClient {
                     endpoint_key
                 ),
                 endpoint_key,
-                lang,
+                lang.as_str(),
             )
         })
     }
 
-    async fn fetch_internal(
+    // `parse` runs on every successful body. A malformed body on a retryable method
+    // (see `retries_parse_failures`) is treated like a transient HTTP failure.
+    async fn fetch_internal<R>(
         &self,
         method: Method,
-        url: &str,
-        lang: &str,
+        endpoint_key: &'static str,
+        lang: &LangCode,
         params: Option<&HashMap<String, String>>,
         json_payload: Option<&Value>,
-        endpoint_key: &'static str,
-    ) -> AppResult<Bytes> {
+        parse: impl Fn(&Bytes) -> AppResult<R>,
+    ) -> AppResult<(HeaderMap, Bytes, R)> {
+        let url = config::endpoint_url(&self.base_url, endpoint_key).ok_or_else(|| {
+            AppError::ConfigError(format!("Invalid endpoint key: {}", endpoint_key))
+        })?;
         let mut last_error: Option<AppError> = None;
+        let retry_parse = retries_parse_failures(&method);
 
         for attempt in 0..=config::MAX_RETRIES {
             let mut headers = config::BASE_UA_HEADERS.clone();
             headers.insert(
-                "x-rpc-language",
-                HeaderValue::from_str(lang).map_err(|_| {
+                LANGUAGE_HEADER,
+                HeaderValue::from_str(lang.as_str()).map_err(|_| {
                     AppError::ConfigError(format!("Invalid lang code for header: {}", lang))
                 })?,
             );
 
-            let mut request_builder = self.client.request(method.clone(), url).headers(headers);
+            let mut request_builder = self
+                .client
+                .request(method.clone(), url.as_str())
+                .headers(headers);
             if let Some(p) = params {
                 request_builder = request_builder.query(p);
             }
@@ -129,19 +145,30 @@ impl ApiClient {
                     let status = resp.status();
 
                     if status.is_success() {
-                        return resp.bytes().await.map_err(|e| {
-                            log(
-                                LogLevel::Warning,
-                                &format!(
-                                    "{} - Error reading success response body: {}",
-                                    log_prefix, e
-                                ),
-                            );
-                            AppError::from(e)
-                        });
+                        let resp_headers = resp.headers().clone();
+                        let bytes =
+                            read_capped_body(resp, endpoint_key, lang.as_str(), &log_prefix)
+                                .await?;
+                        match parse(&bytes) {
+                            Ok(parsed) => return Ok((resp_headers, bytes, parsed)),
+                            Err(error @ AppError::SerdeParse(_)) if retry_parse => {
+                                log(
+                                    LogLevel::Warning,
+                                    &format!("{} Malformed response body; retrying.", log_prefix),
+                                );
+                                last_error = Some(error);
+                            }
+                            Err(error) => return Err(error),
+                        }
                     } else {
                         let error = self
-                            .handle_http_error(resp, status, endpoint_key, lang, &log_prefix)
+                            .handle_http_error(
+                                resp,
+                                status,
+                                endpoint_key,
+                                lang.as_str(),
+                                &log_prefix,
+                            )
                             .await;
                         let should_stop_retrying = matches!(
                             error,
@@ -250,3 +277,354 @@ impl ApiClient {
         AppError::api_error(retcode, error_message, endpoint_key, lang)
     }
 }
+
+/// Compares the language the API says it served against the requested one. A
+/// mismatch is counted and warned once per language and endpoint; with
+/// `--strict-language` it also fails the fetch.
+fn check_served_language(
+    headers: &HeaderMap,
+    endpoint_key: &'static str,
+    lang: &LangCode,
+) -> AppResult<()> {
+    let Some(served) = headers
+        .get(LANGUAGE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|served| !served.is_empty())
+    else {
+        return Ok(());
+    };
+    if served.eq_ignore_ascii_case(lang.as_str()) {
+        return Ok(());
+    }
+
+    if stats::record_language_mismatch(lang.as_str(), endpoint_key) {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Language mismatch [{}] {}: requested '{}', API served '{}'.",
+                lang, endpoint_key, lang, served
+            ),
+        );
+    }
+    if config::strict_language() {
+        return Err(AppError::LanguageMismatch {
+            served: served.to_string(),
+            endpoint: endpoint_key.to_string(),
+            lang: lang.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn parse_wrapper<T>(bytes: &[u8], endpoint_key: &str, lang: &LangCode) -> AppResult<ApiWrapper<T>>
+where
+    T: DeserializeOwned,
+{
+    match serde_json::from_slice(bytes) {
+        Ok(wrapper) => Ok(wrapper),
+        Err(e) => match parse_lossy_utf8(bytes, endpoint_key, lang) {
+            Some(result) => {
+                result.map_err(|e| wrapper_parse_error::<T>(e, bytes, endpoint_key, lang))
+            }
+            None => Err(wrapper_parse_error::<T>(e, bytes, endpoint_key, lang)),
+        },
+    }
+}
+
+// GET bodies can always be refetched; POST only when --retry-post-parse-failures is
+// set, since the list endpoint is idempotent but POST in general is not.
+fn retries_parse_failures(method: &Method) -> bool {
+    match *method {
+        Method::GET | Method::HEAD => true,
+        Method::POST => config::retry_post_parse_failures(),
+        _ => false,
+    }
+}
+
+fn wrapper_parse_error<T>(
+    e: serde_json::Error,
+    bytes: &[u8],
+    endpoint_key: &str,
+    lang: &LangCode,
+) -> AppError {
+    let snippet_len = bytes.len().min(200);
+    let snippet = String::from_utf8_lossy(&bytes[..snippet_len]);
+    log(
+        LogLevel::Error,
+        &format!(
+            "Fail parse API wrapper for {} [{}] Type {}: {}. Snippet: '{}'",
+            endpoint_key,
+            lang,
+            std::any::type_name::<T>(),
+            e,
+            snippet
+        ),
+    );
+
+    AppError::from(e)
+}
+
+// Byte offsets of every invalid UTF-8 sequence, i.e. where U+FFFD will be substituted.
+fn invalid_utf8_offsets(bytes: &[u8]) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut pos = 0;
+    for chunk in bytes.utf8_chunks() {
+        pos += chunk.valid().len();
+        if !chunk.invalid().is_empty() {
+            offsets.push(pos);
+            pos += chunk.invalid().len();
+        }
+    }
+    offsets
+}
+
+// Rejects bodies over the endpoint's cap up front when Content-Length says so, and
+// otherwise reads chunk by chunk so an unbounded body is cut off at the cap.
+async fn read_capped_body(
+    mut resp: Response,
+    endpoint_key: &str,
+    lang: &str,
+    log_prefix: &str,
+) -> AppResult<Bytes> {
+    let limit = config::max_response_bytes(endpoint_key);
+    let too_large = |bytes: u64| {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "{} - Response body of at least {} bytes exceeds the {} MB cap; aborting.",
+                log_prefix,
+                bytes,
+                limit / (1024 * 1024)
+            ),
+        );
+        AppError::ResponseTooLarge {
+            bytes,
+            limit,
+            endpoint: endpoint_key.to_string(),
+            lang: lang.to_string(),
+        }
+    };
+
+    let declared_len = resp.content_length();
+    if let Some(len) = declared_len.filter(|&len| len > limit) {
+        return Err(too_large(len));
+    }
+
+    let mut body = BytesMut::with_capacity(declared_len.unwrap_or(0) as usize);
+    loop {
+        let chunk = resp.chunk().await.map_err(|e| {
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "{} - Error reading success response body: {}",
+                    log_prefix, e
+                ),
+            );
+            AppError::from(e)
+        })?;
+        let Some(chunk) = chunk else {
+            return Ok(body.freeze());
+        };
+        let received = (body.len() + chunk.len()) as u64;
+        if received > limit {
+            return Err(too_large(received));
+        }
+        body.extend_from_slice(&chunk);
+    }
+}
+
+// Byte offsets of `\uXXXX` escapes for a UTF-16 surrogate without its partner.
+// serde_json rejects those even though the bytes are valid UTF-8.
+fn unpaired_surrogate_offsets(text: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let escape_at = |pos: usize| {
+        let hex = bytes.get(pos..pos + 6)?.strip_prefix(b"\\u")?;
+        u16::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()
+    };
+    let mut offsets = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos] != b'\\' {
+            pos += 1;
+            continue;
+        }
+        match escape_at(pos) {
+            Some(0xD800..=0xDBFF) if matches!(escape_at(pos + 6), Some(0xDC00..=0xDFFF)) => {
+                pos += 12;
+            }
+            Some(0xD800..=0xDFFF) => {
+                offsets.push(pos);
+                pos += 6;
+            }
+            // Any other escape, including `\\`, is two bytes long at minimum.
+            _ => pos += 2,
+        }
+    }
+    offsets
+}
+
+// Replaces each unpaired surrogate escape at `offsets` with `\ufffd`.
+fn scrub_unpaired_surrogates(text: &str, offsets: &[usize]) -> String {
+    let mut scrubbed = String::with_capacity(text.len());
+    let mut last = 0;
+    for &offset in offsets {
+        scrubbed.push_str(&text[last..offset]);
+        scrubbed.push_str("\\ufffd");
+        last = offset + 6;
+    }
+    scrubbed.push_str(&text[last..]);
+    scrubbed
+}
+
+// Retries a rejected body after substituting U+FFFD for invalid UTF-8 and for
+// unpaired surrogate escapes. `None` when there was nothing to substitute.
+fn parse_lossy_utf8<T>(
+    bytes: &[u8],
+    endpoint_key: &str,
+    lang: &LangCode,
+) -> Option<Result<ApiWrapper<T>, serde_json::Error>>
+where
+    T: DeserializeOwned,
+{
+    const MAX_LOGGED_OFFSETS: usize = 10;
+
+    let offsets = invalid_utf8_offsets(bytes);
+    let text = String::from_utf8_lossy(bytes);
+    let surrogates = unpaired_surrogate_offsets(&text);
+    if offsets.is_empty() && surrogates.is_empty() {
+        return None;
+    }
+    let text = scrub_unpaired_surrogates(&text, &surrogates);
+    let wrapper = match serde_json::from_str(&text) {
+        Ok(wrapper) => wrapper,
+        Err(e) => return Some(Err(e)),
+    };
+
+    LOSSY_UTF8_RESPONSES.fetch_add(1, Ordering::Relaxed);
+    let describe = |offsets: &[usize]| {
+        let shown = offsets
+            .iter()
+            .take(MAX_LOGGED_OFFSETS)
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let more = offsets.len().saturating_sub(MAX_LOGGED_OFFSETS);
+        if more > 0 {
+            format!("{} (+{} more)", shown, more)
+        } else {
+            shown
+        }
+    };
+    let mut replaced = Vec::new();
+    if !offsets.is_empty() {
+        replaced.push(format!(
+            "{} invalid UTF-8 sequence(s) at byte offset(s) {}",
+            offsets.len(),
+            describe(&offsets)
+        ));
+    }
+    if !surrogates.is_empty() {
+        replaced.push(format!(
+            "{} unpaired surrogate escape(s) at offset(s) {}",
+            surrogates.len(),
+            describe(&surrogates)
+        ));
+    }
+    log(
+        LogLevel::Warning,
+        &format!(
+            "Recovered invalid UTF-8 response for {} [{}]: replaced {}",
+            endpoint_key,
+            lang,
+            replaced.join(" and ")
+        ),
+    );
+    Some(Ok(wrapper))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(bytes: &[u8]) -> AppResult<Option<String>> {
+        let lang = LangCode::parse("en-us").unwrap();
+        parse_wrapper::<Value>(bytes, "test", &lang).map(|wrapper| {
+            wrapper
+                .data
+                .map(|data| data["name"].as_str().unwrap().to_string())
+        })
+    }
+
+    fn body(name: &[u8]) -> Vec<u8> {
+        [
+            br#"{"retcode":0,"message":"OK","data":{"name":""#,
+            name,
+            br#""}}"#,
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn invalid_utf8_bytes_are_replaced() {
+        let name = parse(&body(b"Am\xFFber\xC3")).unwrap();
+        assert_eq!(name.as_deref(), Some("Am\u{FFFD}ber\u{FFFD}"));
+    }
+
+    #[test]
+    fn lone_high_surrogate_escape_is_replaced() {
+        assert!(serde_json::from_slice::<Value>(&body(br"x\ud83dy")).is_err());
+        let name = parse(&body(br"x\ud83dy")).unwrap();
+        assert_eq!(name.as_deref(), Some("x\u{FFFD}y"));
+    }
+
+    #[test]
+    fn lone_low_surrogate_escape_is_replaced() {
+        let name = parse(&body(br"\ude00 end")).unwrap();
+        assert_eq!(name.as_deref(), Some("\u{FFFD} end"));
+    }
+
+    #[test]
+    fn high_surrogate_before_a_non_surrogate_escape_is_replaced() {
+        let name = parse(&body(br"\ud83dA")).unwrap();
+        assert_eq!(name.as_deref(), Some("\u{FFFD}A"));
+    }
+
+    #[test]
+    fn surrogate_pairs_are_kept() {
+        let name = parse(&body(br"\ud83d\ude00 \ud83d")).unwrap();
+        assert_eq!(name.as_deref(), Some("\u{1F600} \u{FFFD}"));
+    }
+
+    #[test]
+    fn escaped_backslash_is_not_an_escape() {
+        let name = parse(&body(br"\\ud83d")).unwrap();
+        assert_eq!(name.as_deref(), Some(r"\ud83d"));
+    }
+
+    #[test]
+    fn invalid_bytes_and_surrogates_together() {
+        let name = parse(&body(b"\xFF\\ud800")).unwrap();
+        assert_eq!(name.as_deref(), Some("\u{FFFD}\u{FFFD}"));
+    }
+
+    #[test]
+    fn malformed_json_still_fails() {
+        assert!(parse(b"{\"retcode\":0,\"data\":\xFF").is_err());
+        assert!(parse(br#"{"retcode":0,"data":"#).is_err());
+    }
+
+    #[test]
+    fn surrogate_offsets() {
+        assert_eq!(unpaired_surrogate_offsets(r#""a\ud83d""#), [2]);
+        assert_eq!(
+            unpaired_surrogate_offsets(r#""\ud83d\ude00""#),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            unpaired_surrogate_offsets(r#""\\ud83d""#),
+            Vec::<usize>::new()
+        );
+        assert_eq!(unpaired_surrogate_offsets(r#""\ud83"#), Vec::<usize>::new());
+    }
+}
@@ -1,22 +1,25 @@
 use super::client::ApiClient;
 use super::model::{
     ApiBulkPage, ApiBulkResponse, ApiCalendarResponse, ApiDetailPage, ApiDetailResponse,
-    ApiListItem, ApiListResponse, ApiNavEntry, ApiNavResponse,
+    ApiListItem, ApiListResponse, ApiNavEntry, ApiNavResponse, ApiPostPreview,
+    ApiPostPreviewResponse,
 };
 use crate::config;
+use crate::core::stats::{LIST_ITEMS_RECOVERED, LIST_ITEMS_SKIPPED};
 use crate::error::{AppError, AppResult};
 use crate::logging::{log, LogLevel};
-use crate::model::common::{EntryId, MenuId};
+use crate::model::common::{EntryId, LangCode, MenuCategory, MenuId};
 use crate::utils;
 use reqwest::Method;
 
-use serde_json::json;
+use serde_json::{from_value, json, Value};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 
-pub async fn fetch_nav(client: &ApiClient, lang: &str) -> AppResult<Vec<ApiNavEntry>> {
+pub async fn fetch_nav(client: &ApiClient, lang: &LangCode) -> AppResult<Vec<ApiNavEntry>> {
     let endpoint_name = "nav";
 
     match client
@@ -34,53 +37,121 @@ pub async fn fetch_nav(client: &ApiClient, lang: &str) -> AppResult<Vec<ApiNavEn
     }
 }
 
+/// Verifies the API is reachable before a full run: the canary language's
+/// navigation must be non-empty and bulk data for `PREFLIGHT_BULK_ID` must resolve.
+pub async fn preflight_check(client: &ApiClient, lang: &str) -> AppResult<()> {
+    let lang_code = LangCode::parse(lang)?;
+
+    let nav = fetch_nav(client, &lang_code)
+        .await
+        .map_err(|e| AppError::Preflight(format!("navigation request [{}]: {}", lang, e)))?;
+    if nav.is_empty() {
+        return Err(AppError::Preflight(format!(
+            "navigation for [{}] is empty",
+            lang
+        )));
+    }
+
+    let probe_ids = HashSet::from([config::PREFLIGHT_BULK_ID]);
+    let bulk = fetch_bulk_data(
+        client,
+        Arc::new(Semaphore::new(1)),
+        &probe_ids,
+        &lang_code,
+        "Preflight",
+    )
+    .await
+    .map_err(|e| AppError::Preflight(format!("bulk request [{}]: {}", lang, e)))?;
+    if !bulk.contains_key(&config::PREFLIGHT_BULK_ID) {
+        return Err(AppError::Preflight(format!(
+            "bulk data for entry {} [{}] is missing",
+            config::PREFLIGHT_BULK_ID,
+            lang
+        )));
+    }
+    Ok(())
+}
+
 pub async fn fetch_menu_list_items(
     client: &ApiClient,
     list_sem: Arc<Semaphore>,
-    lang: &str,
+    lang: &LangCode,
     menu_id: MenuId,
     menu_name: &str,
 ) -> AppResult<Vec<ApiListItem>> {
     let mut all_items: Vec<ApiListItem> = Vec::new();
+    let mut skipped_items = 0usize;
     let mut total_from_api: Option<i64> = None;
     let mut current_page = 1;
+    let page_size = config::page_size();
     let ctx = format!("List Menu:{} ('{}') [{}]", menu_id, menu_name, lang);
-    let endpoint_name = "list";
 
-    let effective_menu_id = if menu_id == 0 { Some(9) } else { Some(menu_id) };
+    let effective_menu_id = if menu_id == 0 {
+        MenuCategory::Materials.menu_id()
+    } else {
+        menu_id
+    };
 
     loop {
-        let payload = json!({
-            "menu_id": effective_menu_id,
-            "page_num": current_page,
-            "page_size": config::PAGE_SIZE,
-            "use_es": true,
-
-            "filters": if effective_menu_id == Some(9) { Some(json!([])) } else { None }
-        });
-
-        let permit = utils::acquire_semaphore(&list_sem, "List Fetch Page").await?;
-        let fetch_result = client
-            .fetch::<ApiListResponse>(Method::POST, endpoint_name, lang, None, Some(&payload))
-            .await;
-        drop(permit);
-
-        match fetch_result {
+        let page_start = (current_page - 1) * page_size;
+        let page_result = match fetch_list_page(
+            client,
+            &list_sem,
+            lang,
+            effective_menu_id,
+            current_page,
+            page_size,
+        )
+        .await
+        {
             Ok(resp_data) => {
                 if total_from_api.is_none() {
                     total_from_api = resp_data.total;
                 }
-                let current_page_items = resp_data.list;
-                let count = current_page_items.len();
+                let (items, skipped) = deserialize_list_items(resp_data.list, page_start, &ctx);
+                if skipped > 0 {
+                    record_list_recovery(items.len(), skipped);
+                }
+                Ok((items, skipped))
+            }
+            Err(AppError::SerdeParse(e)) => {
+                log(
+                    LogLevel::Warning,
+                    &format!(
+                        "{} - Page {} (items {}..{}) failed to parse: {}. Retrying in smaller pages.",
+                        ctx,
+                        current_page,
+                        page_start,
+                        page_start + page_size,
+                        e
+                    ),
+                );
+                recover_list_window(
+                    client,
+                    &list_sem,
+                    lang,
+                    effective_menu_id,
+                    (page_start, page_size),
+                    page_size,
+                    &ctx,
+                )
+                .await
+                .inspect(|(items, skipped)| record_list_recovery(items.len(), *skipped))
+            }
+            Err(e) => Err(e),
+        };
 
-                if count == 0 {
+        match page_result {
+            Ok((current_page_items, skipped)) => {
+                if current_page_items.is_empty() && skipped == 0 {
                     break;
                 }
 
                 all_items.extend(current_page_items);
+                skipped_items += skipped;
 
                 if let Some(expected_total) = total_from_api {
-                    if all_items.len() as i64 >= expected_total {
+                    if (all_items.len() + skipped_items) as i64 >= expected_total {
                         if all_items.len() as i64 > expected_total {
                             log(
                                 LogLevel::Warning,
@@ -137,10 +208,134 @@ pub async fn fetch_menu_list_items(
     Ok(all_items)
 }
 
+async fn fetch_list_page(
+    client: &ApiClient,
+    list_sem: &Arc<Semaphore>,
+    lang: &LangCode,
+    menu_id: MenuId,
+    page_num: usize,
+    page_size: usize,
+) -> AppResult<ApiListResponse> {
+    let is_materials = MenuCategory::from(menu_id) == MenuCategory::Materials;
+    let payload = json!({
+        "menu_id": menu_id,
+        "page_num": page_num,
+        "page_size": page_size,
+        "use_es": true,
+
+        "filters": if is_materials { Some(json!([])) } else { None }
+    });
+
+    let permit = utils::acquire_semaphore(list_sem, "List Fetch Page").await?;
+    let fetch_result = client
+        .fetch::<ApiListResponse>(Method::POST, "list", lang, None, Some(&payload))
+        .await;
+    drop(permit);
+    fetch_result
+}
+
+// Items are deserialized one at a time so a single malformed entry only costs itself.
+fn deserialize_list_items(
+    raw_items: Vec<Value>,
+    first_index: usize,
+    ctx: &str,
+) -> (Vec<ApiListItem>, usize) {
+    let mut skipped = 0;
+    let items = raw_items
+        .into_iter()
+        .enumerate()
+        .filter_map(|(offset, raw)| match from_value::<ApiListItem>(raw) {
+            Ok(item) => Some(item),
+            Err(e) => {
+                log(
+                    LogLevel::Warning,
+                    &format!(
+                        "{} - Skipping malformed list item {}: {}",
+                        ctx,
+                        first_index + offset,
+                        e
+                    ),
+                );
+                skipped += 1;
+                None
+            }
+        })
+        .collect();
+    (items, skipped)
+}
+
+/// Re-fetches the items in `window` (start, len) with pages half the size of the one
+/// that failed, down to `MIN_PAGE_SIZE`. Sub-windows that still fail to parse at the
+/// minimum size are skipped. Returns the kept items in list order and the skipped count.
+async fn recover_list_window(
+    client: &ApiClient,
+    list_sem: &Arc<Semaphore>,
+    lang: &LangCode,
+    menu_id: MenuId,
+    window: (usize, usize),
+    failed_page_size: usize,
+    ctx: &str,
+) -> AppResult<(Vec<ApiListItem>, usize)> {
+    let mut kept: Vec<(usize, Vec<ApiListItem>)> = Vec::new();
+    let mut skipped = 0;
+    let mut pending = vec![(window.0, window.1, failed_page_size)];
+
+    while let Some((start, len, failed_size)) = pending.pop() {
+        let page_size = (failed_size / 2).max(config::MIN_PAGE_SIZE);
+        let end = start + len;
+        for page_idx in start / page_size..=(end - 1) / page_size {
+            let page_start = page_idx * page_size;
+            let window_start = start.max(page_start);
+            let window_end = end.min(page_start + page_size);
+            match fetch_list_page(client, list_sem, lang, menu_id, page_idx + 1, page_size).await {
+                Ok(resp_data) => {
+                    let raw_items = resp_data
+                        .list
+                        .into_iter()
+                        .skip(window_start - page_start)
+                        .take(window_end - window_start)
+                        .collect();
+                    let (items, bad) = deserialize_list_items(raw_items, window_start, ctx);
+                    skipped += bad;
+                    kept.push((window_start, items));
+                }
+                Err(AppError::SerdeParse(_)) if page_size > config::MIN_PAGE_SIZE => {
+                    pending.push((window_start, window_end - window_start, page_size));
+                }
+                Err(AppError::SerdeParse(e)) => {
+                    log(
+                        LogLevel::Warning,
+                        &format!(
+                            "{} - Skipping list items {}..{}: page still fails to parse at size {}: {}",
+                            ctx, window_start, window_end, page_size, e
+                        ),
+                    );
+                    skipped += window_end - window_start;
+                }
+                Err(AppError::ApiError {
+                    retcode: 100010, ..
+                }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    kept.sort_by_key(|(window_start, _)| *window_start);
+    Ok((
+        kept.into_iter().flat_map(|(_, items)| items).collect(),
+        skipped,
+    ))
+}
+
+fn record_list_recovery(kept: usize, skipped: usize) {
+    LIST_ITEMS_RECOVERED.fetch_add(kept as u64, Ordering::Relaxed);
+    LIST_ITEMS_SKIPPED.fetch_add(skipped as u64, Ordering::Relaxed);
+}
+
 pub async fn fetch_entry_detail(
     client: &ApiClient,
     detail_sem: Arc<Semaphore>,
-    lang: &str,
+    lang: &LangCode,
     entry_id: EntryId,
 ) -> AppResult<Option<ApiDetailPage>> {
     let endpoint_name = "detail";
@@ -152,7 +347,21 @@ pub async fn fetch_entry_detail(
     drop(permit);
 
     match fetch_result {
-        Ok(data) => Ok(Some(data.page)),
+        Ok(data) => {
+            let mut page = data.page;
+            // Some responses don't echo the id; the one we asked for is authoritative.
+            if page.id.is_none_or(|id| id <= 0) {
+                log(
+                    LogLevel::Info,
+                    &format!(
+                        "Detail Fetch [{}/{}] - Response page has no id; using the requested one.",
+                        lang, entry_id
+                    ),
+                );
+                page.id = Some(entry_id);
+            }
+            Ok(Some(page))
+        }
 
         Err(AppError::ApiError {
             retcode: 100010, ..
@@ -179,7 +388,7 @@ pub async fn fetch_entry_detail(
 pub async fn fetch_calendar(
     client: &ApiClient,
     cal_sem: Arc<Semaphore>,
-    lang: &str,
+    lang: &LangCode,
 ) -> AppResult<ApiCalendarResponse> {
     let endpoint_name = "calendar";
     let log_ctx = format!("Calendar [{}]", lang);
@@ -214,7 +423,7 @@ pub async fn fetch_bulk_data(
     client: &ApiClient,
     bulk_sem: Arc<Semaphore>,
     ids: &HashSet<EntryId>,
-    lang: &str,
+    lang: &LangCode,
     log_ctx_prefix: &str,
 ) -> AppResult<HashMap<EntryId, ApiBulkPage>> {
     if ids.is_empty() {
@@ -235,7 +444,7 @@ pub async fn fetch_bulk_data(
         let batch_ids: Vec<String> = batch_ids_slice.iter().map(ToString::to_string).collect();
         let params = HashMap::from([("str_entry_page_ids".to_string(), batch_ids.join(","))]);
         let client_clone = client.clone();
-        let lang_clone = lang.to_string();
+        let lang_clone = lang.clone();
         let sem_clone = bulk_sem.clone();
         let batch_num = i + 1;
         let log_ctx_prefix_clone = log_ctx_prefix.to_string();
@@ -320,3 +529,67 @@ pub async fn fetch_bulk_data(
 
     Ok(combined_results)
 }
+
+pub async fn fetch_post_previews(
+    client: &ApiClient,
+    ids: &HashSet<EntryId>,
+    lang: &LangCode,
+) -> AppResult<HashMap<EntryId, ApiPostPreview>> {
+    let endpoint_name = config::ENDPOINT_POST_PREVIEW;
+    if !config::API_ENDPOINT_KEYS.contains(&endpoint_name) {
+        return Err(AppError::PostResolutionNotSupported(format!(
+            "no '{}' endpoint configured",
+            endpoint_name
+        )));
+    }
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut sorted_ids: Vec<EntryId> = ids.iter().cloned().collect();
+    sorted_ids.sort_unstable();
+
+    let mut previews = HashMap::with_capacity(sorted_ids.len());
+    for batch_ids_slice in sorted_ids.chunks(config::BULK_BATCH_SIZE) {
+        let batch_ids: Vec<String> = batch_ids_slice.iter().map(ToString::to_string).collect();
+        let params = HashMap::from([("post_ids".to_string(), batch_ids.join(","))]);
+        match client
+            .fetch::<ApiPostPreviewResponse>(Method::GET, endpoint_name, lang, Some(&params), None)
+            .await
+        {
+            Ok(data) => previews.extend(data.posts.into_iter().map(|p| (p.post_id, p))),
+            Err(e @ (AppError::Reqwest(_) | AppError::Timeout(_) | AppError::ApiError { .. })) => {
+                return Err(AppError::PostResolutionNotSupported(format!(
+                    "endpoint unreachable [{}]: {}",
+                    lang, e
+                )));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(previews)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::model::ApiWrapper;
+    use std::path::Path;
+
+    #[test]
+    fn malformed_list_items_are_skipped_alone() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/list_malformed_items.json");
+        let text = std::fs::read_to_string(path).unwrap();
+        let wrapper: ApiWrapper<ApiListResponse> = serde_json::from_str(&text).unwrap();
+        let page = wrapper.data.unwrap();
+        assert_eq!(page.total, Some(4));
+
+        let (items, skipped) = deserialize_list_items(page.list, 20, "List Menu:2 [en-us]");
+        assert_eq!(skipped, 2);
+        let kept: Vec<(EntryId, &str)> = items
+            .iter()
+            .map(|item| (item.entry_page_id, item.name.as_str()))
+            .collect();
+        assert_eq!(kept, [(1001, "Amber"), (1003, "Kaeya")]);
+    }
+}
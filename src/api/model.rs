@@ -3,12 +3,36 @@ use crate::model::common::{
     deserialize_flexible_i64, deserialize_optional_flexible_i64, deserialize_optional_string,
     deserialize_string_or_default, EntryId, MenuId,
 };
+use crate::telemetry;
 use serde::de::{self, DeserializeOwned, Deserializer, MapAccess, Visitor};
 use serde::Deserialize;
 use serde_json::{from_str, from_value, Value};
 use std::collections::HashMap;
 use std::fmt;
 
+/// Declares `deserialize_with` targets in a module named after the struct; each
+/// one forwards to a lenient helper together with `Struct.field`, so the coercion
+/// report says which field fell back.
+macro_rules! field_deserializers {
+    ($module:ident, $owner:ident { $($field:ident: $ty:ty = $helper:ident,)+ }) => {
+        mod $module {
+            use super::*;
+
+            $(
+                pub(super) fn $field<'de, D>(deserializer: D) -> Result<$ty, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    $helper(
+                        deserializer,
+                        concat!(stringify!($owner), ".", stringify!($field)),
+                    )
+                }
+            )+
+        }
+    };
+}
+
 // --- Struct definitions (ApiWrapper, ApiNavResponse, etc.) remain the same ---
 #[derive(Deserialize, Debug, Clone)]
 pub struct ApiWrapper<T> {
@@ -43,43 +67,63 @@ pub struct ApiNavMenu {
     pub menu_id: MenuId,
 }
 
+field_deserializers!(list_response, ApiListResponse {
+    total: Option<i64> = deserialize_optional_flexible_i64,
+    list: Vec<Value> = deserialize_value_to_vec_list_item,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct ApiListResponse {
-    #[serde(default, deserialize_with = "deserialize_optional_flexible_i64")]
+    #[serde(default, deserialize_with = "list_response::total")]
     pub total: Option<i64>,
-    #[serde(default, deserialize_with = "deserialize_value_to_vec_list_item")]
-    pub list: Vec<ApiListItem>,
+    // Items stay raw so one malformed entry doesn't fail the whole page; the list
+    // fetcher deserializes them into `ApiListItem`.
+    #[serde(default, deserialize_with = "list_response::list")]
+    pub list: Vec<Value>,
 }
 
-fn deserialize_value_to_vec_list_item<'de, D>(deserializer: D) -> Result<Vec<ApiListItem>, D::Error>
+fn deserialize_value_to_vec_list_item<'de, D>(
+    deserializer: D,
+    field: &'static str,
+) -> Result<Vec<Value>, D::Error>
 where
     D: Deserializer<'de>,
 {
     let value = Value::deserialize(deserializer)?;
     match value {
-        Value::Array(arr) => from_value(Value::Array(arr)).map_err(de::Error::custom),
-        _ => Ok(Vec::new()),
+        Value::Array(arr) => Ok(arr),
+        Value::Null => Ok(Vec::new()),
+        _ => {
+            telemetry::record_coercion(field, "non_array");
+            Ok(Vec::new())
+        }
     }
 }
 
+field_deserializers!(list_item, ApiListItem {
+    name: String = deserialize_string_or_default,
+    icon_url: String = deserialize_string_or_default,
+    filter_values: Value = deserialize_value_to_value_object,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiListItem {
     #[serde(deserialize_with = "deserialize_flexible_i64")]
     pub entry_page_id: EntryId,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "list_item::name")]
     pub name: String,
     #[serde(
         default,
         alias = "icon",
-        deserialize_with = "deserialize_string_or_default"
+        deserialize_with = "list_item::icon_url"
     )]
     pub icon_url: String,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub desc: Option<String>,
     #[serde(default)]
     pub display_field: Option<Value>,
-    #[serde(default, deserialize_with = "deserialize_value_to_value_object")]
+    #[serde(default, deserialize_with = "list_item::filter_values")]
     pub filter_values: Value,
 }
 
@@ -89,12 +133,22 @@ pub struct ApiDetailResponse {
     pub page: ApiDetailPage,
 }
 
+field_deserializers!(detail_page, ApiDetailPage {
+    id: Option<i64> = deserialize_optional_flexible_i64,
+    name: String = deserialize_string_or_default,
+    modules: Vec<ApiModule> = deserialize_value_to_vec_module,
+    filter_values: Value = deserialize_value_to_value_object,
+    menu_id: Option<i64> = deserialize_optional_flexible_i64,
+    version: Option<i64> = deserialize_optional_flexible_i64,
+    alias_of: Option<i64> = deserialize_optional_flexible_i64,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiDetailPage {
-    #[serde(default, deserialize_with = "deserialize_optional_flexible_i64")]
+    #[serde(default, deserialize_with = "detail_page::id")]
     pub id: Option<EntryId>,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "detail_page::name")]
     pub name: String,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub desc: Option<String>,
@@ -102,19 +156,29 @@ pub struct ApiDetailPage {
     pub icon_url: Option<String>,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub header_img_url: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "detail_page::modules")]
     pub modules: Vec<ApiModule>,
-    #[serde(default, deserialize_with = "deserialize_value_to_value_object")]
+    #[serde(default, deserialize_with = "detail_page::filter_values")]
     pub filter_values: Value,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub menu_name: Option<String>,
-    #[serde(default, deserialize_with = "deserialize_optional_flexible_i64")]
+    #[serde(default, deserialize_with = "detail_page::menu_id")]
     pub menu_id: Option<MenuId>,
-    #[serde(default, deserialize_with = "deserialize_optional_flexible_i64")]
+    #[serde(default, deserialize_with = "detail_page::version")]
     pub version: Option<i64>,
+    #[serde(
+        default,
+        alias = "redirect_entry_page_id",
+        alias = "redirect_id",
+        deserialize_with = "detail_page::alias_of"
+    )]
+    pub alias_of: Option<EntryId>,
 }
 
-fn deserialize_value_to_value_object<'de, D>(deserializer: D) -> Result<Value, D::Error>
+fn deserialize_value_to_value_object<'de, D>(
+    deserializer: D,
+    field: &'static str,
+) -> Result<Value, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -122,10 +186,17 @@ where
     if value.is_object() {
         Ok(value)
     } else {
+        if !value.is_null() {
+            telemetry::record_coercion(field, "non_object");
+        }
         Ok(Value::Object(serde_json::Map::new()))
     }
 }
 
+field_deserializers!(module, ApiModule {
+    modules: Vec<ApiModule> = deserialize_value_to_vec_module,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiModule {
@@ -137,7 +208,7 @@ pub struct ApiModule {
     pub is_poped: Option<bool>,
     pub is_customize_name: Option<bool>,
     pub is_abstract: Option<bool>,
-    #[serde(default, deserialize_with = "deserialize_value_to_vec_module")]
+    #[serde(default, deserialize_with = "module::modules")]
     pub modules: Vec<ApiModule>,
 }
 
@@ -153,6 +224,10 @@ pub struct ApiBaseInfoItem {
     pub id: Option<String>,
 }
 
+field_deserializers!(ascension_item, ApiAscensionItem {
+    materials: Value = deserialize_string_array_or_null,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiAscensionItem {
@@ -166,7 +241,7 @@ pub struct ApiAscensionItem {
     pub combat_list: Value,
     #[serde(
         default = "default_value_null",
-        deserialize_with = "deserialize_string_array_or_null"
+        deserialize_with = "ascension_item::materials"
     )]
     pub materials: Value,
     pub id: Option<String>,
@@ -188,7 +263,10 @@ fn default_value_null() -> Value {
     Value::Null
 }
 
-fn deserialize_string_array_or_null<'de, D>(deserializer: D) -> Result<Value, D::Error>
+fn deserialize_string_array_or_null<'de, D>(
+    deserializer: D,
+    field: &'static str,
+) -> Result<Value, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -203,7 +281,10 @@ where
         StringArrayOrNull::Strings(s) => {
             Ok(Value::Array(s.into_iter().map(Value::String).collect()))
         }
-        StringArrayOrNull::Null(_) => Ok(Value::Null),
+        StringArrayOrNull::Null(_) => {
+            telemetry::record_coercion(field, "non_string_array");
+            Ok(Value::Null)
+        }
     }
 }
 
@@ -239,44 +320,62 @@ pub struct ApiTalentItem {
     pub id: Option<String>,
 }
 
+field_deserializers!(summary_item, ApiSummaryItem {
+    icon_url: String = deserialize_string_or_default,
+    name: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiSummaryItem {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "summary_item::icon_url")]
     pub icon_url: String,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "summary_item::name")]
     pub name: String,
     #[serde(default, deserialize_with = "deserialize_string_or_value")]
     pub desc: Value,
     pub id: Option<String>,
 }
 
+field_deserializers!(story_item, ApiStoryItem {
+    title: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiStoryItem {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "story_item::title")]
     pub title: String,
     #[serde(default, deserialize_with = "deserialize_string_or_value")]
     pub desc: Value,
     pub id: Option<String>,
 }
 
+field_deserializers!(body_item, ApiBodyItem {
+    title: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiBodyItem {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "body_item::title")]
     pub title: String,
     #[serde(default, deserialize_with = "deserialize_string_or_value")]
     pub content: Value,
     pub id: Option<String>,
 }
 
+field_deserializers!(voice_item, ApiVoiceItem {
+    title: String = deserialize_string_or_default,
+    desc: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiVoiceItem {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "voice_item::title")]
     pub title: String,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "voice_item::desc")]
     pub desc: String,
     #[serde(default)]
     pub audios: Option<Value>,
@@ -293,12 +392,17 @@ pub struct GalleryCharacterWrapper {
 }
 
 // This represents one item *within* the gallery list from the API
+field_deserializers!(gallery_character_item, ApiGalleryCharacterItem {
+    key: String = deserialize_string_or_default,
+    img: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiGalleryCharacterItem {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "gallery_character_item::key")]
     pub key: String,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "gallery_character_item::img")]
     pub img: String,
     #[serde(
         default,
@@ -321,6 +425,11 @@ where
     }
 }
 
+field_deserializers!(artifact_list_item, ApiArtifactListItem {
+    position: String = deserialize_string_or_default,
+    icon_url: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiArtifactListItem {
@@ -328,35 +437,64 @@ pub struct ApiArtifactListItem {
     pub id: Option<String>,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub title: Option<String>,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "artifact_list_item::position")]
     pub position: String,
     #[serde(default, deserialize_with = "deserialize_string_or_value")]
     pub desc: Value,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "artifact_list_item::icon_url")]
     pub icon_url: String,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiReliquaryEffect {
-    #[serde(default, deserialize_with = "deserialize_optional_string")]
-    pub two_set_effect: Option<String>,
-    #[serde(default, deserialize_with = "deserialize_optional_string")]
-    pub four_set_effect: Option<String>,
     pub id: Option<String>,
+    // `two_set_effect`, `four_set_effect` and any other `<count>_set_effect` keys.
+    #[serde(flatten)]
+    pub effects: HashMap<String, Value>,
 }
 
+field_deserializers!(map_data, ApiMapData {
+    url: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiMapData {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "map_data::url")]
     pub url: String,
+    #[serde(
+        default,
+        alias = "markers",
+        deserialize_with = "deserialize_null_to_default_vec"
+    )]
+    pub points: Vec<Value>,
+}
+
+field_deserializers!(map_resource_data, ApiMapResourceData {
+    map_id: Option<i64> = deserialize_optional_flexible_i64,
+    resource_count: Option<i64> = deserialize_optional_flexible_i64,
+});
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiMapResourceData {
+    #[serde(default, deserialize_with = "map_resource_data::map_id")]
+    pub map_id: Option<i64>,
+    #[serde(default, deserialize_with = "map_resource_data::resource_count")]
+    pub resource_count: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub map_url: Option<String>,
 }
 
+field_deserializers!(textual_research_item, ApiTextualResearchItem {
+    title: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiTextualResearchItem {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "textual_research_item::title")]
     pub title: String,
     #[serde(default, deserialize_with = "deserialize_string_or_value")]
     pub desc: Value,
@@ -365,25 +503,36 @@ pub struct ApiTextualResearchItem {
     pub id: Option<String>,
 }
 
+field_deserializers!(timeline_module_content, ApiTimelineModuleContent {
+    desc: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiTimelineModuleContent {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "timeline_module_content::desc")]
     pub desc: String,
 }
 
+field_deserializers!(timeline_event, ApiTimelineEvent {
+    title: String = deserialize_string_or_default,
+    sub_title: String = deserialize_string_or_default,
+    desc: String = deserialize_string_or_default,
+    icon_url: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ApiTimelineEvent {
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub event_id: Option<String>,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "timeline_event::title")]
     pub title: String,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "timeline_event::sub_title")]
     pub sub_title: String,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "timeline_event::desc")]
     pub desc: String,
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "timeline_event::icon_url")]
     pub icon_url: String,
     #[serde(default)]
     pub modules: Vec<ApiTimelineModuleContent>,
@@ -403,28 +552,39 @@ pub struct ApiVideoCollectionDataList {
     pub list: Vec<ApiVideoCategory>,
 }
 
+field_deserializers!(video_category, ApiVideoCategory {
+    name: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct ApiVideoCategory {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "video_category::name")]
     pub name: String,
     #[serde(default)]
     pub videos: Vec<ApiVideoCollectionItem>,
 }
 
+field_deserializers!(video_collection_item, ApiVideoCollectionItem {
+    name: String = deserialize_string_or_default,
+    title: String = deserialize_string_or_default,
+    url: String = deserialize_string_or_default,
+    cover: String = deserialize_string_or_default,
+});
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct ApiVideoCollectionItem {
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "video_collection_item::name")]
     pub name: String, // The name field within the video object itself (if any)
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "video_collection_item::title")]
     pub title: String,
     #[serde(default, deserialize_with = "deserialize_optional_string")]
     pub video_id: Option<String>, // Renamed from 'id' to avoid confusion
-    #[serde(default, deserialize_with = "deserialize_string_or_default")]
+    #[serde(default, deserialize_with = "video_collection_item::url")]
     pub url: String,
     #[serde(
         default,
         alias = "img",
-        deserialize_with = "deserialize_string_or_default"
+        deserialize_with = "video_collection_item::cover"
     )]
     pub cover: String,
     #[serde(default, deserialize_with = "deserialize_flexible_i64")]
@@ -467,6 +627,114 @@ where
     Option::<Vec<String>>::deserialize(deserializer).map(|opt_vec| opt_vec.unwrap_or_default())
 }
 
+field_deserializers!(gift_reward_item, ApiGiftRewardItem {
+    icon_url: String = deserialize_string_or_default,
+    name: String = deserialize_string_or_default,
+    ep_id: Option<i64> = deserialize_optional_flexible_i64,
+});
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiGiftRewardItem {
+    #[serde(
+        default,
+        alias = "icon",
+        deserialize_with = "gift_reward_item::icon_url"
+    )]
+    pub icon_url: String,
+    #[serde(default, deserialize_with = "gift_reward_item::name")]
+    pub name: String,
+    #[serde(
+        default,
+        alias = "entry_page_id",
+        deserialize_with = "gift_reward_item::ep_id"
+    )]
+    pub ep_id: Option<EntryId>,
+    #[serde(default)]
+    pub liked: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiGiftRewardData {
+    #[serde(default, deserialize_with = "deserialize_null_to_default_vec")]
+    pub liked: Vec<ApiGiftRewardItem>,
+    #[serde(default, deserialize_with = "deserialize_null_to_default_vec")]
+    pub disliked: Vec<ApiGiftRewardItem>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiEventCalendarData {
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub start_time: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_string")]
+    pub end_time: Option<String>,
+    #[serde(default)]
+    pub rewards: Value,
+    #[serde(default, deserialize_with = "deserialize_string_or_value")]
+    pub description: Value,
+}
+
+field_deserializers!(achievement_item, ApiAchievementItem {
+    ep_id: Option<i64> = deserialize_optional_flexible_i64,
+    title: String = deserialize_string_or_default,
+});
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiAchievementItem {
+    #[serde(
+        default,
+        alias = "entry_page_id",
+        deserialize_with = "achievement_item::ep_id"
+    )]
+    pub ep_id: Option<EntryId>,
+    #[serde(default, deserialize_with = "achievement_item::title")]
+    pub title: String,
+    #[serde(default, deserialize_with = "deserialize_string_or_value")]
+    pub desc: Value,
+    #[serde(default, alias = "icon", deserialize_with = "deserialize_optional_string")]
+    pub icon_url: Option<String>,
+    #[serde(default)]
+    pub is_hidden: Option<bool>,
+}
+
+field_deserializers!(achievement_group_data, ApiAchievementGroupData {
+    group_name: String = deserialize_string_or_default,
+});
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiAchievementGroupData {
+    #[serde(default, deserialize_with = "achievement_group_data::group_name")]
+    pub group_name: String,
+    #[serde(default, deserialize_with = "deserialize_null_to_default_vec")]
+    pub items: Vec<ApiAchievementItem>,
+}
+
+field_deserializers!(build_section, ApiBuildSection {
+    title: String = deserialize_string_or_default,
+});
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiBuildSection {
+    #[serde(default, deserialize_with = "build_section::title")]
+    pub title: String,
+    #[serde(default, deserialize_with = "deserialize_string_or_value")]
+    pub desc: Value,
+    #[serde(default, deserialize_with = "deserialize_null_to_default_vec")]
+    pub weapons: Vec<Value>,
+    #[serde(
+        default,
+        alias = "reliquaries",
+        deserialize_with = "deserialize_null_to_default_vec"
+    )]
+    pub artifacts: Vec<Value>,
+    pub id: Option<String>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum ApiComponentData {
@@ -482,12 +750,17 @@ pub enum ApiComponentData {
     ArtifactMap(HashMap<String, ApiArtifactListItem>),
     ReliquarySetEffect(ApiReliquaryEffect),
     Map(ApiMapData),
+    MapResource(ApiMapResourceData),
     Customize(Value), // Stores the raw value (string or object)
     TextualResearchList(Vec<ApiTextualResearchItem>),
     Timeline(ApiTimelineListData),
     VideoCollection(Value), // Store raw Value for video collection initially
     Tcg(ApiTcgData),
     DropMaterial(ApiDropMaterialData),
+    GiftReward(ApiGiftRewardData),
+    BuildList(Vec<ApiBuildSection>),
+    EventCalendar(ApiEventCalendarData),
+    AchievementGroup(ApiAchievementGroupData),
     Unknown(Value),
 }
 
@@ -600,6 +873,10 @@ impl<'de> Deserialize<'de> for ApiComponent {
                 config::COMPONENT_MAP => {
                     parse_direct_component::<ApiMapData>(&data_val).map(ApiComponentData::Map)
                 }
+                config::COMPONENT_MAP_RESOURCE => {
+                    parse_direct_component::<ApiMapResourceData>(&data_val)
+                        .map(ApiComponentData::MapResource)
+                }
                 config::COMPONENT_CUSTOMIZE => Ok(ApiComponentData::Customize(data_val.clone())),
                 config::COMPONENT_TEXTUAL_RESEARCH => {
                     parse_list_component::<ApiTextualResearchItem>(&data_val)
@@ -629,6 +906,38 @@ impl<'de> Deserialize<'de> for ApiComponent {
                             .map(ApiComponentData::DropMaterial)
                     }
                 }
+                config::COMPONENT_GIFT_REWARD | config::COMPONENT_COMPANION_GIFT => {
+                    if data_val.is_null() {
+                        Ok(ApiComponentData::GiftReward(ApiGiftRewardData::default()))
+                    } else {
+                        parse_direct_component::<ApiGiftRewardData>(&data_val)
+                            .map(ApiComponentData::GiftReward)
+                    }
+                }
+                config::COMPONENT_RECOMMEND | config::COMPONENT_STRATEGY => {
+                    parse_list_component::<ApiBuildSection>(&data_val)
+                        .map(ApiComponentData::BuildList)
+                }
+                config::COMPONENT_EVENT_CALENDAR => {
+                    if data_val.is_null() {
+                        Ok(ApiComponentData::EventCalendar(
+                            ApiEventCalendarData::default(),
+                        ))
+                    } else {
+                        parse_direct_component::<ApiEventCalendarData>(&data_val)
+                            .map(ApiComponentData::EventCalendar)
+                    }
+                }
+                config::COMPONENT_ACHIEVEMENT_GROUP => {
+                    if data_val.is_null() {
+                        Ok(ApiComponentData::AchievementGroup(
+                            ApiAchievementGroupData::default(),
+                        ))
+                    } else {
+                        parse_direct_component::<ApiAchievementGroupData>(&data_val)
+                            .map(ApiComponentData::AchievementGroup)
+                    }
+                }
                 _ => Ok(ApiComponentData::Unknown(data_val.clone())),
             };
 
@@ -648,7 +957,10 @@ impl<'de> Deserialize<'de> for ApiComponent {
     }
 }
 
-fn deserialize_value_to_vec_module<'de, D>(deserializer: D) -> Result<Vec<ApiModule>, D::Error>
+fn deserialize_value_to_vec_module<'de, D>(
+    deserializer: D,
+    field: &'static str,
+) -> Result<Vec<ApiModule>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -657,23 +969,65 @@ where
         Value::Array(arr) => from_value(Value::Array(arr)).map_err(de::Error::custom),
         Value::Object(map) => {
             if let Some(modules_val) = map.get("modules").or_else(|| map.get("module")) {
-                if modules_val.is_array() {
-                    from_value(modules_val.clone()).map_err(de::Error::custom)
-                } else {
-                    Ok(Vec::new())
+                match modules_val {
+                    Value::Array(_) => from_value(modules_val.clone()).map_err(de::Error::custom),
+                    Value::Object(inner) if is_id_keyed_module_map(inner) => {
+                        id_keyed_modules(inner.clone()).map_err(de::Error::custom)
+                    }
+                    _ => {
+                        telemetry::record_coercion(field, "non_array_modules");
+                        Ok(Vec::new())
+                    }
                 }
             } else if map.contains_key("components") {
                 from_value::<ApiModule>(Value::Object(map))
                     .map(|m| vec![m])
                     .map_err(de::Error::custom)
+            } else if is_id_keyed_module_map(&map) {
+                telemetry::record_coercion(field, "id_keyed_map");
+                id_keyed_modules(map).map_err(de::Error::custom)
             } else {
+                telemetry::record_coercion(field, "object_without_modules");
                 Ok(Vec::new())
             }
         }
-        _ => Ok(Vec::new()),
+        Value::Null => Ok(Vec::new()),
+        _ => {
+            telemetry::record_coercion(field, "scalar");
+            Ok(Vec::new())
+        }
     }
 }
 
+fn is_id_keyed_module_map(map: &serde_json::Map<String, Value>) -> bool {
+    !map.is_empty()
+        && map.values().all(|v| {
+            v.as_object()
+                .is_some_and(|m| m.contains_key("components") || m.contains_key("modules"))
+        })
+}
+
+// Orders by the key's trailing number when present so "module_9" sorts before "module_10".
+fn id_keyed_modules(
+    map: serde_json::Map<String, Value>,
+) -> Result<Vec<ApiModule>, serde_json::Error> {
+    let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+    entries.sort_by_cached_key(|(key, _)| {
+        let digits_start = key.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+        (key[digits_start..].parse::<u64>().ok(), key.clone())
+    });
+
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            from_value::<ApiModule>(value).map(|mut module| {
+                module.id.get_or_insert(key);
+                module
+            })
+        })
+        .collect()
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct ApiBulkResponse {
     #[serde(default)]
@@ -692,6 +1046,22 @@ pub struct ApiBulkPage {
     pub icon_url: Option<String>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiPostPreviewResponse {
+    #[serde(default, deserialize_with = "deserialize_null_to_default_vec")]
+    pub posts: Vec<ApiPostPreview>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiPostPreview {
+    #[serde(deserialize_with = "deserialize_flexible_i64")]
+    pub post_id: EntryId,
+    #[serde(default, alias = "subject", deserialize_with = "deserialize_optional_string")]
+    pub name: Option<String>,
+    #[serde(default, alias = "cover", deserialize_with = "deserialize_optional_string")]
+    pub icon_url: Option<String>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct ApiCalendarResponse {
     #[serde(default)]
@@ -793,3 +1163,29 @@ where
     }
     deserializer.deserialize_any(StringOrValueVisitor)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn coercions_are_recorded_under_the_field_that_fell_back() {
+        telemetry::enable();
+        let page: ApiDetailPage = from_value(json!({
+            "id": "7100",
+            "name": "Probe",
+            "menu_id": true,
+            "version": {},
+            "filter_values": "none"
+        }))
+        .unwrap();
+        assert_eq!(page.menu_id, None);
+        assert_eq!(page.version, None);
+
+        let snapshot = telemetry::coercion_snapshot();
+        assert!(snapshot["ApiDetailPage.menu_id"]["bool"] >= 1);
+        assert!(snapshot["ApiDetailPage.version"]["map"] >= 1);
+        assert!(snapshot["ApiDetailPage.filter_values"]["non_object"] >= 1);
+    }
+}
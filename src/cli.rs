@@ -1,9 +1,108 @@
 use crate::config;
 use crate::error::{AppError, AppResult};
-use crate::logging::{log, LogLevel};
-use clap::Parser;
+use crate::logging::{log, LogFileConfig, LogLevel};
+use crate::model::common::{EntryId, LangCode, MenuId};
+use clap::{Parser, Subcommand, ValueEnum};
+use reqwest::header::HeaderValue;
 use std::collections::HashSet;
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PipelineMode {
+    /// Per-language phase sequences sharing the global concurrency limits.
+    PerLang,
+}
+
+/// Named `ConcurrencyLimits` bundles; the numbers are the base list/detail/bulk/calendar limits.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConcurrencyProfile {
+    /// list 5, detail 8, bulk 10, calendar 2: flaky or rate-limited networks
+    Low,
+    /// list 20, detail 30, bulk 50, calendar 5: the default
+    Balanced,
+    /// list 40, detail 60, bulk 100, calendar 10: fast machine on a good connection
+    High,
+}
+
+impl ConcurrencyProfile {
+    pub fn limits(self) -> config::ConcurrencyLimits {
+        match self {
+            ConcurrencyProfile::Low => config::ConcurrencyLimits::LOW,
+            ConcurrencyProfile::Balanced => config::ConcurrencyLimits::BALANCED,
+            ConcurrencyProfile::High => config::ConcurrencyLimits::HIGH,
+        }
+    }
+}
+
+/// Optional exports written alongside the per-file output during save.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// packs/<lang>.pack: brotli-compressed detail pages with a random-access index
+    Pack,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeaderDedupMode {
+    /// Keep headerImgUrl and drop the gallery item showing the same image
+    DropGallery,
+    /// Keep the gallery item and clear headerImgUrl
+    ClearHeader,
+}
+
+impl HeaderDedupMode {
+    pub fn dedup(self) -> config::HeaderGalleryDedup {
+        match self {
+            HeaderDedupMode::DropGallery => config::HeaderGalleryDedup::DropGalleryItem,
+            HeaderDedupMode::ClearHeader => config::HeaderGalleryDedup::ClearHeader,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AliasDetectionMode {
+    /// Only pages carrying the API's explicit redirect field
+    ExplicitOnly,
+    /// Also pages without a bulk name whose only content is one entry link (default)
+    EntryLink,
+    /// Also single entry link pages that bulk data does name
+    EntryLinkAnyName,
+}
+
+impl AliasDetectionMode {
+    pub fn detection(self) -> config::AliasDetection {
+        match self {
+            AliasDetectionMode::ExplicitOnly => config::AliasDetection::ExplicitOnly,
+            AliasDetectionMode::EntryLink => config::AliasDetection::EntryLink,
+            AliasDetectionMode::EntryLinkAnyName => config::AliasDetection::EntryLinkAnyName,
+        }
+    }
+}
+
+/// Named `DeviceIdentity` presets for the User-Agent and `x-rpc-device_*` headers.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceProfile {
+    /// Google Pixel 5, Android 11: the default
+    #[value(name = "pixel-5")]
+    Pixel5,
+    /// Google Pixel 7, Android 14
+    #[value(name = "pixel-7")]
+    Pixel7,
+    /// Samsung Galaxy S21 (SM-G991B), Android 13
+    #[value(name = "galaxy-s21")]
+    GalaxyS21,
+}
+
+impl DeviceProfile {
+    pub fn identity(self) -> config::DeviceIdentity {
+        match self {
+            DeviceProfile::Pixel5 => config::DeviceIdentity::PIXEL_5,
+            DeviceProfile::Pixel7 => config::DeviceIdentity::PIXEL_7,
+            DeviceProfile::GalaxyS21 => config::DeviceIdentity::GALAXY_S21,
+        }
+    }
+}
 
 #[derive(Parser, Debug)]
 #[command(
@@ -12,9 +111,13 @@ use std::path::PathBuf;
     about = "Fetches and transforms HoYoWiki data for Android app consumption.",
     long_about = None,
     after_help = format!("Supported languages:\n    all, {}", config::SUPPORTED_LANGS.join(", ")),
-    arg_required_else_help = true
+    arg_required_else_help = true,
+    group(clap::ArgGroup::new("test_input").args(["test_detail_file", "test_list_file"]))
 )]
 pub struct CliArgs {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(
         short, long,
         num_args = 1..,
@@ -27,6 +130,105 @@ pub struct CliArgs {
 
     #[arg(
         long,
+        help = "Preset for fast app-development runs: en-us only (unless -l is given), Characters + Weapons menus, no bulk fallback, no calendar, no merged schema, doubled concurrency. Explicit flags override the preset (e.g. --calendar, --bulk-fallback, --merged-schema, --all-menus)",
+        conflicts_with = "test_input"
+    )]
+    quick: bool,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "MENU_IDS",
+        overrides_with = "all_menus",
+        help = "Only fetch lists and details for these menu ids (comma-separated)"
+    )]
+    menus: Vec<MenuId>,
+
+    #[arg(
+        long,
+        overrides_with = "menus",
+        help = "Fetch every menu, lifting the --quick menu set"
+    )]
+    all_menus: bool,
+
+    #[arg(
+        long,
+        overrides_with = "bulk_fallback",
+        help = "Skip the cross-language bulk fallback for entries missing icons"
+    )]
+    no_bulk_fallback: bool,
+
+    #[arg(
+        long,
+        overrides_with = "no_bulk_fallback",
+        help = "Run the cross-language bulk fallback even under --quick"
+    )]
+    bulk_fallback: bool,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "LANGS",
+        help = "Languages the bulk fallback tries for missing icons (comma-separated). Defaults to every other supported language, which costs one fetch set per language; a small high-coverage subset such as en-us,zh-cn is far cheaper"
+    )]
+    icon_fallback_langs: Vec<String>,
+
+    #[arg(
+        long,
+        overrides_with = "calendar",
+        help = "Skip the calendar fetch in Phase 1"
+    )]
+    skip_calendar: bool,
+
+    #[arg(
+        long,
+        overrides_with = "skip_calendar",
+        help = "Fetch the calendar even under --quick"
+    )]
+    calendar: bool,
+
+    #[arg(
+        long,
+        overrides_with = "merged_schema",
+        help = "Skip Phase 6 merged schema generation"
+    )]
+    skip_merged_schema: bool,
+
+    #[arg(
+        long,
+        overrides_with = "skip_merged_schema",
+        help = "Generate the Phase 6 merged schema even under --quick"
+    )]
+    merged_schema: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_parser = clap::value_parser!(u64).range(config::MIN_PAGE_SIZE as u64..=config::MAX_PAGE_SIZE as u64),
+        value_name = "N",
+        help = "Items requested per list page (default 50). Pages that fail to parse are retried at half the size down to 5 to skip only the malformed items"
+    )]
+    page_size: Option<u64>,
+
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u64).range(1..=8),
+        value_name = "N",
+        help = "Multiply the list/detail/bulk/calendar concurrency limits by N"
+    )]
+    concurrency_scale: Option<u64>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "PROFILE",
+        help = "Preset list/detail/bulk/calendar concurrency limits; --concurrency-scale still multiplies them"
+    )]
+    concurrency_profile: Option<ConcurrencyProfile>,
+
+    #[arg(
+        long,
+        global = true,
         default_value = config::DEFAULT_OUT_DIR,
          value_name = "DIR_PATH",
          help = "Output directory path"
@@ -41,17 +243,488 @@ pub struct CliArgs {
     )]
     test_detail_file: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "FILE_PATH",
+        help = "Run in test mode using a local JSON file (list endpoint format)",
+        conflicts_with_all = ["languages", "test_detail_file"]
+    )]
+    test_list_file: Option<String>,
+
+    #[arg(
+        long,
+        default_value_t = config::CHARACTER_MENU_ID,
+        value_name = "MENU_ID",
+        help = "Menu id the list test file belongs to",
+        requires = "test_list_file"
+    )]
+    test_menu_id: MenuId,
+
     #[arg(
         long,
         default_value = "test_output.json",
         value_name = "OUTPUT_FILE",
         help = "Output file name for test mode",
-        requires = "test_detail_file"
+        requires = "test_input"
     )]
     test_output_file: String,
+
+    #[arg(
+        long,
+        value_name = "FILE_PATH",
+        help = "Nav endpoint response used for menu names in test mode",
+        requires = "test_detail_file"
+    )]
+    test_nav_file: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "per-lang",
+        value_name = "MODE",
+        help = "Experimental: run each language through its own nav -> lists -> details -> calendar sequence concurrently, streaming details through transform and save, instead of running phase by phase across all languages",
+        conflicts_with = "test_input"
+    )]
+    pipeline: Option<PipelineMode>,
+
+    #[arg(
+        long,
+        help = "Print the merged configuration (presets, defaults and flags) as JSON and exit without fetching",
+        conflicts_with = "test_input"
+    )]
+    print_config: bool,
+
+    #[arg(
+        long,
+        global = true,
+        default_value_t = config::MAX_RECURSION_DEPTH,
+        value_parser = clap::value_parser!(u32).range(1..),
+        value_name = "N",
+        help = "Maximum HTML nesting depth before a page's markup falls back to a flat, text-only parse"
+    )]
+    max_recursion_depth: u32,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Replace markup deeper than --max-recursion-depth that the flat parse cannot recover with a visible placeholder node instead of dropping it"
+    )]
+    depth_limit_placeholder: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Annotate each detail component with its original API component_id"
+    )]
+    debug_component_ids: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Write detail components as an ordered array of { id, type, data } instead of a map"
+    )]
+    components_as_array: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Emit rich text without <color=...> markup"
+    )]
+    strip_color: bool,
+
+    #[arg(
+        long,
+        global = true,
+        default_value_t = config::SEARCH_INDEX_KEYWORD_CHARS,
+        value_name = "N",
+        help = "Maximum keyword characters per entry in search_index/<lang>.json"
+    )]
+    search_index_chars: usize,
+
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_name = "COMPONENTS",
+        help = "Detail components whose text feeds search index keywords (comma-separated, default: baseInfo,summaryList)"
+    )]
+    search_index_components: Vec<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_name = "IDS",
+        value_parser = clap::value_parser!(EntryId).range(1..),
+        help = "After transform, write bundles/<id>.json with every language's detail page for these entry ids (comma-separated)"
+    )]
+    bundle_entries: Vec<EntryId>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "FORMATS",
+        help = "Extra exports to write during save (comma-separated)"
+    )]
+    export: Vec<ExportFormat>,
+
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_name = "FIELDS",
+        help = "Additional snake_case list filter keys to capture (comma-separated)"
+    )]
+    extra_filter_fields: Vec<String>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Count serde fallback coercions and write them to reports/serde_coercions.json"
+    )]
+    serde_telemetry: bool,
+
+    #[arg(
+        long,
+        help = "Write aggregate output statistics to reports/statistics.json"
+    )]
+    stats_report: bool,
+
+    #[arg(
+        long,
+        help = "List CustomEntry/CustomPost references left without a name in reports/unresolved_refs.json"
+    )]
+    report_unresolved: bool,
+
+    #[arg(
+        long,
+        help = "Write per-language entry ids whose detail fetch errored or came back empty to reports/failed_ids.json"
+    )]
+    save_failed_ids: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Add a derived `progression` component: constellations on character pages, refinement ranks on weapon pages"
+    )]
+    derive_progression: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Add an `eventWindow` { start, end } to event pages whose customize text holds exactly one date range; pages with several or invalid ranges go to reports/event_window_conflicts.json"
+    )]
+    derive_event_windows: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_name = "MENU_IDS",
+        help = "Menus scanned by --derive-event-windows (comma-separated; default: 39, Events)"
+    )]
+    event_menu_ids: Vec<MenuId>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Convert HTML in bulk entry descriptions to plain text (page desc, list desc and CustomEntry desc); raw strings are kept by default"
+    )]
+    parse_bulk_desc: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Also write the untouched API filter_values as rawFilters on list items and detail pages"
+    )]
+    keep_raw_filters: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Add filterVocabulary to each list file: every filter key mapped to its sorted distinct values"
+    )]
+    emit_filter_vocabulary: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "MODE",
+        help = "When a detail page's header image is also a gallery image, drop the gallery item or clear the header"
+    )]
+    header_gallery_dedup: Option<HeaderDedupMode>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "MODE",
+        help = "Which detail pages are written as aliases (aliasOf) of another entry instead of their own content"
+    )]
+    alias_detection: Option<AliasDetectionMode>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Keep components of abstract detail modules, which the web wiki does not render"
+    )]
+    include_abstract_modules: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Add a plain-text `*Text` field next to every rich-text node list in detail components (componentsText for customize/dropMaterial)"
+    )]
+    flatten_html: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Replace whole node lists repeated within a detail page with a `Ref` node into a per-page nodeDefs table (repeats inside otherwise different lists are kept)"
+    )]
+    intern_nodes: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "TEMPLATE",
+        value_parser = parse_entry_url_template,
+        help = "Add a `link` to list items, detail pages and CustomEntry/CustomPost nodes built from this template; `{id}` and `{lang}` are substituted URL-encoded (e.g. https://wiki.hoyolab.com/pc/genshin/entry/{id}?lang={lang})"
+    )]
+    entry_url_template: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        default_value_t = config::OVERSIZED_COMPONENT_KB as u64,
+        value_parser = clap::value_parser!(u64).range(1..),
+        value_name = "KB",
+        help = "Warn about and report (reports/oversized_components.json) detail components larger than this when serialized"
+    )]
+    component_size_warn_kb: u64,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Cut Customize/DropMaterial components over --component-size-warn-kb down to their first 200 nodes, ending with a truncation marker"
+    )]
+    truncate_oversized: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "Write every unrecognized component payload to DIR/<component_id>/<page_id>_<lang>.json"
+    )]
+    dump_unknown_components: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Fingerprint the structure of a sample of raw API responses, compare with the previous run stored in DIR and write reports/schema_drift.json"
+    )]
+    schema_watch: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Reuse each language's resolved bulk data from DIR/<lang>.json when it is recent and covers every needed id, skipping the bulk fetch; refreshed caches are written back"
+    )]
+    bulk_cache: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "N|tab",
+        value_parser = parse_json_indent,
+        help = "Indent for pretty-printed JSON output: a number of spaces (0-8) or `tab`. Defaults to 2 spaces"
+    )]
+    indent: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_parser = clap::value_parser!(u64).range(1..),
+        value_name = "BYTES",
+        help = "Write JSON files compact instead of pretty-printed when the pretty form would exceed BYTES. Off by default: every file is pretty-printed"
+    )]
+    compact_threshold: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        value_parser = parse_api_base_url,
+        help = "Send API requests to this base URL instead of the public wiki API (e.g. a local mock server)"
+    )]
+    api_base_url: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        default_value_t = config::MAX_RESPONSE_MB,
+        value_parser = clap::value_parser!(u64).range(1..),
+        value_name = "MB",
+        help = "Abort an API response whose body is larger than this"
+    )]
+    max_response_mb: u64,
+
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_name = "ENDPOINT=MB",
+        value_parser = parse_endpoint_limit,
+        help = "Per-endpoint response size cap overriding --max-response-mb (endpoints: nav, list, detail, bulk, calendar; e.g. bulk=128)"
+    )]
+    max_response_mb_for: Vec<(String, u64)>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Also retry POST requests (the list endpoint) whose response body is malformed JSON; GET requests always retry"
+    )]
+    retry_post_parse_failures: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Fail a fetch whose x-rpc-language response header names a different language than requested, instead of only warning"
+    )]
+    strict_language: bool,
+
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        default_missing_value = "50",
+        value_parser = clap::value_parser!(u64).range(1..),
+        value_name = "N",
+        help = "Abort a language's transform once more than N detail pages fail to transform (default N: 50); the language is reported as a Transform failure"
+    )]
+    fail_fast: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "STRING",
+        value_parser = parse_user_agent,
+        help = "Replace the User-Agent header sent with every API request"
+    )]
+    user_agent: Option<HeaderValue>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "PROFILE",
+        help = "Device identity for the default User-Agent and the x-rpc-device_* headers"
+    )]
+    device_profile: Option<DeviceProfile>,
+
+    #[arg(
+        long,
+        help = "Treat pre-detail consistency warnings as errors and stop before fetching details"
+    )]
+    abort_on_consistency_warning: bool,
+
+    #[arg(
+        long,
+        help = "Abort the run when any requested language's navigation fetch fails or is empty, instead of skipping that language"
+    )]
+    require_all_langs: bool,
+
+    #[arg(
+        long,
+        help = "Skip the Phase 0 connectivity check against the navigation and bulk endpoints"
+    )]
+    skip_preflight: bool,
+
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u64).range(1..),
+        value_name = "SECONDS",
+        help = "Stop starting new work after this many seconds, save what finished, and exit with code 124"
+    )]
+    max_runtime: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE_PATH",
+        help = "Also write plain (uncolored) log output to this file, rotating by size"
+    )]
+    log_file: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        default_value_t = config::DEFAULT_LOG_FILE_MAX_MB,
+        value_parser = clap::value_parser!(u64).range(1..),
+        value_name = "MB",
+        help = "Rotate the --log-file once it grows past this many megabytes"
+    )]
+    log_file_max_mb: u64,
+
+    #[arg(
+        long,
+        global = true,
+        default_value_t = config::DEFAULT_LOG_FILE_KEEP,
+        value_name = "N",
+        help = "Number of rotated --log-file copies to keep"
+    )]
+    log_file_keep: usize,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    #[command(
+        about = "Remove generated output subdirectories under --out-dir, keeping the list manifest and run history in reports/"
+    )]
+    Clean {
+        #[arg(short, long, help = "Skip the confirmation prompt")]
+        yes: bool,
+    },
+    #[command(about = "Serve the files under --out-dir over HTTP (with CORS) for local preview")]
+    Serve {
+        #[arg(long, default_value_t = config::DEFAULT_SERVE_PORT, help = "Port to listen on")]
+        port: u16,
+        #[arg(
+            long,
+            default_value = "127.0.0.1",
+            help = "Address to bind; use 0.0.0.0 to expose the output on the network"
+        )]
+        host: IpAddr,
+    },
+    #[command(
+        about = "Check a finished output directory: parse every file, cross-check indexes and manifests, exit non-zero on problems"
+    )]
+    Verify {
+        #[arg(help = "Directory to verify [default: --out-dir]")]
+        dir: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Also write the report as JSON to FILE"
+        )]
+        report: Option<PathBuf>,
+    },
+    #[command(hide = true, about = "Print a shell completion script to stdout")]
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
 }
 
 impl CliArgs {
+    pub fn get_command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+
     pub fn get_out_dir(&self) -> PathBuf {
         PathBuf::from(&self.out_dir)
     }
@@ -60,16 +733,353 @@ impl CliArgs {
         self.test_detail_file.as_deref().map(PathBuf::from)
     }
 
+    pub fn get_test_list_file(&self) -> Option<PathBuf> {
+        self.test_list_file.as_deref().map(PathBuf::from)
+    }
+
+    pub fn get_test_menu_id(&self) -> MenuId {
+        self.test_menu_id
+    }
+
     pub fn get_test_output_file(&self) -> PathBuf {
         PathBuf::from(&self.test_output_file)
     }
 
-    pub fn get_languages(&self) -> AppResult<Vec<String>> {
-        if self.test_detail_file.is_some() {
+    pub fn get_test_nav_file(&self) -> Option<PathBuf> {
+        self.test_nav_file.as_deref().map(PathBuf::from)
+    }
+
+    pub fn get_page_size(&self) -> Option<usize> {
+        self.page_size.map(|size| size as usize)
+    }
+
+    pub fn get_max_recursion_depth(&self) -> u32 {
+        self.max_recursion_depth
+    }
+
+    pub fn depth_limit_placeholder(&self) -> bool {
+        self.depth_limit_placeholder
+    }
+
+    pub fn debug_component_ids(&self) -> bool {
+        self.debug_component_ids
+    }
+
+    pub fn components_as_array(&self) -> bool {
+        self.components_as_array
+    }
+
+    pub fn strip_color(&self) -> bool {
+        self.strip_color
+    }
+
+    /// Resolves `--quick` and the individual overrides; explicit flags win over the
+    /// preset, and of two opposing flags the last one given wins.
+    pub fn runtime_settings(&self) -> config::RuntimeSettings {
+        let mut settings = if self.quick {
+            config::RuntimeSettings::quick()
+        } else {
+            config::RuntimeSettings::default()
+        };
+        if self.all_menus {
+            settings.menu_ids = None;
+        } else if !self.menus.is_empty() {
+            settings.menu_ids = Some(self.menus.clone());
+        }
+        if self.no_bulk_fallback {
+            settings.bulk_fallback = false;
+        } else if self.bulk_fallback {
+            settings.bulk_fallback = true;
+        }
+        if self.skip_calendar {
+            settings.skip_calendar = true;
+        } else if self.calendar {
+            settings.skip_calendar = false;
+        }
+        if self.skip_merged_schema {
+            settings.skip_merged_schema = true;
+        } else if self.merged_schema {
+            settings.skip_merged_schema = false;
+        }
+        if let Some(profile) = self.concurrency_profile {
+            settings.concurrency = profile.limits();
+        }
+        if let Some(scale) = self.concurrency_scale {
+            settings.concurrency_scale = scale as usize;
+        }
+        settings
+    }
+
+    pub fn get_icon_fallback_langs(&self) -> Vec<LangCode> {
+        let mut langs: Vec<LangCode> = Vec::new();
+        let mut invalid_langs = Vec::new();
+        for raw in &self.icon_fallback_langs {
+            match LangCode::parse(raw) {
+                Ok(lang) if !langs.contains(&lang) => langs.push(lang),
+                Ok(_) => {}
+                Err(_) => invalid_langs.push(raw.clone()),
+            }
+        }
+        if !invalid_langs.is_empty() {
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "Ignoring unsupported icon fallback languages: {}",
+                    invalid_langs.join(", ")
+                ),
+            );
+        }
+        langs
+    }
+
+    pub fn get_search_index_chars(&self) -> usize {
+        self.search_index_chars
+    }
+
+    pub fn get_search_index_components(&self) -> Vec<String> {
+        self.search_index_components
+            .iter()
+            .map(|component| component.trim().to_string())
+            .filter(|component| !component.is_empty())
+            .collect()
+    }
+
+    pub fn bundle_entries(&self) -> Vec<EntryId> {
+        let mut ids = self.bundle_entries.clone();
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+        ids
+    }
+
+    pub fn export_pack(&self) -> bool {
+        self.export.contains(&ExportFormat::Pack)
+    }
+
+    pub fn get_extra_filter_fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        let mut invalid_fields = Vec::new();
+
+        for raw in &self.extra_filter_fields {
+            let field = raw.trim().to_lowercase();
+            if field.is_empty() {
+                continue;
+            }
+            if field
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            {
+                fields.push(field);
+            } else {
+                invalid_fields.push(raw.clone());
+            }
+        }
+
+        if !invalid_fields.is_empty() {
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "Ignoring invalid extra filter fields (expected snake_case): {}",
+                    invalid_fields.join(", ")
+                ),
+            );
+        }
+        fields
+    }
+
+    pub fn get_log_file_config(&self) -> Option<LogFileConfig> {
+        self.log_file.as_deref().map(|path| LogFileConfig {
+            path: PathBuf::from(path),
+            max_bytes: self.log_file_max_mb.saturating_mul(1024 * 1024),
+            keep: self.log_file_keep,
+        })
+    }
+
+    pub fn serde_telemetry(&self) -> bool {
+        self.serde_telemetry
+    }
+
+    pub fn stats_report(&self) -> bool {
+        self.stats_report
+    }
+
+    pub fn report_unresolved(&self) -> bool {
+        self.report_unresolved
+    }
+
+    pub fn save_failed_ids(&self) -> bool {
+        self.save_failed_ids
+    }
+
+    pub fn derive_progression(&self) -> bool {
+        self.derive_progression
+    }
+
+    pub fn derive_event_windows(&self) -> bool {
+        self.derive_event_windows
+    }
+
+    pub fn event_menu_ids(&self) -> Vec<MenuId> {
+        self.event_menu_ids.clone()
+    }
+
+    pub fn parse_bulk_desc(&self) -> bool {
+        self.parse_bulk_desc
+    }
+
+    pub fn keep_raw_filters(&self) -> bool {
+        self.keep_raw_filters
+    }
+
+    pub fn emit_filter_vocabulary(&self) -> bool {
+        self.emit_filter_vocabulary
+    }
+
+    pub fn header_gallery_dedup(&self) -> Option<config::HeaderGalleryDedup> {
+        self.header_gallery_dedup.map(HeaderDedupMode::dedup)
+    }
+
+    pub fn alias_detection(&self) -> Option<config::AliasDetection> {
+        self.alias_detection.map(AliasDetectionMode::detection)
+    }
+
+    pub fn include_abstract_modules(&self) -> bool {
+        self.include_abstract_modules
+    }
+
+    pub fn intern_nodes(&self) -> bool {
+        self.intern_nodes
+    }
+
+    pub fn flatten_html(&self) -> bool {
+        self.flatten_html
+    }
+
+    pub fn entry_url_template(&self) -> Option<String> {
+        self.entry_url_template.clone()
+    }
+
+    pub fn component_size_warn_kb(&self) -> usize {
+        self.component_size_warn_kb as usize
+    }
+
+    pub fn truncate_oversized(&self) -> bool {
+        self.truncate_oversized
+    }
+
+    pub fn schema_watch_dir(&self) -> Option<PathBuf> {
+        self.schema_watch.clone()
+    }
+
+    pub fn bulk_cache_dir(&self) -> Option<PathBuf> {
+        self.bulk_cache.clone()
+    }
+
+    pub fn dump_unknown_components_dir(&self) -> Option<PathBuf> {
+        self.dump_unknown_components.clone()
+    }
+
+    pub fn api_base_url(&self) -> Option<String> {
+        self.api_base_url.clone()
+    }
+
+    pub fn max_response_mb(&self) -> (u64, Vec<(String, u64)>) {
+        (self.max_response_mb, self.max_response_mb_for.clone())
+    }
+
+    pub fn retry_post_parse_failures(&self) -> bool {
+        self.retry_post_parse_failures
+    }
+
+    pub fn strict_language(&self) -> bool {
+        self.strict_language
+    }
+
+    pub fn fail_fast(&self) -> Option<usize> {
+        self.fail_fast.map(|limit| limit as usize)
+    }
+
+    pub fn user_agent(&self) -> Option<HeaderValue> {
+        self.user_agent.clone()
+    }
+
+    pub fn device_identity(&self) -> Option<config::DeviceIdentity> {
+        self.device_profile.map(DeviceProfile::identity)
+    }
+
+    pub fn json_indent(&self) -> Option<Vec<u8>> {
+        self.indent.clone().map(String::into_bytes)
+    }
+
+    pub fn compact_threshold(&self) -> Option<u64> {
+        self.compact_threshold
+    }
+
+    pub fn abort_on_consistency_warning(&self) -> bool {
+        self.abort_on_consistency_warning
+    }
+
+    pub fn require_all_langs(&self) -> bool {
+        self.require_all_langs
+    }
+
+    pub fn skip_preflight(&self) -> bool {
+        self.skip_preflight
+    }
+
+    pub fn get_max_runtime(&self) -> Option<Duration> {
+        self.max_runtime.map(Duration::from_secs)
+    }
+
+    pub fn print_config(&self) -> bool {
+        self.print_config
+    }
+
+    pub fn use_pipeline(&self) -> bool {
+        matches!(self.pipeline, Some(PipelineMode::PerLang))
+    }
+
+    /// Flags whose output needs every language's transformed data at once, which
+    /// `--pipeline` never holds. Checked here rather than with `conflicts_with`
+    /// because most of them are global and `--pipeline` is not.
+    pub fn pipeline_conflicts(&self) -> Vec<&'static str> {
+        [
+            ("--export", !self.export.is_empty()),
+            ("--bundle-entries", !self.bundle_entries.is_empty()),
+            ("--stats-report", self.stats_report),
+            ("--report-unresolved", self.report_unresolved),
+            // Pipeline mode resolves bulk data per detail batch, never a whole language.
+            ("--bulk-cache", self.bulk_cache.is_some()),
+            (
+                "--search-index-chars",
+                self.search_index_chars != config::SEARCH_INDEX_KEYWORD_CHARS,
+            ),
+            (
+                "--search-index-components",
+                !self.search_index_components.is_empty(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(flag, set)| set.then_some(flag))
+        .collect()
+    }
+
+    pub fn get_languages(&self) -> AppResult<Vec<LangCode>> {
+        if self.test_detail_file.is_some() || self.test_list_file.is_some() {
             if !self.languages.is_empty() {
-                log(LogLevel::Warning, "Ignoring specified languages (--languages/-l) because --test-detail-file is active.");
+                log(
+                    LogLevel::Warning,
+                    "Ignoring specified languages (--languages/-l) because test mode is active.",
+                );
             }
-            return Ok(vec!["test-lang".to_string()]);
+            return Ok(Vec::new());
+        }
+
+        if self.languages.is_empty() && self.quick {
+            log(
+                LogLevel::Info,
+                &format!("Quick mode: defaulting to [{}].", config::QUICK_LANG),
+            );
+            return Ok(vec![LangCode::parse(config::QUICK_LANG)?]);
         }
 
         if self.languages.is_empty() {
@@ -89,19 +1099,17 @@ impl CliArgs {
                 LogLevel::Info,
                 "Processing all supported languages requested.",
             );
-            let mut sorted_langs: Vec<String> = config::SUPPORTED_LANGS.to_vec();
+            let mut sorted_langs = LangCode::all_supported();
             sorted_langs.sort_unstable();
             Ok(sorted_langs)
         } else {
-            let supported_set: HashSet<String> = config::SUPPORTED_LANGS.iter().cloned().collect();
             let mut valid_langs = Vec::new();
             let mut invalid_langs = Vec::new();
 
             for lang in inputs {
-                if supported_set.contains(&lang) {
-                    valid_langs.push(lang);
-                } else {
-                    invalid_langs.push(lang);
+                match LangCode::parse(&lang) {
+                    Ok(code) => valid_langs.push(code),
+                    Err(_) => invalid_langs.push(lang),
                 }
             }
 
@@ -122,11 +1130,150 @@ impl CliArgs {
             }
 
             valid_langs.sort_unstable();
+            valid_langs.dedup();
+            let lang_names: Vec<&str> = valid_langs.iter().map(LangCode::as_str).collect();
             log(
                 LogLevel::Info,
-                &format!("Processing specified languages: {}", valid_langs.join(", ")),
+                &format!("Processing specified languages: {}", lang_names.join(", ")),
             );
             Ok(valid_langs)
         }
     }
 }
+
+fn parse_user_agent(raw: &str) -> Result<HeaderValue, String> {
+    if raw.trim().is_empty() {
+        return Err("the user agent must not be empty".to_string());
+    }
+    HeaderValue::from_str(raw)
+        .map_err(|_| "not a valid header value (control characters are not allowed)".to_string())
+}
+
+fn parse_api_base_url(raw: &str) -> Result<String, String> {
+    let url = raw.trim();
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(url.to_string())
+    } else {
+        Err("expected an http:// or https:// URL".to_string())
+    }
+}
+
+fn parse_endpoint_limit(raw: &str) -> Result<(String, u64), String> {
+    let (endpoint, mb) = raw
+        .split_once('=')
+        .ok_or_else(|| "expected ENDPOINT=MB (e.g. bulk=128)".to_string())?;
+    let endpoint = endpoint.trim();
+    if !config::API_ENDPOINT_KEYS.contains(&endpoint) {
+        return Err(format!(
+            "unknown endpoint `{}` (expected one of: {})",
+            endpoint,
+            config::API_ENDPOINT_KEYS.join(", ")
+        ));
+    }
+    match mb.trim().parse::<u64>() {
+        Ok(mb) if mb > 0 => Ok((endpoint.to_string(), mb)),
+        _ => Err(format!("`{}` is not a positive number of MB", mb.trim())),
+    }
+}
+
+fn parse_entry_url_template(raw: &str) -> Result<String, String> {
+    let template = raw.trim();
+    if template.contains("{id}") {
+        Ok(template.to_string())
+    } else {
+        Err("the template must contain an `{id}` placeholder".to_string())
+    }
+}
+
+fn parse_json_indent(raw: &str) -> Result<String, String> {
+    if raw.eq_ignore_ascii_case("tab") {
+        return Ok("\t".to_string());
+    }
+    match raw.parse::<usize>() {
+        Ok(n) if n <= config::MAX_JSON_INDENT_SPACES => Ok(" ".repeat(n)),
+        _ => Err(format!(
+            "expected a number of spaces (0-{}) or `tab`",
+            config::MAX_JSON_INDENT_SPACES
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(args: &[&str]) -> config::RuntimeSettings {
+        let argv = ["wiki_update"].iter().chain(args);
+        CliArgs::try_parse_from(argv).unwrap().runtime_settings()
+    }
+
+    #[test]
+    fn quick_preset_without_overrides() {
+        assert_eq!(settings(&["--quick"]), config::RuntimeSettings::quick());
+        assert_eq!(
+            settings(&["-l", "en-us"]),
+            config::RuntimeSettings::default()
+        );
+    }
+
+    #[test]
+    fn positive_flags_re_enable_what_quick_turns_off() {
+        let effective = settings(&[
+            "--quick",
+            "--calendar",
+            "--bulk-fallback",
+            "--merged-schema",
+            "--all-menus",
+        ]);
+        assert_eq!(
+            effective,
+            config::RuntimeSettings {
+                concurrency_scale: config::QUICK_CONCURRENCY_SCALE,
+                ..config::RuntimeSettings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn negative_flags_apply_without_quick() {
+        let effective = settings(&[
+            "-l",
+            "en-us",
+            "--skip-calendar",
+            "--no-bulk-fallback",
+            "--skip-merged-schema",
+            "--menus",
+            "2,4",
+        ]);
+        assert!(effective.skip_calendar);
+        assert!(!effective.bulk_fallback);
+        assert!(effective.skip_merged_schema);
+        assert_eq!(effective.menu_ids, Some(vec![2, 4]));
+    }
+
+    #[test]
+    fn last_of_two_opposing_flags_wins() {
+        let effective = settings(&["--quick", "--skip-calendar", "--calendar"]);
+        assert!(!effective.skip_calendar);
+        let effective = settings(&["--quick", "--calendar", "--skip-calendar"]);
+        assert!(effective.skip_calendar);
+
+        let effective = settings(&["--quick", "--menus", "2", "--all-menus"]);
+        assert_eq!(effective.menu_ids, None);
+        let effective = settings(&["--quick", "--all-menus", "--menus", "2"]);
+        assert_eq!(effective.menu_ids, Some(vec![2]));
+    }
+
+    #[test]
+    fn bulk_cache_conflicts_with_pipeline() {
+        let conflicts = |args: &[&str]| {
+            let argv = ["wiki_update"].iter().chain(args);
+            CliArgs::try_parse_from(argv).unwrap().pipeline_conflicts()
+        };
+        assert!(conflicts(&["-l", "en-us", "--pipeline"]).is_empty());
+        assert_eq!(
+            conflicts(&["-l", "en-us", "--pipeline", "--bulk-cache", "cache"]),
+            ["--bulk-cache"]
+        );
+    }
+}
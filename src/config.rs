@@ -1,34 +1,413 @@
-use once_cell::sync::Lazy;
+use crate::error::{AppError, AppResult};
+use crate::model::common::{EntryId, LangCode, MenuId};
+use once_cell::sync::{Lazy, OnceCell};
 use regex::Regex;
 use reqwest::header::{
     HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE, ORIGIN, REFERER, USER_AGENT,
 };
-use std::collections::{HashMap, HashSet};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 pub const DEFAULT_OUT_DIR: &str = "./generated_wiki_data";
+pub const DEFAULT_LOG_FILE_MAX_MB: u64 = 50;
+pub const DEFAULT_LOG_FILE_KEEP: usize = 3;
+pub const DEFAULT_SERVE_PORT: u16 = 8080;
+pub const OUTPUT_SUBDIRS: &[&str] = &[
+    "navigation",
+    "list",
+    "detail",
+    "calendar",
+    "bulk",
+    "merged",
+    "reports",
+    "taxonomy",
+    "search_index",
+    "bundles",
+    "packs",
+];
+/// (subdir, file) pairs `clean` leaves in place: the list manifest (menu rename
+/// detection) and the run history (consistency gate) compare against earlier runs.
+pub const CLEAN_KEPT_FILES: &[(&str, &str)] = &[
+    ("reports", "list_manifest.json"),
+    ("reports", "run_report_history.json"),
+];
+pub const BULK_FILE_WARN_SIZE_MB: f64 = 8.0;
+pub const OVERSIZED_COMPONENT_KB: usize = 256;
+// With --truncate-oversized, Customize/DropMaterial keep this many top-level nodes.
+pub const OVERSIZED_NODE_LIMIT: usize = 200;
+static OVERSIZED_COMPONENT_BYTES: AtomicUsize = AtomicUsize::new(OVERSIZED_COMPONENT_KB * 1024);
+static TRUNCATE_OVERSIZED: AtomicBool = AtomicBool::new(false);
+
+pub fn oversized_component_bytes() -> usize {
+    OVERSIZED_COMPONENT_BYTES.load(Ordering::Relaxed)
+}
+
+pub fn set_oversized_component_kb(kb: usize) {
+    OVERSIZED_COMPONENT_BYTES.store(kb * 1024, Ordering::Relaxed);
+}
+
+pub fn truncate_oversized() -> bool {
+    TRUNCATE_OVERSIZED.load(Ordering::Relaxed)
+}
+
+pub fn set_truncate_oversized(enabled: bool) {
+    TRUNCATE_OVERSIZED.store(enabled, Ordering::Relaxed);
+}
 pub const MAX_LIST_CONCUR: usize = 20;
 pub const MAX_DETAIL_CONCUR: usize = 30;
 pub const MAX_BULK_CONCUR: usize = 50;
 pub const MAX_CALENDAR_CONCUR: usize = 5;
 
+/// Base list/detail/bulk/calendar concurrency limits selected by `--concurrency-profile`;
+/// `--concurrency-scale` multiplies whichever set is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimits {
+    pub list: usize,
+    pub detail: usize,
+    pub bulk: usize,
+    pub calendar: usize,
+}
+
+impl ConcurrencyLimits {
+    /// `low`: list 5, detail 8, bulk 10, calendar 2.
+    pub const LOW: ConcurrencyLimits = ConcurrencyLimits {
+        list: 5,
+        detail: 8,
+        bulk: 10,
+        calendar: 2,
+    };
+    /// `balanced` (default): list 20, detail 30, bulk 50, calendar 5.
+    pub const BALANCED: ConcurrencyLimits = ConcurrencyLimits {
+        list: MAX_LIST_CONCUR,
+        detail: MAX_DETAIL_CONCUR,
+        bulk: MAX_BULK_CONCUR,
+        calendar: MAX_CALENDAR_CONCUR,
+    };
+    /// `high`: list 40, detail 60, bulk 100, calendar 10.
+    pub const HIGH: ConcurrencyLimits = ConcurrencyLimits {
+        list: 40,
+        detail: 60,
+        bulk: 100,
+        calendar: 10,
+    };
+}
+
+// Menu ids behind `MenuCategory`; EVENTS_MENU_ID sits with --derive-event-windows.
+pub const CHARACTER_MENU_ID: MenuId = 2;
+pub const WEAPON_MENU_ID: MenuId = 4;
+pub const ARTIFACT_MENU_ID: MenuId = 5;
+pub const ENEMY_MENU_ID: MenuId = 7;
+pub const MATERIAL_MENU_ID: MenuId = 9;
+pub const TCG_MENU_ID: MenuId = 231;
+
+pub const QUICK_LANG: &str = "en-us";
+pub const QUICK_MENU_IDS: [MenuId; 2] = [CHARACTER_MENU_ID, WEAPON_MENU_ID];
+pub const QUICK_CONCURRENCY_SCALE: usize = 2;
+
+/// Run-wide settings that presets and CLI flags resolve into. A run takes them
+/// through `FetchOptions`; `apply` publishes them to the accessors below for
+/// the CLI and `--print-config`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeSettings {
+    pub menu_ids: Option<Vec<MenuId>>,
+    pub bulk_fallback: bool,
+    pub skip_calendar: bool,
+    pub skip_merged_schema: bool,
+    pub concurrency: ConcurrencyLimits,
+    pub concurrency_scale: usize,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        RuntimeSettings {
+            menu_ids: None,
+            bulk_fallback: true,
+            skip_calendar: false,
+            skip_merged_schema: false,
+            concurrency: ConcurrencyLimits::BALANCED,
+            concurrency_scale: 1,
+        }
+    }
+}
+
+impl RuntimeSettings {
+    /// The `--quick` preset: a small menu set, no bulk fallback, calendar and
+    /// merged schema skipped, and higher concurrency.
+    pub fn quick() -> Self {
+        RuntimeSettings {
+            menu_ids: Some(QUICK_MENU_IDS.to_vec()),
+            bulk_fallback: false,
+            skip_calendar: true,
+            skip_merged_schema: true,
+            concurrency: ConcurrencyLimits::BALANCED,
+            concurrency_scale: QUICK_CONCURRENCY_SCALE,
+        }
+    }
+
+    /// The settings last published with [`apply`](Self::apply), or the defaults.
+    pub fn current() -> Self {
+        RuntimeSettings {
+            menu_ids: menu_allowlist().map(<[MenuId]>::to_vec),
+            bulk_fallback: bulk_fallback(),
+            skip_calendar: skip_calendar(),
+            skip_merged_schema: skip_merged_schema(),
+            concurrency: ConcurrencyLimits {
+                list: LIST_CONCUR.load(Ordering::Relaxed),
+                detail: DETAIL_CONCUR.load(Ordering::Relaxed),
+                bulk: BULK_CONCUR.load(Ordering::Relaxed),
+                calendar: CALENDAR_CONCUR.load(Ordering::Relaxed),
+            },
+            concurrency_scale: CONCURRENCY_SCALE.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Publishes the settings to the process-wide accessors below. The menu
+    /// allowlist can only be set once per process; a different one is an error.
+    pub fn apply(&self) -> AppResult<()> {
+        if let Some(menu_ids) = &self.menu_ids {
+            let current = MENU_ALLOWLIST.get_or_init(|| menu_ids.clone());
+            if current != menu_ids {
+                return Err(AppError::ConfigError(format!(
+                    "Menu allowlist is already set to {:?}; cannot change it to {:?}",
+                    current, menu_ids
+                )));
+            }
+        }
+        BULK_FALLBACK.store(self.bulk_fallback, Ordering::Relaxed);
+        SKIP_CALENDAR.store(self.skip_calendar, Ordering::Relaxed);
+        SKIP_MERGED_SCHEMA.store(self.skip_merged_schema, Ordering::Relaxed);
+        LIST_CONCUR.store(self.concurrency.list.max(1), Ordering::Relaxed);
+        DETAIL_CONCUR.store(self.concurrency.detail.max(1), Ordering::Relaxed);
+        BULK_CONCUR.store(self.concurrency.bulk.max(1), Ordering::Relaxed);
+        CALENDAR_CONCUR.store(self.concurrency.calendar.max(1), Ordering::Relaxed);
+        CONCURRENCY_SCALE.store(self.concurrency_scale.max(1), Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn menu_allowed(&self, menu_id: MenuId) -> bool {
+        self.menu_ids
+            .as_ref()
+            .is_none_or(|menu_ids| menu_ids.contains(&menu_id))
+    }
+
+    pub fn list_concurrency(&self) -> usize {
+        self.scaled(self.concurrency.list)
+    }
+
+    pub fn detail_concurrency(&self) -> usize {
+        self.scaled(self.concurrency.detail)
+    }
+
+    pub fn bulk_concurrency(&self) -> usize {
+        self.scaled(self.concurrency.bulk)
+    }
+
+    pub fn calendar_concurrency(&self) -> usize {
+        self.scaled(self.concurrency.calendar)
+    }
+
+    fn scaled(&self, limit: usize) -> usize {
+        limit.max(1) * self.concurrency_scale.max(1)
+    }
+}
+
+static MENU_ALLOWLIST: OnceCell<Vec<MenuId>> = OnceCell::new();
+static BULK_FALLBACK: AtomicBool = AtomicBool::new(true);
+static SKIP_CALENDAR: AtomicBool = AtomicBool::new(false);
+static SKIP_MERGED_SCHEMA: AtomicBool = AtomicBool::new(false);
+static LIST_CONCUR: AtomicUsize = AtomicUsize::new(MAX_LIST_CONCUR);
+static DETAIL_CONCUR: AtomicUsize = AtomicUsize::new(MAX_DETAIL_CONCUR);
+static BULK_CONCUR: AtomicUsize = AtomicUsize::new(MAX_BULK_CONCUR);
+static CALENDAR_CONCUR: AtomicUsize = AtomicUsize::new(MAX_CALENDAR_CONCUR);
+static CONCURRENCY_SCALE: AtomicUsize = AtomicUsize::new(1);
+
+pub fn menu_allowed(menu_id: MenuId) -> bool {
+    MENU_ALLOWLIST
+        .get()
+        .is_none_or(|menu_ids| menu_ids.contains(&menu_id))
+}
+
+pub fn menu_allowlist() -> Option<&'static [MenuId]> {
+    MENU_ALLOWLIST.get().map(Vec::as_slice)
+}
+
+pub fn bulk_fallback() -> bool {
+    BULK_FALLBACK.load(Ordering::Relaxed)
+}
+
+static ICON_FALLBACK_LANGS: OnceCell<Vec<LangCode>> = OnceCell::new();
+
+pub fn set_icon_fallback_langs(langs: &[LangCode]) {
+    if !langs.is_empty() {
+        let _ = ICON_FALLBACK_LANGS.set(langs.to_vec());
+    }
+}
+
+/// Languages tried for entries whose primary bulk data lacks an icon: every other
+/// supported language unless `--icon-fallback-langs` narrowed the set.
+pub fn icon_fallback_langs_for(primary: &str) -> Vec<LangCode> {
+    ICON_FALLBACK_LANGS
+        .get()
+        .cloned()
+        .unwrap_or_else(LangCode::all_supported)
+        .into_iter()
+        .filter(|lang| lang.as_str() != primary)
+        .collect()
+}
+
+pub fn skip_calendar() -> bool {
+    SKIP_CALENDAR.load(Ordering::Relaxed)
+}
+
+pub fn skip_merged_schema() -> bool {
+    SKIP_MERGED_SCHEMA.load(Ordering::Relaxed)
+}
+
+pub fn list_concurrency() -> usize {
+    LIST_CONCUR.load(Ordering::Relaxed) * CONCURRENCY_SCALE.load(Ordering::Relaxed)
+}
+
+pub fn detail_concurrency() -> usize {
+    DETAIL_CONCUR.load(Ordering::Relaxed) * CONCURRENCY_SCALE.load(Ordering::Relaxed)
+}
+
+pub fn bulk_concurrency() -> usize {
+    BULK_CONCUR.load(Ordering::Relaxed) * CONCURRENCY_SCALE.load(Ordering::Relaxed)
+}
+
+pub fn calendar_concurrency() -> usize {
+    CALENDAR_CONCUR.load(Ordering::Relaxed) * CONCURRENCY_SCALE.load(Ordering::Relaxed)
+}
+
 pub const HTTP_TIMEOUT_SECONDS: u64 = 35;
 pub const HTTP_CONNECT_TIMEOUT: u64 = 20;
 pub const MAX_RETRIES: u32 = 3;
 pub const RETRY_DELAY_BASE_SECS: f32 = 1.5;
+static RETRY_POST_PARSE_FAILURES: AtomicBool = AtomicBool::new(false);
+
+pub fn retry_post_parse_failures() -> bool {
+    RETRY_POST_PARSE_FAILURES.load(Ordering::Relaxed)
+}
+
+pub fn set_retry_post_parse_failures(enabled: bool) {
+    RETRY_POST_PARSE_FAILURES.store(enabled, Ordering::Relaxed);
+}
 
 const BASE_API_URL: &str = "https://sg-wiki-api-static.hoyolab.com/hoyowiki/genshin/wapi";
-pub const PAGE_SIZE: i64 = 50;
+// Default for `ApiClient::new`; a client built with `with_base_url` ignores it.
+static API_BASE_URL: OnceCell<String> = OnceCell::new();
+
+/// Sets the process-wide API base URL once; a different URL later is an error.
+pub fn set_api_base_url(url: String) -> AppResult<()> {
+    let url = url.trim_end_matches('/').to_string();
+    let current = API_BASE_URL.get_or_init(|| url.clone());
+    if *current != url {
+        return Err(AppError::ConfigError(format!(
+            "API base URL is already set to {}; cannot change it to {}",
+            current, url
+        )));
+    }
+    Ok(())
+}
+
+pub fn api_base_url() -> &'static str {
+    API_BASE_URL.get().map_or(BASE_API_URL, String::as_str)
+}
+pub const PAGE_SIZE: usize = 50;
+// Smallest page a list fetch halves down to when isolating an unparsable window.
+pub const MIN_PAGE_SIZE: usize = 5;
+pub const MAX_PAGE_SIZE: usize = 100;
+static LIST_PAGE_SIZE: AtomicUsize = AtomicUsize::new(PAGE_SIZE);
+
+pub fn page_size() -> usize {
+    LIST_PAGE_SIZE.load(Ordering::Relaxed)
+}
+
+pub fn set_page_size(size: usize) {
+    LIST_PAGE_SIZE.store(size.clamp(MIN_PAGE_SIZE, MAX_PAGE_SIZE), Ordering::Relaxed);
+}
 pub const BULK_BATCH_SIZE: usize = 50;
+// A --bulk-cache file older than this is refetched.
+pub const BULK_CACHE_MAX_AGE_HOURS: i64 = 24;
+static BULK_CACHE_DIR: OnceCell<PathBuf> = OnceCell::new();
 
-pub static API_ENDPOINTS: Lazy<HashMap<&'static str, String>> = Lazy::new(|| {
-    HashMap::from([
-        ("nav", format!("{}/home/navigation", BASE_API_URL)),
-        ("list", format!("{}/get_entry_page_list", BASE_API_URL)),
-        ("detail", format!("{}/entry_page", BASE_API_URL)),
-        ("bulk", format!("{}/entry_pages", BASE_API_URL)),
-        ("calendar", format!("{}/home/calendar", BASE_API_URL)),
-    ])
-});
+pub fn set_bulk_cache_dir(dir: PathBuf) {
+    let _ = BULK_CACHE_DIR.set(dir);
+}
+
+pub fn bulk_cache_dir() -> Option<&'static Path> {
+    BULK_CACHE_DIR.get().map(PathBuf::as_path)
+}
+pub const PIPELINE_CHANNEL_CAPACITY: usize = 64;
+pub const PIPELINE_DETAIL_BATCH: usize = 16;
+pub const STATS_MAX_SAMPLES: usize = 10_000;
+// Files `verify` reads and parses at once.
+pub const VERIFY_READ_CONCURRENCY: usize = 16;
+// --schema-watch fingerprints roughly one in this many parameterized requests.
+pub const SCHEMA_WATCH_SAMPLE_MODULUS: u64 = 32;
+static SCHEMA_WATCH_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+pub fn set_schema_watch_dir(dir: PathBuf) {
+    let _ = SCHEMA_WATCH_DIR.set(dir);
+}
+
+pub fn schema_watch_dir() -> Option<&'static Path> {
+    SCHEMA_WATCH_DIR.get().map(PathBuf::as_path)
+}
+// Embedded material/JSON string parse failures: logged samples per kind, payload preview length.
+pub const PARSE_FAILURE_LOG_SAMPLES: usize = 5;
+pub const PARSE_FAILURE_PAYLOAD_CHARS: usize = 80;
+pub const PREFLIGHT_LANG: &str = "en-us";
+pub const PREFLIGHT_BULK_ID: EntryId = 1;
+// Not one of API_ENDPOINT_KEYS: the wiki API has no public post preview route yet.
+pub const ENDPOINT_POST_PREVIEW: &str = "post_preview";
+
+pub const API_ENDPOINT_KEYS: [&str; 5] = ["nav", "list", "detail", "bulk", "calendar"];
+
+/// Full URL of the `API_ENDPOINT_KEYS` entry `key` under `base`.
+pub fn endpoint_url(base: &str, key: &str) -> Option<String> {
+    let path = match key {
+        "nav" => "home/navigation",
+        "list" => "get_entry_page_list",
+        "detail" => "entry_page",
+        "bulk" => "entry_pages",
+        "calendar" => "home/calendar",
+        _ => return None,
+    };
+    Some(format!("{}/{}", base.trim_end_matches('/'), path))
+}
+
+// Response body cap; `--max-response-mb-for` raises or lowers it per API_ENDPOINT_KEYS entry.
+pub const MAX_RESPONSE_MB: u64 = 32;
+static MAX_RESPONSE_BYTES: AtomicU64 = AtomicU64::new(MAX_RESPONSE_MB * 1024 * 1024);
+static MAX_RESPONSE_BYTES_BY_ENDPOINT: OnceCell<HashMap<String, u64>> = OnceCell::new();
+
+// Caps past u64::MAX bytes mean "no limit" rather than wrapping to a tiny one.
+fn mb_to_bytes(mb: u64) -> u64 {
+    mb.saturating_mul(1024 * 1024)
+}
+
+pub fn set_max_response_mb(default_mb: u64, per_endpoint: &[(String, u64)]) {
+    MAX_RESPONSE_BYTES.store(mb_to_bytes(default_mb), Ordering::Relaxed);
+    if !per_endpoint.is_empty() {
+        let _ = MAX_RESPONSE_BYTES_BY_ENDPOINT.set(
+            per_endpoint
+                .iter()
+                .map(|(endpoint, mb)| (endpoint.clone(), mb_to_bytes(*mb)))
+                .collect(),
+        );
+    }
+}
+
+pub fn max_response_bytes(endpoint_key: &str) -> u64 {
+    MAX_RESPONSE_BYTES_BY_ENDPOINT
+        .get()
+        .and_then(|limits| limits.get(endpoint_key).copied())
+        .unwrap_or_else(|| MAX_RESPONSE_BYTES.load(Ordering::Relaxed))
+}
 
 pub static SUPPORTED_LANGS: Lazy<Vec<String>> = Lazy::new(|| {
     vec![
@@ -40,30 +419,84 @@ pub static SUPPORTED_LANGS: Lazy<Vec<String>> = Lazy::new(|| {
     .collect()
 });
 
-const ANDROID_VER: &str = "11";
-const DEVICE_MODEL: &str = "Pixel 5";
-const BUILD_ID: &str = "RQ3A.211001.001";
 const CHROME_VER: &str = "107.0.0.0";
 const WEBKIT_VER: &str = "537.36";
-static USER_AGENT_VAL: Lazy<String> = Lazy::new(|| {
-    format!("Mozilla/5.0 (Linux; Android {}; {} Build/{}; wv) AppleWebKit/{} (KHTML, like Gecko) Version/4.0 Chrome/{} Mobile Safari/{}", ANDROID_VER, DEVICE_MODEL, BUILD_ID, WEBKIT_VER, CHROME_VER, WEBKIT_VER)
-});
 const ORIGIN_VAL: &str = "https://www.hoyolab.com";
 const REFERER_VAL: &str = "https://www.hoyolab.com/";
 
+/// Android WebView identity behind the User-Agent and `x-rpc-device_*` headers,
+/// selected by `--device-profile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceIdentity {
+    pub android_version: &'static str,
+    pub model: &'static str,
+    pub build_id: &'static str,
+    // URL-encoded, as the app sends it.
+    pub device_name: &'static str,
+}
+
+impl DeviceIdentity {
+    /// `pixel-5` (default).
+    pub const PIXEL_5: DeviceIdentity = DeviceIdentity {
+        android_version: "11",
+        model: "Pixel 5",
+        build_id: "RQ3A.211001.001",
+        device_name: "Google%20Pixel%205",
+    };
+    /// `pixel-7`.
+    pub const PIXEL_7: DeviceIdentity = DeviceIdentity {
+        android_version: "14",
+        model: "Pixel 7",
+        build_id: "UQ1A.240205.004",
+        device_name: "Google%20Pixel%207",
+    };
+    /// `galaxy-s21`.
+    pub const GALAXY_S21: DeviceIdentity = DeviceIdentity {
+        android_version: "13",
+        model: "SM-G991B",
+        build_id: "TP1A.220624.014",
+        device_name: "Samsung%20SM-G991B",
+    };
+
+    pub fn user_agent(&self) -> String {
+        format!("Mozilla/5.0 (Linux; Android {}; {} Build/{}; wv) AppleWebKit/{} (KHTML, like Gecko) Version/4.0 Chrome/{} Mobile Safari/{}", self.android_version, self.model, self.build_id, WEBKIT_VER, CHROME_VER, WEBKIT_VER)
+    }
+}
+
+// Like the API base URL, these must be set before the first request builds BASE_UA_HEADERS.
+static DEVICE_IDENTITY: OnceCell<DeviceIdentity> = OnceCell::new();
+static USER_AGENT_OVERRIDE: OnceCell<HeaderValue> = OnceCell::new();
+
+pub fn set_device_identity(identity: DeviceIdentity) {
+    let _ = DEVICE_IDENTITY.set(identity);
+}
+
+pub fn set_user_agent(user_agent: HeaderValue) {
+    let _ = USER_AGENT_OVERRIDE.set(user_agent);
+}
+
 pub static BASE_UA_HEADERS: Lazy<HeaderMap> = Lazy::new(|| {
+    let device = DEVICE_IDENTITY
+        .get()
+        .copied()
+        .unwrap_or(DeviceIdentity::PIXEL_5);
+    let user_agent = USER_AGENT_OVERRIDE.get().cloned().unwrap_or_else(|| {
+        HeaderValue::from_str(&device.user_agent())
+            .expect("device profile user agents are valid header values")
+    });
+
     let mut h = HeaderMap::new();
-    h.insert(USER_AGENT, HeaderValue::from_static(&USER_AGENT_VAL));
+    h.insert(USER_AGENT, user_agent);
     h.insert(ORIGIN, HeaderValue::from_static(ORIGIN_VAL));
     h.insert(REFERER, HeaderValue::from_static(REFERER_VAL));
 
     h.insert(
         HeaderName::from_static("x-rpc-device_name"),
-        HeaderValue::from_static("Google%20Pixel%205"),
+        HeaderValue::from_static(device.device_name),
     );
     h.insert(
         HeaderName::from_static("x-rpc-device_model"),
-        HeaderValue::from_static("Pixel 5"),
+        HeaderValue::from_static(device.model),
     );
     h.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
     h.insert(
@@ -82,6 +515,239 @@ pub static BASE_UA_HEADERS: Lazy<HeaderMap> = Lazy::new(|| {
 });
 
 pub const MAX_RECURSION_DEPTH: u32 = 15;
+static RECURSION_DEPTH_LIMIT: AtomicU32 = AtomicU32::new(MAX_RECURSION_DEPTH);
+
+pub fn max_recursion_depth() -> u32 {
+    RECURSION_DEPTH_LIMIT.load(Ordering::Relaxed)
+}
+
+pub fn set_max_recursion_depth(limit: u32) {
+    RECURSION_DEPTH_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+pub const DEPTH_LIMIT_PLACEHOLDER: &str = "[HTML Depth Limit Exceeded]";
+static DEPTH_LIMIT_PLACEHOLDER_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether over-deep markup is replaced by a visible placeholder node instead of
+/// being dropped.
+pub fn depth_limit_placeholder() -> bool {
+    DEPTH_LIMIT_PLACEHOLDER_ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_depth_limit_placeholder(enabled: bool) {
+    DEPTH_LIMIT_PLACEHOLDER_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+static DEBUG_COMPONENT_IDS: AtomicBool = AtomicBool::new(false);
+
+pub fn debug_component_ids() -> bool {
+    DEBUG_COMPONENT_IDS.load(Ordering::Relaxed)
+}
+
+pub fn set_debug_component_ids(enabled: bool) {
+    DEBUG_COMPONENT_IDS.store(enabled, Ordering::Relaxed);
+}
+
+static COMPONENTS_AS_ARRAY: AtomicBool = AtomicBool::new(false);
+
+pub fn components_as_array() -> bool {
+    COMPONENTS_AS_ARRAY.load(Ordering::Relaxed)
+}
+
+pub fn set_components_as_array(enabled: bool) {
+    COMPONENTS_AS_ARRAY.store(enabled, Ordering::Relaxed);
+}
+
+pub const MAX_RUNTIME_GRACE_SECS: u64 = 120;
+pub const EXIT_CODE_TIMEOUT: i32 = 124;
+
+pub const DEFAULT_JSON_INDENT: &[u8] = b"  ";
+pub const MAX_JSON_INDENT_SPACES: usize = 8;
+static JSON_INDENT: OnceCell<Vec<u8>> = OnceCell::new();
+
+pub fn set_json_indent(indent: Vec<u8>) {
+    let _ = JSON_INDENT.set(indent);
+}
+
+/// Indent unit for every pretty-printed JSON file this tool writes.
+pub fn json_indent() -> &'static [u8] {
+    JSON_INDENT.get().map_or(DEFAULT_JSON_INDENT, Vec::as_slice)
+}
+
+static COMPACT_THRESHOLD: AtomicU64 = AtomicU64::new(0);
+
+/// `--compact-threshold`: pretty JSON larger than this many bytes is written compact.
+pub fn compact_threshold() -> Option<u64> {
+    Some(COMPACT_THRESHOLD.load(Ordering::Relaxed)).filter(|&bytes| bytes > 0)
+}
+
+pub fn set_compact_threshold(bytes: Option<u64>) {
+    COMPACT_THRESHOLD.store(bytes.unwrap_or(0), Ordering::Relaxed);
+}
+
+pub const SEARCH_INDEX_KEYWORD_CHARS: usize = 200;
+pub const SEARCH_INDEX_COMPONENTS: [&str; 2] = [COMPONENT_BASE_INFO, COMPONENT_SUMMARY_LIST];
+static SEARCH_INDEX_KEYWORD_LIMIT: AtomicUsize = AtomicUsize::new(SEARCH_INDEX_KEYWORD_CHARS);
+static SEARCH_INDEX_COMPONENT_KEYS: OnceCell<Vec<String>> = OnceCell::new();
+
+pub fn search_index_keyword_chars() -> usize {
+    SEARCH_INDEX_KEYWORD_LIMIT.load(Ordering::Relaxed)
+}
+
+pub fn set_search_index_keyword_chars(limit: usize) {
+    SEARCH_INDEX_KEYWORD_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+pub fn set_search_index_components(components: &[String]) {
+    if !components.is_empty() {
+        let _ = SEARCH_INDEX_COMPONENT_KEYS.set(components.to_vec());
+    }
+}
+
+pub fn search_index_components() -> &'static [String] {
+    SEARCH_INDEX_COMPONENT_KEYS.get_or_init(|| {
+        SEARCH_INDEX_COMPONENTS
+            .iter()
+            .map(|key| key.to_string())
+            .collect()
+    })
+}
+
+static BUNDLE_ENTRY_IDS: OnceCell<Vec<EntryId>> = OnceCell::new();
+
+pub fn set_bundle_entries(ids: &[EntryId]) {
+    if !ids.is_empty() {
+        let _ = BUNDLE_ENTRY_IDS.set(ids.to_vec());
+    }
+}
+
+pub fn bundle_entries() -> &'static [EntryId] {
+    BUNDLE_ENTRY_IDS.get().map_or(&[], Vec::as_slice)
+}
+
+// Detail transform failures tolerated per language under `--fail-fast`; 0 leaves the cap off.
+static DETAIL_TRANSFORM_FAIL_LIMIT: AtomicUsize = AtomicUsize::new(0);
+
+pub fn detail_transform_fail_limit() -> Option<usize> {
+    Some(DETAIL_TRANSFORM_FAIL_LIMIT.load(Ordering::Relaxed)).filter(|&limit| limit > 0)
+}
+
+pub fn set_detail_transform_fail_limit(limit: Option<usize>) {
+    DETAIL_TRANSFORM_FAIL_LIMIT.store(limit.unwrap_or(0), Ordering::Relaxed);
+}
+
+static STRICT_LANGUAGE: AtomicBool = AtomicBool::new(false);
+
+/// Whether a response whose `x-rpc-language` differs from the requested
+/// language fails the fetch instead of only being counted.
+pub fn strict_language() -> bool {
+    STRICT_LANGUAGE.load(Ordering::Relaxed)
+}
+
+pub fn set_strict_language(enabled: bool) {
+    STRICT_LANGUAGE.store(enabled, Ordering::Relaxed);
+}
+
+static EXPORT_PACK: AtomicBool = AtomicBool::new(false);
+
+pub fn export_pack() -> bool {
+    EXPORT_PACK.load(Ordering::Relaxed)
+}
+
+pub fn set_export_pack(enabled: bool) {
+    EXPORT_PACK.store(enabled, Ordering::Relaxed);
+}
+
+static STRIP_COLOR: AtomicBool = AtomicBool::new(false);
+
+pub fn strip_color() -> bool {
+    STRIP_COLOR.load(Ordering::Relaxed)
+}
+
+pub fn set_strip_color(enabled: bool) {
+    STRIP_COLOR.store(enabled, Ordering::Relaxed);
+}
+
+static STATS_REPORT: AtomicBool = AtomicBool::new(false);
+
+pub fn stats_report() -> bool {
+    STATS_REPORT.load(Ordering::Relaxed)
+}
+
+pub fn set_stats_report(enabled: bool) {
+    STATS_REPORT.store(enabled, Ordering::Relaxed);
+}
+
+static REPORT_UNRESOLVED: AtomicBool = AtomicBool::new(false);
+
+pub fn report_unresolved() -> bool {
+    REPORT_UNRESOLVED.load(Ordering::Relaxed)
+}
+
+pub fn set_report_unresolved(enabled: bool) {
+    REPORT_UNRESOLVED.store(enabled, Ordering::Relaxed);
+}
+
+static SAVE_FAILED_IDS: AtomicBool = AtomicBool::new(false);
+
+pub fn save_failed_ids() -> bool {
+    SAVE_FAILED_IDS.load(Ordering::Relaxed)
+}
+
+pub fn set_save_failed_ids(enabled: bool) {
+    SAVE_FAILED_IDS.store(enabled, Ordering::Relaxed);
+}
+
+static SKIP_PREFLIGHT: AtomicBool = AtomicBool::new(false);
+
+pub fn skip_preflight() -> bool {
+    SKIP_PREFLIGHT.load(Ordering::Relaxed)
+}
+
+pub fn set_skip_preflight(enabled: bool) {
+    SKIP_PREFLIGHT.store(enabled, Ordering::Relaxed);
+}
+
+static ABORT_ON_CONSISTENCY_WARNING: AtomicBool = AtomicBool::new(false);
+
+pub fn abort_on_consistency_warning() -> bool {
+    ABORT_ON_CONSISTENCY_WARNING.load(Ordering::Relaxed)
+}
+
+pub fn set_abort_on_consistency_warning(enabled: bool) {
+    ABORT_ON_CONSISTENCY_WARNING.store(enabled, Ordering::Relaxed);
+}
+
+static REQUIRE_ALL_LANGS: AtomicBool = AtomicBool::new(false);
+
+pub fn require_all_langs() -> bool {
+    REQUIRE_ALL_LANGS.load(Ordering::Relaxed)
+}
+
+pub fn set_require_all_langs(enabled: bool) {
+    REQUIRE_ALL_LANGS.store(enabled, Ordering::Relaxed);
+}
+
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+static CURRENT_PHASE: Mutex<&'static str> = Mutex::new("Startup");
+
+pub fn shutdown_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::Relaxed)
+}
+
+pub fn request_shutdown() {
+    SHUTDOWN_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+pub fn current_phase() -> &'static str {
+    CURRENT_PHASE.lock().map_or("Unknown", |phase| *phase)
+}
+
+pub fn set_current_phase(phase: &'static str) {
+    if let Ok(mut current) = CURRENT_PHASE.lock() {
+        *current = phase;
+    }
+}
 
 pub const KEY_CHAR_VISION: &str = "character_vision";
 pub const KEY_CHAR_RARITY: &str = "character_rarity";
@@ -115,6 +781,30 @@ pub static LIST_FILTER_FIELDS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
     .collect()
 });
 
+static EXTRA_FILTER_FIELDS: OnceCell<Vec<&'static str>> = OnceCell::new();
+
+pub fn set_extra_filter_fields(fields: &[String]) {
+    let mut extras: Vec<&'static str> = Vec::new();
+    for field in fields {
+        if LIST_FILTER_FIELDS.contains(field.as_str()) || extras.contains(&field.as_str()) {
+            continue;
+        }
+        extras.push(Box::leak(field.clone().into_boxed_str()));
+    }
+    let _ = EXTRA_FILTER_FIELDS.set(extras);
+}
+
+pub fn extra_filter_fields() -> &'static [&'static str] {
+    EXTRA_FILTER_FIELDS.get().map_or(&[], Vec::as_slice)
+}
+
+pub fn list_filter_fields() -> impl Iterator<Item = &'static str> {
+    LIST_FILTER_FIELDS
+        .iter()
+        .copied()
+        .chain(extra_filter_fields().iter().copied())
+}
+
 pub static MULTI_VALUE_FILTER_FIELDS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
     ["card_character_camp", "object_type", "reliquary_effect"]
         .iter()
@@ -133,12 +823,243 @@ pub const COMPONENT_GALLERY_CHARACTER: &str = "gallery_character";
 pub const COMPONENT_ARTIFACT_LIST: &str = "artifact_list";
 pub const COMPONENT_RELIQUARY_SET_EFFECT: &str = "reliquary_set_effect";
 pub const COMPONENT_MAP: &str = "map";
+pub const COMPONENT_MAP_RESOURCE: &str = "map_resource";
 pub const COMPONENT_CUSTOMIZE: &str = "customize";
 pub const COMPONENT_BODY: &str = "body";
 pub const COMPONENT_TIMELINE: &str = "timeline";
 pub const COMPONENT_VIDEO_COLLECTION: &str = "video_collection";
 pub const COMPONENT_TCG: &str = "tcg";
 pub const COMPONENT_DROP_MATERIAL: &str = "drop_material";
+pub const COMPONENT_GIFT_REWARD: &str = "gift_reward";
+pub const COMPONENT_COMPANION_GIFT: &str = "companion_gift";
+pub const COMPONENT_RECOMMEND: &str = "recommend";
+pub const COMPONENT_STRATEGY: &str = "strategy";
+pub const COMPONENT_EVENT_CALENDAR: &str = "event_calendar";
+pub const COMPONENT_ACHIEVEMENT_GROUP: &str = "achievement_group";
+// Derived from other components with --derive-progression; never sent by the API.
+pub const COMPONENT_PROGRESSION: &str = "progression";
+// Unknown components are stored raw; embedded JSON strings are decoded this many
+// levels deep and opaque blobs longer than this are replaced by a marker.
+pub const UNKNOWN_JSON_DECODE_DEPTH: u32 = 2;
+pub const UNKNOWN_BLOB_MAX_CHARS: usize = 4096;
+static UNKNOWN_DUMP_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+pub fn set_dump_unknown_components_dir(dir: PathBuf) {
+    let _ = UNKNOWN_DUMP_DIR.set(dir);
+}
+
+/// Where raw Unknown component payloads are written, if `--dump-unknown-components` is set.
+pub fn dump_unknown_components_dir() -> Option<&'static Path> {
+    UNKNOWN_DUMP_DIR.get().map(PathBuf::as_path)
+}
+pub const MAX_CONSTELLATIONS: usize = 6;
+
+/// baseInfo key labels that mark weapon refinement rows, per language.
+pub fn refinement_labels(lang: &str) -> &'static [&'static str] {
+    match lang {
+        "de-de" => &["Verfeinerung"],
+        "en-us" => &["Refinement"],
+        "es-es" => &["Refinamiento"],
+        "fr-fr" => &["Raffinement"],
+        "id-id" => &["Refinement"],
+        "it-it" => &["Raffinamento"],
+        "ja-jp" => &["精錬"],
+        "ko-kr" => &["재련"],
+        "pt-pt" => &["Refinamento"],
+        "ru-ru" => &["Пробуждение"],
+        "vi-vn" => &["Tinh Luyện"],
+        "zh-cn" => &["精炼"],
+        "zh-tw" => &["精煉"],
+        _ => &[],
+    }
+}
+
+static PARSE_BULK_DESC: AtomicBool = AtomicBool::new(false);
+
+pub fn parse_bulk_desc() -> bool {
+    PARSE_BULK_DESC.load(Ordering::Relaxed)
+}
+
+pub fn set_parse_bulk_desc(enabled: bool) {
+    PARSE_BULK_DESC.store(enabled, Ordering::Relaxed);
+}
+
+static FLATTEN_HTML: AtomicBool = AtomicBool::new(false);
+
+pub fn flatten_html() -> bool {
+    FLATTEN_HTML.load(Ordering::Relaxed)
+}
+
+pub fn set_flatten_html(enabled: bool) {
+    FLATTEN_HTML.store(enabled, Ordering::Relaxed);
+}
+
+static KEEP_RAW_FILTERS: AtomicBool = AtomicBool::new(false);
+
+pub fn keep_raw_filters() -> bool {
+    KEEP_RAW_FILTERS.load(Ordering::Relaxed)
+}
+
+pub fn set_keep_raw_filters(enabled: bool) {
+    KEEP_RAW_FILTERS.store(enabled, Ordering::Relaxed);
+}
+
+static EMIT_FILTER_VOCABULARY: AtomicBool = AtomicBool::new(false);
+
+pub fn emit_filter_vocabulary() -> bool {
+    EMIT_FILTER_VOCABULARY.load(Ordering::Relaxed)
+}
+
+pub fn set_emit_filter_vocabulary(enabled: bool) {
+    EMIT_FILTER_VOCABULARY.store(enabled, Ordering::Relaxed);
+}
+
+/// Which copy `--header-gallery-dedup` removes when a detail page's header image
+/// is also one of its gallery images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HeaderGalleryDedup {
+    DropGalleryItem,
+    ClearHeader,
+}
+
+static HEADER_GALLERY_DEDUP: OnceCell<HeaderGalleryDedup> = OnceCell::new();
+
+pub fn header_gallery_dedup() -> Option<HeaderGalleryDedup> {
+    HEADER_GALLERY_DEDUP.get().copied()
+}
+
+pub fn set_header_gallery_dedup(mode: HeaderGalleryDedup) {
+    let _ = HEADER_GALLERY_DEDUP.set(mode);
+}
+
+/// Which detail pages `--alias-detection` marks as aliases of another entry. The
+/// API's explicit redirect field is always honoured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AliasDetection {
+    /// Only the explicit redirect field.
+    ExplicitOnly,
+    /// Also pages without a bulk name whose only component is a single entry link.
+    #[default]
+    EntryLink,
+    /// Single entry link pages even when bulk data names them.
+    EntryLinkAnyName,
+}
+
+static ALIAS_DETECTION: OnceCell<AliasDetection> = OnceCell::new();
+
+pub fn alias_detection() -> AliasDetection {
+    ALIAS_DETECTION.get().copied().unwrap_or_default()
+}
+
+pub fn set_alias_detection(mode: AliasDetection) {
+    let _ = ALIAS_DETECTION.set(mode);
+}
+
+// `--entry-url-template`: deep link pattern with `{id}` / `{lang}` placeholders.
+static ENTRY_URL_TEMPLATE: OnceCell<String> = OnceCell::new();
+
+pub fn set_entry_url_template(template: String) {
+    let _ = ENTRY_URL_TEMPLATE.set(template);
+}
+
+pub fn entry_url_template() -> Option<&'static str> {
+    ENTRY_URL_TEMPLATE.get().map(String::as_str)
+}
+
+static INCLUDE_ABSTRACT_MODULES: AtomicBool = AtomicBool::new(false);
+
+pub fn include_abstract_modules() -> bool {
+    INCLUDE_ABSTRACT_MODULES.load(Ordering::Relaxed)
+}
+
+pub fn set_include_abstract_modules(enabled: bool) {
+    INCLUDE_ABSTRACT_MODULES.store(enabled, Ordering::Relaxed);
+}
+
+// Node lists shorter than this when serialized stay inline under --intern-nodes; a Ref
+// would save little.
+pub const INTERN_MIN_BYTES: usize = 64;
+static INTERN_NODES: AtomicBool = AtomicBool::new(false);
+
+pub fn intern_nodes() -> bool {
+    INTERN_NODES.load(Ordering::Relaxed)
+}
+
+pub fn set_intern_nodes(enabled: bool) {
+    INTERN_NODES.store(enabled, Ordering::Relaxed);
+}
+
+static DERIVE_PROGRESSION: AtomicBool = AtomicBool::new(false);
+
+pub fn derive_progression() -> bool {
+    DERIVE_PROGRESSION.load(Ordering::Relaxed)
+}
+
+pub fn set_derive_progression(enabled: bool) {
+    DERIVE_PROGRESSION.store(enabled, Ordering::Relaxed);
+}
+
+pub const EVENTS_MENU_ID: MenuId = 39;
+pub const DEFAULT_EVENT_MENU_IDS: [MenuId; 1] = [EVENTS_MENU_ID];
+static DERIVE_EVENT_WINDOWS: AtomicBool = AtomicBool::new(false);
+static EVENT_MENU_IDS: OnceCell<Vec<MenuId>> = OnceCell::new();
+
+pub fn derive_event_windows() -> bool {
+    DERIVE_EVENT_WINDOWS.load(Ordering::Relaxed)
+}
+
+pub fn set_derive_event_windows(enabled: bool) {
+    DERIVE_EVENT_WINDOWS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn set_event_menu_ids(ids: &[MenuId]) {
+    if !ids.is_empty() {
+        let _ = EVENT_MENU_IDS.set(ids.to_vec());
+    }
+}
+
+/// Menus whose detail pages are scanned by --derive-event-windows.
+pub fn event_menu_ids() -> &'static [MenuId] {
+    EVENT_MENU_IDS
+        .get()
+        .map_or(&DEFAULT_EVENT_MENU_IDS, Vec::as_slice)
+}
+
+// Date-range patterns for --derive-event-windows. Every pattern captures `sy`/`sm`/`sd`
+// and `ey`/`em`/`ed`; the end year may be omitted ("2024/05/01 ~ 05/21"). Times after
+// a date are matched but dropped.
+static EVENT_WINDOW_NUMERIC_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?P<sy>\d{4})[/.\-](?P<sm>\d{1,2})[/.\-](?P<sd>\d{1,2})(?:\s*\([^)]{1,6}\))?(?:\s*\d{1,2}:\d{2}(?::\d{2})?)?\s*(?:~|～|〜|-|–|—|至|to)\s*(?:(?P<ey>\d{4})[/.\-])?(?P<em>\d{1,2})[/.\-](?P<ed>\d{1,2})",
+    )
+    .unwrap()
+});
+static EVENT_WINDOW_CJK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?P<sy>\d{4})\s*年\s*(?P<sm>\d{1,2})\s*月\s*(?P<sd>\d{1,2})\s*日(?:\s*[（(][^）)]{1,4}[）)])?(?:\s*\d{1,2}[:：]\d{2}(?:[:：]\d{2})?)?\s*(?:~|～|〜|-|–|—|至)\s*(?:(?P<ey>\d{4})\s*年\s*)?(?P<em>\d{1,2})\s*月\s*(?P<ed>\d{1,2})\s*日",
+    )
+    .unwrap()
+});
+static EVENT_WINDOW_EN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)(?P<sm>jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?\s+(?P<sd>\d{1,2}),?\s+(?P<sy>\d{4})(?:,?\s*\d{1,2}:\d{2}(?::\d{2})?)?\s*(?:~|-|–|—|to)\s*(?P<em>jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?\s+(?P<ed>\d{1,2}),?\s+(?P<ey>\d{4})",
+    )
+    .unwrap()
+});
+
+/// Date-range patterns tried for `lang`, per language.
+pub fn event_window_patterns(lang: &str) -> Vec<&'static Regex> {
+    match lang {
+        "en-us" => vec![&EVENT_WINDOW_NUMERIC_RE, &EVENT_WINDOW_EN_RE],
+        "ja-jp" | "zh-cn" | "zh-tw" => {
+            vec![&EVENT_WINDOW_NUMERIC_RE, &EVENT_WINDOW_CJK_RE]
+        }
+        lang if SUPPORTED_LANGS.iter().any(|l| l == lang) => vec![&EVENT_WINDOW_NUMERIC_RE],
+        _ => Vec::new(),
+    }
+}
 
 pub static HEADING_TAGS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
     ["h1", "h2", "h3", "h4", "h5", "h6"]
@@ -201,6 +1122,137 @@ pub static TARGET_HTML_CUSTOM_TAGS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
     .collect()
 });
 
+/// The merged configuration of a run as printed by `--print-config`: read back
+/// from the accessors above after presets and flags have been applied.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfig {
+    pub version: &'static str,
+    pub languages: Vec<String>,
+    pub mode: &'static str,
+    pub api_base_url: &'static str,
+    pub menu_ids: Option<Vec<MenuId>>,
+    pub concurrency: ConcurrencyConfig,
+    pub network: NetworkConfig,
+    pub output: OutputConfig,
+    pub features: BTreeMap<&'static str, bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConcurrencyConfig {
+    pub list: usize,
+    pub detail: usize,
+    pub bulk: usize,
+    pub calendar: usize,
+    pub page_size: usize,
+    pub bulk_batch_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConfig {
+    pub http_timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub max_retries: u32,
+    pub retry_delay_base_secs: f32,
+    pub max_runtime_secs: Option<u64>,
+    pub detail_transform_fail_limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputConfig {
+    pub out_dir: PathBuf,
+    pub bulk_cache_dir: Option<&'static Path>,
+    pub subdirs: &'static [&'static str],
+    pub json_indent: String,
+    pub compact_threshold: Option<u64>,
+    pub search_index_keyword_chars: usize,
+    pub search_index_components: &'static [String],
+    pub bundle_entries: &'static [EntryId],
+    pub event_menu_ids: &'static [MenuId],
+    pub entry_url_template: Option<&'static str>,
+    pub oversized_component_kb: usize,
+    pub header_gallery_dedup: Option<HeaderGalleryDedup>,
+    pub alias_detection: AliasDetection,
+}
+
+impl EffectiveConfig {
+    pub fn resolve(
+        languages: &[LangCode],
+        out_dir: &Path,
+        pipeline: bool,
+        max_runtime_secs: Option<u64>,
+    ) -> Self {
+        EffectiveConfig {
+            version: env!("CARGO_PKG_VERSION"),
+            languages: languages.iter().map(|lang| lang.to_string()).collect(),
+            mode: if pipeline { "pipeline" } else { "staged" },
+            api_base_url: api_base_url(),
+            menu_ids: menu_allowlist().map(<[MenuId]>::to_vec),
+            concurrency: ConcurrencyConfig {
+                list: list_concurrency(),
+                detail: detail_concurrency(),
+                bulk: bulk_concurrency(),
+                calendar: calendar_concurrency(),
+                page_size: page_size(),
+                bulk_batch_size: BULK_BATCH_SIZE,
+            },
+            network: NetworkConfig {
+                http_timeout_secs: HTTP_TIMEOUT_SECONDS,
+                connect_timeout_secs: HTTP_CONNECT_TIMEOUT,
+                max_retries: MAX_RETRIES,
+                retry_delay_base_secs: RETRY_DELAY_BASE_SECS,
+                max_runtime_secs,
+                detail_transform_fail_limit: detail_transform_fail_limit(),
+            },
+            output: OutputConfig {
+                out_dir: out_dir.to_path_buf(),
+                bulk_cache_dir: bulk_cache_dir(),
+                subdirs: OUTPUT_SUBDIRS,
+                json_indent: String::from_utf8_lossy(json_indent()).into_owned(),
+                compact_threshold: compact_threshold(),
+                search_index_keyword_chars: search_index_keyword_chars(),
+                search_index_components: search_index_components(),
+                bundle_entries: bundle_entries(),
+                event_menu_ids: event_menu_ids(),
+                entry_url_template: entry_url_template(),
+                oversized_component_kb: oversized_component_bytes() / 1024,
+                header_gallery_dedup: header_gallery_dedup(),
+                alias_detection: alias_detection(),
+            },
+            features: BTreeMap::from([
+                ("abortOnConsistencyWarning", abort_on_consistency_warning()),
+                ("bulkFallback", bulk_fallback()),
+                ("componentsAsArray", components_as_array()),
+                ("debugComponentIds", debug_component_ids()),
+                ("depthLimitPlaceholder", depth_limit_placeholder()),
+                ("deriveEventWindows", derive_event_windows()),
+                ("deriveProgression", derive_progression()),
+                ("emitFilterVocabulary", emit_filter_vocabulary()),
+                ("exportPack", export_pack()),
+                ("flattenHtml", flatten_html()),
+                ("includeAbstractModules", include_abstract_modules()),
+                ("internNodes", intern_nodes()),
+                ("keepRawFilters", keep_raw_filters()),
+                ("parseBulkDesc", parse_bulk_desc()),
+                ("reportUnresolved", report_unresolved()),
+                ("requireAllLangs", require_all_langs()),
+                ("retryPostParseFailures", retry_post_parse_failures()),
+                ("saveFailedIds", save_failed_ids()),
+                ("skipCalendar", skip_calendar()),
+                ("skipMergedSchema", skip_merged_schema()),
+                ("skipPreflight", skip_preflight()),
+                ("statsReport", stats_report()),
+                ("strictLanguage", strict_language()),
+                ("stripColor", strip_color()),
+                ("truncateOversized", truncate_oversized()),
+            ]),
+        }
+    }
+}
+
 pub static FORBIDDEN_CHARS_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r#"[<>:"/\\|?*\x00-\x1f\x7f\^!@#$%^&*()+={}\[\];,.'’]"#).unwrap());
 pub static WHITESPACE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\s_]+").unwrap());
@@ -210,5 +1262,7 @@ pub static RE_ADJACENT_SAME_CLR: Lazy<Regex> =
 
 pub static RE_ADJACENT_CLR: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"</color><color=#([0-9A-Fa-f]{6,8})>").unwrap());
+pub static RE_COLOR_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"</?color(=#[0-9A-Fa-f]{6,8})?>").unwrap());
 pub static RE_EMPTY_COLOR: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"<color=#[0-9A-Fa-f]{6,8}>\s*</color>").unwrap());
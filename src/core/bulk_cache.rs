@@ -0,0 +1,256 @@
+use crate::config;
+use crate::error::AppResult;
+use crate::io;
+use crate::logging::{log, LogLevel};
+use crate::model::common::EntryId;
+use crate::transform::bulk::{BulkInfo, BulkStore};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever `BulkCacheEntry` changes; caches of another version are refetched.
+/// Version 2 added the per-field language provenance.
+pub const BULK_CACHE_FORMAT_VERSION: u32 = 2;
+
+/// One language's resolved bulk data as written to `--bulk-cache <dir>/<lang>.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkCacheFile {
+    /// Missing in version 1 files, which read as 0.
+    #[serde(default)]
+    pub format_version: u32,
+    pub language: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub fetched_at: DateTime<Utc>,
+    /// Every id the cached bulk phase asked for, including ones the API had no data for.
+    pub requested_ids: BTreeSet<EntryId>,
+    pub entries: BTreeMap<EntryId, BulkCacheEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkCacheEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_lang: Option<String>,
+}
+
+impl BulkCacheFile {
+    pub fn from_store(lang: &str, requested_ids: &HashSet<EntryId>, store: &BulkStore) -> Self {
+        BulkCacheFile {
+            format_version: BULK_CACHE_FORMAT_VERSION,
+            language: lang.to_string(),
+            fetched_at: Utc::now(),
+            requested_ids: requested_ids.iter().copied().collect(),
+            entries: store
+                .iter()
+                .map(|(&id, info)| {
+                    let entry = BulkCacheEntry {
+                        name: info.name.clone(),
+                        desc: info.desc.clone(),
+                        icon: info.best_icon_url.clone(),
+                        name_lang: info.name_lang.clone(),
+                        desc_lang: info.desc_lang.clone(),
+                        icon_lang: info.icon_lang.clone(),
+                    };
+                    (id, entry)
+                })
+                .collect(),
+        }
+    }
+
+    /// Fresh when written in the current format, younger than
+    /// `BULK_CACHE_MAX_AGE_HOURS` and it asked for every id this run needs.
+    pub fn is_fresh_for(&self, ids: &HashSet<EntryId>) -> bool {
+        let age = Utc::now().signed_duration_since(self.fetched_at);
+        self.format_version == BULK_CACHE_FORMAT_VERSION
+            && age.num_hours() < config::BULK_CACHE_MAX_AGE_HOURS
+            && ids.iter().all(|id| self.requested_ids.contains(id))
+    }
+
+    pub fn into_store(self) -> BulkStore {
+        let map: HashMap<EntryId, BulkInfo> = self
+            .entries
+            .into_iter()
+            .map(|(id, entry)| {
+                let info = BulkInfo {
+                    name: entry.name,
+                    desc: entry.desc,
+                    best_icon_url: entry.icon,
+                    name_lang: entry.name_lang,
+                    desc_lang: entry.desc_lang,
+                    icon_lang: entry.icon_lang,
+                };
+                (id, info)
+            })
+            .collect();
+        BulkStore::from_map(map)
+    }
+}
+
+fn cache_path(cache_dir: &Path, lang: &str) -> PathBuf {
+    cache_dir.join(format!("{}.json", lang))
+}
+
+/// The cached store for `lang`, or `None` when it is missing, unreadable, of an
+/// older format, stale or lacks some of `ids`.
+pub async fn load_bulk_cache(
+    cache_dir: &Path,
+    lang: &str,
+    ids: &HashSet<EntryId>,
+) -> Option<BulkStore> {
+    let path = cache_path(cache_dir, lang);
+    let content = tokio::fs::read_to_string(&path).await.ok()?;
+    let cache: BulkCacheFile = match serde_json::from_str(&content) {
+        Ok(cache) => cache,
+        Err(e) => {
+            log(
+                LogLevel::Warning,
+                &format!("Ignoring unreadable bulk cache {}: {}", path.display(), e),
+            );
+            return None;
+        }
+    };
+    if !cache.is_fresh_for(ids) {
+        log(
+            LogLevel::Info,
+            &format!(
+                "Bulk cache for [{}] is outdated, stale or incomplete; refetching.",
+                lang
+            ),
+        );
+        return None;
+    }
+    Some(cache.into_store())
+}
+
+pub async fn save_bulk_cache(cache_dir: &Path, cache: BulkCacheFile) -> AppResult<bool> {
+    tokio::fs::create_dir_all(cache_dir).await?;
+    let path = cache_path(cache_dir, &cache.language);
+    let ctx = format!("Bulk cache [{}]", cache.language);
+    io::save_json(path, cache, ctx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::bulk::build_bulk_entries;
+    use chrono::Duration;
+
+    const LANG: &str = "ja-jp";
+
+    fn store() -> BulkStore {
+        BulkStore::from_map(HashMap::from([
+            (
+                1001,
+                BulkInfo {
+                    name: Some("アンバー".to_string()),
+                    desc: Some("偵察騎士".to_string()),
+                    best_icon_url: Some("https://example.test/en/1001.png".to_string()),
+                    name_lang: Some(LANG.to_string()),
+                    desc_lang: Some(LANG.to_string()),
+                    icon_lang: Some("en-us".to_string()),
+                },
+            ),
+            (
+                1002,
+                BulkInfo {
+                    name: Some("ガイア".to_string()),
+                    name_lang: Some(LANG.to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]))
+    }
+
+    fn requested() -> HashSet<EntryId> {
+        HashSet::from([1001, 1002, 1003])
+    }
+
+    fn fields(store: &BulkStore) -> BTreeMap<EntryId, [Option<String>; 6]> {
+        store
+            .iter()
+            .map(|(&id, info)| {
+                let fields = [
+                    info.name.clone(),
+                    info.desc.clone(),
+                    info.best_icon_url.clone(),
+                    info.name_lang.clone(),
+                    info.desc_lang.clone(),
+                    info.icon_lang.clone(),
+                ];
+                (id, fields)
+            })
+            .collect()
+    }
+
+    async fn write_raw(dir: &Path, cache: serde_json::Value) {
+        tokio::fs::write(cache_path(dir, LANG), cache.to_string())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn saved_cache_loads_back_with_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = BulkCacheFile::from_store(LANG, &requested(), &store());
+        assert!(save_bulk_cache(dir.path(), cache).await.unwrap());
+
+        let loaded = load_bulk_cache(dir.path(), LANG, &HashSet::from([1001, 1003]))
+            .await
+            .expect("fresh cache");
+        assert_eq!(fields(&loaded), fields(&store()));
+        let written = build_bulk_entries(&loaded, LANG);
+        assert_eq!(written, build_bulk_entries(&store(), LANG));
+        assert_eq!(written[0].icon_lang.as_deref(), Some("en-us"));
+    }
+
+    #[tokio::test]
+    async fn stale_incomplete_and_old_caches_are_refetched() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_bulk_cache(dir.path(), LANG, &requested())
+            .await
+            .is_none());
+
+        let cache = BulkCacheFile::from_store(LANG, &requested(), &store());
+        save_bulk_cache(dir.path(), cache.clone()).await.unwrap();
+        let more_ids = HashSet::from([1001, 1004]);
+        assert!(load_bulk_cache(dir.path(), LANG, &more_ids).await.is_none());
+
+        let stale = BulkCacheFile {
+            fetched_at: Utc::now() - Duration::hours(config::BULK_CACHE_MAX_AGE_HOURS),
+            ..cache.clone()
+        };
+        save_bulk_cache(dir.path(), stale).await.unwrap();
+        assert!(load_bulk_cache(dir.path(), LANG, &requested())
+            .await
+            .is_none());
+
+        let mut version_one = serde_json::to_value(&cache).unwrap();
+        version_one.as_object_mut().unwrap().remove("formatVersion");
+        write_raw(dir.path(), version_one).await;
+        assert!(load_bulk_cache(dir.path(), LANG, &requested())
+            .await
+            .is_none());
+
+        write_raw(dir.path(), serde_json::json!({ "language": LANG })).await;
+        assert!(load_bulk_cache(dir.path(), LANG, &requested())
+            .await
+            .is_none());
+
+        save_bulk_cache(dir.path(), cache).await.unwrap();
+        assert!(load_bulk_cache(dir.path(), LANG, &requested())
+            .await
+            .is_some());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::core::data_store::TransformedData;
+use crate::model::common::EntryId;
+use crate::model::output::OutputDetailPage;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const BUNDLES_DIR: &str = "bundles";
+
+/// One entry's detail page in every language it was transformed for
+/// (`bundles/<entry_id>.json`, written for `--bundle-entries`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryBundle<'a> {
+    #[serde(rename = "epId")]
+    pub ep_id: EntryId,
+    pub languages: BTreeMap<&'a str, &'a OutputDetailPage>,
+    pub components: BundleComponentSummary<'a>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleComponentSummary<'a> {
+    /// Component keys present on the page, per language.
+    pub by_lang: BTreeMap<&'a str, BTreeSet<&'a str>>,
+    /// Components missing from some bundled languages, with the languages lacking them.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub missing_in: BTreeMap<&'a str, Vec<&'a str>>,
+}
+
+/// Bundles for the requested ids, in the order given. Ids without a detail page in any
+/// language are returned separately so the caller can report them.
+pub fn build_entry_bundles<'a>(
+    data: &'a TransformedData,
+    entry_ids: &[EntryId],
+) -> (Vec<EntryBundle<'a>>, Vec<EntryId>) {
+    let mut bundles = Vec::with_capacity(entry_ids.len());
+    let mut not_found = Vec::new();
+    for &ep_id in entry_ids {
+        let languages: BTreeMap<&str, &OutputDetailPage> = data
+            .details
+            .iter()
+            .filter_map(|(lang, details)| {
+                details
+                    .iter()
+                    .find(|page| page.id == ep_id)
+                    .map(|page| (lang.as_str(), page))
+            })
+            .collect();
+        if languages.is_empty() {
+            not_found.push(ep_id);
+            continue;
+        }
+        let components = summarize_components(&languages);
+        bundles.push(EntryBundle {
+            ep_id,
+            languages,
+            components,
+        });
+    }
+    (bundles, not_found)
+}
+
+fn summarize_components<'a>(
+    languages: &BTreeMap<&'a str, &'a OutputDetailPage>,
+) -> BundleComponentSummary<'a> {
+    let by_lang: BTreeMap<&str, BTreeSet<&str>> = languages
+        .iter()
+        .map(|(&lang, page)| (lang, page.components.keys().map(String::as_str).collect()))
+        .collect();
+    let all_keys: BTreeSet<&str> = by_lang.values().flatten().copied().collect();
+    let missing_in = all_keys
+        .into_iter()
+        .filter_map(|key| {
+            let lacking: Vec<&str> = by_lang
+                .iter()
+                .filter(|(_, keys)| !keys.contains(key))
+                .map(|(&lang, _)| lang)
+                .collect();
+            (!lacking.is_empty()).then_some((key, lacking))
+        })
+        .collect();
+    BundleComponentSummary {
+        by_lang,
+        missing_in,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::model::{ApiDetailResponse, ApiWrapper};
+    use crate::transform::bulk::BulkStore;
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    async fn fixture_page(name: &str, lang: &str) -> OutputDetailPage {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join(name);
+        let text = std::fs::read_to_string(path).unwrap();
+        let wrapper: ApiWrapper<ApiDetailResponse> = serde_json::from_str(&text).unwrap();
+        crate::transform::detail::transform_detail_page(
+            wrapper.data.unwrap().page,
+            Arc::new(BulkStore::default()),
+            Default::default(),
+            lang,
+        )
+        .await
+        .unwrap()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn bundles_an_entry_across_two_languages() {
+        let en = fixture_page("detail_character_ascension.json", "en-us").await;
+        let mut ja = fixture_page("detail_character_ascension.json", "ja-jp").await;
+        let ep_id = en.id;
+        let mut en_keys: Vec<String> = en.components.keys().cloned().collect();
+        en_keys.sort();
+        assert!(!en_keys.is_empty());
+        let dropped = en_keys[0].clone();
+        ja.components.remove(&dropped);
+
+        let data = TransformedData {
+            details: HashMap::from([
+                ("en-us".to_string(), Arc::new(vec![en])),
+                ("ja-jp".to_string(), Arc::new(vec![ja])),
+            ]),
+            ..Default::default()
+        };
+        let (bundles, not_found) = build_entry_bundles(&data, &[ep_id, 999_999]);
+        assert_eq!(not_found, [999_999]);
+        assert_eq!(bundles.len(), 1);
+
+        let bundle = &bundles[0];
+        assert_eq!(bundle.ep_id, ep_id);
+        assert_eq!(
+            bundle.languages.keys().copied().collect::<Vec<_>>(),
+            ["en-us", "ja-jp"]
+        );
+        let en_set: BTreeSet<&str> = en_keys.iter().map(String::as_str).collect();
+        let ja_set: BTreeSet<&str> = en_set
+            .iter()
+            .copied()
+            .filter(|key| *key != dropped)
+            .collect();
+        assert_eq!(bundle.components.by_lang["en-us"], en_set);
+        assert_eq!(bundle.components.by_lang["ja-jp"], ja_set);
+        assert_eq!(
+            bundle.components.missing_in,
+            BTreeMap::from([(dropped.as_str(), vec!["ja-jp"])])
+        );
+
+        let json = serde_json::to_value(bundle).unwrap();
+        assert_eq!(json["epId"], ep_id);
+        assert_eq!(json["languages"]["ja-jp"]["epId"], ep_id);
+        assert_eq!(json["components"]["missingIn"][&dropped][0], "ja-jp");
+    }
+
+    #[test]
+    fn languages_without_the_entry_are_left_out() {
+        let page = OutputDetailPage {
+            id: 7,
+            ..Default::default()
+        };
+        let data = TransformedData {
+            details: HashMap::from([
+                ("en-us".to_string(), Arc::new(vec![page])),
+                ("ja-jp".to_string(), Arc::new(Vec::new())),
+            ]),
+            ..Default::default()
+        };
+        let (bundles, not_found) = build_entry_bundles(&data, &[7]);
+        assert!(not_found.is_empty());
+        assert_eq!(
+            bundles[0].languages.keys().copied().collect::<Vec<_>>(),
+            ["en-us"]
+        );
+        assert!(bundles[0].components.missing_in.is_empty());
+    }
+}
@@ -0,0 +1,395 @@
+use crate::api::model::{ApiListItem, ApiNavEntry};
+use crate::config;
+use crate::core::stats::RunStats;
+use crate::error::AppResult;
+use crate::io;
+use crate::logging::{log, LogLevel};
+use crate::model::common::MenuId;
+use crate::transform;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+pub const RUN_HISTORY_FILE: &str = "run_report_history.json";
+const RUN_HISTORY_MAX_ENTRIES: usize = 30;
+const LIST_TOTAL_TOLERANCE: f64 = 0.20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsistencyWarning {
+    MenuCountMismatch {
+        lang: String,
+        nav_menus: usize,
+        list_menus: usize,
+        missing_menu_ids: Vec<MenuId>,
+    },
+    NoListItems {
+        lang: String,
+    },
+    ListTotalDrift {
+        previous: usize,
+        current: usize,
+    },
+}
+
+impl fmt::Display for ConsistencyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsistencyWarning::MenuCountMismatch {
+                lang,
+                nav_menus,
+                list_menus,
+                missing_menu_ids,
+            } => write!(
+                f,
+                "[{}] {} navigation menu(s) but only {} non-empty list(s); missing menus: {:?}",
+                lang, nav_menus, list_menus, missing_menu_ids
+            ),
+            ConsistencyWarning::NoListItems { lang } => {
+                write!(f, "[{}] has zero list items", lang)
+            }
+            ConsistencyWarning::ListTotalDrift { previous, current } => write!(
+                f,
+                "Total list items changed from {} to {} (more than {:.0}% vs previous run)",
+                previous,
+                current,
+                LIST_TOTAL_TOLERANCE * 100.0
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReportEntry {
+    pub timestamp: String,
+    #[serde(default)]
+    pub list_items_by_lang: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preflight_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub latency_ms: BTreeMap<String, LatencyPercentiles>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyPercentiles {
+    pub p50: u32,
+    pub p95: u32,
+    pub p99: u32,
+}
+
+impl RunReportEntry {
+    pub fn new(list_items_by_lang: BTreeMap<String, usize>) -> Self {
+        RunReportEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            list_items_by_lang,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_latencies(mut self, run_stats: &RunStats) -> Self {
+        self.latency_ms = run_stats
+            .iter()
+            .filter(|(_, s)| !s.durations_ms.is_empty())
+            .map(|(category, s)| {
+                let percentiles = LatencyPercentiles {
+                    p50: s.p50_ms(),
+                    p95: s.p95_ms(),
+                    p99: s.p99_ms(),
+                };
+                (category.clone(), percentiles)
+            })
+            .collect();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReportHistory {
+    #[serde(default)]
+    pub runs: Vec<RunReportEntry>,
+}
+
+pub fn check_pre_phase3_consistency(
+    nav: &HashMap<String, Vec<ApiNavEntry>>,
+    lists: &HashMap<String, HashMap<MenuId, Vec<ApiListItem>>>,
+    expected_langs: &[String],
+    history: Option<&RunReportHistory>,
+) -> Vec<ConsistencyWarning> {
+    let mut warnings = Vec::new();
+    let current_counts = list_item_counts(lists);
+
+    for lang in expected_langs {
+        let nav_menu_ids: HashSet<MenuId> = nav
+            .get(lang)
+            .into_iter()
+            .flatten()
+            .filter_map(transform::common::transform_nav_item)
+            .map(|menu| menu.menu_id)
+            .collect();
+        let lang_lists = lists.get(lang);
+        let non_empty_menu_ids: HashSet<MenuId> = lang_lists
+            .into_iter()
+            .flatten()
+            .filter(|(_, items)| !items.is_empty())
+            .map(|(menu_id, _)| *menu_id)
+            .collect();
+
+        if nav_menu_ids.len() != non_empty_menu_ids.len() {
+            let mut missing_menu_ids: Vec<MenuId> = nav_menu_ids
+                .difference(&non_empty_menu_ids)
+                .copied()
+                .collect();
+            missing_menu_ids.sort_unstable();
+            warnings.push(ConsistencyWarning::MenuCountMismatch {
+                lang: lang.clone(),
+                nav_menus: nav_menu_ids.len(),
+                list_menus: non_empty_menu_ids.len(),
+                missing_menu_ids,
+            });
+        }
+
+        if current_counts.get(lang).copied().unwrap_or(0) == 0 {
+            warnings.push(ConsistencyWarning::NoListItems { lang: lang.clone() });
+        }
+    }
+
+    if let Some(previous_run) = history.and_then(|h| h.runs.last()) {
+        // Only compare languages present in both runs so a different -l selection is not drift.
+        let (previous, current) = expected_langs
+            .iter()
+            .filter_map(|lang| {
+                previous_run
+                    .list_items_by_lang
+                    .get(lang)
+                    .map(|&prev| (prev, current_counts.get(lang).copied().unwrap_or(0)))
+            })
+            .fold((0usize, 0usize), |(p, c), (prev, cur)| (p + prev, c + cur));
+        if previous > 0 {
+            let drift = (current as f64 - previous as f64).abs() / previous as f64;
+            if drift > LIST_TOTAL_TOLERANCE {
+                warnings.push(ConsistencyWarning::ListTotalDrift { previous, current });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Logs each warning, as errors under `--abort-on-consistency-warning`. Returns
+/// whether the caller should abort before `next_step`.
+pub fn log_warnings(warnings: &[ConsistencyWarning], next_step: &str) -> bool {
+    let abort_on_warning = config::abort_on_consistency_warning();
+    let level = if abort_on_warning {
+        LogLevel::Error
+    } else {
+        LogLevel::Warning
+    };
+    for warning in warnings {
+        log(level, &format!("Consistency check: {}", warning));
+    }
+    if abort_on_warning && !warnings.is_empty() {
+        log(
+            LogLevel::Error,
+            &format!(
+                "Aborting before {}: {} consistency warning(s) with --abort-on-consistency-warning.",
+                next_step,
+                warnings.len()
+            ),
+        );
+        return true;
+    }
+    false
+}
+
+pub fn list_item_counts(
+    lists: &HashMap<String, HashMap<MenuId, Vec<ApiListItem>>>,
+) -> BTreeMap<String, usize> {
+    lists
+        .iter()
+        .map(|(lang, menus)| (lang.clone(), menus.values().map(Vec::len).sum()))
+        .collect()
+}
+
+fn history_path(out_dir: &Path) -> PathBuf {
+    out_dir.join("reports").join(RUN_HISTORY_FILE)
+}
+
+pub fn load_run_history(out_dir: &Path) -> Option<RunReportHistory> {
+    let path = history_path(out_dir);
+    let content = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(history) => Some(history),
+        Err(e) => {
+            log(
+                LogLevel::Warning,
+                &format!("Ignoring unreadable run history {}: {}", path.display(), e),
+            );
+            None
+        }
+    }
+}
+
+pub async fn record_run(
+    out_dir: &Path,
+    mut history: RunReportHistory,
+    entry: RunReportEntry,
+) -> AppResult<bool> {
+    history.runs.push(entry);
+    let overflow = history.runs.len().saturating_sub(RUN_HISTORY_MAX_ENTRIES);
+    history.runs.drain(..overflow);
+
+    let path = history_path(out_dir);
+    tokio::fs::create_dir_all(out_dir.join("reports")).await?;
+    io::save_json(path, history, "Run history".to_string()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn nav(menu_ids: &[MenuId]) -> Vec<ApiNavEntry> {
+        menu_ids
+            .iter()
+            .map(|id| {
+                serde_json::from_value(json!({
+                    "menu": { "menu_id": id.to_string() },
+                    "name": format!("Menu {}", id),
+                    "icon_url": ""
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn items(count: usize) -> Vec<ApiListItem> {
+        (1..=count)
+            .map(|id| serde_json::from_value(json!({ "entry_page_id": id.to_string() })).unwrap())
+            .collect()
+    }
+
+    type Lists = HashMap<String, HashMap<MenuId, Vec<ApiListItem>>>;
+
+    fn lists(entries: &[(&str, MenuId, usize)]) -> Lists {
+        let mut lists: Lists = HashMap::new();
+        for &(lang, menu_id, count) in entries {
+            lists
+                .entry(lang.to_string())
+                .or_default()
+                .insert(menu_id, items(count));
+        }
+        lists
+    }
+
+    fn langs(langs: &[&str]) -> Vec<String> {
+        langs.iter().map(ToString::to_string).collect()
+    }
+
+    fn history(counts: &[(&str, usize)]) -> RunReportHistory {
+        RunReportHistory {
+            runs: vec![RunReportEntry::new(
+                counts
+                    .iter()
+                    .map(|(lang, count)| (lang.to_string(), *count))
+                    .collect(),
+            )],
+        }
+    }
+
+    #[test]
+    fn consistent_data_has_no_warnings() {
+        let nav = HashMap::from([("en-us".to_string(), nav(&[2, 4]))]);
+        let lists = lists(&[("en-us", 2, 3), ("en-us", 4, 1)]);
+        let warnings = check_pre_phase3_consistency(&nav, &lists, &langs(&["en-us"]), None);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn empty_list_counts_as_a_missing_menu() {
+        let nav = HashMap::from([("en-us".to_string(), nav(&[2, 4, 5]))]);
+        let lists = lists(&[("en-us", 2, 3), ("en-us", 4, 0)]);
+        let warnings = check_pre_phase3_consistency(&nav, &lists, &langs(&["en-us"]), None);
+        assert_eq!(
+            warnings,
+            [ConsistencyWarning::MenuCountMismatch {
+                lang: "en-us".to_string(),
+                nav_menus: 3,
+                list_menus: 1,
+                missing_menu_ids: vec![4, 5],
+            }]
+        );
+    }
+
+    #[test]
+    fn language_without_lists_warns_twice() {
+        let nav = HashMap::from([
+            ("en-us".to_string(), nav(&[2])),
+            ("ja-jp".to_string(), nav(&[2])),
+        ]);
+        let lists = lists(&[("en-us", 2, 3)]);
+        let warnings =
+            check_pre_phase3_consistency(&nav, &lists, &langs(&["en-us", "ja-jp"]), None);
+        assert_eq!(
+            warnings,
+            [
+                ConsistencyWarning::MenuCountMismatch {
+                    lang: "ja-jp".to_string(),
+                    nav_menus: 1,
+                    list_menus: 0,
+                    missing_menu_ids: vec![2],
+                },
+                ConsistencyWarning::NoListItems {
+                    lang: "ja-jp".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn list_total_drift_beyond_tolerance_warns() {
+        let nav = HashMap::from([("en-us".to_string(), nav(&[2]))]);
+        let lists = lists(&[("en-us", 2, 7)]);
+        let expected = langs(&["en-us"]);
+
+        let within = history(&[("en-us", 8)]);
+        assert!(check_pre_phase3_consistency(&nav, &lists, &expected, Some(&within)).is_empty());
+
+        let beyond = history(&[("en-us", 10)]);
+        assert_eq!(
+            check_pre_phase3_consistency(&nav, &lists, &expected, Some(&beyond)),
+            [ConsistencyWarning::ListTotalDrift {
+                previous: 10,
+                current: 7
+            }]
+        );
+    }
+
+    #[test]
+    fn drift_only_compares_languages_in_both_runs() {
+        let nav = HashMap::from([("en-us".to_string(), nav(&[2]))]);
+        let lists = lists(&[("en-us", 2, 10)]);
+        let previous = history(&[("en-us", 10), ("ja-jp", 500)]);
+        let warnings =
+            check_pre_phase3_consistency(&nav, &lists, &langs(&["en-us"]), Some(&previous));
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        let empty_previous = history(&[("ko-kr", 10)]);
+        let warnings =
+            check_pre_phase3_consistency(&nav, &lists, &langs(&["en-us"]), Some(&empty_previous));
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn only_the_latest_run_is_compared() {
+        let nav = HashMap::from([("en-us".to_string(), nav(&[2]))]);
+        let lists = lists(&[("en-us", 2, 10)]);
+        let mut runs = history(&[("en-us", 100)]);
+        runs.runs.extend(history(&[("en-us", 11)]).runs);
+        let warnings = check_pre_phase3_consistency(&nav, &lists, &langs(&["en-us"]), Some(&runs));
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+}
@@ -1,10 +1,12 @@
 use crate::api;
+use crate::core::consistency_gate::RunReportEntry;
 use crate::model;
 use crate::model::common::{EntryId, MenuId};
 use crate::transform::bulk::BulkStore;
 use schemars::JsonSchema;
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 #[derive(Debug, Default)]
 pub struct RawData {
@@ -14,17 +16,24 @@ pub struct RawData {
     pub calendars: HashMap<String, api::model::ApiCalendarResponse>,
 }
 
+// The large per-language vectors are shared with the write-behind save queue.
 #[derive(Debug, Default, JsonSchema, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransformedData {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub navigation: HashMap<String, Vec<model::output::OutputNavMenuItem>>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub lists: HashMap<String, Vec<model::output::OutputListFile>>,
+    pub lists: HashMap<String, Arc<Vec<model::output::OutputListFile>>>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub details: HashMap<String, Vec<model::output::OutputDetailPage>>,
+    pub details: HashMap<String, Arc<Vec<model::output::OutputDetailPage>>>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub calendars: HashMap<String, model::output::OutputCalendarFile>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub bulk: HashMap<String, Arc<Vec<model::output::OutputBulkEntry>>>,
+    /// Run history entry for this fetch; `run` appends it once the output is saved.
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub run_report: Option<RunReportEntry>,
 }
 
 #[derive(Default)]
@@ -32,5 +41,6 @@ pub struct InMemoryDataStore {
     pub raw: RawData,
     pub transformed: TransformedData,
     pub all_ids: HashMap<String, HashSet<EntryId>>,
+    pub post_ids: HashMap<String, HashSet<EntryId>>,
     pub all_bulk_stores: HashMap<String, BulkStore>,
 }
@@ -0,0 +1,234 @@
+use crate::error::AppResult;
+use crate::io;
+use crate::model::common::{EntryId, MenuId};
+use crate::model::output::{
+    OutputDetailIndex, OutputDetailIndexEntry, OutputDetailPage, OutputListFile, OutputListIndex,
+    OutputListIndexEntry,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub const DIR_INDEX_FILE: &str = "index.json";
+
+pub type PendingIndexes = (
+    Vec<(PathBuf, OutputDetailIndex)>,
+    Vec<(PathBuf, OutputListIndex)>,
+);
+
+/// Per-language `index.json` contents for the `detail/` and `list/` directories,
+/// filled in by save tasks as each file is actually written.
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryIndexes {
+    inner: Arc<Mutex<IndexedFiles>>,
+}
+
+#[derive(Debug, Default)]
+struct IndexedFiles {
+    details: BTreeMap<String, BTreeMap<EntryId, OutputDetailIndexEntry>>,
+    lists: BTreeMap<String, BTreeMap<MenuId, OutputListIndexEntry>>,
+}
+
+pub fn detail_index_entry(page: &OutputDetailPage) -> OutputDetailIndexEntry {
+    OutputDetailIndexEntry {
+        id: page.id,
+        menu_id: page.menu_id,
+        name: page.name.clone(),
+        icon_url: page.icon_url.clone(),
+        version: page.version,
+    }
+}
+
+pub fn list_index_entry(list_file: &OutputListFile) -> OutputListIndexEntry {
+    OutputListIndexEntry {
+        menu_id: list_file.menu_id,
+        menu_name: list_file.menu_name.clone(),
+        total_items: list_file.total_items,
+    }
+}
+
+impl DirectoryIndexes {
+    pub fn record_detail(&self, lang: &str, entry: OutputDetailIndexEntry) {
+        if let Ok(mut files) = self.inner.lock() {
+            files
+                .details
+                .entry(lang.to_string())
+                .or_default()
+                .insert(entry.id, entry);
+        }
+    }
+
+    pub fn record_list(&self, lang: &str, entry: OutputListIndexEntry) {
+        if let Ok(mut files) = self.inner.lock() {
+            files
+                .lists
+                .entry(lang.to_string())
+                .or_default()
+                .insert(entry.menu_id, entry);
+        }
+    }
+
+    /// Drains the recorded entries into `(path, index)` pairs under `out_dir`.
+    pub fn take(&self, out_dir: &Path) -> PendingIndexes {
+        let Ok(mut files) = self.inner.lock() else {
+            return (Vec::new(), Vec::new());
+        };
+        let details = std::mem::take(&mut files.details)
+            .into_iter()
+            .map(|(lang, entries)| {
+                let path = out_dir.join("detail").join(&lang).join(DIR_INDEX_FILE);
+                let entries: Vec<_> = entries.into_values().collect();
+                let index = OutputDetailIndex {
+                    language: lang,
+                    total_entries: entries.len(),
+                    entries,
+                };
+                (path, index)
+            })
+            .collect();
+        let lists = std::mem::take(&mut files.lists)
+            .into_iter()
+            .map(|(lang, menus)| {
+                let path = out_dir.join("list").join(&lang).join(DIR_INDEX_FILE);
+                let menus: Vec<_> = menus.into_values().collect();
+                let index = OutputListIndex {
+                    language: lang,
+                    total_menus: menus.len(),
+                    menus,
+                };
+                (path, index)
+            })
+            .collect();
+        (details, lists)
+    }
+}
+
+pub async fn save_detail_index(path: PathBuf, index: OutputDetailIndex) -> AppResult<bool> {
+    let ctx = format!("Detail index [{}]", index.language);
+    io::save_json(path, index, ctx).await
+}
+
+pub async fn save_list_index(path: PathBuf, index: OutputListIndex) -> AppResult<bool> {
+    let ctx = format!("List index [{}]", index.language);
+    io::save_json(path, index, ctx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::processor::run_save_queue;
+    use crate::transform::LangTransformResult;
+    use serde_json::{from_value, json, Value};
+    use tokio::sync::mpsc;
+
+    fn detail(id: EntryId, name: &str, menu_id: MenuId) -> OutputDetailPage {
+        OutputDetailPage {
+            id,
+            name: Some(name.to_string()),
+            icon_url: Some(format!("https://example.test/icons/{}.png", id)),
+            menu_id,
+            version: 7,
+            ..Default::default()
+        }
+    }
+
+    fn list_file(menu_id: MenuId, menu_name: &str, ids: &[EntryId]) -> OutputListFile {
+        let list: Vec<Value> = ids.iter().map(|id| json!({ "epId": id })).collect();
+        from_value(json!({
+            "version": 0,
+            "language": "en-us",
+            "menuId": menu_id,
+            "menuName": menu_name,
+            "totalItems": ids.len(),
+            "list": list
+        }))
+        .unwrap()
+    }
+
+    fn read_json(path: &Path) -> Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn take_drains_sorted_entries_per_language() {
+        let indexes = DirectoryIndexes::default();
+        indexes.record_detail("en-us", detail_index_entry(&detail(2001, "Dull Blade", 4)));
+        indexes.record_detail("en-us", detail_index_entry(&detail(1001, "Amber", 2)));
+        indexes.record_detail("ja-jp", detail_index_entry(&detail(1001, "アンバー", 2)));
+        indexes.record_list(
+            "en-us",
+            list_index_entry(&list_file(2, "Characters", &[1001])),
+        );
+
+        let out_dir = Path::new("out");
+        let (details, lists) = indexes.take(out_dir);
+        let detail_paths: Vec<_> = details.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(
+            detail_paths,
+            [
+                out_dir.join("detail/en-us/index.json"),
+                out_dir.join("detail/ja-jp/index.json")
+            ]
+        );
+        let en_ids: Vec<EntryId> = details[0].1.entries.iter().map(|e| e.id).collect();
+        assert_eq!(en_ids, [1001, 2001]);
+        assert_eq!(details[0].1.total_entries, 2);
+        assert_eq!(lists.len(), 1);
+        assert_eq!(lists[0].0, out_dir.join("list/en-us/index.json"));
+
+        let (details, lists) = indexes.take(out_dir);
+        assert!(details.is_empty() && lists.is_empty());
+    }
+
+    #[tokio::test]
+    async fn indexes_match_the_files_written_and_skip_failed_saves() {
+        let out = tempfile::tempdir().unwrap();
+        // A directory where 1002.json should go makes that save fail.
+        std::fs::create_dir_all(out.path().join("detail/en-us/1002.json")).unwrap();
+
+        let result = LangTransformResult {
+            lang: "en-us".to_string(),
+            navigation: Vec::new(),
+            lists: Arc::new(vec![
+                list_file(2, "Characters", &[1001, 1002]),
+                list_file(4, "Weapons", &[2001]),
+            ]),
+            details: Arc::new(vec![
+                detail(1001, "Amber", 2),
+                detail(1002, "Kaeya", 2),
+                detail(2001, "Dull Blade", 4),
+            ]),
+            calendar: None,
+            bulk: Arc::new(Vec::new()),
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        tx.send(result).unwrap();
+        drop(tx);
+        let stats = run_save_queue(rx, out.path().to_path_buf()).await;
+        assert_eq!(stats.fail, 1);
+
+        let detail_dir = out.path().join("detail/en-us");
+        let index = read_json(&detail_dir.join(DIR_INDEX_FILE));
+        let entries = index["entries"].as_array().unwrap();
+        assert_eq!(index["totalEntries"], entries.len());
+        let ids: Vec<&Value> = entries.iter().map(|entry| &entry["epId"]).collect();
+        assert_eq!(ids, [1001, 2001]);
+        for entry in entries {
+            let page = read_json(&detail_dir.join(format!("{}.json", entry["epId"])));
+            for key in ["epId", "menuId", "name", "iconUrl", "version"] {
+                assert_eq!(entry[key], page[key], "{} of {}", key, entry["epId"]);
+            }
+        }
+
+        let list_dir = out.path().join("list/en-us");
+        let index = read_json(&list_dir.join(DIR_INDEX_FILE));
+        let menus = index["menus"].as_array().unwrap();
+        assert_eq!(index["totalMenus"], 2);
+        for menu in menus {
+            let list = read_json(&list_dir.join(format!("{}.json", menu["menuId"])));
+            for key in ["menuId", "menuName", "totalItems"] {
+                assert_eq!(menu[key], list[key], "{} of menu {}", key, menu["menuId"]);
+            }
+        }
+    }
+}
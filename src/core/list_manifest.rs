@@ -0,0 +1,255 @@
+use crate::error::AppResult;
+use crate::io;
+use crate::logging::{log, LogLevel};
+use crate::model::common::MenuId;
+use crate::model::output::OutputListFile;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const LIST_MANIFEST_FILE: &str = "list_manifest.json";
+
+/// What each `list/<lang>/<menu_id>.json` held when it was last written.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ListManifest {
+    pub langs: BTreeMap<String, BTreeMap<MenuId, ListManifestRecord>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListManifestRecord {
+    pub menu_name: String,
+    pub total_items: usize,
+}
+
+/// A list file that now holds a differently-named menu than in an earlier run,
+/// typically after HoYo re-numbered a menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListMenuRename {
+    pub lang: String,
+    pub menu_id: MenuId,
+    pub previous_name: String,
+    pub current_name: String,
+}
+
+impl ListManifest {
+    /// Lists whose `<menu_id>.json` held a differently-named menu when last written.
+    pub fn renames(&self, lang: &str, lists: &[OutputListFile]) -> Vec<ListMenuRename> {
+        let Some(previous) = self.langs.get(lang) else {
+            return Vec::new();
+        };
+        lists
+            .iter()
+            .filter_map(|list_file| {
+                let record = previous.get(&list_file.menu_id)?;
+                (record.menu_name != list_file.menu_name).then(|| ListMenuRename {
+                    lang: lang.to_string(),
+                    menu_id: list_file.menu_id,
+                    previous_name: record.menu_name.clone(),
+                    current_name: list_file.menu_name.clone(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn record(&mut self, lang: &str, lists: &[OutputListFile]) {
+        let records = self.langs.entry(lang.to_string()).or_default();
+        for list_file in lists {
+            records.insert(
+                list_file.menu_id,
+                ListManifestRecord {
+                    menu_name: list_file.menu_name.clone(),
+                    total_items: list_file.total_items,
+                },
+            );
+        }
+    }
+}
+
+/// Keeps one list file per menu id; of two lists sharing an id the larger one wins,
+/// since both would be written to the same `<menu_id>.json`. Without collisions
+/// the shared vector is returned as is.
+pub fn drop_colliding_lists(
+    lang: &str,
+    lists: Arc<Vec<OutputListFile>>,
+) -> Arc<Vec<OutputListFile>> {
+    let mut kept: Vec<usize> = Vec::with_capacity(lists.len());
+    let mut slot_by_menu: HashMap<MenuId, usize> = HashMap::new();
+    for (index, list_file) in lists.iter().enumerate() {
+        let Some(&slot) = slot_by_menu.get(&list_file.menu_id) else {
+            slot_by_menu.insert(list_file.menu_id, kept.len());
+            kept.push(index);
+            continue;
+        };
+        let existing = &lists[kept[slot]];
+        let (winner, loser) = if list_file.total_items > existing.total_items {
+            (list_file, existing)
+        } else {
+            (existing, list_file)
+        };
+        log(
+            LogLevel::Error,
+            &format!(
+                "List file collision for menu {} [{}]: keeping '{}' ({} items), dropping '{}' ({} items).",
+                list_file.menu_id,
+                lang,
+                winner.menu_name,
+                winner.total_items,
+                loser.menu_name,
+                loser.total_items
+            ),
+        );
+        if list_file.total_items > existing.total_items {
+            kept[slot] = index;
+        }
+    }
+    if kept.len() == lists.len() {
+        return lists;
+    }
+    Arc::new(kept.into_iter().map(|index| lists[index].clone()).collect())
+}
+
+fn manifest_path(out_dir: &Path) -> PathBuf {
+    out_dir.join("reports").join(LIST_MANIFEST_FILE)
+}
+
+pub fn load_list_manifest(out_dir: &Path) -> ListManifest {
+    let path = manifest_path(out_dir);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return ListManifest::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Ignoring unreadable list manifest {}: {}",
+                path.display(),
+                e
+            ),
+        );
+        ListManifest::default()
+    })
+}
+
+pub async fn save_list_manifest(out_dir: &Path, manifest: ListManifest) -> AppResult<bool> {
+    tokio::fs::create_dir_all(out_dir.join("reports")).await?;
+    io::save_json(
+        manifest_path(out_dir),
+        manifest,
+        "List manifest".to_string(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::core::consistency_gate::RUN_HISTORY_FILE;
+    use chrono::Utc;
+
+    const LANG: &str = "en-us";
+
+    fn list_file(menu_id: MenuId, menu_name: &str, total_items: usize) -> OutputListFile {
+        OutputListFile {
+            version: Utc::now(),
+            language: LANG.to_string(),
+            menu_id,
+            menu_name: menu_name.to_string(),
+            total_items,
+            filter_vocabulary: HashMap::new(),
+            list: Vec::new(),
+        }
+    }
+
+    fn names(lists: &[OutputListFile]) -> Vec<(MenuId, &str)> {
+        lists
+            .iter()
+            .map(|list_file| (list_file.menu_id, list_file.menu_name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn two_menus_on_one_list_file_keep_the_larger() {
+        let lists = Arc::new(vec![
+            list_file(2, "Characters", 80),
+            list_file(4, "Weapons", 200),
+            list_file(2, "Character Archive", 120),
+            list_file(2, "Trial Characters", 120),
+        ]);
+        let kept = drop_colliding_lists(LANG, lists);
+        assert_eq!(
+            names(&kept),
+            [(2, "Character Archive"), (4, "Weapons")],
+            "larger list wins, ties keep the first, order follows the first occurrence"
+        );
+
+        let distinct = Arc::new(vec![
+            list_file(2, "Characters", 80),
+            list_file(4, "Weapons", 200),
+        ]);
+        assert!(Arc::ptr_eq(
+            &drop_colliding_lists(LANG, Arc::clone(&distinct)),
+            &distinct
+        ));
+    }
+
+    #[tokio::test]
+    async fn menu_renamed_between_runs_is_reported() {
+        let out = tempfile::tempdir().unwrap();
+        let mut first_run = load_list_manifest(out.path());
+        assert!(first_run.langs.is_empty());
+        first_run.record(
+            LANG,
+            &[list_file(2, "Characters", 80), list_file(4, "Weapons", 200)],
+        );
+        save_list_manifest(out.path(), first_run).await.unwrap();
+
+        let second_run = load_list_manifest(out.path());
+        let current = [
+            list_file(2, "Characters", 81),
+            list_file(4, "Namecards", 150),
+            list_file(5, "Artifacts", 40),
+        ];
+        assert_eq!(
+            second_run.renames(LANG, &current),
+            [ListMenuRename {
+                lang: LANG.to_string(),
+                menu_id: 4,
+                previous_name: "Weapons".to_string(),
+                current_name: "Namecards".to_string(),
+            }]
+        );
+        assert!(second_run.renames("ja-jp", &current).is_empty());
+    }
+
+    #[tokio::test]
+    async fn clean_keeps_the_manifest() {
+        assert!(config::CLEAN_KEPT_FILES.contains(&("reports", LIST_MANIFEST_FILE)));
+        assert!(config::CLEAN_KEPT_FILES.contains(&("reports", RUN_HISTORY_FILE)));
+
+        let out = tempfile::tempdir().unwrap();
+        let mut manifest = ListManifest::default();
+        manifest.record(LANG, &[list_file(2, "Characters", 80)]);
+        save_list_manifest(out.path(), manifest).await.unwrap();
+        let reports = out.path().join("reports");
+        std::fs::write(reports.join("statistics.json"), "{}").unwrap();
+        std::fs::create_dir_all(reports.join("nested")).unwrap();
+        std::fs::create_dir_all(out.path().join("list/en-us")).unwrap();
+
+        crate::io::clean_output_directories(out.path())
+            .await
+            .unwrap();
+
+        assert!(!out.path().join("list").exists());
+        let left: Vec<_> = std::fs::read_dir(&reports)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(left, [LIST_MANIFEST_FILE]);
+        let reloaded = load_list_manifest(out.path());
+        assert_eq!(reloaded.langs[LANG][&2].menu_name, "Characters");
+    }
+}
@@ -4,9 +4,11 @@ use crate::error::AppResult;
 use crate::io;
 use crate::model::common::MenuId;
 use crate::model::output::{
-    ComponentData, FilterValue, OutputCalendarAbstract, OutputCalendarFile, OutputCalendarItem,
-    OutputCalendarOpItem, OutputDetailPage, OutputGalleryCharacterItem, OutputListFile,
-    OutputListItem, OutputNavMenuItem,
+    ComponentData, FilterValue, OutputAchievementGroupData, OutputBuildSection, OutputBulkEntry,
+    OutputCalendarAbstract, OutputCalendarFile, OutputCalendarItem, OutputCalendarOpItem,
+    OutputDetailPage, OutputEventCalendarData, OutputGalleryCharacterItem, OutputGiftRewardData,
+    OutputListFile, OutputListItem, OutputMapResourceData, OutputNavMenuItem,
+    OutputProgressionData, ProgressionKind,
 };
 use crate::transform::common::to_camel_case;
 use chrono::{DateTime, Utc};
@@ -53,13 +55,26 @@ pub async fn create_merged_schema_files(
     )
     .await?;
 
+    let bulk_example = generate_merged_bulk_example(&transformed_data);
+    let path_bulk = merged_dir.join("bulk.json");
+
+    io::save_json(path_bulk, bulk_example, "Merged Bulk Schema".to_string()).await?;
+
     Ok(())
 }
 
+// Per-language maps are HashMaps; examples are picked in language order so the merged
+// files only change when the data does.
+fn by_lang<T>(per_lang: &HashMap<String, T>) -> Vec<(&String, &T)> {
+    let mut entries: Vec<(&String, &T)> = per_lang.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
 fn generate_merged_nav_example(transformed_data: &TransformedData) -> Vec<OutputNavMenuItem> {
-    transformed_data
-        .navigation
-        .values()
+    by_lang(&transformed_data.navigation)
+        .into_iter()
+        .map(|(_, menus)| menus)
         .find(|v| !v.is_empty())
         .cloned()
         .unwrap_or_else(|| {
@@ -71,42 +86,82 @@ fn generate_merged_nav_example(transformed_data: &TransformedData) -> Vec<Output
         })
 }
 
+fn generate_merged_bulk_example(transformed_data: &TransformedData) -> Vec<OutputBulkEntry> {
+    let mut example_entry = by_lang(&transformed_data.bulk)
+        .into_iter()
+        .find_map(|(_, entries)| entries.iter().min_by_key(|entry| entry.id))
+        .cloned()
+        .unwrap_or_else(|| OutputBulkEntry {
+            id: 0,
+            ..Default::default()
+        });
+
+    if example_entry.name.is_none() {
+        example_entry.name = Some("Default Name".to_string());
+    }
+    if example_entry.desc.is_none() {
+        example_entry.desc = Some("Default description.".to_string());
+    }
+    if example_entry.icon_url.is_none() {
+        example_entry.icon_url = Some("default.png".to_string());
+    }
+    for source_lang in [
+        &mut example_entry.name_lang,
+        &mut example_entry.desc_lang,
+        &mut example_entry.icon_lang,
+    ] {
+        source_lang.get_or_insert_with(|| config::PREFLIGHT_LANG.to_string());
+    }
+    vec![example_entry]
+}
+
 fn generate_merged_list_example(transformed_data: &TransformedData) -> OutputListFile {
     let mut all_filter_keys: HashSet<String> = HashSet::new();
     let mut first_list_item: Option<OutputListItem> = None;
     let mut first_list_file_metadata: Option<(i64, String, String, MenuId)> = None;
+    let list_metadata = |lang: &String, list_file: &OutputListFile| {
+        (
+            list_file.version.timestamp(),
+            lang.clone(),
+            list_file.menu_name.clone(),
+            list_file.menu_id,
+        )
+    };
 
-    for (lang, lang_lists) in &transformed_data.lists {
-        for list_file in lang_lists {
-            if !list_file.list.is_empty() {
-                if first_list_item.is_none() {
-                    first_list_item = list_file.list.first().cloned();
-                }
-                if first_list_file_metadata.is_none() {
-                    first_list_file_metadata = Some((
-                        list_file.version.timestamp(),
-                        lang.clone(),
-                        list_file.menu_name.clone(),
-                        list_file.menu_id,
-                    ));
-                }
-            } else if first_list_file_metadata.is_none() {
-                first_list_file_metadata = Some((
-                    list_file.version.timestamp(),
-                    lang.clone(),
-                    list_file.menu_name.clone(),
-                    list_file.menu_id,
-                ));
-            }
+    for (lang, lang_lists) in by_lang(&transformed_data.lists) {
+        for list_file in lang_lists.iter() {
             for item in &list_file.list {
                 for key in item.filter_values.keys() {
                     all_filter_keys.insert(key.clone());
                 }
             }
         }
+        if first_list_item.is_some() {
+            continue;
+        }
+        // Lowest entry id in the first language that has any items, with its list's metadata.
+        let lowest = lang_lists
+            .iter()
+            .filter_map(|list_file| {
+                list_file
+                    .list
+                    .iter()
+                    .min_by_key(|item| item.id)
+                    .map(|item| (list_file, item))
+            })
+            .min_by_key(|(list_file, item)| (item.id, list_file.menu_id));
+        if let Some((list_file, item)) = lowest {
+            first_list_item = Some(item.clone());
+            first_list_file_metadata = Some(list_metadata(lang, list_file));
+        } else if first_list_file_metadata.is_none() {
+            first_list_file_metadata = lang_lists
+                .iter()
+                .min_by_key(|list_file| list_file.menu_id)
+                .map(|list_file| list_metadata(lang, list_file));
+        }
     }
 
-    for &snake_key in config::LIST_FILTER_FIELDS.iter() {
+    for snake_key in config::list_filter_fields() {
         all_filter_keys.insert(to_camel_case(snake_key));
     }
 
@@ -125,6 +180,8 @@ fn generate_merged_list_example(transformed_data: &TransformedData) -> OutputLis
         icon_url: "default.png".to_string(),
         desc: Some("Default description.".to_string()),
         filter_values: HashMap::new(),
+        raw_filters: None,
+        link: None,
     });
 
     let mut sorted_keys: Vec<String> = all_filter_keys.into_iter().collect();
@@ -157,6 +214,7 @@ fn generate_merged_list_example(transformed_data: &TransformedData) -> OutputLis
         menu_id,
         menu_name,
         total_items: 1,
+        filter_vocabulary: HashMap::new(),
         list: vec![example_item],
     }
 }
@@ -168,7 +226,7 @@ fn generate_merged_detail_example(transformed_data: &TransformedData) -> OutputD
     let mut all_component_keys: HashSet<String> = transformed_data
         .details
         .values()
-        .flatten()
+        .flat_map(|pages| pages.iter())
         .flat_map(|page| page.components.keys().cloned())
         .collect();
 
@@ -183,19 +241,34 @@ fn generate_merged_detail_example(transformed_data: &TransformedData) -> OutputD
         config::COMPONENT_ARTIFACT_LIST,
         config::COMPONENT_RELIQUARY_SET_EFFECT,
         config::COMPONENT_MAP,
+        config::COMPONENT_MAP_RESOURCE,
         config::COMPONENT_CUSTOMIZE,
         config::COMPONENT_TEXTUAL_RESEARCH,
         config::COMPONENT_TIMELINE,
         config::COMPONENT_VIDEO_COLLECTION,
         config::COMPONENT_TCG,
         config::COMPONENT_DROP_MATERIAL,
+        config::COMPONENT_GIFT_REWARD,
+        config::COMPONENT_RECOMMEND,
+        config::COMPONENT_EVENT_CALENDAR,
+        config::COMPONENT_ACHIEVEMENT_GROUP,
+        config::COMPONENT_PROGRESSION,
         config::COMPONENT_BODY,
     ];
     for &key in default_keys_camel.iter() {
         all_component_keys.insert(to_camel_case(key));
     }
 
-    for page in transformed_data.details.values().flatten() {
+    let ordered_pages: Vec<&OutputDetailPage> = by_lang(&transformed_data.details)
+        .into_iter()
+        .flat_map(|(_, pages)| {
+            let mut pages: Vec<&OutputDetailPage> = pages.iter().collect();
+            pages.sort_unstable_by_key(|page| page.id);
+            pages
+        })
+        .collect();
+
+    for page in ordered_pages {
         if first_page_metadata.is_none() {
             first_page_metadata = Some(OutputDetailPage {
                 id: page.id,
@@ -203,11 +276,22 @@ fn generate_merged_detail_example(transformed_data: &TransformedData) -> OutputD
                 desc: page.desc.clone(),
                 icon_url: page.icon_url.clone(),
                 header_img_url: page.header_img_url.clone(),
+                primary_image: page.primary_image.clone(),
                 filter_values: page.filter_values.clone(),
+                raw_filters: page.raw_filters.clone(),
+                link: page.link.clone(),
                 menu_id: page.menu_id,
                 menu_name: page.menu_name.clone(),
+                menu_ids: page.menu_ids.clone(),
                 version: page.version,
                 components: HashMap::new(),
+                alias_of: None,
+                component_ids: HashMap::new(),
+                components_text: HashMap::new(),
+                popped_components: page.popped_components.clone(),
+                degraded_components: Vec::new(),
+                node_defs: Vec::new(),
+                event_window: page.event_window.clone(),
             });
         }
 
@@ -230,21 +314,39 @@ fn generate_merged_detail_example(transformed_data: &TransformedData) -> OutputD
         desc: Some("Default Desc".to_string()),
         icon_url: Some("default.png".to_string()),
         header_img_url: Some("default_header.png".to_string()),
+        primary_image: Some("default_header.png".to_string()),
         filter_values: HashMap::new(),
+        raw_filters: None,
+        link: None,
         menu_id: 0,
         menu_name: Some("Default Menu".to_string()),
+        menu_ids: Vec::new(),
         version: Utc::now().timestamp(),
         components: HashMap::new(),
+        alias_of: None,
+        component_ids: HashMap::new(),
+        components_text: HashMap::new(),
+        popped_components: Vec::new(),
+        degraded_components: Vec::new(),
+        node_defs: Vec::new(),
+        event_window: None,
     });
+    base_page.alias_of = Some(0);
+    if base_page.menu_name.is_none() {
+        base_page.menu_name = Some("Default Menu".to_string());
+    }
+    if base_page.menu_ids.is_empty() {
+        base_page.menu_ids = vec![base_page.menu_id];
+    }
 
     let mut all_filter_keys_detail: HashSet<String> = transformed_data
         .details
         .values()
-        .flatten()
+        .flat_map(|pages| pages.iter())
         .flat_map(|page| page.filter_values.keys().cloned())
         .collect();
 
-    for &snake_key in config::LIST_FILTER_FIELDS.iter() {
+    for snake_key in config::list_filter_fields() {
         all_filter_keys_detail.insert(to_camel_case(snake_key));
     }
 
@@ -303,12 +405,44 @@ fn create_default_component_data(camel_case_key: &str) -> ComponentData {
         "artifactList" => ComponentData::ArtifactList(Default::default()),
         "reliquarySetEffect" => ComponentData::ReliquarySetEffect(Default::default()),
         "map" => ComponentData::MapUrl(Default::default()),
+        "mapResource" => ComponentData::MapResource(OutputMapResourceData {
+            map_id: Some(0),
+            resource_count: Some(0),
+            map_url: Some("https://default.map".to_string()),
+        }),
         "customize" => ComponentData::Customize(Vec::new()),
         "textualResearch" => ComponentData::TextualResearch(vec![Default::default()]),
         "timeline" => ComponentData::Timeline(Default::default()),
         "videoCollection" => ComponentData::VideoCollection(vec![Default::default()]),
         "tcg" => ComponentData::Tcg(Default::default()),
         "dropMaterial" => ComponentData::DropMaterial(Vec::new()),
+        "giftReward" | "companionGift" => ComponentData::GiftReward(OutputGiftRewardData {
+            liked: vec![Default::default()],
+            disliked: vec![Default::default()],
+        }),
+        "recommend" | "strategy" => ComponentData::Build(vec![OutputBuildSection {
+            title: "Default Build".to_string(),
+            weapons: vec![Default::default()],
+            artifacts: vec![Default::default()],
+            ..Default::default()
+        }]),
+        "eventCalendar" => ComponentData::EventCalendar(OutputEventCalendarData {
+            start_time: Some("2024-01-01T10:00:00Z".to_string()),
+            end_time: Some("2024-01-15T03:59:59Z".to_string()),
+            start_timestamp: Some(1704103200),
+            end_timestamp: Some(1705291199),
+            rewards: Some(Vec::new()),
+            description: Vec::new(),
+            ..Default::default()
+        }),
+        "progression" => ComponentData::Progression(OutputProgressionData {
+            kind: ProgressionKind::Constellation,
+            entries: vec![Default::default()],
+        }),
+        "achievementGroup" => ComponentData::AchievementGroup(OutputAchievementGroupData {
+            group_name: "Default Group".to_string(),
+            items: vec![Default::default()],
+        }),
         _ => ComponentData::Unknown(Value::Null),
     }
 }
@@ -318,7 +452,7 @@ fn generate_merged_calendar_example(transformed_data: &TransformedData) -> Outpu
     let mut merged_op_items: Option<Vec<OutputCalendarOpItem>> = None;
     let mut first_calendar_metadata: Option<(i64, String)> = None;
 
-    for (lang, calendar_file) in &transformed_data.calendars {
+    for (lang, calendar_file) in by_lang(&transformed_data.calendars) {
         if first_calendar_metadata.is_none() {
             first_calendar_metadata = Some((calendar_file.version.timestamp(), lang.clone()));
         }
@@ -449,3 +583,97 @@ fn generate_merged_calendar_example(transformed_data: &TransformedData) -> Outpu
         op: merged_op_items.unwrap_or_default(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::model::{ApiDetailResponse, ApiWrapper};
+    use crate::transform::bulk::BulkStore;
+    use schemars::{schema_for, JsonSchema};
+    use serde::Serialize;
+
+    fn assert_matches_schema<T: JsonSchema + Serialize>(example: &T, label: &str) {
+        let schema = serde_json::to_value(schema_for!(T)).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let instance = serde_json::to_value(example).unwrap();
+        let errors: Vec<String> = validator
+            .iter_errors(&instance)
+            .map(|e| format!("{} at {}", e, e.instance_path))
+            .collect();
+        assert!(
+            errors.is_empty(),
+            "{} example violates its schema:\n{}",
+            label,
+            errors.join("\n")
+        );
+    }
+
+    fn assert_examples_match_schemas(transformed_data: &TransformedData) {
+        assert_matches_schema(&generate_merged_nav_example(transformed_data), "navigation");
+        assert_matches_schema(&generate_merged_list_example(transformed_data), "list");
+        assert_matches_schema(&generate_merged_detail_example(transformed_data), "detail");
+        assert_matches_schema(
+            &generate_merged_calendar_example(transformed_data),
+            "calendar",
+        );
+        assert_matches_schema(&generate_merged_bulk_example(transformed_data), "bulk");
+    }
+
+    async fn fixture_details() -> Vec<OutputDetailPage> {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let mut paths: Vec<_> = std::fs::read_dir(fixtures)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("detail_"))
+            })
+            .collect();
+        paths.sort();
+
+        let mut pages = Vec::new();
+        for path in paths {
+            let text = std::fs::read_to_string(&path).unwrap();
+            let wrapper: ApiWrapper<ApiDetailResponse> = serde_json::from_str(&text).unwrap();
+            let raw_page = wrapper.data.unwrap().page;
+            let transformed = crate::transform::detail::transform_detail_page(
+                raw_page,
+                Arc::new(BulkStore::default()),
+                Default::default(),
+                "en-us",
+            )
+            .await;
+            if let Ok(Some(page)) = transformed {
+                pages.push(page);
+            }
+        }
+        pages
+    }
+
+    #[test]
+    fn mismatched_shape_is_rejected() {
+        let example = generate_merged_list_example(&TransformedData::default());
+        let mut instance = serde_json::to_value(example).unwrap();
+        instance["menuId"] = Value::String("not a number".to_string());
+        let schema = serde_json::to_value(schema_for!(OutputListFile)).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        assert!(!validator.is_valid(&instance));
+    }
+
+    #[test]
+    fn default_examples_match_their_schemas() {
+        assert_examples_match_schemas(&TransformedData::default());
+    }
+
+    #[tokio::test]
+    async fn fixture_examples_match_their_schemas() {
+        let details = fixture_details().await;
+        assert!(!details.is_empty());
+        let transformed_data = TransformedData {
+            details: HashMap::from([("en-us".to_string(), Arc::new(details))]),
+            ..Default::default()
+        };
+        assert_examples_match_schemas(&transformed_data);
+    }
+}
@@ -1,4 +1,14 @@
+pub mod bulk_cache;
+pub mod bundle;
+pub mod consistency_gate;
 pub mod data_store;
+pub mod dir_index;
+pub mod list_manifest;
 pub mod merged_schema;
+pub mod pipeline;
 pub mod processor;
+pub mod schema_watch;
+pub mod statistics;
 pub mod stats;
+pub mod updater;
+pub mod verify;
@@ -0,0 +1,599 @@
+use crate::api::client::ApiClient;
+use crate::api::fetchers;
+use crate::api::model::ApiDetailPage;
+use crate::config::{self, RuntimeSettings};
+use crate::core::consistency_gate::{self, RunReportHistory};
+use crate::core::data_store::RawData;
+use crate::core::processor::{self, BulkFetchOutcome};
+use crate::core::stats::{self, CategoryStats, RunStats};
+use crate::error::AppResult;
+use crate::io;
+use crate::logging::{log, LogLevel};
+use crate::model::common::{EntryId, LangCode, MenuId};
+use crate::model::output::OutputAliasEntry;
+use crate::transform::{self, bulk::BulkInfo, bulk::BulkStore, LangTransformResult};
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+
+struct PipelineContext {
+    client: Arc<ApiClient>,
+    list_sem: Arc<Semaphore>,
+    detail_sem: Arc<Semaphore>,
+    bulk_sem: Arc<Semaphore>,
+    cal_sem: Arc<Semaphore>,
+    settings: RuntimeSettings,
+    run_history: Option<RunReportHistory>,
+    /// Set once any language fails the consistency gate with --abort-on-consistency-warning.
+    gate_aborted: AtomicBool,
+}
+
+#[derive(Default)]
+struct LangBulkState {
+    known: HashMap<EntryId, BulkInfo>,
+    requested: HashSet<EntryId>,
+    primary_stats: CategoryStats,
+    fallback_stats: CategoryStats,
+}
+
+impl LangBulkState {
+    async fn ensure_ids(
+        &mut self,
+        ctx: &PipelineContext,
+        lang: &LangCode,
+        ids: &HashSet<EntryId>,
+        detail_names: &HashMap<EntryId, String>,
+    ) {
+        let missing: HashSet<EntryId> = ids.difference(&self.requested).copied().collect();
+        if missing.is_empty() {
+            return;
+        }
+        self.requested.extend(missing.iter().copied());
+
+        match processor::fetch_lang_bulk_store(
+            &ctx.client,
+            ctx.bulk_sem.clone(),
+            lang,
+            &missing,
+            detail_names,
+            ctx.settings.bulk_fallback,
+        )
+        .await
+        {
+            Ok(outcome) => self.absorb(outcome),
+            Err(e) => {
+                log(
+                    LogLevel::Error,
+                    &format!("Pipeline bulk processing failed [{}]: {:?}", lang, e),
+                );
+                self.primary_stats.total_tasks += 1;
+                self.primary_stats.add_fail();
+            }
+        }
+    }
+
+    fn absorb(&mut self, outcome: BulkFetchOutcome) {
+        self.primary_stats.add_elapsed(outcome.primary_elapsed);
+        for &elapsed in &outcome.fallback_elapsed {
+            self.fallback_stats.add_elapsed(elapsed);
+        }
+        self.primary_stats.ok += outcome.primary_ok;
+        self.primary_stats.fail += outcome.primary_fail;
+        self.primary_stats.total_tasks += outcome.primary_ok + outcome.primary_fail;
+        self.fallback_stats.ok += outcome.fallback_ok;
+        self.fallback_stats.fail += outcome.fallback_fail;
+        self.fallback_stats.total_tasks += outcome.fallback_ok + outcome.fallback_fail;
+        self.known.extend(outcome.store.into_inner());
+    }
+
+    fn store_for(&self, ids: &HashSet<EntryId>) -> BulkStore {
+        BulkStore::from_map(
+            ids.iter()
+                .filter_map(|id| self.known.get(id).map(|info| (*id, info.clone())))
+                .collect(),
+        )
+    }
+}
+
+pub async fn run(target_langs: Vec<LangCode>, out_dir: PathBuf) -> AppResult<i32> {
+    let overall_start_time = Instant::now();
+    let start_ts_str = Utc::now().format("%Y-%m-%d %H:%M:%S %Z").to_string();
+
+    log(
+        LogLevel::Step,
+        &format!(
+            "Starting Wiki Update (pipeline mode, experimental) for {} language(s) at {}",
+            target_langs.len(),
+            start_ts_str
+        ),
+    );
+    log(
+        LogLevel::Info,
+        &format!("Output Directory: {}", out_dir.display()),
+    );
+
+    let client = Arc::new(ApiClient::new()?);
+    processor::run_preflight(&client).await?;
+    let settings = RuntimeSettings::current();
+    let ctx = Arc::new(PipelineContext {
+        client,
+        list_sem: Arc::new(Semaphore::new(settings.list_concurrency())),
+        detail_sem: Arc::new(Semaphore::new(settings.detail_concurrency())),
+        bulk_sem: Arc::new(Semaphore::new(settings.bulk_concurrency())),
+        cal_sem: Arc::new(Semaphore::new(settings.calendar_concurrency())),
+        settings,
+        run_history: consistency_gate::load_run_history(&out_dir),
+        gate_aborted: AtomicBool::new(false),
+    });
+
+    let mut run_stats = stats::initialize_stats();
+    let mut alias_report: BTreeMap<String, Vec<OutputAliasEntry>> = BTreeMap::new();
+
+    io::ensure_output_directories(&out_dir).await?;
+    let (save_tx, save_rx) = mpsc::unbounded_channel::<LangTransformResult>();
+    let saver_task = tokio::spawn(processor::run_save_queue(save_rx, out_dir.clone()));
+
+    config::set_current_phase("Pipeline Fetch & Transform");
+    let mut lang_tasks = JoinSet::new();
+    for lang in target_langs.iter() {
+        let ctx_clone = ctx.clone();
+        let lang_clone = lang.clone();
+        let save_tx_clone = save_tx.clone();
+        lang_tasks.spawn(async move {
+            run_language_pipeline(ctx_clone, lang_clone, save_tx_clone).await
+        });
+    }
+    drop(save_tx);
+
+    let require_all_langs = config::require_all_langs();
+    let mut nav_missing: Vec<String> = Vec::new();
+    while let Some(result) = lang_tasks.join_next().await {
+        match result {
+            Ok((lang, lang_stats, mut aliases)) => {
+                if ctx.gate_aborted.load(Ordering::Relaxed) {
+                    lang_tasks.abort_all();
+                }
+                if require_all_langs && lang_stats["Navigation"].ok == 0 {
+                    // Languages run independently here, so the others are cancelled
+                    // mid-flight; files they already saved stay on disk.
+                    nav_missing.push(lang.to_string());
+                    lang_tasks.abort_all();
+                }
+                aliases.sort_unstable_by_key(|entry| entry.id);
+                alias_report.insert(lang.to_string(), aliases);
+                for (category, cat_stats) in &lang_stats {
+                    run_stats
+                        .entry(category.clone())
+                        .or_default()
+                        .merge(cat_stats);
+                }
+            }
+            Err(e) if e.is_cancelled() => {}
+            Err(e) => {
+                log(
+                    LogLevel::Error,
+                    &format!("Language pipeline task panicked: {}", e),
+                );
+                run_stats.get_mut("Navigation").unwrap().add_fail();
+            }
+        }
+    }
+    let abort_message = if !nav_missing.is_empty() {
+        Some(format!(
+            "Aborting pipeline: no navigation data for [{}] with --require-all-langs.",
+            nav_missing.join(", ")
+        ))
+    } else if ctx.gate_aborted.load(Ordering::Relaxed) {
+        Some(
+            "Aborting pipeline: consistency warning(s) with --abort-on-consistency-warning."
+                .to_string(),
+        )
+    } else {
+        None
+    };
+    if let Some(message) = abort_message {
+        log(LogLevel::Error, &message);
+        // Every language task has ended and dropped its sender, so the queue only
+        // finishes the saves already handed to it.
+        if let Err(e) = saver_task.await {
+            log(LogLevel::Error, &format!("Save queue task panicked: {}", e));
+        }
+        return Ok(1);
+    }
+
+    config::set_current_phase("Save Files");
+    match saver_task.await {
+        Ok(save_stats) => {
+            run_stats.insert("Save Files".to_string(), save_stats);
+        }
+        Err(e) => {
+            log(LogLevel::Error, &format!("Save queue task panicked: {}", e));
+            run_stats.get_mut("Save Files").unwrap().add_fail();
+        }
+    }
+    processor::save_alias_report(
+        &out_dir,
+        alias_report,
+        run_stats.get_mut("Save Files").unwrap(),
+    )
+    .await;
+    processor::save_coercion_report(&out_dir, run_stats.get_mut("Save Files").unwrap()).await;
+    processor::save_component_key_collision_report(
+        &out_dir,
+        run_stats.get_mut("Save Files").unwrap(),
+    )
+    .await;
+    processor::save_event_window_conflict_report(
+        &out_dir,
+        run_stats.get_mut("Save Files").unwrap(),
+    )
+    .await;
+    processor::save_schema_drift_report(&out_dir, run_stats.get_mut("Save Files").unwrap()).await;
+    processor::save_failed_ids_report(&out_dir, run_stats.get_mut("Save Files").unwrap()).await;
+
+    log(
+        LogLevel::Info,
+        "Merged schemas, taxonomy files, search indexes, counts and the oversized component report are not generated in pipeline mode; run the staged mode to refresh them.",
+    );
+
+    let overall_duration = overall_start_time.elapsed();
+    stats::print_summary(&run_stats, &target_langs, overall_duration);
+    Ok(stats::determine_exit_code(&run_stats))
+}
+
+async fn run_language_pipeline(
+    ctx: Arc<PipelineContext>,
+    lang: LangCode,
+    save_tx: mpsc::UnboundedSender<LangTransformResult>,
+) -> (LangCode, RunStats, Vec<OutputAliasEntry>) {
+    let lang_start_time = Instant::now();
+    let mut lang_stats = stats::initialize_stats();
+    log(
+        LogLevel::Step,
+        &format!("--- Pipeline [{}] started ---", lang),
+    );
+
+    lang_stats.get_mut("Navigation").unwrap().set_total(1);
+    if !ctx.settings.skip_calendar {
+        lang_stats.get_mut("Calendar Fetch").unwrap().set_total(1);
+    }
+    let bootstrap = processor::fetch_language_bootstrap(
+        ctx.client.clone(),
+        ctx.cal_sem.clone(),
+        lang.clone(),
+        ctx.settings.skip_calendar,
+    )
+    .await;
+    let Some(active) = processor::record_language_bootstrap(bootstrap, &mut lang_stats) else {
+        return (lang, lang_stats, Vec::new());
+    };
+    let nav_entries = active.nav;
+
+    let lists = fetch_language_lists(&ctx, &lang, &nav_entries, &mut lang_stats).await;
+
+    // The staged run checks all languages at once before Phase 3; here each
+    // language is checked on its own before its detail fetch.
+    let lang_key = lang.to_string();
+    let consistency_warnings = consistency_gate::check_pre_phase3_consistency(
+        &HashMap::from([(lang_key.clone(), nav_entries.clone())]),
+        &HashMap::from([(lang_key.clone(), lists.clone())]),
+        &[lang_key],
+        ctx.run_history.as_ref(),
+    );
+    let gate_step = format!("the detail fetch for [{}]", lang);
+    if consistency_gate::log_warnings(&consistency_warnings, &gate_step) {
+        ctx.gate_aborted.store(true, Ordering::Relaxed);
+        return (lang, lang_stats, Vec::new());
+    }
+
+    let mut detail_ids: Vec<EntryId> = lists
+        .values()
+        .flatten()
+        .map(|item| item.entry_page_id)
+        .filter(|&id| id > 0)
+        .collect();
+    detail_ids.sort_unstable();
+    detail_ids.dedup();
+    lang_stats
+        .get_mut("Detail Fetch")
+        .unwrap()
+        .set_total(detail_ids.len());
+
+    let nav_lookup = Arc::new(transform::build_nav_lookup(&nav_entries));
+    let detail_fallbacks = Arc::new(transform::common::DetailFallbacks {
+        list: transform::common::build_list_fallback_map(&lists, &nav_lookup),
+        nav: Arc::clone(&nav_lookup),
+    });
+    let (detail_tx, detail_rx) = mpsc::channel::<ApiDetailPage>(config::PIPELINE_CHANNEL_CAPACITY);
+    let mut bulk_state = LangBulkState::default();
+    let mut aliases = Vec::new();
+
+    let (detail_stats, ()) = tokio::join!(
+        fetch_details_into(&ctx, &lang, detail_ids, detail_tx),
+        enrich_and_transform_details(
+            &ctx,
+            &lang,
+            detail_rx,
+            &mut bulk_state,
+            &detail_fallbacks,
+            &mut aliases,
+            &save_tx
+        ),
+    );
+    lang_stats.insert("Detail Fetch".to_string(), detail_stats);
+
+    let mut raw_for_lang = RawData::default();
+    if let Some(calendar_data) = active.calendar {
+        raw_for_lang
+            .calendars
+            .insert(lang.to_string(), calendar_data);
+    }
+
+    raw_for_lang
+        .navigation
+        .insert(lang.to_string(), nav_entries);
+    raw_for_lang.lists.insert(lang.to_string(), lists);
+
+    let remaining_ids = transform::collect_all_ids(&raw_for_lang)
+        .0
+        .remove(lang.as_str())
+        .unwrap_or_default();
+    bulk_state
+        .ensure_ids(&ctx, &lang, &remaining_ids, &HashMap::new())
+        .await;
+
+    let full_store = BulkStore::from_map(std::mem::take(&mut bulk_state.known));
+    let transform_stats = lang_stats.get_mut("Transform").unwrap();
+    transform_stats.set_total(1);
+    match transform::transform_language_data(
+        lang.as_str(),
+        Arc::new(raw_for_lang),
+        full_store,
+        nav_lookup,
+    )
+    .await
+    {
+        Ok(lang_result) => {
+            transform_stats.add_ok();
+            if save_tx.send(lang_result).is_err() {
+                log(
+                    LogLevel::Warning,
+                    &format!(
+                        "Save queue closed; output for [{}] will not be written.",
+                        lang
+                    ),
+                );
+            }
+        }
+        Err(e) => {
+            transform_stats.add_fail();
+            log(
+                LogLevel::Error,
+                &format!("Language transformation failed [{}]: {:?}", lang, e),
+            );
+        }
+    }
+
+    lang_stats.insert("Bulk Primary".to_string(), bulk_state.primary_stats);
+    lang_stats.insert("Bulk Fallback".to_string(), bulk_state.fallback_stats);
+
+    log(
+        LogLevel::Success,
+        &format!(
+            "--- Pipeline [{}] complete | Elapsed: {:?} ---",
+            lang,
+            lang_start_time.elapsed()
+        ),
+    );
+    (lang, lang_stats, aliases)
+}
+
+async fn fetch_language_lists(
+    ctx: &PipelineContext,
+    lang: &LangCode,
+    nav_entries: &[crate::api::model::ApiNavEntry],
+    lang_stats: &mut RunStats,
+) -> HashMap<MenuId, Vec<crate::api::model::ApiListItem>> {
+    let (nav_menus, duplicates) = transform::common::transform_nav_items(nav_entries);
+    transform::common::log_nav_duplicates(&duplicates, lang.as_str());
+    let menus: Vec<(MenuId, String)> = nav_menus
+        .into_iter()
+        .filter(|menu| ctx.settings.menu_allowed(menu.menu_id))
+        .map(|menu| (menu.menu_id, menu.name))
+        .collect();
+
+    let stats_list = lang_stats.get_mut("List Fetch").unwrap();
+    stats_list.set_total(menus.len());
+
+    let mut lists = HashMap::new();
+    let mut list_stream = stream::iter(menus)
+        .take_while(|_| futures::future::ready(!config::shutdown_requested()))
+        .map(|(menu_id, menu_name)| {
+            let client_c = ctx.client.clone();
+            let list_sem_c = ctx.list_sem.clone();
+            async move {
+                let result = fetchers::fetch_menu_list_items(
+                    &client_c, list_sem_c, lang, menu_id, &menu_name,
+                )
+                .await;
+                (menu_id, result)
+            }
+        })
+        .buffer_unordered(ctx.settings.list_concurrency() * 2);
+
+    while let Some((menu_id, result)) = list_stream.next().await {
+        match result {
+            Ok(items) if items.is_empty() => stats_list.add_skip(),
+            Ok(items) => {
+                stats_list.add_ok();
+                lists.insert(menu_id, items);
+            }
+            Err(e) => {
+                log(
+                    LogLevel::Warning,
+                    &format!(
+                        "List Fetch Error for Menu {} [{}]: {:?} - Marking as Skipped",
+                        menu_id, lang, e
+                    ),
+                );
+                stats_list.add_skip();
+            }
+        }
+    }
+    lists
+}
+
+async fn fetch_details_into(
+    ctx: &PipelineContext,
+    lang: &LangCode,
+    detail_ids: Vec<EntryId>,
+    detail_tx: mpsc::Sender<ApiDetailPage>,
+) -> CategoryStats {
+    let mut stats_detail = CategoryStats::default();
+    stats_detail.set_total(detail_ids.len());
+    let log_interval = std::cmp::max(50, (detail_ids.len() / 20).max(1));
+
+    let mut detail_stream = stream::iter(detail_ids)
+        .take_while(|_| futures::future::ready(!config::shutdown_requested()))
+        .map(|entry_id| {
+            let client_c = ctx.client.clone();
+            let detail_sem_c = ctx.detail_sem.clone();
+            async move {
+                (
+                    entry_id,
+                    fetchers::fetch_entry_detail(&client_c, detail_sem_c, lang, entry_id).await,
+                )
+            }
+        })
+        .buffer_unordered(ctx.settings.detail_concurrency() * 2);
+
+    while let Some((entry_id, result)) = detail_stream.next().await {
+        match result {
+            Ok(Some(detail_page)) => {
+                stats_detail.add_ok();
+                if detail_tx.send(detail_page).await.is_err() {
+                    log(
+                        LogLevel::Error,
+                        &format!("Detail pipeline closed early for [{}].", lang),
+                    );
+                    break;
+                }
+            }
+            Ok(None) => {
+                stats_detail.add_skip();
+                stats::record_failed_detail(lang.as_str(), entry_id);
+            }
+            Err(e) => {
+                log(
+                    LogLevel::Warning,
+                    &format!(
+                        "Detail Fetch Error for [{}]: {:?} - Marking as Skipped",
+                        lang, e
+                    ),
+                );
+                stats_detail.add_skip();
+                stats::record_failed_detail(lang.as_str(), entry_id);
+            }
+        }
+        let current_processed = stats_detail.get_processed();
+        if current_processed % log_interval == 0 || current_processed == stats_detail.total_tasks {
+            processor::log_progress(
+                &format!("Detail Fetch [{}]", lang),
+                &stats_detail,
+                current_processed,
+            );
+        }
+    }
+    stats_detail
+}
+
+async fn enrich_and_transform_details(
+    ctx: &PipelineContext,
+    lang: &LangCode,
+    mut detail_rx: mpsc::Receiver<ApiDetailPage>,
+    bulk_state: &mut LangBulkState,
+    detail_fallbacks: &Arc<transform::common::DetailFallbacks>,
+    aliases: &mut Vec<OutputAliasEntry>,
+    save_tx: &mpsc::UnboundedSender<LangTransformResult>,
+) {
+    let mut batch = Vec::with_capacity(config::PIPELINE_DETAIL_BATCH);
+
+    while detail_rx
+        .recv_many(&mut batch, config::PIPELINE_DETAIL_BATCH)
+        .await
+        > 0
+    {
+        let pages_with_ids: Vec<(ApiDetailPage, HashSet<EntryId>)> = batch
+            .drain(..)
+            .map(|page| {
+                let ids = transform::collect_detail_ids(&page, lang.as_str());
+                (page, ids)
+            })
+            .collect();
+
+        let batch_ids: HashSet<EntryId> = pages_with_ids
+            .iter()
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        let detail_names =
+            transform::bulk::build_detail_name_map(pages_with_ids.iter().map(|(page, _)| page));
+        bulk_state
+            .ensure_ids(ctx, lang, &batch_ids, &detail_names)
+            .await;
+
+        let mut transform_tasks = JoinSet::new();
+        for (page, ids) in pages_with_ids {
+            let bulk_store = Arc::new(bulk_state.store_for(&ids));
+            let detail_fallbacks_c = Arc::clone(detail_fallbacks);
+            let lang_c = lang.to_string();
+            transform_tasks.spawn(async move {
+                transform::detail::transform_detail_page(
+                    page,
+                    bulk_store,
+                    detail_fallbacks_c,
+                    &lang_c,
+                )
+                .await
+            });
+        }
+
+        while let Some(result) = transform_tasks.join_next().await {
+            match result {
+                Ok(Ok(Some(detail_page))) => {
+                    if let Some(alias_of) = detail_page.alias_of {
+                        aliases.push(OutputAliasEntry {
+                            id: detail_page.id,
+                            alias_of,
+                        });
+                    }
+                    let lang_result = LangTransformResult {
+                        lang: lang.to_string(),
+                        details: Arc::new(vec![detail_page]),
+                        ..Default::default()
+                    };
+                    if save_tx.send(lang_result).is_err() {
+                        log(
+                            LogLevel::Warning,
+                            &format!("Save queue closed; detail output for [{}] dropped.", lang),
+                        );
+                    }
+                }
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => log(
+                    LogLevel::Warning,
+                    &format!("Detail transform error [{}]: {:?}", lang, e),
+                ),
+                Err(e) => log(
+                    LogLevel::Error,
+                    &format!("Detail transform task panicked [{}]: {}", lang, e),
+                ),
+            }
+        }
+    }
+}
@@ -1,26 +1,79 @@
 use crate::api::client::ApiClient;
 use crate::api::fetchers;
-use crate::config;
-use crate::core::data_store::InMemoryDataStore;
+use crate::api::model::{ApiCalendarResponse, ApiNavEntry};
+use crate::config::{self, RuntimeSettings};
+use crate::core::bulk_cache;
+use crate::core::bundle;
+use crate::core::consistency_gate;
+use crate::core::data_store::{InMemoryDataStore, TransformedData};
+use crate::core::dir_index::{self, DirectoryIndexes};
+use crate::core::list_manifest;
 use crate::core::merged_schema;
-use crate::core::stats::{self, CategoryStats};
+use crate::core::schema_watch;
+use crate::core::statistics;
+use crate::core::stats::{self, CategoryStats, RunStats};
 use crate::error::{AppError, AppResult};
 use crate::io;
 use crate::logging::{log, LogLevel};
-use crate::model::common::{EntryId, MenuId};
-use crate::transform::{self, bulk::BulkStore};
+use crate::model::common::{EntryId, LangCode, MenuId};
+use crate::model::output::OutputAliasEntry;
+use crate::telemetry;
+use crate::transform::{self, bulk::BulkStore, search_index, taxonomy, LangTransformResult};
+use crate::utils;
 use chrono::Utc;
 use futures::stream::{self, StreamExt};
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::fs;
-use tokio::sync::Semaphore;
+use tokio::sync::{mpsc, Semaphore};
 use tokio::task::JoinSet;
 
-pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32> {
+/// Options for [`fetch_and_transform`].
+#[derive(Debug, Clone, Default)]
+pub struct FetchOptions {
+    /// Output directory whose run history feeds the pre-Phase 3 consistency
+    /// check. It is only read; `None` skips the history check entirely.
+    /// [`run`] appends [`TransformedData::run_report`] after saving.
+    pub history_dir: Option<PathBuf>,
+    /// Write-behind sink receiving each language's output as soon as it is transformed.
+    pub save_sink: Option<mpsc::UnboundedSender<LangTransformResult>>,
+    /// Settings for this run; `None` uses the ones published with
+    /// [`RuntimeSettings::apply`].
+    pub settings: Option<RuntimeSettings>,
+    /// API base URL for this run; `None` uses `config::api_base_url`.
+    pub base_url: Option<String>,
+}
+
+/// Options for [`save`].
+#[derive(Debug, Clone, Default)]
+pub struct SaveOptions {
+    /// Stats of a write-behind queue that already wrote the per-language
+    /// files (see [`FetchOptions::save_sink`]). `None` makes [`save`] write them.
+    pub streamed: Option<CategoryStats>,
+    /// Leave out the Phase 6 merged schema ([`RuntimeSettings::skip_merged_schema`]).
+    pub skip_merged_schema: bool,
+}
+
+pub async fn run(target_langs: Vec<LangCode>, out_dir: PathBuf) -> AppResult<i32> {
+    match run_to_stats(&target_langs, out_dir, &RuntimeSettings::current(), None).await {
+        Ok(run_stats) => Ok(stats::determine_exit_code(&run_stats)),
+        Err(AppError::RunAborted(_)) => Ok(1),
+        Err(e) => Err(e),
+    }
+}
+
+/// Body of [`run`]; a gate abort surfaces as [`AppError::RunAborted`] rather
+/// than an exit code.
+pub(crate) async fn run_to_stats(
+    target_langs: &[LangCode],
+    out_dir: PathBuf,
+    settings: &RuntimeSettings,
+    base_url: Option<&str>,
+) -> AppResult<RunStats> {
     let overall_start_time = Instant::now();
     let start_ts_str = Utc::now().format("%Y-%m-%d %H:%M:%S %Z").to_string();
 
@@ -37,59 +90,157 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
         &format!("Output Directory: {}", out_dir.display()),
     );
 
-    let client = Arc::new(ApiClient::new()?);
+    io::ensure_output_directories(&out_dir).await?;
+    let (save_tx, save_rx) = mpsc::unbounded_channel::<LangTransformResult>();
+    let saver_task = tokio::spawn(run_save_queue(save_rx, out_dir.clone()));
+
+    let fetch_opts = FetchOptions {
+        history_dir: Some(out_dir.clone()),
+        save_sink: Some(save_tx),
+        settings: Some(settings.clone()),
+        base_url: base_url.map(str::to_string),
+    };
+    let fetched = fetch_and_transform(target_langs, &fetch_opts).await;
+    drop(fetch_opts);
+    let (mut transformed_data, mut run_stats) = fetched?;
+    let run_report = transformed_data.run_report.take();
+
+    // How long saving trails the transform; without write-behind this would be
+    // the whole per-language save phase.
+    let transform_done_time = Instant::now();
+    let streamed = match saver_task.await {
+        Ok(save_stats) => save_stats,
+        Err(e) => {
+            log(LogLevel::Error, &format!("Save queue task panicked: {}", e));
+            let mut save_stats = CategoryStats::default();
+            save_stats.add_fail();
+            save_stats
+        }
+    };
+    log(
+        LogLevel::Info,
+        &format!(
+            "Save queue drained {:.2?} after the transform finished ({} file(s)).",
+            transform_done_time.elapsed(),
+            streamed.total_tasks
+        ),
+    );
+    let save_stats = save(
+        Arc::new(transformed_data),
+        &out_dir,
+        SaveOptions {
+            streamed: Some(streamed),
+            skip_merged_schema: settings.skip_merged_schema,
+        },
+    )
+    .await?;
+    for (category, stats) in save_stats {
+        run_stats.entry(category).or_default().merge(&stats);
+    }
+    if let Some(run_entry) = run_report {
+        let stats_save = run_stats.entry("Save Files".to_string()).or_default();
+        stats_save.total_tasks += 1;
+        let history = consistency_gate::load_run_history(&out_dir).unwrap_or_default();
+        let history_result = consistency_gate::record_run(&out_dir, history, run_entry).await;
+        tally_save_result(stats_save, Ok(history_result));
+    }
+
+    let overall_duration = overall_start_time.elapsed();
+    stats::print_summary(&run_stats, target_langs, overall_duration);
+
+    Ok(run_stats)
+}
+
+/// Runs Phases 0-5 (preflight, fetch, bulk resolution and transform) and returns the
+/// transformed data without writing any output files.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wiki_update::model::common::LangCode;
+/// use wiki_update::{fetch_and_transform, FetchOptions};
+///
+/// # async fn example() -> wiki_update::error::AppResult<()> {
+/// let langs = vec![LangCode::parse("en-us")?];
+/// let (data, stats) = fetch_and_transform(&langs, &FetchOptions::default()).await?;
+/// println!("{} detail pages", data.details.get("en-us").map_or(0, |pages| pages.len()));
+/// println!("{} detail fetches failed", stats["Detail Fetch"].fail);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn fetch_and_transform(
+    target_langs: &[LangCode],
+    opts: &FetchOptions,
+) -> AppResult<(TransformedData, RunStats)> {
+    let settings = opts
+        .settings
+        .clone()
+        .unwrap_or_else(RuntimeSettings::current);
+    let client = Arc::new(match &opts.base_url {
+        Some(url) => ApiClient::with_base_url(url)?,
+        None => ApiClient::new()?,
+    });
 
-    let list_sem = Arc::new(Semaphore::new(config::MAX_LIST_CONCUR));
-    let detail_sem = Arc::new(Semaphore::new(config::MAX_DETAIL_CONCUR));
-    let bulk_sem = Arc::new(Semaphore::new(config::MAX_BULK_CONCUR));
-    let cal_sem = Arc::new(Semaphore::new(config::MAX_CALENDAR_CONCUR));
+    let preflight_ms = run_preflight(&client).await?;
+
+    let list_sem = Arc::new(Semaphore::new(settings.list_concurrency()));
+    let detail_sem = Arc::new(Semaphore::new(settings.detail_concurrency()));
+    let bulk_sem = Arc::new(Semaphore::new(settings.bulk_concurrency()));
+    let cal_sem = Arc::new(Semaphore::new(settings.calendar_concurrency()));
 
     let mut run_stats = stats::initialize_stats();
     let mut data_store = InMemoryDataStore::default();
 
     let nav_start_time = Instant::now();
-    log(LogLevel::Step, "--- Phase 1: Navigation Fetch ---");
+    config::set_current_phase("Navigation Fetch");
+    log(
+        LogLevel::Step,
+        "--- Phase 1: Language Bootstrap (Navigation & Calendar) ---",
+    );
+    if settings.skip_calendar {
+        log(LogLevel::Info, "Skipping calendar fetch.");
+    } else {
+        run_stats
+            .get_mut("Calendar Fetch")
+            .unwrap()
+            .set_total(target_langs.len());
+    }
     run_stats
         .get_mut("Navigation")
         .unwrap()
         .set_total(target_langs.len());
-    let mut nav_tasks = JoinSet::new();
+    let mut bootstrap_tasks = JoinSet::new();
     for lang in target_langs.iter() {
-        let client_clone = client.clone();
-        let lang_clone = lang.clone();
-        nav_tasks.spawn(async move {
-            let result = fetchers::fetch_nav(&client_clone, &lang_clone).await;
-            (lang_clone, result)
-        });
+        bootstrap_tasks.spawn(fetch_language_bootstrap(
+            client.clone(),
+            cal_sem.clone(),
+            lang.clone(),
+            settings.skip_calendar,
+        ));
     }
-    while let Some(result) = nav_tasks.join_next().await {
-        let stats_nav = run_stats.get_mut("Navigation").unwrap();
+    while let Some(result) = bootstrap_tasks.join_next().await {
         match result {
-            Ok((lang, Ok(nav_entries))) => {
-                if nav_entries.is_empty() {
-                    log(
-                        LogLevel::Warning,
-                        &format!("Navigation fetch for [{}] returned empty.", lang),
-                    );
-                    stats_nav.add_skip();
-                } else {
-                    stats_nav.add_ok();
-                    data_store.raw.navigation.insert(lang, nav_entries);
+            Ok(bootstrap) => {
+                let lang = bootstrap.lang.clone();
+                if let Some(active) = record_language_bootstrap(bootstrap, &mut run_stats) {
+                    data_store
+                        .raw
+                        .navigation
+                        .insert(lang.to_string(), active.nav);
+                    if let Some(calendar_data) = active.calendar {
+                        data_store
+                            .raw
+                            .calendars
+                            .insert(lang.to_string(), calendar_data);
+                    }
                 }
             }
-            Ok((lang, Err(e))) => {
+            Err(e) => {
                 log(
-                    LogLevel::Warning,
-                    &format!(
-                        "Navigation fetch failed for [{}]: {:?}, skipping language.",
-                        lang, e
-                    ),
+                    LogLevel::Error,
+                    &format!("Language bootstrap task panicked: {}", e),
                 );
-                stats_nav.add_fail();
-            }
-            Err(e) => {
-                log(LogLevel::Error, &format!("Nav fetch task panicked: {}", e));
-                stats_nav.add_fail();
+                run_stats.get_mut("Navigation").unwrap().add_fail();
             }
         }
     }
@@ -98,24 +249,58 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
         &run_stats["Navigation"],
         nav_start_time.elapsed(),
     );
+    if !settings.skip_calendar {
+        log_phase_completion(
+            "Calendar Fetch",
+            &run_stats["Calendar Fetch"],
+            nav_start_time.elapsed(),
+        );
+    }
+    let nav_missing: Vec<&str> = target_langs
+        .iter()
+        .map(LangCode::as_str)
+        .filter(|lang| !data_store.raw.navigation.contains_key(*lang))
+        .collect();
+    if config::require_all_langs() && !nav_missing.is_empty() {
+        log(
+            LogLevel::Error,
+            &format!(
+                "Aborting after Phase 1: no navigation data for [{}] with --require-all-langs.",
+                nav_missing.join(", ")
+            ),
+        );
+        return Err(AppError::RunAborted(format!(
+            "navigation missing for {}",
+            nav_missing.join(", ")
+        )));
+    }
     if data_store.raw.navigation.is_empty() && !target_langs.is_empty() {
         log(
             LogLevel::Error,
             "Critical: No navigation data fetched for any language. Cannot proceed.",
         );
-        return Ok(1);
+        return Err(AppError::RunAborted(
+            "no navigation data fetched".to_string(),
+        ));
     }
 
     let list_start_time = Instant::now();
+    config::set_current_phase("List Fetch");
     log(LogLevel::Step, "--- Phase 2: List Fetch ---");
-    let mut list_fetch_input: Vec<(String, MenuId, String)> = Vec::new();
-    for (lang, nav_entries) in &data_store.raw.navigation {
-        let lang_list_map = data_store.raw.lists.entry(lang.clone()).or_default();
-        for entry in nav_entries {
-            if let Some(menu) = transform::common::transform_nav_item(entry) {
-                list_fetch_input.push((lang.clone(), menu.menu_id, menu.name));
-                lang_list_map.insert(menu.menu_id, Vec::new());
-            }
+    let mut list_fetch_input: Vec<(LangCode, MenuId, String)> = Vec::new();
+    for lang in target_langs.iter() {
+        let Some(nav_entries) = data_store.raw.navigation.get(lang.as_str()) else {
+            continue;
+        };
+        let lang_list_map = data_store.raw.lists.entry(lang.to_string()).or_default();
+        let (menus, duplicates) = transform::common::transform_nav_items(nav_entries);
+        transform::common::log_nav_duplicates(&duplicates, lang.as_str());
+        for menu in menus
+            .into_iter()
+            .filter(|menu| settings.menu_allowed(menu.menu_id))
+        {
+            list_fetch_input.push((lang.clone(), menu.menu_id, menu.name));
+            lang_list_map.insert(menu.menu_id, Vec::new());
         }
     }
     let total_list_tasks = list_fetch_input.len();
@@ -132,11 +317,13 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
             &format!("Fetching {} Lists...", total_list_tasks),
         );
         let list_stream = stream::iter(list_fetch_input)
+            .take_while(|_| futures::future::ready(!config::shutdown_requested()))
             .map(|(lang, menu_id, menu_name)| {
                 let client_c = client.clone();
                 let list_sem_c = list_sem.clone();
                 let menu_name_c = menu_name;
                 async move {
+                    let dispatched_at = Instant::now();
                     let result = fetchers::fetch_menu_list_items(
                         &client_c,
                         list_sem_c,
@@ -145,13 +332,14 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
                         &menu_name_c,
                     )
                     .await;
-                    (lang, menu_id, result)
+                    (lang, menu_id, result, dispatched_at.elapsed())
                 }
             })
-            .buffer_unordered(config::MAX_LIST_CONCUR * 2);
+            .buffer_unordered(settings.list_concurrency() * 2);
 
-        list_stream.for_each(|(lang, menu_id, result)| {
+        list_stream.for_each(|(lang, menu_id, result, elapsed)| {
              let stats_list = run_stats.get_mut("List Fetch").unwrap();
+             stats_list.add_elapsed(elapsed);
              let current_processed = list_processed_count.fetch_add(1, Ordering::Relaxed) + 1;
              match result {
                  Ok(items) => {
@@ -159,7 +347,7 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
                         stats_list.add_skip();
                     } else {
                         stats_list.add_ok();
-                        if let Some(lm) = data_store.raw.lists.get_mut(&lang) {
+                        if let Some(lm) = data_store.raw.lists.get_mut(lang.as_str()) {
                             lm.insert(menu_id, items);
                          } else {
                              log(LogLevel::Warning, &format!("List Fetch: Could not find language map entry for [{}] after fetch", lang));
@@ -188,10 +376,33 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
         list_start_time.elapsed(),
     );
 
+    let run_history = opts
+        .history_dir
+        .as_deref()
+        .and_then(consistency_gate::load_run_history);
+    let expected_langs: Vec<String> = target_langs.iter().map(ToString::to_string).collect();
+    let consistency_warnings = consistency_gate::check_pre_phase3_consistency(
+        &data_store.raw.navigation,
+        &data_store.raw.lists,
+        &expected_langs,
+        run_history.as_ref(),
+    );
+    if consistency_gate::log_warnings(&consistency_warnings, "Phase 3") {
+        return Err(AppError::RunAborted(format!(
+            "{} consistency warning(s)",
+            consistency_warnings.len()
+        )));
+    }
+    let list_item_counts = consistency_gate::list_item_counts(&data_store.raw.lists);
+
     let detail_start_time = Instant::now();
+    config::set_current_phase("Detail Fetch");
     log(LogLevel::Step, "--- Phase 3: Detail Fetch ---");
-    let mut detail_fetch_input: Vec<(String, EntryId)> = Vec::new();
-    for (lang, lists_map) in &data_store.raw.lists {
+    let mut detail_fetch_input: Vec<(LangCode, EntryId)> = Vec::new();
+    for lang in target_langs.iter() {
+        let Some(lists_map) = data_store.raw.lists.get(lang.as_str()) else {
+            continue;
+        };
         for items in lists_map.values() {
             for item in items {
                 if item.entry_page_id > 0 {
@@ -217,21 +428,24 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
             &format!("Fetching {} unique Details...", total_detail_tasks),
         );
         let detail_stream = stream::iter(detail_fetch_input)
+            .take_while(|_| futures::future::ready(!config::shutdown_requested()))
             .map(|(lang, entry_id)| {
                 let client_c = client.clone();
                 let detail_sem_c = detail_sem.clone();
                 async move {
+                    let dispatched_at = Instant::now();
                     let result =
                         fetchers::fetch_entry_detail(&client_c, detail_sem_c, &lang, entry_id)
                             .await;
-                    (lang, result)
+                    (lang, entry_id, result, dispatched_at.elapsed())
                 }
             })
-            .buffer_unordered(config::MAX_DETAIL_CONCUR * 2);
+            .buffer_unordered(settings.detail_concurrency() * 2);
 
         detail_stream
-            .for_each(|(lang, result)| {
+            .for_each(|(lang, entry_id, result, elapsed)| {
                 let stats_detail = run_stats.get_mut("Detail Fetch").unwrap();
+                stats_detail.add_elapsed(elapsed);
                 let current_processed = detail_processed_count.fetch_add(1, Ordering::Relaxed) + 1;
                 match result {
                     Ok(Some(detail_page)) => {
@@ -239,12 +453,13 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
                         data_store
                             .raw
                             .details
-                            .entry(lang)
+                            .entry(lang.to_string())
                             .or_default()
                             .push(detail_page);
                     }
                     Ok(None) => {
                         stats_detail.add_skip();
+                        stats::record_failed_detail(lang.as_str(), entry_id);
                     }
                     Err(e) => {
                         log(
@@ -255,6 +470,7 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
                             ),
                         );
                         stats_detail.add_skip();
+                        stats::record_failed_detail(lang.as_str(), entry_id);
                     }
                 }
                 if current_processed % detail_log_interval == 0
@@ -277,66 +493,20 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
         detail_start_time.elapsed(),
     );
 
-    let calendar_start_time = Instant::now();
-    log(LogLevel::Step, "--- Phase 4: Calendar Fetch ---");
-    run_stats
-        .get_mut("Calendar Fetch")
-        .unwrap()
-        .set_total(target_langs.len());
-    let mut cal_tasks = JoinSet::new();
-    for lang in target_langs.iter() {
-        let client_clone = client.clone();
-        let cal_sem_clone = cal_sem.clone();
-        let lang_clone = lang.clone();
-        cal_tasks.spawn(async move {
-            let result = fetchers::fetch_calendar(&client_clone, cal_sem_clone, &lang_clone).await;
-            (lang_clone, result)
-        });
-    }
-    while let Some(result) = cal_tasks.join_next().await {
-        let stats_cal = run_stats.get_mut("Calendar Fetch").unwrap();
-        match result {
-            Ok((lang, Ok(calendar_data))) => {
-                if calendar_data.calendar.is_empty() && calendar_data.op.is_empty() {
-                    stats_cal.add_skip();
-                } else {
-                    stats_cal.add_ok();
-                    data_store.raw.calendars.insert(lang, calendar_data);
-                }
-            }
-            Ok((lang, Err(e))) => {
-                log(
-                    LogLevel::Warning,
-                    &format!("Calendar fetch failed for [{}]: {:?}", lang, e),
-                );
-                stats_cal.add_fail();
-            }
-            Err(e) => {
-                log(
-                    LogLevel::Error,
-                    &format!("Calendar fetch task panicked: {}", e),
-                );
-                stats_cal.add_fail();
-            }
-        }
-    }
-    log_phase_completion(
-        "Calendar Fetch",
-        &run_stats["Calendar Fetch"],
-        calendar_start_time.elapsed(),
-    );
-
     let bulk_start_time = Instant::now();
-    log(LogLevel::Step, "--- Phase 5: Bulk Data Fetch & Process ---");
+    config::set_current_phase("Bulk Data Fetch");
+    log(LogLevel::Step, "--- Phase 4: Bulk Data Fetch & Process ---");
     log(LogLevel::Info, "Collecting all unique Entry IDs...");
-    data_store.all_ids = transform::collect_all_ids(&data_store.raw);
+    (data_store.all_ids, data_store.post_ids) = transform::collect_all_ids(&data_store.raw);
     let total_unique_ids: usize = data_store.all_ids.values().map(HashSet::len).sum();
+    let total_post_ids: usize = data_store.post_ids.values().map(HashSet::len).sum();
     log(
         LogLevel::Success,
         &format!(
-            "Collected {} unique ID(s) across {} language(s).",
+            "Collected {} unique ID(s) across {} language(s) ({} post ID(s) tracked separately).",
             total_unique_ids,
-            data_store.all_ids.len()
+            data_store.all_ids.len(),
+            total_post_ids
         ),
     );
 
@@ -351,10 +521,7 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
     let total_fallback_fetch_units: usize = data_store
         .all_ids
         .iter()
-        .map(|(_lang, ids)| {
-            let fallback_langs_count = config::SUPPORTED_LANGS.len().saturating_sub(1);
-            ids.len() * fallback_langs_count / config::BULK_BATCH_SIZE.max(1)
-        })
+        .map(|(lang, ids)| fallback_fetch_units(lang, ids.len(), settings.bulk_fallback))
         .sum();
 
     run_stats
@@ -377,8 +544,13 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
         );
 
         for lang in target_langs.iter() {
-            let ids_for_lang_arc =
-                Arc::new(data_store.all_ids.get(lang).cloned().unwrap_or_default());
+            let ids_for_lang_arc = Arc::new(
+                data_store
+                    .all_ids
+                    .get(lang.as_str())
+                    .cloned()
+                    .unwrap_or_default(),
+            );
 
             if ids_for_lang_arc.is_empty() {
                 log(
@@ -390,102 +562,91 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
                 );
                 data_store
                     .all_bulk_stores
-                    .insert(lang.clone(), BulkStore::default());
+                    .insert(lang.to_string(), BulkStore::default());
                 continue;
             }
 
+            if let Some(cache_dir) = config::bulk_cache_dir() {
+                if let Some(store) =
+                    bulk_cache::load_bulk_cache(cache_dir, lang.as_str(), &ids_for_lang_arc).await
+                {
+                    log(
+                        LogLevel::Info,
+                        &format!(
+                            "Bulk: Using cached data for [{}] ({} entries).",
+                            lang,
+                            store.len()
+                        ),
+                    );
+                    let primary_units = bulk_batch_count(ids_for_lang_arc.len());
+                    primary_bulk_processed_ids.fetch_add(primary_units, Ordering::Relaxed);
+                    run_stats.get_mut("Bulk Primary").unwrap().skip_or_empty += primary_units;
+                    run_stats.get_mut("Bulk Fallback").unwrap().skip_or_empty +=
+                        fallback_fetch_units(
+                            lang.as_str(),
+                            ids_for_lang_arc.len(),
+                            settings.bulk_fallback,
+                        );
+                    data_store.all_bulk_stores.insert(lang.to_string(), store);
+                    continue;
+                }
+            }
+
             let lang_clone = lang.clone();
             let client_clone = client.clone();
             let bulk_sem_clone = bulk_sem.clone();
             let primary_counter_clone = primary_bulk_processed_ids.clone();
+            let bulk_fallback = settings.bulk_fallback;
+            let detail_name_map = data_store
+                .raw
+                .details
+                .get(lang.as_str())
+                .map(transform::bulk::build_detail_name_map)
+                .unwrap_or_default();
 
             lang_bulk_processing_tasks.spawn(async move {
-
-                let primary_fetch_result = fetchers::fetch_bulk_data(&client_clone, bulk_sem_clone.clone(), &ids_for_lang_arc, &lang_clone, "Bulk Primary").await;
-                 let (primary_bulk_map, prim_ok_count, prim_fail_skip_count) = match primary_fetch_result {
-                    Ok(map) => (map.clone(), (map.len() + config::BULK_BATCH_SIZE -1)/ config::BULK_BATCH_SIZE.max(1) , 0 ),
-                    Err(e) => {
-                         log(LogLevel::Warning, &format!("Bulk Primary fetch failed entirely for [{}]: {:?}", lang_clone, e));
-
-                         (HashMap::new(), 0, (ids_for_lang_arc.len() + config::BULK_BATCH_SIZE -1) / config::BULK_BATCH_SIZE.max(1) )
-                    }
-                 };
-                primary_counter_clone.fetch_add( (ids_for_lang_arc.len() + config::BULK_BATCH_SIZE - 1) / config::BULK_BATCH_SIZE.max(1), Ordering::Relaxed);
-
-
-                 let mut ids_needing_fallback = HashSet::new();
-                 for id in ids_for_lang_arc.iter() {
-                     let needs_fallback = primary_bulk_map.get(id)
-                        .and_then(|p| p.icon_url.as_deref())
-                         .map_or(true, |icon| icon.is_empty() || icon.contains("invalid-file"));
-                     if needs_fallback { ids_needing_fallback.insert(*id); }
-                 }
-
-                 let mut lang_fallback_map: HashMap<String, HashMap<EntryId, crate::api::model::ApiBulkPage>> = HashMap::new();
-                 let mut fallback_ok_batches = 0;
-                 let mut fallback_fail_batches = 0;
-
-                 if !ids_needing_fallback.is_empty() {
-                    let mut fallback_tasks = JoinSet::new();
-                     let fallback_langs: Vec<String> = config::SUPPORTED_LANGS.iter()
-                         .filter(|&fl| *fl != lang_clone)
-                         .cloned()
-                         .collect();
-
-                     let ids_needing_fallback_arc = Arc::new(ids_needing_fallback);
-
-                     for fallback_lang in fallback_langs {
-                         let client_c = client_clone.clone();
-                        let bulk_sem_c = bulk_sem_clone.clone();
-                        let lang_c = fallback_lang;
-                        let ids_c = ids_needing_fallback_arc.clone();
-                        let ctx = format!("Bulk Fallback ({}) for [{}]", lang_c, lang_clone);
-
-                         fallback_tasks.spawn(async move {
-                             let result = fetchers::fetch_bulk_data(&client_c, bulk_sem_c, &ids_c, &lang_c, &ctx).await;
-                             let batches = (ids_c.len() + config::BULK_BATCH_SIZE -1) / config::BULK_BATCH_SIZE.max(1);
-                             (lang_c, result.map(|map| (map, batches)))
-                         });
-                    }
-
-                    while let Some(fall_result) = fallback_tasks.join_next().await {
-                         match fall_result {
-                            Ok((lang_key, Ok((map, batches)))) => {
-                                 if !map.is_empty() {
-                                     lang_fallback_map.insert(lang_key, map);
-                                 }
-                                 fallback_ok_batches += batches;
-                             }
-                            Ok((lang_key, Err(_))) => {
-                                log(LogLevel::Warning, &format!("Bulk Fallback fetch failed entirely for [{}] (fallback for [{}])", lang_key, lang_clone));
-                                fallback_fail_batches += (ids_needing_fallback_arc.len() + config::BULK_BATCH_SIZE - 1) / config::BULK_BATCH_SIZE.max(1);
-                            }
-                             Err(e) => {
-                                log(LogLevel::Error, &format!("Bulk Fallback task panicked: {}", e));
-                                fallback_fail_batches += (ids_needing_fallback_arc.len() + config::BULK_BATCH_SIZE - 1) / config::BULK_BATCH_SIZE.max(1);
-                            }
-                        }
-                    }
-                 }
-
-                 match transform::bulk::process_bulk_data(primary_bulk_map, lang_fallback_map, &ids_for_lang_arc) {
-                    Ok(store) => Ok((lang_clone, store, prim_ok_count, prim_fail_skip_count, fallback_ok_batches, fallback_fail_batches)),
-                     Err(e) => {
-                         log(LogLevel::Error, &format!("Bulk processing failed for [{}]: {:?}.", lang_clone, e));
-                         Err(AppError::TransformError(format!("Bulk processing failed for {}", lang_clone)))
-                     }
-                 }
+                let outcome = fetch_lang_bulk_store(
+                    &client_clone,
+                    bulk_sem_clone,
+                    &lang_clone,
+                    &ids_for_lang_arc,
+                    &detail_name_map,
+                    bulk_fallback,
+                )
+                .await;
+                primary_counter_clone
+                    .fetch_add(bulk_batch_count(ids_for_lang_arc.len()), Ordering::Relaxed);
+                outcome.map(|outcome| (lang_clone, ids_for_lang_arc, outcome))
             });
         }
 
+        let mut fresh_bulk_caches = Vec::new();
         while let Some(join_result) = lang_bulk_processing_tasks.join_next().await {
             match join_result {
-                Ok(Ok((lang, store, prim_ok, prim_fail_skip, fall_ok, fall_fail_skip))) => {
-                    data_store.all_bulk_stores.insert(lang.clone(), store);
-                    run_stats.get_mut("Bulk Primary").unwrap().ok += prim_ok;
-                    run_stats.get_mut("Bulk Primary").unwrap().fail += prim_fail_skip;
-                    run_stats.get_mut("Bulk Fallback").unwrap().ok += fall_ok;
-                    run_stats.get_mut("Bulk Fallback").unwrap().fail += fall_fail_skip;
+                Ok(Ok((lang, ids, outcome))) => {
+                    // Only a store whose primary batches all succeeded is worth reusing.
+                    if config::bulk_cache_dir().is_some() && outcome.primary_fail == 0 {
+                        fresh_bulk_caches.push(bulk_cache::BulkCacheFile::from_store(
+                            lang.as_str(),
+                            &ids,
+                            &outcome.store,
+                        ));
+                    }
+                    run_stats
+                        .get_mut("Bulk Primary")
+                        .unwrap()
+                        .add_elapsed(outcome.primary_elapsed);
+                    let stats_fallback = run_stats.get_mut("Bulk Fallback").unwrap();
+                    for &elapsed in &outcome.fallback_elapsed {
+                        stats_fallback.add_elapsed(elapsed);
+                    }
+                    data_store
+                        .all_bulk_stores
+                        .insert(lang.to_string(), outcome.store);
+                    run_stats.get_mut("Bulk Primary").unwrap().ok += outcome.primary_ok;
+                    run_stats.get_mut("Bulk Primary").unwrap().fail += outcome.primary_fail;
+                    run_stats.get_mut("Bulk Fallback").unwrap().ok += outcome.fallback_ok;
+                    run_stats.get_mut("Bulk Fallback").unwrap().fail += outcome.fallback_fail;
 
                     let current_processed = primary_bulk_processed_ids.load(Ordering::Relaxed);
                     if current_processed % bulk_log_interval == 0
@@ -514,6 +675,13 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
                 }
             }
         }
+
+        if let Some(cache_dir) = config::bulk_cache_dir() {
+            for cache in fresh_bulk_caches {
+                // Failures are already logged; the next run simply refetches.
+                let _ = bulk_cache::save_bulk_cache(cache_dir, cache).await;
+            }
+        }
     } else {
         log(
             LogLevel::Warning,
@@ -530,214 +698,1260 @@ pub async fn run(target_langs: Vec<String>, out_dir: PathBuf) -> AppResult<i32>
         &run_stats["Bulk Fallback"],
         bulk_start_time.elapsed(),
     );
+    if total_post_ids > 0 {
+        attach_post_previews(
+            &client,
+            target_langs,
+            &data_store.post_ids,
+            &mut data_store.all_bulk_stores,
+        )
+        .await;
+    }
 
     let transform_start_time = Instant::now();
-    log(LogLevel::Step, "--- Phase 6: Transforming Data ---");
-    let transformed_data = transform::transform_all_data(
+    config::set_current_phase("Transform & Save");
+    log(
+        LogLevel::Step,
+        "--- Phase 5: Transforming & Saving Data (write-behind) ---",
+    );
+    let (mut transformed_data, transform_stats) = transform::transform_all_data(
         Arc::new(data_store.raw),
         data_store.all_bulk_stores,
-        &target_langs,
+        target_langs,
+        opts.save_sink.clone(),
     )
     .await?;
-    let transformed_data_arc = Arc::new(transformed_data);
     log_phase_completion(
         "Transforming Data",
-        &CategoryStats::default(),
+        &transform_stats,
         transform_start_time.elapsed(),
     );
+    run_stats
+        .get_mut("Transform")
+        .unwrap()
+        .merge(&transform_stats);
 
-    let save_start_time = Instant::now();
-    log(LogLevel::Step, "--- Phase 7: Saving Transformed Data ---");
-    io::ensure_output_directories(&out_dir).await?;
-    let mut save_tasks = JoinSet::new();
-    let mut total_files_to_save = 0usize;
-
-    let nav_base_dir = out_dir.join("navigation");
-    if transformed_data_arc
-        .navigation
-        .values()
-        .any(|v| !v.is_empty())
-    {
-        fs::create_dir_all(&nav_base_dir).await?;
-        for (lang, nav_items) in transformed_data_arc.navigation.iter() {
-            if nav_items.is_empty() {
-                continue;
+    if !config::shutdown_requested() {
+        transformed_data.run_report = Some(
+            consistency_gate::RunReportEntry {
+                preflight_ms,
+                ..consistency_gate::RunReportEntry::new(list_item_counts)
             }
-            let path = nav_base_dir.join(format!("{}.json", lang));
-            let ctx = format!("Nav [{}]", lang);
-            total_files_to_save += 1;
-            let nav_items_clone = nav_items.clone();
-            save_tasks.spawn(io::save_json(path, nav_items_clone, ctx));
-        }
+            .with_latencies(&run_stats),
+        );
     }
 
-    let list_base_dir = out_dir.join("list");
-    if transformed_data_arc.lists.values().any(|v| !v.is_empty()) {
-        fs::create_dir_all(&list_base_dir).await?;
-        for (lang, list_files) in transformed_data_arc.lists.iter() {
-            if list_files.is_empty() {
-                continue;
-            }
-            let lang_list_dir = list_base_dir.join(lang);
-            fs::create_dir_all(&lang_list_dir).await?;
-            for list_file in list_files {
-                let file_name = format!("{}.json", list_file.menu_id);
-                let path = lang_list_dir.join(file_name);
-                let ctx = format!("List M:{} [{}]", list_file.menu_id, lang);
-                total_files_to_save += 1;
-                let list_file_clone = list_file.clone();
-                save_tasks.spawn(io::save_json(path, list_file_clone, ctx));
+    Ok((transformed_data, run_stats))
+}
+
+/// Phase 0: probes navigation and bulk data once before any real work, unless
+/// `--skip-preflight`. Returns how long the probe took.
+pub(crate) async fn run_preflight(client: &ApiClient) -> AppResult<Option<u64>> {
+    if config::skip_preflight() {
+        log(LogLevel::Info, "Skipping Phase 0 (Preflight).");
+        return Ok(None);
+    }
+    let preflight_start_time = Instant::now();
+    config::set_current_phase("Preflight");
+    log(LogLevel::Step, "--- Phase 0: Preflight ---");
+    if let Err(e) = fetchers::preflight_check(client, config::PREFLIGHT_LANG).await {
+        log(LogLevel::Error, &e.to_string());
+        return Err(e);
+    }
+    let elapsed = preflight_start_time.elapsed();
+    log(
+        LogLevel::Success,
+        &format!("Preflight passed in {:.2?}.", elapsed),
+    );
+    Ok(Some(elapsed.as_millis() as u64))
+}
+
+/// Writes the reports, taxonomy files and merged schemas for `transformed`
+/// under `out_dir`, plus the per-language files unless they were already
+/// streamed. Returns the `"Save Files"` (and any `"Schema Generation"`) stats.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use std::sync::Arc;
+/// use wiki_update::model::common::LangCode;
+/// use wiki_update::{fetch_and_transform, save, FetchOptions, SaveOptions};
+///
+/// # async fn example() -> wiki_update::error::AppResult<()> {
+/// let langs = vec![LangCode::parse("en-us")?];
+/// let (data, _) = fetch_and_transform(&langs, &FetchOptions::default()).await?;
+/// let stats = save(Arc::new(data), Path::new("web/public/data"), SaveOptions::default()).await?;
+/// assert_eq!(stats["Save Files"].fail, 0);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn save(
+    transformed: Arc<TransformedData>,
+    out_dir: &Path,
+    opts: SaveOptions,
+) -> AppResult<RunStats> {
+    let save_start_time = Instant::now();
+    let mut run_stats = RunStats::new();
+    let save_stats = match opts.streamed {
+        Some(save_stats) => save_stats,
+        None => {
+            io::ensure_output_directories(out_dir).await?;
+            let (save_tx, save_rx) = mpsc::unbounded_channel::<LangTransformResult>();
+            let saver_task = tokio::spawn(run_save_queue(save_rx, out_dir.to_path_buf()));
+            for lang_result in language_results(&transformed) {
+                let _ = save_tx.send(lang_result);
             }
+            drop(save_tx);
+            saver_task.await.unwrap_or_else(|e| {
+                log(LogLevel::Error, &format!("Save queue task panicked: {}", e));
+                let mut save_stats = CategoryStats::default();
+                save_stats.add_fail();
+                save_stats
+            })
         }
+    };
+    if save_stats.total_tasks == 0 {
+        log(
+            LogLevel::Warning,
+            "No transformed data files generated to save.",
+        );
     }
+    let stats_save = run_stats.entry("Save Files".to_string()).or_default();
+    *stats_save = save_stats;
 
-    let detail_base_dir = out_dir.join("detail");
-    if transformed_data_arc.details.values().any(|v| !v.is_empty()) {
-        fs::create_dir_all(&detail_base_dir).await?;
-        for (lang, detail_pages) in transformed_data_arc.details.iter() {
-            if detail_pages.is_empty() {
-                continue;
+    let alias_report: BTreeMap<String, Vec<OutputAliasEntry>> = transformed
+        .details
+        .iter()
+        .map(|(lang, details)| {
+            (
+                lang.clone(),
+                transform::detail::collect_alias_entries(details),
+            )
+        })
+        .collect();
+    save_alias_report(out_dir, alias_report, stats_save).await;
+    save_coercion_report(out_dir, stats_save).await;
+    save_component_key_collision_report(out_dir, stats_save).await;
+    save_event_window_conflict_report(out_dir, stats_save).await;
+    save_failed_ids_report(out_dir, stats_save).await;
+    save_schema_drift_report(out_dir, stats_save).await;
+    save_taxonomy_files(out_dir, Arc::clone(&transformed), stats_save).await;
+    log_phase_completion("Save Files", stats_save, save_start_time.elapsed());
+
+    save_report_file(
+        out_dir,
+        statistics::COUNTS_FILE,
+        statistics::build_counts(&transformed),
+        run_stats.entry("Save Files".to_string()).or_default(),
+    )
+    .await;
+
+    if opts.skip_merged_schema {
+        log(LogLevel::Info, "Skipping Phase 6 merged schema generation.");
+    } else {
+        let schema_start_time = Instant::now();
+        config::set_current_phase("Merged Schema");
+        log(
+            LogLevel::Step,
+            "--- Phase 6: Creating Merged Schema Files ---",
+        );
+        match merged_schema::create_merged_schema_files(Arc::clone(&transformed), out_dir).await {
+            Ok(_) => {
+                let mut schema_stats = CategoryStats::default();
+                schema_stats.add_ok();
+                log_phase_completion(
+                    "Schema Generation",
+                    &schema_stats,
+                    schema_start_time.elapsed(),
+                );
             }
-            let lang_detail_dir = detail_base_dir.join(lang);
-            fs::create_dir_all(&lang_detail_dir).await?;
-            for detail_page in detail_pages {
-                let file_name = format!("{}.json", detail_page.id);
-                let path = lang_detail_dir.join(file_name);
-                let ctx = format!("Detail E:{} [{}]", detail_page.id, lang);
-                total_files_to_save += 1;
-                let detail_page_clone = detail_page.clone();
-                save_tasks.spawn(io::save_json(path, detail_page_clone, ctx));
+            Err(e) => {
+                log(
+                    LogLevel::Error,
+                    &format!("Failed to generate merged schema files: {:?}", e),
+                );
+
+                run_stats
+                    .entry("Schema Generation".to_string())
+                    .or_default()
+                    .add_fail();
             }
         }
     }
 
-    let calendar_base_dir = out_dir.join("calendar");
-    if !transformed_data_arc.calendars.is_empty() {
-        fs::create_dir_all(&calendar_base_dir).await?;
-        for (lang, calendar_file) in transformed_data_arc.calendars.iter() {
-            let path = calendar_base_dir.join(format!("{}.json", lang));
-            let ctx = format!("Calendar [{}]", lang);
-            total_files_to_save += 1;
-            let calendar_file_clone = calendar_file.clone();
-            save_tasks.spawn(io::save_json(path, calendar_file_clone, ctx));
-        }
+    save_search_index_files(
+        out_dir,
+        Arc::clone(&transformed),
+        run_stats.entry("Save Files".to_string()).or_default(),
+    )
+    .await;
+
+    save_oversized_components_report(
+        out_dir,
+        Arc::clone(&transformed),
+        run_stats.entry("Save Files".to_string()).or_default(),
+    )
+    .await;
+
+    if !config::bundle_entries().is_empty() {
+        save_entry_bundles(
+            out_dir,
+            Arc::clone(&transformed),
+            run_stats.entry("Save Files".to_string()).or_default(),
+        )
+        .await;
     }
 
-    run_stats
-        .get_mut("Save Files")
-        .unwrap()
-        .set_total(total_files_to_save);
-    if total_files_to_save > 0 {
-        log(
-            LogLevel::Info,
-            &format!("Saving {} files...", total_files_to_save),
-        );
-        while let Some(result) = save_tasks.join_next().await {
-            let stats_save = run_stats.get_mut("Save Files").unwrap();
-            match result {
-                Ok(Ok(true)) => {
-                    stats_save.add_ok();
-                }
+    if config::report_unresolved() {
+        save_unresolved_refs_report(
+            out_dir,
+            Arc::clone(&transformed),
+            run_stats.entry("Save Files".to_string()).or_default(),
+        )
+        .await;
+    }
 
-                Ok(Err(e)) => {
-                    stats_save.add_fail();
-                    log(
-                        LogLevel::Error,
-                        &format!("Save task failed internally: {:?}", e),
-                    );
-                }
-                Err(e) => {
-                    stats_save.add_fail();
-                    log(LogLevel::Error, &format!("Save task panicked: {}", e));
-                }
+    if config::stats_report() {
+        save_statistics_report(
+            out_dir,
+            transformed,
+            run_stats.entry("Save Files".to_string()).or_default(),
+        )
+        .await;
+    }
 
-                Ok(Ok(false)) => {
-                    stats_save.add_fail();
-                    log(
-                        LogLevel::Error,
-                        "Save task reported failure (returned false).",
-                    );
-                }
-            }
+    Ok(run_stats)
+}
+
+// Rebuilds per-language save units from already-collected output.
+fn language_results(transformed: &TransformedData) -> Vec<LangTransformResult> {
+    let langs: BTreeSet<&String> = transformed
+        .navigation
+        .keys()
+        .chain(transformed.lists.keys())
+        .chain(transformed.details.keys())
+        .chain(transformed.calendars.keys())
+        .chain(transformed.bulk.keys())
+        .collect();
+    langs
+        .into_iter()
+        .map(|lang| LangTransformResult {
+            lang: lang.clone(),
+            navigation: transformed
+                .navigation
+                .get(lang)
+                .cloned()
+                .unwrap_or_default(),
+            lists: transformed.lists.get(lang).cloned().unwrap_or_default(),
+            details: transformed.details.get(lang).cloned().unwrap_or_default(),
+            calendar: transformed.calendars.get(lang).cloned(),
+            bulk: transformed.bulk.get(lang).cloned().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Navigation and calendar responses for one language, fetched concurrently.
+pub(crate) struct LanguageBootstrap {
+    pub lang: LangCode,
+    nav: AppResult<Vec<ApiNavEntry>>,
+    // None when --skip-calendar is set.
+    calendar: Option<AppResult<ApiCalendarResponse>>,
+}
+
+/// A language whose navigation came back; only these reach the later phases.
+pub(crate) struct ActiveLanguage {
+    pub nav: Vec<ApiNavEntry>,
+    pub calendar: Option<ApiCalendarResponse>,
+}
+
+pub(crate) async fn fetch_language_bootstrap(
+    client: Arc<ApiClient>,
+    cal_sem: Arc<Semaphore>,
+    lang: LangCode,
+    skip_calendar: bool,
+) -> LanguageBootstrap {
+    let calendar = async {
+        if skip_calendar {
+            None
+        } else {
+            Some(fetchers::fetch_calendar(&client, cal_sem, &lang).await)
         }
-    } else {
-        log(
-            LogLevel::Warning,
-            "No transformed data files generated to save.",
-        );
+    };
+    let (nav, calendar) = tokio::join!(fetchers::fetch_nav(&client, &lang), calendar);
+    LanguageBootstrap {
+        lang,
+        nav,
+        calendar,
     }
-    log_phase_completion(
-        "Save Files",
-        &run_stats["Save Files"],
-        save_start_time.elapsed(),
-    );
+}
 
-    let schema_start_time = Instant::now();
-    log(
-        LogLevel::Step,
-        "--- Phase 8: Creating Merged Schema Files ---",
-    );
-    match merged_schema::create_merged_schema_files(transformed_data_arc, &out_dir).await {
-        Ok(_) => log_phase_completion(
-            "Schema Generation",
-            &CategoryStats {
-                ok: 1,
-                ..Default::default()
-            },
-            schema_start_time.elapsed(),
-        ),
+/// Records a bootstrap in the "Navigation" and "Calendar Fetch" stats. A language
+/// without navigation is inactive: its calendar is dropped (and counted as skipped)
+/// so it doesn't produce a calendar-only output.
+pub(crate) fn record_language_bootstrap(
+    bootstrap: LanguageBootstrap,
+    run_stats: &mut RunStats,
+) -> Option<ActiveLanguage> {
+    let lang = bootstrap.lang;
+    let stats_nav = run_stats.get_mut("Navigation").unwrap();
+    let nav = match bootstrap.nav {
+        Ok(entries) if !entries.is_empty() => {
+            stats_nav.add_ok();
+            Some(entries)
+        }
+        Ok(_) => {
+            log(
+                LogLevel::Warning,
+                &format!("Navigation fetch for [{}] returned empty.", lang),
+            );
+            stats_nav.add_skip();
+            None
+        }
         Err(e) => {
             log(
-                LogLevel::Error,
-                &format!("Failed to generate merged schema files: {:?}", e),
+                LogLevel::Warning,
+                &format!(
+                    "Navigation fetch failed for [{}]: {:?}, skipping language.",
+                    lang, e
+                ),
             );
-
-            run_stats
-                .entry("Schema Generation".to_string())
-                .or_default()
-                .add_fail();
+            stats_nav.add_fail();
+            None
         }
-    }
+    };
 
-    let overall_duration = overall_start_time.elapsed();
-    stats::print_summary(&run_stats, &target_langs, overall_duration);
-    let exit_code = stats::determine_exit_code(&run_stats);
+    let stats_cal = run_stats.get_mut("Calendar Fetch").unwrap();
+    let calendar = match bootstrap.calendar {
+        None => None,
+        Some(Ok(_)) if nav.is_none() => {
+            stats_cal.add_skip();
+            None
+        }
+        Some(Ok(calendar_data)) => {
+            if calendar_data.calendar.is_empty() && calendar_data.op.is_empty() {
+                stats_cal.add_skip();
+                None
+            } else {
+                stats_cal.add_ok();
+                Some(calendar_data)
+            }
+        }
+        Some(Err(e)) => {
+            log(
+                LogLevel::Warning,
+                &format!("Calendar fetch failed for [{}]: {:?}", lang, e),
+            );
+            stats_cal.add_fail();
+            None
+        }
+    };
 
-    Ok(exit_code)
+    nav.map(|nav| ActiveLanguage { nav, calendar })
 }
 
-fn log_progress(phase: &str, stats: &CategoryStats, current_processed: usize) {
-    if stats.total_tasks == 0 {
-        return;
-    }
-    let percentage = (current_processed as f32 / stats.total_tasks.max(1) as f32) * 100.0;
-    log(
-        LogLevel::Info,
-        &format!(
-            "{} progress: {}/{} ({:.1}%) [OK: {}, Skip: {}, Fail: {}]",
-            phase,
-            current_processed,
-            stats.total_tasks,
-            percentage,
-            stats.ok,
-            stats.skip_or_empty,
-            stats.fail
-        ),
-    );
+pub(crate) struct BulkFetchOutcome {
+    pub store: BulkStore,
+    pub primary_ok: usize,
+    pub primary_fail: usize,
+    pub fallback_ok: usize,
+    pub fallback_fail: usize,
+    pub primary_elapsed: Duration,
+    pub fallback_elapsed: Vec<Duration>,
 }
 
-fn log_phase_completion(phase: &str, stats: &CategoryStats, elapsed: Duration) {
-    let level = if stats.fail > 0 {
-        LogLevel::Warning
+/// Fallback batches planned for `lang`: one pass over its ids per fallback language.
+fn fallback_fetch_units(lang: &str, id_count: usize, bulk_fallback: bool) -> usize {
+    let fallback_langs_count = if bulk_fallback {
+        config::icon_fallback_langs_for(lang).len()
     } else {
-        LogLevel::Success
+        0
     };
-    let phase_name = format!("{} Phase", phase);
+    id_count * fallback_langs_count / config::BULK_BATCH_SIZE.max(1)
+}
 
-    let should_log = stats.total_tasks > 0
-        || matches!(
-            phase,
-            "Navigation Fetch"
+#[inline]
+pub(crate) fn bulk_batch_count(id_count: usize) -> usize {
+    (id_count + config::BULK_BATCH_SIZE - 1) / config::BULK_BATCH_SIZE.max(1)
+}
+
+async fn attach_post_previews(
+    client: &Arc<ApiClient>,
+    target_langs: &[LangCode],
+    post_ids: &HashMap<String, HashSet<EntryId>>,
+    bulk_stores: &mut HashMap<String, BulkStore>,
+) {
+    for lang in target_langs {
+        let Some(ids) = post_ids.get(lang.as_str()).filter(|ids| !ids.is_empty()) else {
+            continue;
+        };
+        match fetchers::fetch_post_previews(client, ids, lang).await {
+            Ok(previews) => {
+                let posts = transform::bulk::process_post_previews(previews);
+                log(
+                    LogLevel::Info,
+                    &format!(
+                        "Post previews [{}]: resolved {}/{} post(s).",
+                        lang,
+                        posts.len(),
+                        ids.len()
+                    ),
+                );
+                let store = bulk_stores.remove(lang.as_str()).unwrap_or_default();
+                bulk_stores.insert(lang.to_string(), store.with_post_previews(posts));
+            }
+            Err(AppError::PostResolutionNotSupported(reason)) => {
+                log(
+                    LogLevel::Info,
+                    &format!(
+                        "Post previews unavailable ({}); post references will be left blank.",
+                        reason
+                    ),
+                );
+                return;
+            }
+            Err(e) => log(
+                LogLevel::Warning,
+                &format!("Post preview fetch failed for [{}]: {:?}", lang, e),
+            ),
+        }
+    }
+}
+
+pub(crate) async fn fetch_lang_bulk_store(
+    client: &Arc<ApiClient>,
+    bulk_sem: Arc<Semaphore>,
+    lang: &LangCode,
+    ids: &HashSet<EntryId>,
+    detail_name_map: &HashMap<EntryId, String>,
+    bulk_fallback: bool,
+) -> AppResult<BulkFetchOutcome> {
+    let primary_dispatched_at = Instant::now();
+    let primary_fetch_result =
+        fetchers::fetch_bulk_data(client, bulk_sem.clone(), ids, lang, "Bulk Primary").await;
+    let primary_elapsed = primary_dispatched_at.elapsed();
+    let (primary_bulk_map, primary_ok, primary_fail) = match primary_fetch_result {
+        Ok(map) => {
+            let ok_batches = bulk_batch_count(map.len());
+            (map, ok_batches, 0)
+        }
+        Err(e) => {
+            log(
+                LogLevel::Warning,
+                &format!("Bulk Primary fetch failed entirely for [{}]: {:?}", lang, e),
+            );
+            (HashMap::new(), 0, bulk_batch_count(ids.len()))
+        }
+    };
+
+    let ids_needing_fallback: HashSet<EntryId> = ids
+        .iter()
+        .filter(|id| {
+            primary_bulk_map
+                .get(id)
+                .and_then(|p| p.icon_url.as_deref())
+                .map_or(true, |icon| {
+                    icon.is_empty() || icon.contains("invalid-file")
+                })
+        })
+        .copied()
+        .collect();
+
+    let mut lang_fallback_map: HashMap<String, HashMap<EntryId, crate::api::model::ApiBulkPage>> =
+        HashMap::new();
+    let mut fallback_ok = 0;
+    let mut fallback_fail = 0;
+    let mut fallback_elapsed = Vec::new();
+
+    if !ids_needing_fallback.is_empty() && bulk_fallback {
+        let mut fallback_tasks = JoinSet::new();
+        let ids_needing_fallback_arc = Arc::new(ids_needing_fallback);
+        let fallback_batches = bulk_batch_count(ids_needing_fallback_arc.len());
+
+        for fallback_lang in config::icon_fallback_langs_for(lang.as_str()) {
+            let client_c = client.clone();
+            let bulk_sem_c = bulk_sem.clone();
+            let ids_c = ids_needing_fallback_arc.clone();
+            let ctx = format!("Bulk Fallback ({}) for [{}]", fallback_lang, lang);
+
+            fallback_tasks.spawn(async move {
+                let dispatched_at = Instant::now();
+                let result =
+                    fetchers::fetch_bulk_data(&client_c, bulk_sem_c, &ids_c, &fallback_lang, &ctx)
+                        .await;
+                (fallback_lang, result, dispatched_at.elapsed())
+            });
+        }
+
+        while let Some(fall_result) = fallback_tasks.join_next().await {
+            match fall_result {
+                Ok((lang_key, Ok(map), elapsed)) => {
+                    fallback_elapsed.push(elapsed);
+                    if !map.is_empty() {
+                        lang_fallback_map.insert(lang_key.to_string(), map);
+                    }
+                    fallback_ok += fallback_batches;
+                }
+                Ok((lang_key, Err(_), elapsed)) => {
+                    fallback_elapsed.push(elapsed);
+                    log(
+                        LogLevel::Warning,
+                        &format!(
+                            "Bulk Fallback fetch failed entirely for [{}] (fallback for [{}])",
+                            lang_key, lang
+                        ),
+                    );
+                    fallback_fail += fallback_batches;
+                }
+                Err(e) => {
+                    log(
+                        LogLevel::Error,
+                        &format!("Bulk Fallback task panicked: {}", e),
+                    );
+                    fallback_fail += fallback_batches;
+                }
+            }
+        }
+    }
+
+    match transform::bulk::process_bulk_data(
+        primary_bulk_map,
+        lang_fallback_map,
+        ids,
+        detail_name_map,
+        lang.as_str(),
+    ) {
+        Ok(store) => Ok(BulkFetchOutcome {
+            store,
+            primary_ok,
+            primary_fail,
+            fallback_ok,
+            fallback_fail,
+            primary_elapsed,
+            fallback_elapsed,
+        }),
+        Err(e) => {
+            log(
+                LogLevel::Error,
+                &format!("Bulk processing failed for [{}]: {:?}.", lang, e),
+            );
+            Err(AppError::TransformError(format!(
+                "Bulk processing failed for {}",
+                lang
+            )))
+        }
+    }
+}
+
+pub(crate) async fn run_save_queue(
+    mut save_rx: mpsc::UnboundedReceiver<LangTransformResult>,
+    out_dir: PathBuf,
+) -> CategoryStats {
+    let mut save_stats = CategoryStats::default();
+    let mut save_tasks: JoinSet<AppResult<bool>> = JoinSet::new();
+    let mut queue_open = true;
+    let previous_manifest = list_manifest::load_list_manifest(&out_dir);
+    let mut manifest = previous_manifest.clone();
+    let dir_indexes = DirectoryIndexes::default();
+
+    loop {
+        tokio::select! {
+            received = save_rx.recv(), if queue_open => match received {
+                Some(mut lang_result) => {
+                    let lang = lang_result.lang.clone();
+                    lang_result.lists = list_manifest::drop_colliding_lists(&lang, lang_result.lists);
+                    let renames = previous_manifest.renames(&lang, &lang_result.lists);
+                    for rename in &renames {
+                        log(
+                            LogLevel::Warning,
+                            &format!(
+                                "List file for menu {} [{}] previously held '{}', now '{}'.",
+                                rename.menu_id, lang, rename.previous_name, rename.current_name
+                            ),
+                        );
+                    }
+                    stats::record_list_menu_renames(renames);
+                    manifest.record(&lang, &lang_result.lists);
+                    match queue_language_saves(&mut save_tasks, &out_dir, &dir_indexes, lang_result).await {
+                        Ok(queued) => {
+                            save_stats.total_tasks += queued;
+                            if queued > 1 {
+                                log(
+                                    LogLevel::Info,
+                                    &format!("Queued {} file(s) for saving [{}]", queued, lang),
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            log(
+                                LogLevel::Error,
+                                &format!("Failed to queue saves for [{}]: {:?}", lang, e),
+                            );
+                            save_stats.total_tasks += 1;
+                            save_stats.add_fail();
+                        }
+                    }
+                }
+                None => queue_open = false,
+            },
+            Some(result) = save_tasks.join_next(), if !save_tasks.is_empty() => {
+                tally_save_result(&mut save_stats, result);
+            }
+            else => break,
+        }
+    }
+
+    if !manifest.langs.is_empty() {
+        save_stats.total_tasks += 1;
+        let result = list_manifest::save_list_manifest(&out_dir, manifest).await;
+        tally_save_result(&mut save_stats, Ok(result));
+    }
+
+    // Written after every save task has finished so each index lists only the
+    // files that actually made it to disk.
+    let (detail_indexes, list_indexes) = dir_indexes.take(&out_dir);
+    for (path, index) in detail_indexes {
+        save_stats.total_tasks += 1;
+        let result = dir_index::save_detail_index(path, index).await;
+        tally_save_result(&mut save_stats, Ok(result));
+    }
+    for (path, index) in list_indexes {
+        save_stats.total_tasks += 1;
+        let result = dir_index::save_list_index(path, index).await;
+        tally_save_result(&mut save_stats, Ok(result));
+    }
+
+    save_stats
+}
+
+async fn queue_language_saves(
+    save_tasks: &mut JoinSet<AppResult<bool>>,
+    out_dir: &Path,
+    dir_indexes: &DirectoryIndexes,
+    lang_result: LangTransformResult,
+) -> AppResult<usize> {
+    let lang = lang_result.lang;
+    let mut queued = 0usize;
+
+    if !lang_result.navigation.is_empty() {
+        let nav_base_dir = out_dir.join("navigation");
+        fs::create_dir_all(&nav_base_dir).await?;
+        let path = nav_base_dir.join(format!("{}.json", lang));
+        let ctx = format!("Nav [{}]", lang);
+        queued += 1;
+        save_tasks.spawn(io::save_json(path, lang_result.navigation, ctx));
+    }
+
+    if !lang_result.lists.is_empty() {
+        let lang_list_dir = out_dir.join("list").join(&lang);
+        fs::create_dir_all(&lang_list_dir).await?;
+        for (index, list_file) in lang_result.lists.iter().enumerate() {
+            let file_name = format!("{}.json", list_file.menu_id);
+            let path = lang_list_dir.join(file_name);
+            let ctx = format!("List M:{} [{}]", list_file.menu_id, lang);
+            queued += 1;
+            let index_entry = dir_index::list_index_entry(list_file);
+            let list_file = io::SharedItem::new(Arc::clone(&lang_result.lists), index);
+            let dir_indexes = dir_indexes.clone();
+            let lang = lang.clone();
+            save_tasks.spawn(async move {
+                let saved = io::save_json(path, list_file, ctx).await?;
+                if saved {
+                    dir_indexes.record_list(&lang, index_entry);
+                }
+                Ok(saved)
+            });
+        }
+    }
+
+    if !lang_result.details.is_empty() {
+        if config::export_pack() {
+            queued += 1;
+            save_tasks.spawn(io::pack::save_detail_pack(
+                out_dir.to_path_buf(),
+                lang.clone(),
+                Arc::clone(&lang_result.details),
+            ));
+        }
+        let lang_detail_dir = out_dir.join("detail").join(&lang);
+        fs::create_dir_all(&lang_detail_dir).await?;
+        for (index, detail_page) in lang_result.details.iter().enumerate() {
+            let file_name = format!("{}.json", detail_page.id);
+            let path = lang_detail_dir.join(file_name);
+            let ctx = format!("Detail E:{} [{}]", detail_page.id, lang);
+            queued += 1;
+            let index_entry = dir_index::detail_index_entry(detail_page);
+            let detail_page = io::SharedItem::new(Arc::clone(&lang_result.details), index);
+            let dir_indexes = dir_indexes.clone();
+            let lang = lang.clone();
+            save_tasks.spawn(async move {
+                let saved = io::save_detail_page(path, detail_page, ctx).await?;
+                if saved {
+                    dir_indexes.record_detail(&lang, index_entry);
+                }
+                Ok(saved)
+            });
+        }
+    }
+
+    if !lang_result.bulk.is_empty() {
+        let bulk_base_dir = out_dir.join("bulk");
+        fs::create_dir_all(&bulk_base_dir).await?;
+        let path = bulk_base_dir.join(format!("{}.json", lang));
+        let ctx = format!("Bulk [{}]", lang);
+        queued += 1;
+        let bulk_entries = lang_result.bulk;
+        save_tasks.spawn(async move {
+            let saved = io::save_json(path.clone(), bulk_entries, ctx.clone()).await?;
+            io::warn_if_oversized(&path, config::BULK_FILE_WARN_SIZE_MB, &ctx).await;
+            Ok(saved)
+        });
+    }
+
+    if let Some(calendar_file) = lang_result.calendar {
+        let calendar_base_dir = out_dir.join("calendar");
+        fs::create_dir_all(&calendar_base_dir).await?;
+        let path = calendar_base_dir.join(format!("{}.json", lang));
+        let ctx = format!("Calendar [{}]", lang);
+        queued += 1;
+        save_tasks.spawn(io::save_json(path, calendar_file, ctx));
+    }
+
+    Ok(queued)
+}
+
+pub(crate) async fn save_alias_report(
+    out_dir: &Path,
+    mut alias_report: BTreeMap<String, Vec<OutputAliasEntry>>,
+    stats_save: &mut CategoryStats,
+) {
+    alias_report.retain(|_lang, aliases| !aliases.is_empty());
+    if alias_report.is_empty() {
+        return;
+    }
+
+    let alias_count: usize = alias_report.values().map(Vec::len).sum();
+    log(
+        LogLevel::Info,
+        &format!(
+            "Detected {} alias page(s) across {} language(s).",
+            alias_count,
+            alias_report.len()
+        ),
+    );
+
+    save_report_file(out_dir, "aliases.json", alias_report, stats_save).await;
+}
+
+async fn save_taxonomy_files(
+    out_dir: &Path,
+    transformed: Arc<TransformedData>,
+    stats_save: &mut CategoryStats,
+) {
+    let taxonomy_dir = out_dir.join("taxonomy");
+    if let Err(e) = fs::create_dir_all(&taxonomy_dir).await {
+        log(
+            LogLevel::Error,
+            &format!(
+                "Failed to create taxonomy directory {}: {}",
+                taxonomy_dir.display(),
+                e
+            ),
+        );
+        stats_save.add_fail();
+        return;
+    }
+
+    let mut langs: BTreeSet<&String> = transformed.lists.keys().collect();
+    langs.extend(transformed.details.keys());
+    for lang in langs {
+        let data = Arc::clone(&transformed);
+        let lang_key = lang.clone();
+        let json_result = utils::run_blocking(move || {
+            let lists = data.lists.get(&lang_key).map_or(&[][..], |v| v.as_slice());
+            let details = data
+                .details
+                .get(&lang_key)
+                .map_or(&[][..], |v| v.as_slice());
+            io::to_json_string_for_save(&taxonomy::build_taxonomy(lists, details))
+        })
+        .await;
+
+        stats_save.total_tasks += 1;
+        let fpath = taxonomy_dir.join(format!("{}.json", lang));
+        let result = match json_result {
+            Ok(json) => io::save_json_string(fpath, json, format!("Taxonomy [{}]", lang)).await,
+            Err(e) => Err(e),
+        };
+        tally_save_result(stats_save, Ok(result));
+    }
+}
+
+async fn save_search_index_files(
+    out_dir: &Path,
+    transformed: Arc<TransformedData>,
+    stats_save: &mut CategoryStats,
+) {
+    let index_dir = out_dir.join(search_index::SEARCH_INDEX_DIR);
+    if let Err(e) = fs::create_dir_all(&index_dir).await {
+        log(
+            LogLevel::Error,
+            &format!(
+                "Failed to create search index directory {}: {}",
+                index_dir.display(),
+                e
+            ),
+        );
+        stats_save.add_fail();
+        return;
+    }
+
+    let langs: BTreeSet<&String> = transformed.details.keys().collect();
+    for lang in langs {
+        let data = Arc::clone(&transformed);
+        let lang_key = lang.clone();
+        let json_result = utils::run_blocking(move || {
+            let details = data
+                .details
+                .get(&lang_key)
+                .map_or(&[][..], |v| v.as_slice());
+            let index = search_index::build_search_index(
+                details,
+                config::search_index_components(),
+                config::search_index_keyword_chars(),
+            );
+            io::to_json_string_for_save(&index)
+        })
+        .await;
+
+        stats_save.total_tasks += 1;
+        let fpath = index_dir.join(format!("{}.json", lang));
+        let result = match json_result {
+            Ok(json) => io::save_json_string(fpath, json, format!("Search Index [{}]", lang)).await,
+            Err(e) => Err(e),
+        };
+        tally_save_result(stats_save, Ok(result));
+    }
+}
+
+async fn save_entry_bundles(
+    out_dir: &Path,
+    transformed: Arc<TransformedData>,
+    stats_save: &mut CategoryStats,
+) {
+    let bundle_dir = out_dir.join(bundle::BUNDLES_DIR);
+    if let Err(e) = fs::create_dir_all(&bundle_dir).await {
+        log(
+            LogLevel::Error,
+            &format!(
+                "Failed to create bundle directory {}: {}",
+                bundle_dir.display(),
+                e
+            ),
+        );
+        stats_save.add_fail();
+        return;
+    }
+
+    let bundles_result = utils::run_blocking(move || {
+        let (bundles, not_found) =
+            bundle::build_entry_bundles(&transformed, config::bundle_entries());
+        let files = bundles
+            .iter()
+            .map(|b| io::to_json_string_for_save(b).map(|json| (b.ep_id, b.languages.len(), json)))
+            .collect::<AppResult<Vec<_>>>()?;
+        Ok((files, not_found))
+    })
+    .await;
+
+    let (files, not_found) = match bundles_result {
+        Ok(result) => result,
+        Err(e) => {
+            log(
+                LogLevel::Error,
+                &format!("Failed to build entry bundles: {}", e),
+            );
+            stats_save.add_fail();
+            return;
+        }
+    };
+    if !not_found.is_empty() {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "No detail page in any language for bundle entr{} {:?}; skipped.",
+                if not_found.len() == 1 { "y" } else { "ies" },
+                not_found
+            ),
+        );
+    }
+    for (ep_id, lang_count, json) in files {
+        stats_save.total_tasks += 1;
+        let fpath = bundle_dir.join(format!("{}.json", ep_id));
+        let ctx = format!("Entry Bundle [{} / {} lang(s)]", ep_id, lang_count);
+        tally_save_result(stats_save, Ok(io::save_json_string(fpath, json, ctx).await));
+    }
+}
+
+async fn save_statistics_report(
+    out_dir: &Path,
+    transformed: Arc<TransformedData>,
+    stats_save: &mut CategoryStats,
+) {
+    let out_dir_owned = out_dir.to_path_buf();
+    let report_result = utils::run_blocking(move || {
+        let mut report = statistics::build_statistics(&transformed);
+        report.total_output_bytes = statistics::output_dir_bytes(&out_dir_owned);
+        report.semaphore_waits = stats::semaphore_wait_summaries().into_iter().collect();
+        Ok(report)
+    })
+    .await;
+
+    match report_result {
+        Ok(report) => {
+            log(
+                LogLevel::Info,
+                &format!(
+                    "Statistics: {} page(s), {:.1} component(s)/page, {:.1} MB written.",
+                    report.total_pages,
+                    report.avg_components_per_page,
+                    report.total_output_bytes as f64 / (1024.0 * 1024.0)
+                ),
+            );
+            save_report_file(out_dir, statistics::STATISTICS_FILE, report, stats_save).await;
+        }
+        Err(e) => {
+            log(
+                LogLevel::Error,
+                &format!("Failed to build statistics report: {:?}", e),
+            );
+            stats_save.total_tasks += 1;
+            stats_save.add_fail();
+        }
+    }
+}
+
+async fn save_oversized_components_report(
+    out_dir: &Path,
+    transformed: Arc<TransformedData>,
+    stats_save: &mut CategoryStats,
+) {
+    const MAX_LOGGED_COMPONENTS: usize = 20;
+
+    let threshold = config::oversized_component_bytes();
+    let oversized = match utils::run_cpu_intensive(move || {
+        statistics::collect_oversized_components(&transformed, threshold)
+    })
+    .await
+    {
+        Ok(oversized) => oversized,
+        Err(e) => {
+            log(
+                LogLevel::Error,
+                &format!("Failed to measure component sizes: {:?}", e),
+            );
+            stats_save.total_tasks += 1;
+            stats_save.add_fail();
+            return;
+        }
+    };
+    if oversized.is_empty() {
+        return;
+    }
+
+    for component in oversized.iter().take(MAX_LOGGED_COMPONENTS) {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Oversized component '{}' [{} / {}]: {:.1} KB, {} node(s).",
+                component.component,
+                component.lang,
+                component.page_id,
+                component.size_bytes as f64 / 1024.0,
+                component.node_count
+            ),
+        );
+    }
+    if oversized.len() > MAX_LOGGED_COMPONENTS {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "... {} more oversized component(s); see reports/{}.",
+                oversized.len() - MAX_LOGGED_COMPONENTS,
+                statistics::OVERSIZED_COMPONENTS_FILE
+            ),
+        );
+    }
+
+    save_report_file(
+        out_dir,
+        statistics::OVERSIZED_COMPONENTS_FILE,
+        oversized,
+        stats_save,
+    )
+    .await;
+}
+
+async fn save_unresolved_refs_report(
+    out_dir: &Path,
+    transformed: Arc<TransformedData>,
+    stats_save: &mut CategoryStats,
+) {
+    const MAX_LOGGED_IDS: usize = 20;
+
+    let report =
+        match utils::run_cpu_intensive(move || statistics::collect_unresolved_refs(&transformed))
+            .await
+        {
+            Ok(report) => report,
+            Err(e) => {
+                log(
+                    LogLevel::Error,
+                    &format!("Failed to collect unresolved references: {:?}", e),
+                );
+                stats_save.total_tasks += 1;
+                stats_save.add_fail();
+                return;
+            }
+        };
+
+    let format_ids = |ids: &BTreeSet<EntryId>| {
+        let shown: Vec<String> = ids
+            .iter()
+            .take(MAX_LOGGED_IDS)
+            .map(ToString::to_string)
+            .collect();
+        match ids.len().saturating_sub(MAX_LOGGED_IDS) {
+            0 => shown.join(", "),
+            more => format!("{} (+{} more)", shown.join(", "), more),
+        }
+    };
+    for (lang, refs) in report.iter().filter(|(_, refs)| !refs.is_empty()) {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Unresolved refs [{}]: {} entry node(s) over {} id(s) [{}], {} post node(s) over {} id(s) [{}].",
+                lang,
+                refs.entry_nodes,
+                refs.entry_ids.len(),
+                format_ids(&refs.entry_ids),
+                refs.post_nodes,
+                refs.post_ids.len(),
+                format_ids(&refs.post_ids)
+            ),
+        );
+    }
+
+    save_report_file(
+        out_dir,
+        statistics::UNRESOLVED_REFS_FILE,
+        report,
+        stats_save,
+    )
+    .await;
+}
+
+pub(crate) async fn save_schema_drift_report(out_dir: &Path, stats_save: &mut CategoryStats) {
+    let Some(watch_dir) = config::schema_watch_dir() else {
+        return;
+    };
+    match schema_watch::finish(watch_dir).await {
+        Ok(Some(report)) => {
+            save_report_file(out_dir, schema_watch::SCHEMA_DRIFT_FILE, report, stats_save).await
+        }
+        Ok(None) => log(
+            LogLevel::Info,
+            "Schema watch: no responses were sampled this run.",
+        ),
+        Err(e) => {
+            log(
+                LogLevel::Error,
+                &format!("Schema watch comparison failed: {:?}", e),
+            );
+            stats_save.total_tasks += 1;
+            stats_save.add_fail();
+        }
+    }
+}
+
+pub(crate) async fn save_component_key_collision_report(
+    out_dir: &Path,
+    stats_save: &mut CategoryStats,
+) {
+    let collisions = transform::detail::take_component_key_collisions();
+    if collisions.is_empty() {
+        return;
+    }
+    log(
+        LogLevel::Warning,
+        &format!(
+            "{} component key collision(s); see reports/{}.",
+            collisions.len(),
+            transform::detail::COMPONENT_KEY_COLLISIONS_FILE
+        ),
+    );
+    save_report_file(
+        out_dir,
+        transform::detail::COMPONENT_KEY_COLLISIONS_FILE,
+        collisions,
+        stats_save,
+    )
+    .await;
+}
+
+pub(crate) async fn save_event_window_conflict_report(
+    out_dir: &Path,
+    stats_save: &mut CategoryStats,
+) {
+    let conflicts = transform::event_window::take_event_window_conflicts();
+    if conflicts.is_empty() {
+        return;
+    }
+    log(
+        LogLevel::Warning,
+        &format!(
+            "{} event page(s) without an unambiguous date range; see reports/{}.",
+            conflicts.len(),
+            transform::event_window::EVENT_WINDOW_CONFLICTS_FILE
+        ),
+    );
+    save_report_file(
+        out_dir,
+        transform::event_window::EVENT_WINDOW_CONFLICTS_FILE,
+        conflicts,
+        stats_save,
+    )
+    .await;
+}
+
+pub(crate) async fn save_failed_ids_report(out_dir: &Path, stats_save: &mut CategoryStats) {
+    let failed = stats::take_failed_detail_ids();
+    if !config::save_failed_ids() {
+        return;
+    }
+    let total: usize = failed.values().map(BTreeSet::len).sum();
+    log(
+        LogLevel::Info,
+        &format!(
+            "{} failed detail fetch(es) listed in reports/{}.",
+            total,
+            stats::FAILED_IDS_FILE
+        ),
+    );
+    save_report_file(out_dir, stats::FAILED_IDS_FILE, failed, stats_save).await;
+}
+
+pub(crate) async fn save_coercion_report(out_dir: &Path, stats_save: &mut CategoryStats) {
+    if !telemetry::is_enabled() {
+        return;
+    }
+
+    let coercions = telemetry::coercion_snapshot();
+    let total_coercions: u64 = coercions.values().flat_map(|kinds| kinds.values()).sum();
+    let level = if total_coercions > 0 {
+        LogLevel::Warning
+    } else {
+        LogLevel::Info
+    };
+    log(
+        level,
+        &format!(
+            "Serde coercions: {} fallback(s) across {} field(s).",
+            total_coercions,
+            coercions.len()
+        ),
+    );
+
+    save_report_file(out_dir, "serde_coercions.json", coercions, stats_save).await;
+}
+
+async fn save_report_file<T>(
+    out_dir: &Path,
+    file_name: &str,
+    report: T,
+    stats_save: &mut CategoryStats,
+) where
+    T: Serialize + Send + Sync + 'static,
+{
+    let reports_dir = out_dir.join("reports");
+    stats_save.total_tasks += 1;
+    let result = match fs::create_dir_all(&reports_dir).await {
+        Ok(()) => {
+            io::save_json(
+                reports_dir.join(file_name),
+                report,
+                format!("Report {}", file_name),
+            )
+            .await
+        }
+        Err(e) => Err(e.into()),
+    };
+    tally_save_result(stats_save, Ok(result));
+}
+
+fn tally_save_result(
+    stats_save: &mut CategoryStats,
+    result: Result<AppResult<bool>, tokio::task::JoinError>,
+) {
+    match result {
+        Ok(Ok(true)) => {
+            stats_save.add_ok();
+        }
+
+        Ok(Err(e)) => {
+            stats_save.add_fail();
+            log(
+                LogLevel::Error,
+                &format!("Save task failed internally: {:?}", e),
+            );
+        }
+        Err(e) => {
+            stats_save.add_fail();
+            log(LogLevel::Error, &format!("Save task panicked: {}", e));
+        }
+
+        Ok(Ok(false)) => {
+            stats_save.add_fail();
+            log(
+                LogLevel::Error,
+                "Save task reported failure (returned false).",
+            );
+        }
+    }
+}
+
+pub(crate) fn log_progress(phase: &str, stats: &CategoryStats, current_processed: usize) {
+    if stats.total_tasks == 0 {
+        return;
+    }
+    let percentage = (current_processed as f32 / stats.total_tasks.max(1) as f32) * 100.0;
+    log(
+        LogLevel::Info,
+        &format!(
+            "{} progress: {}/{} ({:.1}%) [OK: {}, Skip: {}, Fail: {}]",
+            phase,
+            current_processed,
+            stats.total_tasks,
+            percentage,
+            stats.ok,
+            stats.skip_or_empty,
+            stats.fail
+        ),
+    );
+}
+
+pub(crate) fn log_phase_completion(phase: &str, stats: &CategoryStats, elapsed: Duration) {
+    let level = if stats.fail > 0 {
+        LogLevel::Warning
+    } else {
+        LogLevel::Success
+    };
+    let phase_name = format!("{} Phase", phase);
+
+    let should_log = stats.total_tasks > 0
+        || matches!(
+            phase,
+            "Navigation Fetch"
                 | "Calendar Fetch"
                 | "Transforming Data"
                 | "Save Files"
@@ -755,3 +1969,106 @@ fn log_phase_completion(phase: &str, stats: &CategoryStats, elapsed: Duration) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bootstrap(
+        nav: AppResult<Vec<ApiNavEntry>>,
+        calendar: Option<AppResult<ApiCalendarResponse>>,
+    ) -> LanguageBootstrap {
+        LanguageBootstrap {
+            lang: LangCode::parse("en-us").unwrap(),
+            nav,
+            calendar,
+        }
+    }
+
+    fn nav_entries() -> Vec<ApiNavEntry> {
+        serde_json::from_value(json!([{ "menu": { "menu_id": "2" }, "name": "Characters" }]))
+            .unwrap()
+    }
+
+    fn calendar(items: usize) -> ApiCalendarResponse {
+        ApiCalendarResponse {
+            calendar: vec![json!({ "drop_day": [1] }); items],
+            op: Vec::new(),
+        }
+    }
+
+    fn failure() -> AppError {
+        AppError::Timeout("mock".to_string())
+    }
+
+    fn run_stats() -> RunStats {
+        ["Navigation", "Calendar Fetch"]
+            .into_iter()
+            .map(|name| (name.to_string(), CategoryStats::default()))
+            .collect()
+    }
+
+    /// (ok, fail, skip) for one stats category.
+    fn counts(run_stats: &RunStats, name: &str) -> (usize, usize, usize) {
+        let stats = &run_stats[name];
+        (stats.ok, stats.fail, stats.skip_or_empty)
+    }
+
+    #[test]
+    fn language_with_navigation_is_active() {
+        let mut stats = run_stats();
+        let active = record_language_bootstrap(
+            bootstrap(Ok(nav_entries()), Some(Ok(calendar(1)))),
+            &mut stats,
+        )
+        .expect("active language");
+        assert_eq!(active.nav.len(), 1);
+        assert!(active.calendar.is_some());
+        assert_eq!(counts(&stats, "Navigation"), (1, 0, 0));
+        assert_eq!(counts(&stats, "Calendar Fetch"), (1, 0, 0));
+    }
+
+    #[test]
+    fn empty_navigation_skips_the_language_and_its_calendar() {
+        let mut stats = run_stats();
+        let active =
+            record_language_bootstrap(bootstrap(Ok(Vec::new()), Some(Ok(calendar(1)))), &mut stats);
+        assert!(active.is_none());
+        assert_eq!(counts(&stats, "Navigation"), (0, 0, 1));
+        assert_eq!(counts(&stats, "Calendar Fetch"), (0, 0, 1));
+    }
+
+    #[test]
+    fn failed_navigation_skips_the_language() {
+        let mut stats = run_stats();
+        let active =
+            record_language_bootstrap(bootstrap(Err(failure()), Some(Ok(calendar(1)))), &mut stats);
+        assert!(active.is_none());
+        assert_eq!(counts(&stats, "Navigation"), (0, 1, 0));
+        assert_eq!(counts(&stats, "Calendar Fetch"), (0, 0, 1));
+
+        // A calendar that failed on its own is still counted as a failure.
+        let mut stats = run_stats();
+        record_language_bootstrap(bootstrap(Err(failure()), Some(Err(failure()))), &mut stats);
+        assert_eq!(counts(&stats, "Calendar Fetch"), (0, 1, 0));
+    }
+
+    #[test]
+    fn skipped_or_empty_calendar_keeps_the_language_active() {
+        let mut stats = run_stats();
+        let active = record_language_bootstrap(bootstrap(Ok(nav_entries()), None), &mut stats)
+            .expect("active language");
+        assert!(active.calendar.is_none());
+        assert_eq!(counts(&stats, "Calendar Fetch"), (0, 0, 0));
+
+        let active = record_language_bootstrap(
+            bootstrap(Ok(nav_entries()), Some(Ok(calendar(0)))),
+            &mut stats,
+        )
+        .expect("active language");
+        assert!(active.calendar.is_none());
+        assert_eq!(counts(&stats, "Navigation"), (2, 0, 0));
+        assert_eq!(counts(&stats, "Calendar Fetch"), (0, 0, 1));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::config;
+use crate::core::stats::SCHEMA_DRIFT_ENDPOINTS;
+use crate::error::AppResult;
+use crate::io;
+use crate::logging::{log, LogLevel};
+use crate::model::common::LangCode;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+pub const FINGERPRINT_FILE: &str = "fingerprints.json";
+pub const SCHEMA_DRIFT_FILE: &str = "schema_drift.json";
+
+// Every endpoint key observed this run -> union of "path:type" over the sampled responses.
+static OBSERVED: Lazy<Mutex<BTreeMap<String, BTreeSet<String>>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintFile {
+    pub generated_at: String,
+    pub endpoints: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointDrift {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub retyped: Vec<String>,
+}
+
+impl EndpointDrift {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.retyped.is_empty()
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDriftReport {
+    pub generated_at: String,
+    // None on the first run against a watch directory: the fingerprints become the baseline.
+    pub previous_run_at: Option<String>,
+    pub endpoints: BTreeMap<String, EndpointDrift>,
+}
+
+/// Structural fingerprint of a JSON value: one `path:type` string per node, with
+/// array elements collapsed to `[]` and numeric object keys (entry ids) to `{}`.
+pub fn fingerprint(value: &Value) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+    collect_paths(value, "$", &mut paths);
+    paths
+}
+
+fn collect_paths(value: &Value, path: &str, paths: &mut BTreeSet<String>) {
+    paths.insert(format!("{}:{}", path, type_name(value)));
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let segment = if !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit()) {
+                    "{}"
+                } else {
+                    key.as_str()
+                };
+                collect_paths(child, &format!("{}.{}", path, segment), paths);
+            }
+        }
+        Value::Array(items) => {
+            let item_path = format!("{}[]", path);
+            for item in items {
+                collect_paths(item, &item_path, paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Records the fingerprint of a successful raw response if `--schema-watch` is on and
+/// the request falls in the deterministic sample.
+pub fn observe(
+    endpoint_key: &str,
+    lang: &LangCode,
+    params: Option<&HashMap<String, String>>,
+    payload: Option<&Value>,
+    bytes: &[u8],
+) {
+    if config::schema_watch_dir().is_none() || !is_sampled(endpoint_key, lang, params, payload) {
+        return;
+    }
+    let Ok(value) = serde_json::from_slice::<Value>(bytes) else {
+        return;
+    };
+    let paths = fingerprint(&value);
+    if let Ok(mut observed) = OBSERVED.lock() {
+        observed
+            .entry(endpoint_key.to_string())
+            .or_default()
+            .extend(paths);
+    }
+}
+
+// Parameterless requests (nav, calendar) are always sampled; the rest by a stable
+// hash of the request so the same entries are compared from run to run.
+fn is_sampled(
+    endpoint_key: &str,
+    lang: &LangCode,
+    params: Option<&HashMap<String, String>>,
+    payload: Option<&Value>,
+) -> bool {
+    if params.is_none_or(HashMap::is_empty) && payload.is_none() {
+        return true;
+    }
+    let mut sorted_params: Vec<(&String, &String)> =
+        params.map(|p| p.iter().collect()).unwrap_or_default();
+    sorted_params.sort_unstable();
+    let identity = format!(
+        "{}|{}|{:?}|{}",
+        endpoint_key,
+        lang,
+        sorted_params,
+        payload.map(Value::to_string).unwrap_or_default()
+    );
+    fnv1a(identity.as_bytes()).is_multiple_of(config::SCHEMA_WATCH_SAMPLE_MODULUS)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn split_path_type(entry: &str) -> (&str, &str) {
+    entry.rsplit_once(':').unwrap_or((entry, ""))
+}
+
+fn path_types(paths: &BTreeSet<String>) -> BTreeMap<&str, BTreeSet<&str>> {
+    let mut by_path: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for entry in paths {
+        let (path, kind) = split_path_type(entry);
+        by_path.entry(path).or_default().insert(kind);
+    }
+    by_path
+}
+
+pub fn diff_fingerprints(previous: &BTreeSet<String>, current: &BTreeSet<String>) -> EndpointDrift {
+    let previous_types = path_types(previous);
+    let current_types = path_types(current);
+    let mut drift = EndpointDrift::default();
+    for (path, types) in &current_types {
+        match previous_types.get(path) {
+            None => drift.added.push(path.to_string()),
+            Some(old_types) if old_types != types => drift.retyped.push(format!(
+                "{}: {} -> {}",
+                path,
+                old_types.iter().copied().collect::<Vec<_>>().join("|"),
+                types.iter().copied().collect::<Vec<_>>().join("|")
+            )),
+            Some(_) => {}
+        }
+    }
+    drift.removed = previous_types
+        .keys()
+        .filter(|path| !current_types.contains_key(*path))
+        .map(|path| path.to_string())
+        .collect();
+    drift
+}
+
+fn load_fingerprints(path: &Path) -> Option<FingerprintFile> {
+    let content = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "Ignoring unreadable schema fingerprints {}: {}",
+                    path.display(),
+                    e
+                ),
+            );
+            None
+        }
+    }
+}
+
+/// Compares this run's fingerprints with the previous ones in `watch_dir`, stores the
+/// merged set for the next run and returns the drift report. Endpoints not sampled this
+/// run keep their previous fingerprint.
+pub async fn finish(watch_dir: &Path) -> AppResult<Option<SchemaDriftReport>> {
+    let current = OBSERVED
+        .lock()
+        .map(|mut observed| std::mem::take(&mut *observed))
+        .unwrap_or_default();
+    if current.is_empty() {
+        return Ok(None);
+    }
+
+    let path = watch_dir.join(FINGERPRINT_FILE);
+    let previous = load_fingerprints(&path);
+    let now = Utc::now().to_rfc3339();
+
+    let mut endpoints = BTreeMap::new();
+    if let Some(previous) = &previous {
+        for (endpoint, paths) in &current {
+            if let Some(previous_paths) = previous.endpoints.get(endpoint) {
+                let drift = diff_fingerprints(previous_paths, paths);
+                if !drift.is_empty() {
+                    endpoints.insert(endpoint.clone(), drift);
+                }
+            }
+        }
+    }
+    SCHEMA_DRIFT_ENDPOINTS.store(endpoints.len() as u64, Ordering::Relaxed);
+
+    let previous_run_at = previous.as_ref().map(|file| file.generated_at.clone());
+    let mut merged = previous.map(|file| file.endpoints).unwrap_or_default();
+    merged.extend(current);
+    tokio::fs::create_dir_all(watch_dir).await?;
+    io::save_json(
+        path,
+        FingerprintFile {
+            generated_at: now.clone(),
+            endpoints: merged,
+        },
+        "Schema fingerprints".to_string(),
+    )
+    .await?;
+
+    Ok(Some(SchemaDriftReport {
+        generated_at: now,
+        previous_run_at,
+        endpoints,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn drift(previous: Value, current: Value) -> EndpointDrift {
+        diff_fingerprints(&fingerprint(&previous), &fingerprint(&current))
+    }
+
+    #[test]
+    fn retyped_field_is_drift() {
+        let drift = drift(
+            json!({ "data": { "list": [{ "id": 1, "name": "Amber" }] } }),
+            json!({ "data": { "list": [{ "id": "1", "name": "Amber" }] } }),
+        );
+        assert_eq!(drift.retyped, ["$.data.list[].id: number -> string"]);
+        assert!(drift.added.is_empty() && drift.removed.is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_keys_are_drift() {
+        let drift = drift(
+            json!({ "data": { "name": "Amber", "icon_url": "" } }),
+            json!({ "data": { "name": "Amber", "header_img_url": "" } }),
+        );
+        assert_eq!(drift.added, ["$.data.header_img_url"]);
+        assert_eq!(drift.removed, ["$.data.icon_url"]);
+        assert!(drift.retyped.is_empty());
+    }
+
+    #[test]
+    fn entry_ids_and_array_lengths_are_not_drift() {
+        let drift = drift(
+            json!({ "pages": { "1001": { "name": "Amber" } }, "list": [1] }),
+            json!({ "pages": { "2001": { "name": "Dull Blade" } }, "list": [1, 2, 3] }),
+        );
+        assert!(drift.is_empty(), "{:?}", drift);
+    }
+}
@@ -0,0 +1,498 @@
+use crate::core::data_store::TransformedData;
+use crate::core::stats::WaitSummary;
+use crate::model::common::{EntryId, MenuId};
+use crate::model::html::HtmlNode;
+use crate::model::output::{ComponentData, OutputDetailPage};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+use std::io;
+use std::path::Path;
+
+pub const STATISTICS_FILE: &str = "statistics.json";
+const LARGEST_PAGES_KEPT: usize = 10;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatisticsReport {
+    pub generated_at: String,
+    pub total_pages: usize,
+    pub total_components: usize,
+    pub avg_components_per_page: f64,
+    /// Components recovered with the flat HTML parse, see `degradedComponents`.
+    pub degraded_components: usize,
+    pub total_output_bytes: u64,
+    pub entries_per_menu: BTreeMap<String, BTreeMap<MenuId, usize>>,
+    pub component_types: BTreeMap<&'static str, usize>,
+    pub html_node_types: BTreeMap<String, usize>,
+    pub largest_pages: Vec<PageSize>,
+    /// Permit wait per fetch semaphore, from `stats::semaphore_wait_summaries`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub semaphore_waits: BTreeMap<&'static str, WaitSummary>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageSize {
+    pub bytes: usize,
+    pub lang: String,
+    #[serde(rename = "epId")]
+    pub ep_id: EntryId,
+}
+
+/// Keeps the `capacity` largest pages seen so far in a min-heap.
+#[derive(Debug)]
+pub struct TopPages {
+    capacity: usize,
+    heap: BinaryHeap<Reverse<PageSize>>,
+}
+
+impl TopPages {
+    pub fn new(capacity: usize) -> Self {
+        TopPages {
+            capacity,
+            heap: BinaryHeap::with_capacity(capacity + 1),
+        }
+    }
+
+    pub fn push(&mut self, page: PageSize) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.heap.len() < self.capacity {
+            self.heap.push(Reverse(page));
+        } else if self.heap.peek().is_some_and(|Reverse(min)| page > *min) {
+            self.heap.pop();
+            self.heap.push(Reverse(page));
+        }
+    }
+
+    pub fn into_sorted_vec(self) -> Vec<PageSize> {
+        let mut pages: Vec<PageSize> = self.heap.into_iter().map(|Reverse(p)| p).collect();
+        pages.sort_unstable_by(|a, b| b.cmp(a));
+        pages
+    }
+}
+
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Size of the page as written by the saver (pretty-printed JSON with the configured
+/// indent), without buffering it.
+pub fn serialized_size(page: &OutputDetailPage) -> usize {
+    counted_size(page)
+}
+
+pub fn component_size(data: &ComponentData) -> usize {
+    counted_size(data)
+}
+
+fn counted_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut counter = ByteCounter::default();
+    match crate::io::to_json_writer(&mut counter, value) {
+        Ok(()) => counter.0,
+        Err(_) => 0,
+    }
+}
+
+pub fn count_html_nodes(data: &ComponentData, node_defs: &[Vec<HtmlNode>]) -> usize {
+    let mut count = 0;
+    visit_resolved_html_nodes(data, node_defs, &mut |_, _| count += 1);
+    count
+}
+
+pub fn count_html_node_types(
+    data: &ComponentData,
+    node_defs: &[Vec<HtmlNode>],
+    counts: &mut BTreeMap<String, usize>,
+) {
+    visit_resolved_html_nodes(data, node_defs, &mut |node_type, _| {
+        *counts.entry(node_type.to_string()).or_default() += 1;
+    });
+}
+
+/// Calls `visit` with the `type` tag and value of every HtmlNode inside `data`,
+/// whichever component field it sits in.
+pub fn visit_html_nodes(data: &ComponentData, visit: &mut dyn FnMut(&str, HtmlNode)) {
+    if let Ok(value) = serde_json::to_value(data) {
+        visit_html_nodes_in_value(&value, visit);
+    }
+}
+
+/// [`visit_html_nodes`] with each `--intern-nodes` Ref replaced by the nodes of
+/// its `node_defs` entry (the page's `nodeDefs`).
+pub fn visit_resolved_html_nodes(
+    data: &ComponentData,
+    node_defs: &[Vec<HtmlNode>],
+    visit: &mut dyn FnMut(&str, HtmlNode),
+) {
+    visit_html_nodes(data, &mut |node_type, node| match node {
+        HtmlNode::Ref { id } => {
+            if let Some(def) = node_defs
+                .get(id)
+                .and_then(|def| serde_json::to_value(def).ok())
+            {
+                visit_html_nodes_in_value(&def, visit);
+            }
+        }
+        _ => visit(node_type, node),
+    });
+}
+
+fn visit_html_nodes_in_value(value: &Value, visit: &mut dyn FnMut(&str, HtmlNode)) {
+    match value {
+        Value::Object(map) => {
+            if let Some(node_type) = map.get("type").and_then(Value::as_str) {
+                if let Ok(node) = HtmlNode::deserialize(value) {
+                    visit(node_type, node);
+                    return;
+                }
+            }
+            for child in map.values() {
+                visit_html_nodes_in_value(child, visit);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                visit_html_nodes_in_value(item, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn build_statistics(data: &TransformedData) -> StatisticsReport {
+    let mut report = StatisticsReport {
+        generated_at: Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+
+    for (lang, lists) in &data.lists {
+        let menus = report.entries_per_menu.entry(lang.clone()).or_default();
+        for list_file in lists.iter() {
+            *menus.entry(list_file.menu_id).or_default() += list_file.list.len();
+        }
+    }
+
+    let mut largest = TopPages::new(LARGEST_PAGES_KEPT);
+    for (lang, details) in &data.details {
+        for page in details.iter() {
+            report.total_pages += 1;
+            report.total_components += page.components.len();
+            report.degraded_components += page.degraded_components.len();
+            for component in page.components.values() {
+                *report
+                    .component_types
+                    .entry(component.discriminant_str())
+                    .or_default() += 1;
+                count_html_node_types(component, &page.node_defs, &mut report.html_node_types);
+            }
+            largest.push(PageSize {
+                bytes: serialized_size(page),
+                lang: lang.clone(),
+                ep_id: page.id,
+            });
+        }
+    }
+
+    report.avg_components_per_page = if report.total_pages == 0 {
+        0.0
+    } else {
+        report.total_components as f64 / report.total_pages as f64
+    };
+    report.largest_pages = largest.into_sorted_vec();
+    report
+}
+
+pub fn output_dir_bytes(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(ft) if ft.is_dir() => output_dir_bytes(&entry.path()),
+            Ok(ft) if ft.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+pub const COUNTS_FILE: &str = "counts.json";
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LangCounts {
+    pub list_items: BTreeMap<MenuId, usize>,
+    pub total_details: usize,
+}
+
+/// Items per menu list and detail pages per language, for spotting coverage drops
+/// between runs without opening the output files.
+pub fn build_counts(data: &TransformedData) -> BTreeMap<String, LangCounts> {
+    let mut counts: BTreeMap<String, LangCounts> = BTreeMap::new();
+    for (lang, lists) in &data.lists {
+        let lang_counts = counts.entry(lang.clone()).or_default();
+        for list_file in lists.iter() {
+            *lang_counts.list_items.entry(list_file.menu_id).or_default() += list_file.list.len();
+        }
+    }
+    for (lang, details) in &data.details {
+        counts.entry(lang.clone()).or_default().total_details = details.len();
+    }
+    counts
+}
+
+pub const OVERSIZED_COMPONENTS_FILE: &str = "oversized_components.json";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OversizedComponent {
+    pub page_id: EntryId,
+    pub lang: String,
+    pub component: String,
+    pub node_count: usize,
+    pub size_bytes: usize,
+}
+
+/// Components whose serialized size exceeds `threshold_bytes`, largest first.
+pub fn collect_oversized_components(
+    data: &TransformedData,
+    threshold_bytes: usize,
+) -> Vec<OversizedComponent> {
+    let mut oversized = Vec::new();
+    for (lang, details) in &data.details {
+        for page in details.iter() {
+            for (key, component) in &page.components {
+                let size_bytes = component_size(component);
+                if size_bytes > threshold_bytes {
+                    oversized.push(OversizedComponent {
+                        page_id: page.id,
+                        lang: lang.clone(),
+                        component: key.clone(),
+                        node_count: count_html_nodes(component, &page.node_defs),
+                        size_bytes,
+                    });
+                }
+            }
+        }
+    }
+    oversized.sort_unstable_by(|a, b| {
+        b.size_bytes
+            .cmp(&a.size_bytes)
+            .then_with(|| a.lang.cmp(&b.lang))
+            .then_with(|| a.page_id.cmp(&b.page_id))
+    });
+    oversized
+}
+
+pub const UNRESOLVED_REFS_FILE: &str = "unresolved_refs.json";
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedRefs {
+    pub entry_nodes: usize,
+    pub post_nodes: usize,
+    pub entry_ids: BTreeSet<EntryId>,
+    pub post_ids: BTreeSet<EntryId>,
+}
+
+impl UnresolvedRefs {
+    pub fn is_empty(&self) -> bool {
+        self.entry_nodes == 0 && self.post_nodes == 0
+    }
+}
+
+/// CustomEntry/CustomPost nodes still nameless after bulk resolution, per language.
+pub fn collect_unresolved_refs(data: &TransformedData) -> BTreeMap<String, UnresolvedRefs> {
+    let mut report = BTreeMap::new();
+    for (lang, details) in &data.details {
+        let mut refs = UnresolvedRefs::default();
+        for page in details.iter() {
+            for component in page.components.values() {
+                visit_resolved_html_nodes(component, &page.node_defs, &mut |_, node| match node {
+                    HtmlNode::CustomEntry { ep_id, name, .. } if name.trim().is_empty() => {
+                        refs.entry_nodes += 1;
+                        refs.entry_ids.insert(ep_id);
+                    }
+                    HtmlNode::CustomPost { post_id, name, .. } if name.trim().is_empty() => {
+                        refs.post_nodes += 1;
+                        refs.post_ids.insert(post_id);
+                    }
+                    _ => {}
+                });
+            }
+        }
+        report.insert(lang.clone(), refs);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::OutputListFile;
+    use crate::transform::intern;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn text(text: &str) -> HtmlNode {
+        HtmlNode::RichText {
+            text: text.to_string(),
+            alignment: None,
+        }
+    }
+
+    fn unnamed_entry(ep_id: EntryId) -> HtmlNode {
+        HtmlNode::CustomEntry {
+            ep_id,
+            name: String::new(),
+            desc: None,
+            icon_url: String::new(),
+            amount: 0,
+            display_style: "link".to_string(),
+            menu_id: None,
+            link: None,
+        }
+    }
+
+    fn detail_page(id: EntryId, components: &[(&str, ComponentData)]) -> OutputDetailPage {
+        OutputDetailPage {
+            id,
+            menu_id: 2,
+            components: components
+                .iter()
+                .map(|(key, data)| (key.to_string(), data.clone()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn with_details(lang: &str, pages: Vec<OutputDetailPage>) -> TransformedData {
+        TransformedData {
+            details: HashMap::from([(lang.to_string(), Arc::new(pages))]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn interned_nodes_are_counted_through_their_defs() {
+        let repeated = vec![
+            text("Dropped by the Stormterror in his weekly challenge."),
+            unnamed_entry(7),
+        ];
+        let plain = detail_page(
+            1,
+            &[
+                ("customize", ComponentData::Customize(repeated.clone())),
+                ("dropMaterial", ComponentData::DropMaterial(repeated)),
+            ],
+        );
+        let mut interned = plain.clone();
+        interned.node_defs = intern::intern_node_lists(&mut interned.components);
+        assert_eq!(interned.node_defs.len(), 1);
+
+        let plain = with_details("en-us", vec![plain]);
+        let interned = with_details("en-us", vec![interned]);
+
+        let node_types = build_statistics(&interned).html_node_types;
+        assert_eq!(node_types, build_statistics(&plain).html_node_types);
+        assert_eq!(node_types.get("CustomEntry"), Some(&2));
+        assert!(!node_types.contains_key("Ref"));
+
+        let refs = &collect_unresolved_refs(&interned)["en-us"];
+        assert_eq!(refs.entry_nodes, 2);
+        assert_eq!(refs.entry_ids, BTreeSet::from([7]));
+    }
+
+    fn list_file(menu_id: MenuId, ids: &[EntryId]) -> OutputListFile {
+        let list: Vec<Value> = ids.iter().map(|id| json!({ "epId": id })).collect();
+        serde_json::from_value(json!({
+            "version": 0,
+            "language": "en-us",
+            "menuId": menu_id,
+            "totalItems": ids.len(),
+            "list": list
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn totals_menus_and_largest_pages() {
+        // Eleven English pages growing by ten characters each, and one Japanese page
+        // larger than all of them.
+        let en_pages: Vec<OutputDetailPage> = (1..=11)
+            .map(|id| {
+                let body = text(&"x".repeat(id as usize * 10));
+                detail_page(id, &[("customize", ComponentData::Customize(vec![body]))])
+            })
+            .collect();
+        let big = text(&"y".repeat(500));
+        let ja_page = detail_page(
+            1,
+            &[
+                ("customize", ComponentData::Customize(vec![big.clone()])),
+                ("dropMaterial", ComponentData::DropMaterial(vec![big])),
+            ],
+        );
+        let mut data = with_details("en-us", en_pages.clone());
+        data.details
+            .insert("ja-jp".to_string(), Arc::new(vec![ja_page.clone()]));
+        data.lists = HashMap::from([
+            (
+                "en-us".to_string(),
+                Arc::new(vec![
+                    list_file(2, &[1, 2, 3]),
+                    list_file(4, &[4]),
+                    list_file(2, &[5, 6]),
+                ]),
+            ),
+            ("ja-jp".to_string(), Arc::new(vec![list_file(2, &[1])])),
+        ]);
+
+        let report = build_statistics(&data);
+        assert_eq!(report.total_pages, 12);
+        assert_eq!(report.total_components, 13);
+        assert_eq!(report.avg_components_per_page, 13.0 / 12.0);
+        assert_eq!(
+            report.component_types,
+            BTreeMap::from([("Customize", 12), ("DropMaterial", 1)])
+        );
+        assert_eq!(
+            report.html_node_types,
+            BTreeMap::from([("RichText".to_string(), 13)])
+        );
+        assert_eq!(
+            report.entries_per_menu,
+            BTreeMap::from([
+                ("en-us".to_string(), BTreeMap::from([(2, 5), (4, 1)])),
+                ("ja-jp".to_string(), BTreeMap::from([(2, 1)])),
+            ])
+        );
+
+        let largest: Vec<(&str, EntryId)> = report
+            .largest_pages
+            .iter()
+            .map(|page| (page.lang.as_str(), page.ep_id))
+            .collect();
+        let mut expected = vec![("ja-jp", 1)];
+        expected.extend((3..=11).rev().map(|id| ("en-us", id)));
+        assert_eq!(largest, expected);
+        assert_eq!(report.largest_pages[0].bytes, serialized_size(&ja_page));
+        assert_eq!(report.largest_pages[9].bytes, serialized_size(&en_pages[2]));
+    }
+}
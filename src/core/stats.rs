@@ -1,13 +1,189 @@
+use crate::config;
+use crate::core::list_manifest::ListMenuRename;
 use crate::logging::{log, LogLevel};
-use std::collections::BTreeMap;
+use crate::model::common::{EntryId, LangCode};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::time::Duration;
 
+pub static DEPTH_LIMIT_WARNINGS: AtomicU64 = AtomicU64::new(0);
+pub static DEGRADED_COMPONENTS: AtomicU64 = AtomicU64::new(0);
+pub static LOSSY_UTF8_RESPONSES: AtomicU64 = AtomicU64::new(0);
+pub static COLOR_TAG_REPAIRS: AtomicU64 = AtomicU64::new(0);
+pub static LIST_ITEMS_RECOVERED: AtomicU64 = AtomicU64::new(0);
+pub static LIST_ITEMS_SKIPPED: AtomicU64 = AtomicU64::new(0);
+pub static SCHEMA_DRIFT_ENDPOINTS: AtomicU64 = AtomicU64::new(0);
+pub static LANGUAGE_MISMATCHES: AtomicU64 = AtomicU64::new(0);
+
+pub const FAILED_IDS_FILE: &str = "failed_ids.json";
+
+static FAILED_DETAIL_IDS: Lazy<Mutex<BTreeMap<String, BTreeSet<EntryId>>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Detail fetches that errored or returned no page, kept for `--save-failed-ids`.
+pub fn record_failed_detail(lang: &str, entry_id: EntryId) {
+    if let Ok(mut failed) = FAILED_DETAIL_IDS.lock() {
+        failed.entry(lang.to_string()).or_default().insert(entry_id);
+    }
+}
+
+pub fn take_failed_detail_ids() -> BTreeMap<String, BTreeSet<EntryId>> {
+    FAILED_DETAIL_IDS
+        .lock()
+        .map(|mut failed| std::mem::take(&mut *failed))
+        .unwrap_or_default()
+}
+
+static LIST_MENU_RENAMES: Lazy<Mutex<Vec<ListMenuRename>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// List files found holding a differently-named menu than the list manifest recorded.
+pub fn record_list_menu_renames(renames: Vec<ListMenuRename>) {
+    if let Ok(mut recorded) = LIST_MENU_RENAMES.lock() {
+        recorded.extend(renames);
+    }
+}
+
+static LANGUAGE_MISMATCH_SEEN: Lazy<Mutex<BTreeSet<(String, &'static str)>>> =
+    Lazy::new(|| Mutex::new(BTreeSet::new()));
+
+/// Counts a response served in a different language than requested; returns
+/// `true` the first time a language/endpoint pair is seen so it is warned once.
+pub fn record_language_mismatch(lang: &str, endpoint_key: &'static str) -> bool {
+    LANGUAGE_MISMATCHES.fetch_add(1, Ordering::Relaxed);
+    LANGUAGE_MISMATCH_SEEN
+        .lock()
+        .map(|mut seen| seen.insert((lang.to_string(), endpoint_key)))
+        .unwrap_or(false)
+}
+
+/// Labels passed to `utils::acquire_semaphore` whose permit waits are recorded.
+pub const SEMAPHORE_LABELS: [&str; 4] = [
+    "List Fetch Page",
+    "Detail Fetch Item",
+    "Bulk Fetch Batch",
+    "Calendar Fetch",
+];
+/// Upper bounds (ms, inclusive) of the wait histogram buckets; one overflow bucket follows.
+pub const SEMAPHORE_WAIT_BUCKETS_MS: [u64; 5] = [1, 10, 100, 1_000, 10_000];
+
+static SEMAPHORE_WAITS: [WaitHistogram; SEMAPHORE_LABELS.len()] =
+    [const { WaitHistogram::new() }; SEMAPHORE_LABELS.len()];
+
+/// Time spent waiting for permits of one semaphore.
+///
+/// Waits near zero mean the semaphore is rarely full and raising its concurrency will
+/// not help. A high average with most waits in the upper buckets means tasks queue
+/// behind it: it is the bottleneck, so raise its limit if the API tolerates the load.
+/// A low average with a high max usually points at a few long requests holding permits.
+#[derive(Debug)]
+pub struct WaitHistogram {
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+    buckets: [AtomicU64; SEMAPHORE_WAIT_BUCKETS_MS.len() + 1],
+}
+
+impl WaitHistogram {
+    pub const fn new() -> Self {
+        WaitHistogram {
+            total_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+            buckets: [const { AtomicU64::new(0) }; SEMAPHORE_WAIT_BUCKETS_MS.len() + 1],
+        }
+    }
+
+    pub fn record(&self, wait: Duration) {
+        let us = u64::try_from(wait.as_micros()).unwrap_or(u64::MAX);
+        let bucket = SEMAPHORE_WAIT_BUCKETS_MS
+            .iter()
+            .position(|&bound| us <= bound * 1_000)
+            .unwrap_or(SEMAPHORE_WAIT_BUCKETS_MS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    pub fn summary(&self) -> WaitSummary {
+        let buckets: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        let count: u64 = buckets.iter().sum();
+        let total_ms = self.total_us.load(Ordering::Relaxed) as f64 / 1_000.0;
+        let bucket_counts = SEMAPHORE_WAIT_BUCKETS_MS
+            .iter()
+            .map(|&bound| Some(bound))
+            .chain(std::iter::once(None))
+            .zip(buckets)
+            .map(|(upper_ms, count)| WaitBucket { upper_ms, count })
+            .collect();
+        WaitSummary {
+            count,
+            avg_ms: if count > 0 {
+                total_ms / count as f64
+            } else {
+                0.0
+            },
+            max_ms: self.max_us.load(Ordering::Relaxed) as f64 / 1_000.0,
+            buckets: bucket_counts,
+        }
+    }
+}
+
+impl Default for WaitHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitSummary {
+    pub count: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub buckets: Vec<WaitBucket>,
+}
+
+/// Waits of at most `upper_ms`; the last bucket (`upper_ms: null`) holds the rest.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitBucket {
+    pub upper_ms: Option<u64>,
+    pub count: u64,
+}
+
+/// The histogram for a label in `SEMAPHORE_LABELS`; other labels are not recorded.
+pub fn semaphore_wait_histogram(label: &str) -> Option<&'static WaitHistogram> {
+    SEMAPHORE_LABELS
+        .iter()
+        .position(|&known| known == label)
+        .map(|i| &SEMAPHORE_WAITS[i])
+}
+
+/// Wait summaries for every semaphore that handed out at least one permit, in
+/// `SEMAPHORE_LABELS` order.
+pub fn semaphore_wait_summaries() -> Vec<(&'static str, WaitSummary)> {
+    SEMAPHORE_LABELS
+        .iter()
+        .zip(&SEMAPHORE_WAITS)
+        .map(|(&label, histogram)| (label, histogram.summary()))
+        .filter(|(_, summary)| summary.count > 0)
+        .collect()
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct CategoryStats {
     pub ok: usize,
     pub fail: usize,
     pub skip_or_empty: usize,
     pub total_tasks: usize,
+    pub durations_ms: Vec<u32>,
+    duration_stride: usize,
+    durations_seen: usize,
 }
 
 impl CategoryStats {
@@ -26,6 +202,61 @@ impl CategoryStats {
     pub fn get_processed(&self) -> usize {
         self.ok + self.fail + self.skip_or_empty
     }
+    pub fn merge(&mut self, other: &CategoryStats) {
+        self.ok += other.ok;
+        self.fail += other.fail;
+        self.skip_or_empty += other.skip_or_empty;
+        self.total_tasks += other.total_tasks;
+        self.durations_ms.extend_from_slice(&other.durations_ms);
+        self.durations_seen += other.durations_seen;
+        self.duration_stride = self.duration_stride.max(other.duration_stride);
+        self.thin_durations();
+    }
+
+    /// Records one request duration. Past `STATS_MAX_SAMPLES` the samples are
+    /// halved and only every Nth later duration is kept, so memory stays bounded.
+    pub fn add_duration(&mut self, ms: u32) {
+        let stride = self.duration_stride.max(1);
+        if self.durations_seen.is_multiple_of(stride) {
+            self.durations_ms.push(ms);
+        }
+        self.durations_seen += 1;
+        self.thin_durations();
+    }
+
+    pub fn add_elapsed(&mut self, elapsed: Duration) {
+        self.add_duration(u32::try_from(elapsed.as_millis()).unwrap_or(u32::MAX));
+    }
+
+    fn thin_durations(&mut self) {
+        while self.durations_ms.len() > config::STATS_MAX_SAMPLES {
+            self.durations_ms = self.durations_ms.iter().step_by(2).copied().collect();
+            self.duration_stride = self.duration_stride.max(1) * 2;
+        }
+    }
+
+    pub fn p50_ms(&self) -> u32 {
+        self.percentile_ms(50.0)
+    }
+
+    pub fn p95_ms(&self) -> u32 {
+        self.percentile_ms(95.0)
+    }
+
+    pub fn p99_ms(&self) -> u32 {
+        self.percentile_ms(99.0)
+    }
+
+    // Nearest-rank percentile; 0 when no durations were recorded.
+    fn percentile_ms(&self, pct: f64) -> u32 {
+        if self.durations_ms.is_empty() {
+            return 0;
+        }
+        let mut sorted = self.durations_ms.clone();
+        sorted.sort_unstable();
+        let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
 }
 
 pub type RunStats = BTreeMap<String, CategoryStats>;
@@ -40,16 +271,18 @@ pub fn initialize_stats() -> RunStats {
     stats.insert("Bulk Primary".to_string(), Default::default());
     stats.insert("Bulk Fallback".to_string(), Default::default());
 
+    stats.insert("Transform".to_string(), Default::default());
     stats.insert("Save Files".to_string(), Default::default());
     stats
 }
 
-pub fn print_summary(stats: &RunStats, languages: &[String], duration: Duration) {
+pub fn print_summary(stats: &RunStats, languages: &[LangCode], duration: Duration) {
     let sep = "=".repeat(60);
     let title = format!("Run Summary ({} Languages)", languages.len());
     println!("\n{}\n{:^60}\n{}", sep, title, sep);
     if !languages.is_empty() {
-        println!("Languages:         {}", languages.join(", "));
+        let lang_names: Vec<&str> = languages.iter().map(LangCode::as_str).collect();
+        println!("Languages:         {}", lang_names.join(", "));
     }
     println!("Total Run Time:    {:.3?}", duration);
     println!("{}", "-".repeat(60));
@@ -72,6 +305,7 @@ pub fn print_summary(stats: &RunStats, languages: &[String], duration: Duration)
         "Calendar Fetch",
         "Bulk Primary",
         "Bulk Fallback",
+        "Transform",
         "Save Files",
     ];
 
@@ -112,8 +346,120 @@ pub fn print_summary(stats: &RunStats, languages: &[String], duration: Duration)
         );
     }
 
+    let timed: Vec<(&str, &CategoryStats)> = categories_order
+        .iter()
+        .filter_map(|&cat_name| stats.get(cat_name).map(|s| (cat_name, s)))
+        .filter(|(_, s)| !s.durations_ms.is_empty())
+        .collect();
+    if !timed.is_empty() {
+        println!("{}", "-".repeat(60));
+        println!(
+            "{:<17} {:<12} {:<12} {:<12}",
+            "Latency", "p50 (ms)", "p95 (ms)", "p99 (ms)"
+        );
+        for (cat_name, s) in timed {
+            println!(
+                "{:<17} {:<12} {:<12} {:<12}",
+                cat_name,
+                s.p50_ms(),
+                s.p95_ms(),
+                s.p99_ms()
+            );
+        }
+    }
+
+    let semaphore_waits = semaphore_wait_summaries();
+    if !semaphore_waits.is_empty() {
+        println!("{}", "-".repeat(60));
+        println!(
+            "{:<17} {:<12} {:<12} {:<12}",
+            "Permit Wait", "avg (ms)", "max (ms)", "Acquired"
+        );
+        for (label, summary) in semaphore_waits {
+            println!(
+                "{:<17} {:<12.1} {:<12.1} {:<12}",
+                label, summary.avg_ms, summary.max_ms, summary.count
+            );
+        }
+    }
+
+    let depth_limit_warnings = DEPTH_LIMIT_WARNINGS.load(Ordering::Relaxed);
+    if depth_limit_warnings > 0 {
+        println!("{}", "-".repeat(60));
+        println!("{:<17} {:<8}", "HTML Depth Limit", depth_limit_warnings);
+    }
+
+    let degraded_components = DEGRADED_COMPONENTS.load(Ordering::Relaxed);
+    if degraded_components > 0 {
+        println!("{}", "-".repeat(60));
+        println!("{:<17} {:<8}", "Degraded Comps", degraded_components);
+    }
+
+    let lossy_utf8_responses = LOSSY_UTF8_RESPONSES.load(Ordering::Relaxed);
+    if lossy_utf8_responses > 0 {
+        println!("{}", "-".repeat(60));
+        println!("{:<17} {:<8}", "Lossy UTF-8", lossy_utf8_responses);
+    }
+
+    let language_mismatches = LANGUAGE_MISMATCHES.load(Ordering::Relaxed);
+    if language_mismatches > 0 {
+        println!("{}", "-".repeat(60));
+        println!("{:<17} {:<8}", "Lang Mismatch", language_mismatches);
+    }
+
+    let color_tag_repairs = COLOR_TAG_REPAIRS.load(Ordering::Relaxed);
+    if color_tag_repairs > 0 {
+        println!("{}", "-".repeat(60));
+        println!("{:<17} {:<8}", "Color Tag Repairs", color_tag_repairs);
+    }
+
+    let list_items_recovered = LIST_ITEMS_RECOVERED.load(Ordering::Relaxed);
+    let list_items_skipped = LIST_ITEMS_SKIPPED.load(Ordering::Relaxed);
+    if list_items_recovered > 0 || list_items_skipped > 0 {
+        println!("{}", "-".repeat(60));
+        println!("{:<17} {:<8} {:<8}", "List Recovery", "Kept", "Skipped");
+        println!(
+            "{:<17} {:<8} {:<8}",
+            "", list_items_recovered, list_items_skipped
+        );
+    }
+
     println!("{}", sep);
 
+    let schema_drift_endpoints = SCHEMA_DRIFT_ENDPOINTS.load(Ordering::Relaxed);
+    if schema_drift_endpoints > 0 {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "API schema drift detected in {} endpoint(s); see reports/schema_drift.json",
+                schema_drift_endpoints
+            ),
+        );
+    }
+
+    let list_menu_renames = LIST_MENU_RENAMES
+        .lock()
+        .map(|renames| renames.clone())
+        .unwrap_or_default();
+    if !list_menu_renames.is_empty() {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "{} list file(s) now hold a differently-named menu than in an earlier run; the old category was overwritten:",
+                list_menu_renames.len()
+            ),
+        );
+        for rename in &list_menu_renames {
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "  list/{}/{}.json: '{}' -> '{}'",
+                    rename.lang, rename.menu_id, rename.previous_name, rename.current_name
+                ),
+            );
+        }
+    }
+
     log_overall_status(stats, grand_total_fetch_fail, languages.is_empty());
 
     let end_ts_str = chrono::Utc::now()
@@ -152,11 +498,180 @@ pub fn determine_exit_code(stats: &RunStats) -> i32 {
     .iter()
     .any(|&cat| stats.get(cat).map_or(false, |s| s.fail > 0));
 
+    let transform_failures = stats.get("Transform").map_or(0, |s| s.fail) > 0;
     let save_failures = stats.get("Save Files").map_or(0, |s| s.fail) > 0;
 
-    if fetch_failures || save_failures {
+    if fetch_failures || transform_failures || save_failures {
         1
     } else {
         0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_with(durations: &[u32]) -> CategoryStats {
+        let mut stats = CategoryStats::default();
+        for &ms in durations {
+            stats.add_duration(ms);
+        }
+        stats
+    }
+
+    #[test]
+    fn percentiles_of_no_samples_are_zero() {
+        let stats = CategoryStats::default();
+        assert_eq!((stats.p50_ms(), stats.p95_ms(), stats.p99_ms()), (0, 0, 0));
+    }
+
+    #[test]
+    fn percentiles_of_a_single_sample() {
+        let stats = stats_with(&[42]);
+        assert_eq!(
+            (stats.p50_ms(), stats.p95_ms(), stats.p99_ms()),
+            (42, 42, 42)
+        );
+    }
+
+    #[test]
+    fn percentiles_of_identical_samples() {
+        let stats = stats_with(&[7; 25]);
+        assert_eq!((stats.p50_ms(), stats.p95_ms(), stats.p99_ms()), (7, 7, 7));
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let durations: Vec<u32> = (1..=100).collect();
+        let stats = stats_with(&durations);
+        assert_eq!(
+            (stats.p50_ms(), stats.p95_ms(), stats.p99_ms()),
+            (50, 95, 99)
+        );
+
+        let stats = stats_with(&[10, 20, 30, 40]);
+        assert_eq!(
+            (stats.p50_ms(), stats.p95_ms(), stats.p99_ms()),
+            (20, 40, 40)
+        );
+    }
+
+    #[test]
+    fn percentiles_ignore_insertion_order() {
+        let sorted: Vec<u32> = (1..=200).collect();
+        let mut unsorted = sorted.clone();
+        unsorted.reverse();
+        unsorted.swap(3, 150);
+        unsorted.swap(17, 90);
+
+        let a = stats_with(&sorted);
+        let b = stats_with(&unsorted);
+        assert_eq!(
+            (a.p50_ms(), a.p95_ms(), a.p99_ms()),
+            (b.p50_ms(), b.p95_ms(), b.p99_ms())
+        );
+        assert_eq!(a.durations_ms, sorted);
+    }
+
+    #[test]
+    fn samples_are_capped_and_stay_representative() {
+        let total = config::STATS_MAX_SAMPLES * 5 + 3;
+        let durations: Vec<u32> = (0..total as u32).collect();
+        let stats = stats_with(&durations);
+
+        assert!(stats.durations_ms.len() <= config::STATS_MAX_SAMPLES);
+        assert!(stats.durations_ms.len() > config::STATS_MAX_SAMPLES / 2);
+        assert_eq!(stats.durations_seen, total);
+        // Every kept sample is on the current stride, so the spread is even.
+        assert!(stats
+            .durations_ms
+            .iter()
+            .all(|ms| (*ms as usize).is_multiple_of(stats.duration_stride)));
+        let p50 = stats.p50_ms() as f64;
+        assert!((p50 - total as f64 / 2.0).abs() < total as f64 * 0.01);
+    }
+
+    #[test]
+    fn merge_keeps_the_cap() {
+        let half = config::STATS_MAX_SAMPLES / 2 + 1;
+        let durations: Vec<u32> = (0..half as u32).collect();
+        let mut merged = stats_with(&durations);
+        let other = stats_with(&durations);
+        assert_eq!(merged.durations_ms.len(), half);
+
+        merged.merge(&other);
+        assert!(merged.durations_ms.len() <= config::STATS_MAX_SAMPLES);
+        assert_eq!(merged.durations_ms.len(), half);
+        assert_eq!(merged.durations_seen, half * 2);
+        assert_eq!(merged.duration_stride, 2);
+
+        // Later durations follow the doubled stride.
+        merged.add_duration(1);
+        merged.add_duration(2);
+        assert_eq!(merged.durations_ms.len(), half + 1);
+    }
+
+    fn bucket_counts(histogram: &WaitHistogram) -> Vec<u64> {
+        histogram
+            .summary()
+            .buckets
+            .iter()
+            .map(|b| b.count)
+            .collect()
+    }
+
+    #[test]
+    fn wait_histogram_bounds_are_inclusive() {
+        let histogram = WaitHistogram::new();
+        for ms in SEMAPHORE_WAIT_BUCKETS_MS {
+            histogram.record(Duration::from_millis(ms));
+        }
+        assert_eq!(bucket_counts(&histogram), [1, 1, 1, 1, 1, 0]);
+
+        let upper: Vec<Option<u64>> = histogram
+            .summary()
+            .buckets
+            .iter()
+            .map(|b| b.upper_ms)
+            .collect();
+        assert_eq!(
+            upper,
+            [
+                Some(1),
+                Some(10),
+                Some(100),
+                Some(1_000),
+                Some(10_000),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn wait_histogram_just_past_a_bound_moves_up() {
+        let histogram = WaitHistogram::new();
+        for ms in SEMAPHORE_WAIT_BUCKETS_MS {
+            histogram.record(Duration::from_millis(ms) + Duration::from_micros(1));
+        }
+        assert_eq!(bucket_counts(&histogram), [0, 1, 1, 1, 1, 1]);
+
+        histogram.record(Duration::ZERO);
+        histogram.record(Duration::from_secs(3_600));
+        assert_eq!(bucket_counts(&histogram), [1, 1, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn wait_histogram_summary() {
+        let histogram = WaitHistogram::new();
+        assert_eq!(histogram.summary().count, 0);
+        assert_eq!(histogram.summary().avg_ms, 0.0);
+
+        histogram.record(Duration::from_millis(10));
+        histogram.record(Duration::from_millis(30));
+        let summary = histogram.summary();
+        assert_eq!(summary.count, 2);
+        assert_eq!(summary.avg_ms, 20.0);
+        assert_eq!(summary.max_ms, 30.0);
+    }
+}
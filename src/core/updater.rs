@@ -0,0 +1,92 @@
+use crate::config::{ConcurrencyLimits, RuntimeSettings};
+use crate::core::processor;
+use crate::core::stats::RunStats;
+use crate::error::AppResult;
+use crate::model::common::LangCode;
+use std::path::PathBuf;
+
+/// Library entry point for a full update run: fetch, transform and write every
+/// output file for the configured languages.
+///
+/// The runtime settings and base URL belong to the updater: [`run`] hands them
+/// to the run instead of the process-wide `config` accessors, so updaters with
+/// different settings can run one after another in the same process.
+///
+/// [`run`]: WikiUpdater::run
+///
+/// # Examples
+///
+/// ```no_run
+/// use wiki_update::config::ConcurrencyLimits;
+/// use wiki_update::model::common::LangCode;
+/// use wiki_update::WikiUpdater;
+///
+/// # async fn example() -> wiki_update::error::AppResult<()> {
+/// let stats = WikiUpdater::new("./out")
+///     .languages(vec![LangCode::parse("en-us")?])
+///     .concurrency(ConcurrencyLimits::LOW)
+///     .run()
+///     .await?;
+/// println!("{} detail fetches failed", stats["Detail Fetch"].fail);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WikiUpdater {
+    languages: Vec<LangCode>,
+    out_dir: PathBuf,
+    settings: RuntimeSettings,
+    base_url: Option<String>,
+}
+
+impl WikiUpdater {
+    /// An updater writing to `out_dir` for every supported language with the
+    /// default runtime settings.
+    pub fn new(out_dir: impl Into<PathBuf>) -> Self {
+        WikiUpdater {
+            languages: LangCode::all_supported(),
+            out_dir: out_dir.into(),
+            settings: RuntimeSettings::default(),
+            base_url: None,
+        }
+    }
+
+    pub fn languages(mut self, languages: Vec<LangCode>) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = out_dir.into();
+        self
+    }
+
+    pub fn concurrency(mut self, limits: ConcurrencyLimits) -> Self {
+        self.settings.concurrency = limits;
+        self
+    }
+
+    /// Replaces all runtime settings, e.g. with [`RuntimeSettings::quick`].
+    /// Call before [`concurrency`](Self::concurrency) to keep custom limits.
+    pub fn settings(mut self, settings: RuntimeSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    /// Runs the staged update and returns the per-category stats. A consistency
+    /// or language gate abort surfaces as [`AppError::RunAborted`](crate::error::AppError::RunAborted).
+    pub async fn run(self) -> AppResult<RunStats> {
+        processor::run_to_stats(
+            &self.languages,
+            self.out_dir,
+            &self.settings,
+            self.base_url.as_deref(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,736 @@
+//! `wiki_update verify <dir>`: re-reads a finished output directory, parses every
+//! file back into its output model and checks the files agree with each other and
+//! with the manifests written alongside them. Nothing is fetched or rewritten.
+
+use crate::config;
+use crate::core::dir_index::DIR_INDEX_FILE;
+use crate::core::list_manifest::{ListManifest, LIST_MANIFEST_FILE};
+use crate::error::{AppError, AppResult};
+use crate::io::pack::{PackReader, PACK_DIR};
+use crate::logging::{log, LogLevel};
+use crate::model::common::{EntryId, MenuId};
+use crate::model::output::{
+    OutputBulkEntry, OutputCalendarFile, OutputDetailIndex, OutputDetailPage, OutputListFile,
+    OutputListIndex, OutputNavMenuItem,
+};
+use crate::utils;
+use futures::{stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// What a file under the output directory is, going by its path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VerifiedKind {
+    Navigation,
+    List,
+    ListIndex,
+    Detail,
+    DetailIndex,
+    Calendar,
+    Bulk,
+    ListManifest,
+    Pack,
+    /// Any other JSON file; only checked to be well-formed.
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyProblem {
+    /// Relative to the verified directory.
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyReport {
+    pub dir: PathBuf,
+    pub files_checked: usize,
+    pub files_by_kind: BTreeMap<VerifiedKind, usize>,
+    pub problems: Vec<VerifyProblem>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct VerifyTarget {
+    rel: PathBuf,
+    kind: VerifiedKind,
+    lang: Option<String>,
+}
+
+/// What the cross-file checks need from one parsed file.
+#[derive(Debug)]
+enum FileFacts {
+    None,
+    List(ListFacts),
+    Detail(EntryId),
+    ListIndex(OutputListIndex),
+    DetailIndex(OutputDetailIndex),
+    ListManifest(ListManifest),
+}
+
+#[derive(Debug)]
+struct ListFacts {
+    menu_id: MenuId,
+    menu_name: String,
+    total_items: usize,
+}
+
+#[derive(Debug)]
+struct FileCheck {
+    target: VerifyTarget,
+    facts: FileFacts,
+    problems: Vec<String>,
+}
+
+/// Checks every file under `dir`, reading at most `VERIFY_READ_CONCURRENCY` at once.
+///
+/// # Examples
+///
+/// ```
+/// use wiki_update::core::verify::verify_output_dir;
+///
+/// let dir = std::env::temp_dir().join(format!("wiki_update_verify_{}", std::process::id()));
+/// let write = |rel: &str, body: &str| {
+///     let path = dir.join(rel);
+///     std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+///     std::fs::write(path, body).unwrap();
+/// };
+/// write(
+///     "list/en-us/2.json",
+///     r#"{"version":1700000000,"language":"en-us","menuId":2,"totalItems":1,"list":[{"epId":5}]}"#,
+/// );
+/// write("detail/en-us/5.json", r#"{"epId":5,"menuId":2,"version":1700000000}"#);
+///
+/// let runtime = tokio::runtime::Runtime::new().unwrap();
+/// let report = runtime.block_on(verify_output_dir(&dir)).unwrap();
+/// assert!(report.is_ok());
+/// assert_eq!(report.files_checked, 2);
+///
+/// // A list whose count is off, a detail saved under the wrong id and a truncated file.
+/// write(
+///     "list/en-us/3.json",
+///     r#"{"version":1700000000,"language":"en-us","menuId":3,"totalItems":4,"list":[{"epId":5}]}"#,
+/// );
+/// write("detail/en-us/6.json", r#"{"epId":5,"menuId":2,"version":1700000000}"#);
+/// write("detail/en-us/7.json", r#"{"epId":7,"menuId""#);
+///
+/// let report = runtime.block_on(verify_output_dir(&dir)).unwrap();
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// let bad: Vec<_> = report.problems.iter().map(|p| p.path.as_str()).collect();
+/// assert_eq!(bad, ["detail/en-us/6.json", "detail/en-us/7.json", "list/en-us/3.json"]);
+/// ```
+pub async fn verify_output_dir(dir: &Path) -> AppResult<VerifyReport> {
+    if !dir.is_dir() {
+        return Err(AppError::Argument(format!(
+            "'{}' is not a directory",
+            dir.display()
+        )));
+    }
+
+    let targets = collect_targets(dir).await?;
+    let mut checks: Vec<FileCheck> = stream::iter(targets)
+        .map(|target| check_file(dir, target))
+        .buffer_unordered(config::VERIFY_READ_CONCURRENCY)
+        .collect()
+        .await;
+    checks.sort_by(|a, b| a.target.rel.cmp(&b.target.rel));
+
+    let mut files_by_kind = BTreeMap::new();
+    let mut problems = Vec::new();
+    for check in &checks {
+        *files_by_kind.entry(check.target.kind).or_insert(0) += 1;
+        problems.extend(check.problems.iter().map(|message| VerifyProblem {
+            path: display_rel(&check.target.rel),
+            message: message.clone(),
+        }));
+    }
+    problems.extend(cross_check(&checks));
+    problems.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(VerifyReport {
+        dir: dir.to_path_buf(),
+        files_checked: checks.len(),
+        files_by_kind,
+        problems,
+    })
+}
+
+/// Logs the per-kind counts and every problem in `report`.
+pub fn log_verify_report(report: &VerifyReport) {
+    for (kind, count) in &report.files_by_kind {
+        log(
+            LogLevel::Info,
+            &format!("  {:<12} {:>6} file(s)", format!("{:?}", kind), count),
+        );
+    }
+    for problem in &report.problems {
+        log(
+            LogLevel::Error,
+            &format!("{}: {}", problem.path, problem.message),
+        );
+    }
+    if report.is_ok() {
+        log(
+            LogLevel::Success,
+            &format!(
+                "Verified {} file(s) under {}: no problems found.",
+                report.files_checked,
+                report.dir.display()
+            ),
+        );
+    } else {
+        log(
+            LogLevel::Error,
+            &format!(
+                "Verified {} file(s) under {}: {} problem(s) found.",
+                report.files_checked,
+                report.dir.display(),
+                report.problems.len()
+            ),
+        );
+    }
+}
+
+async fn collect_targets(dir: &Path) -> AppResult<Vec<VerifyTarget>> {
+    let mut targets = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let mut read_dir = tokio::fs::read_dir(&current).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Ok(rel) = path.strip_prefix(dir) else {
+                continue;
+            };
+            if let Some(target) = classify(rel) {
+                targets.push(target);
+            }
+        }
+    }
+    Ok(targets)
+}
+
+/// Infers a file's kind from where the save phase puts it; `None` for files
+/// that are not JSON or packs (logs, temp files).
+fn classify(rel: &Path) -> Option<VerifyTarget> {
+    let parts: Vec<&str> = rel.iter().filter_map(|part| part.to_str()).collect();
+    let file_name = *parts.last()?;
+    let (stem, ext) = file_name.rsplit_once('.')?;
+    let target = |kind, lang: Option<&str>| VerifyTarget {
+        rel: rel.to_path_buf(),
+        kind,
+        lang: lang.map(str::to_string),
+    };
+
+    if ext == "pack" {
+        let in_pack_dir = matches!(parts.as_slice(), [dir, _] if *dir == PACK_DIR);
+        return in_pack_dir.then(|| target(VerifiedKind::Pack, Some(stem)));
+    }
+    if ext != "json" {
+        return None;
+    }
+    Some(match parts.as_slice() {
+        ["navigation", _] => target(VerifiedKind::Navigation, Some(stem)),
+        ["calendar", _] => target(VerifiedKind::Calendar, Some(stem)),
+        ["bulk", _] => target(VerifiedKind::Bulk, Some(stem)),
+        ["reports", name] if *name == LIST_MANIFEST_FILE => {
+            target(VerifiedKind::ListManifest, None)
+        }
+        ["list", lang, name] if *name == DIR_INDEX_FILE => {
+            target(VerifiedKind::ListIndex, Some(lang))
+        }
+        ["list", lang, _] => target(VerifiedKind::List, Some(lang)),
+        ["detail", lang, name] if *name == DIR_INDEX_FILE => {
+            target(VerifiedKind::DetailIndex, Some(lang))
+        }
+        ["detail", lang, _] => target(VerifiedKind::Detail, Some(lang)),
+        _ => target(VerifiedKind::Json, None),
+    })
+}
+
+async fn check_file(dir: &Path, target: VerifyTarget) -> FileCheck {
+    let mut problems = Vec::new();
+    let facts = match tokio::fs::read(dir.join(&target.rel)).await {
+        Ok(bytes) => check_contents(&target, bytes, &mut problems).await,
+        Err(e) => {
+            problems.push(format!("unreadable: {}", e));
+            FileFacts::None
+        }
+    };
+    FileCheck {
+        target,
+        facts,
+        problems,
+    }
+}
+
+async fn check_contents(
+    target: &VerifyTarget,
+    bytes: Vec<u8>,
+    problems: &mut Vec<String>,
+) -> FileFacts {
+    let lang = target.lang.as_deref().unwrap_or_default();
+    let stem = target
+        .rel
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+
+    match target.kind {
+        VerifiedKind::Navigation => {
+            parse::<Vec<OutputNavMenuItem>>(&bytes, problems);
+            FileFacts::None
+        }
+        VerifiedKind::Bulk => {
+            parse::<Vec<OutputBulkEntry>>(&bytes, problems);
+            FileFacts::None
+        }
+        VerifiedKind::Calendar => {
+            if let Some(calendar) = parse::<OutputCalendarFile>(&bytes, problems) {
+                check_language(&calendar.language, lang, problems);
+            }
+            FileFacts::None
+        }
+        VerifiedKind::Json => {
+            parse::<Value>(&bytes, problems);
+            FileFacts::None
+        }
+        VerifiedKind::ListManifest => {
+            parse::<ListManifest>(&bytes, problems).map_or(FileFacts::None, FileFacts::ListManifest)
+        }
+        VerifiedKind::List => {
+            let Some(list_file) = parse::<OutputListFile>(&bytes, problems) else {
+                return FileFacts::None;
+            };
+            check_language(&list_file.language, lang, problems);
+            check_file_id("menuId", list_file.menu_id, stem, problems);
+            if list_file.total_items != list_file.list.len() {
+                problems.push(format!(
+                    "totalItems is {} but the list has {} item(s)",
+                    list_file.total_items,
+                    list_file.list.len()
+                ));
+            }
+            FileFacts::List(ListFacts {
+                menu_id: list_file.menu_id,
+                menu_name: list_file.menu_name,
+                total_items: list_file.total_items,
+            })
+        }
+        VerifiedKind::Detail => {
+            let Some(page) = parse_detail_page(&bytes, problems) else {
+                return FileFacts::None;
+            };
+            check_file_id("epId", page.id, stem, problems);
+            FileFacts::Detail(page.id)
+        }
+        VerifiedKind::ListIndex => {
+            let Some(index) = parse::<OutputListIndex>(&bytes, problems) else {
+                return FileFacts::None;
+            };
+            check_language(&index.language, lang, problems);
+            check_total("totalMenus", index.total_menus, index.menus.len(), problems);
+            FileFacts::ListIndex(index)
+        }
+        VerifiedKind::DetailIndex => {
+            let Some(index) = parse::<OutputDetailIndex>(&bytes, problems) else {
+                return FileFacts::None;
+            };
+            check_language(&index.language, lang, problems);
+            check_total(
+                "totalEntries",
+                index.total_entries,
+                index.entries.len(),
+                problems,
+            );
+            FileFacts::DetailIndex(index)
+        }
+        VerifiedKind::Pack => {
+            match utils::run_blocking(move || verify_pack(&bytes)).await {
+                Ok(pack_problems) => problems.extend(pack_problems),
+                Err(e) => problems.push(e.to_string()),
+            }
+            FileFacts::None
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(bytes: &[u8], problems: &mut Vec<String>) -> Option<T> {
+    serde_json::from_slice(bytes)
+        .map_err(|e| problems.push(format!("does not parse: {}", e)))
+        .ok()
+}
+
+/// Detail pages saved with `--components-as-array` hold a list where the model
+/// expects a map; those are checked without their components.
+fn parse_detail_page(bytes: &[u8], problems: &mut Vec<String>) -> Option<OutputDetailPage> {
+    let mut value: Value = parse(bytes, problems)?;
+    if let Some(object) = value.as_object_mut() {
+        if object.get("components").is_some_and(Value::is_array) {
+            object.remove("components");
+        }
+    }
+    serde_json::from_value(value)
+        .map_err(|e| problems.push(format!("does not parse: {}", e)))
+        .ok()
+}
+
+fn check_language(found: &str, expected: &str, problems: &mut Vec<String>) {
+    if found != expected {
+        problems.push(format!(
+            "language is '{}' but the path says '{}'",
+            found, expected
+        ));
+    }
+}
+
+fn check_file_id(field: &str, id: i64, stem: &str, problems: &mut Vec<String>) {
+    if stem.parse::<i64>().ok() != Some(id) {
+        problems.push(format!(
+            "{} is {} but the file is named {}.json",
+            field, id, stem
+        ));
+    }
+}
+
+fn check_total(field: &str, total: usize, len: usize, problems: &mut Vec<String>) {
+    if total != len {
+        problems.push(format!(
+            "{} is {} but there are {} entries",
+            field, total, len
+        ));
+    }
+}
+
+/// Reads back every entry, which checks its length and crc32 against the index.
+fn verify_pack(bytes: &[u8]) -> AppResult<Vec<String>> {
+    let reader = PackReader::open(bytes)?;
+    let mut ids: Vec<EntryId> = reader.ids().collect();
+    ids.sort_unstable();
+    Ok(ids
+        .into_iter()
+        .filter_map(|id| match reader.read(id) {
+            Ok(_) => None,
+            Err(e) => Some(e.to_string()),
+        })
+        .collect())
+}
+
+/// Problems only visible across files: index entries without files (and the
+/// reverse) and list files that disagree with their index or the list manifest.
+fn cross_check(checks: &[FileCheck]) -> Vec<VerifyProblem> {
+    let mut lists: BTreeMap<&str, BTreeMap<MenuId, &ListFacts>> = BTreeMap::new();
+    let mut details: BTreeMap<&str, BTreeSet<EntryId>> = BTreeMap::new();
+    for check in checks {
+        let lang = check.target.lang.as_deref().unwrap_or_default();
+        match &check.facts {
+            FileFacts::List(facts) => {
+                lists.entry(lang).or_default().insert(facts.menu_id, facts);
+            }
+            FileFacts::Detail(id) => {
+                details.entry(lang).or_default().insert(*id);
+            }
+            _ => {}
+        }
+    }
+
+    let mut problems = Vec::new();
+    for check in checks {
+        let path = display_rel(&check.target.rel);
+        let lang = check.target.lang.as_deref().unwrap_or_default();
+        let mut problem = |message: String| {
+            problems.push(VerifyProblem {
+                path: path.clone(),
+                message,
+            })
+        };
+        match &check.facts {
+            FileFacts::ListIndex(index) => {
+                let files = lists.get(lang);
+                let indexed: BTreeSet<MenuId> = index.menus.iter().map(|m| m.menu_id).collect();
+                for entry in &index.menus {
+                    match files.and_then(|files| files.get(&entry.menu_id)) {
+                        None => problem(format!(
+                            "lists menu {} but list/{}/{}.json is missing or unreadable",
+                            entry.menu_id, lang, entry.menu_id
+                        )),
+                        Some(facts) => {
+                            check_list_record(
+                                entry.menu_id,
+                                (&entry.menu_name, entry.total_items),
+                                facts,
+                                &mut problem,
+                            );
+                        }
+                    }
+                }
+                for menu_id in files.into_iter().flat_map(|files| files.keys()) {
+                    if !indexed.contains(menu_id) {
+                        problem(format!("does not list menu {}", menu_id));
+                    }
+                }
+            }
+            FileFacts::DetailIndex(index) => {
+                let files = details.get(lang);
+                let indexed: BTreeSet<EntryId> = index.entries.iter().map(|e| e.id).collect();
+                for id in &indexed {
+                    if !files.is_some_and(|files| files.contains(id)) {
+                        problem(format!(
+                            "lists entry {} but detail/{}/{}.json is missing or unreadable",
+                            id, lang, id
+                        ));
+                    }
+                }
+                for id in files.into_iter().flatten() {
+                    if !indexed.contains(id) {
+                        problem(format!("does not list entry {}", id));
+                    }
+                }
+            }
+            FileFacts::ListManifest(manifest) => {
+                for (manifest_lang, records) in &manifest.langs {
+                    let Some(files) = lists.get(manifest_lang.as_str()) else {
+                        continue;
+                    };
+                    for (menu_id, record) in records {
+                        if let Some(facts) = files.get(menu_id) {
+                            check_list_record(
+                                *menu_id,
+                                (&record.menu_name, record.total_items),
+                                facts,
+                                &mut problem,
+                            );
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    problems
+}
+
+fn check_list_record(
+    menu_id: MenuId,
+    (menu_name, total_items): (&str, usize),
+    facts: &ListFacts,
+    problem: &mut impl FnMut(String),
+) {
+    if menu_name != facts.menu_name {
+        problem(format!(
+            "menu {} is named '{}' but its list file says '{}'",
+            menu_id, menu_name, facts.menu_name
+        ));
+    }
+    if total_items != facts.total_items {
+        problem(format!(
+            "menu {} has {} item(s) but its list file has {}",
+            menu_id, total_items, facts.total_items
+        ));
+    }
+}
+
+fn display_rel(rel: &Path) -> String {
+    rel.iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::pack::encode_pack;
+    use serde_json::json;
+
+    const VERSION: i64 = 1_700_000_000;
+
+    fn write(dir: &Path, rel: &str, body: impl AsRef<[u8]>) {
+        let path = dir.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, body).unwrap();
+    }
+
+    fn write_json(dir: &Path, rel: &str, value: Value) {
+        write(dir, rel, value.to_string());
+    }
+
+    fn list_json(lang: &str, menu_id: MenuId, name: &str, ids: &[EntryId]) -> Value {
+        let list: Vec<Value> = ids.iter().map(|id| json!({ "epId": id })).collect();
+        json!({
+            "version": VERSION,
+            "language": lang,
+            "menuId": menu_id,
+            "menuName": name,
+            "totalItems": ids.len(),
+            "list": list
+        })
+    }
+
+    fn detail_json(id: EntryId) -> Value {
+        json!({ "epId": id, "menuId": 2, "version": VERSION })
+    }
+
+    /// One language of a consistent output tree.
+    fn write_valid_tree(dir: &Path) {
+        write_json(
+            dir,
+            "navigation/en-us.json",
+            json!([{ "menuId": 2, "name": "Characters" }]),
+        );
+        write_json(
+            dir,
+            "list/en-us/2.json",
+            list_json("en-us", 2, "Characters", &[1001, 1002]),
+        );
+        write_json(
+            dir,
+            "list/en-us/index.json",
+            json!({
+                "language": "en-us",
+                "totalMenus": 1,
+                "menus": [{ "menuId": 2, "menuName": "Characters", "totalItems": 2 }]
+            }),
+        );
+        write_json(dir, "detail/en-us/1001.json", detail_json(1001));
+        write_json(dir, "detail/en-us/1002.json", detail_json(1002));
+        write_json(
+            dir,
+            "detail/en-us/index.json",
+            json!({
+                "language": "en-us",
+                "totalEntries": 2,
+                "entries": [
+                    { "epId": 1001, "menuId": 2, "version": VERSION },
+                    { "epId": 1002, "menuId": 2, "version": VERSION }
+                ]
+            }),
+        );
+        write_json(
+            dir,
+            "calendar/en-us.json",
+            json!({ "version": VERSION, "language": "en-us" }),
+        );
+        write_json(dir, "bulk/en-us.json", json!([]));
+        write_json(
+            dir,
+            "reports/list_manifest.json",
+            json!({ "en-us": { "2": { "menuName": "Characters", "totalItems": 2 } } }),
+        );
+        let entries = vec![
+            (1001, serde_json::to_vec(&detail_json(1001)).unwrap()),
+            (1002, serde_json::to_vec(&detail_json(1002)).unwrap()),
+        ];
+        write(dir, "packs/en-us.pack", encode_pack(&entries).unwrap().0);
+        write(dir, "logs/run.log", "not json");
+    }
+
+    fn problem_paths(report: &VerifyReport) -> Vec<&str> {
+        report.problems.iter().map(|p| p.path.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn valid_tree_has_no_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        write_valid_tree(dir.path());
+
+        let report = verify_output_dir(dir.path()).await.unwrap();
+        assert!(report.is_ok(), "{:?}", report.problems);
+        assert_eq!(report.files_checked, 10);
+        assert_eq!(report.files_by_kind[&VerifiedKind::Detail], 2);
+        assert_eq!(report.files_by_kind[&VerifiedKind::Pack], 1);
+        assert_eq!(report.files_by_kind[&VerifiedKind::ListManifest], 1);
+        assert!(!report.files_by_kind.contains_key(&VerifiedKind::Json));
+    }
+
+    #[tokio::test]
+    async fn corrupted_files_are_reported_per_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        write_valid_tree(dir);
+
+        write(dir, "navigation/en-us.json", "[{\"menuId\": 2,");
+        write_json(
+            dir,
+            "calendar/en-us.json",
+            json!({ "version": VERSION, "language": "ja-jp" }),
+        );
+        write_json(dir, "detail/en-us/1003.json", detail_json(1003));
+        std::fs::remove_file(dir.join("detail/en-us/1002.json")).unwrap();
+        write_json(
+            dir,
+            "list/en-us/2.json",
+            list_json("en-us", 2, "Heroes", &[1001]),
+        );
+        let mut pack = std::fs::read(dir.join("packs/en-us.pack")).unwrap();
+        let last = pack.len() - 1;
+        pack[last] ^= 0xff;
+        write(dir, "packs/en-us.pack", pack);
+
+        let report = verify_output_dir(dir).await.unwrap();
+        let mut paths = problem_paths(&report);
+        paths.dedup();
+        assert_eq!(
+            paths,
+            [
+                "calendar/en-us.json",
+                "detail/en-us/index.json",
+                "list/en-us/index.json",
+                "navigation/en-us.json",
+                "packs/en-us.pack",
+                "reports/list_manifest.json",
+            ]
+        );
+        let messages = |path: &str| -> Vec<&str> {
+            report
+                .problems
+                .iter()
+                .filter(|p| p.path == path)
+                .map(|p| p.message.as_str())
+                .collect()
+        };
+        assert_eq!(
+            messages("detail/en-us/index.json"),
+            [
+                "lists entry 1002 but detail/en-us/1002.json is missing or unreadable",
+                "does not list entry 1003",
+            ]
+        );
+        assert_eq!(
+            messages("list/en-us/index.json"),
+            [
+                "menu 2 is named 'Characters' but its list file says 'Heroes'",
+                "menu 2 has 2 item(s) but its list file has 1",
+            ]
+        );
+        assert!(messages("navigation/en-us.json")[0].starts_with("does not parse"));
+        assert_eq!(
+            messages("calendar/en-us.json"),
+            ["language is 'ja-jp' but the path says 'en-us'"]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_directory_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+        assert!(matches!(
+            verify_output_dir(&missing).await,
+            Err(AppError::Argument(_))
+        ));
+    }
+}
@@ -34,6 +34,19 @@ pub enum AppError {
     JoinError(String),
     #[error("Timeout during operation: {0}")]
     Timeout(String),
+    #[error("Response body too large: at least {bytes} bytes, limit {limit} (Endpoint: {endpoint}, Lang: {lang})")]
+    ResponseTooLarge {
+        bytes: u64,
+        limit: u64,
+        endpoint: String,
+        lang: String,
+    },
+    #[error("API served '{served}' for requested language '{lang}' (Endpoint: {endpoint})")]
+    LanguageMismatch {
+        served: String,
+        endpoint: String,
+        lang: String,
+    },
     #[error("Recursion depth limit ({limit}) reached during {context}")]
     RecursionLimit { context: String, limit: u32 },
     #[error("Hex decoding error: {0}")]
@@ -42,6 +55,12 @@ pub enum AppError {
     ColorParse(String),
     #[error("Configuration error: {0}")]
     ConfigError(String),
+    #[error("Post preview resolution not supported: {0}")]
+    PostResolutionNotSupported(String),
+    #[error("Preflight check failed: {0}")]
+    Preflight(String),
+    #[error("Run aborted: {0}")]
+    RunAborted(String),
     #[error("Semaphore acquisition error: {0}")]
     SemaphoreAcquire(String),
     #[error("Unexpected internal error: {0}")]
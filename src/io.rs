@@ -1,12 +1,20 @@
 use crate::config;
 use crate::error::{AppError, AppResult};
 use crate::logging::{log, LogLevel};
+use crate::model::common::EntryId;
+use crate::model::output::OutputDetailPage;
 use crate::utils;
-use serde::Serialize;
+use serde::{Serialize, Serializer};
+use serde_json::ser::PrettyFormatter;
+use serde_json::{json, Value};
+use std::borrow::Borrow;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::fs::{self, File};
 use tokio::io::AsyncWriteExt;
 
+pub mod pack;
+
 pub fn clean_filename<S: AsRef<str>>(name: S) -> String {
     let name_ref = name.as_ref().trim();
     if name_ref.is_empty() {
@@ -48,6 +56,95 @@ pub async fn ensure_output_directories(base_dir: &Path) -> AppResult<()> {
     Ok(())
 }
 
+pub fn check_clean_target(base_dir: &Path) -> AppResult<PathBuf> {
+    let resolved = std::fs::canonicalize(base_dir).map_err(|e| map_io_error(e, base_dir))?;
+
+    let home_dir = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .and_then(|h| std::fs::canonicalize(h).ok());
+
+    let is_root = resolved.parent().is_none();
+    let is_home = home_dir.as_deref() == Some(resolved.as_path());
+
+    if is_root || is_home {
+        return Err(AppError::Argument(format!(
+            "Refusing to clean '{}': path resolves to a root or home directory.",
+            resolved.display()
+        )));
+    }
+    Ok(resolved)
+}
+
+/// Removes every `OUTPUT_SUBDIRS` directory under `base_dir`, except that the
+/// cross-run state in `CLEAN_KEPT_FILES` stays behind in its directory.
+pub async fn clean_output_directories(base_dir: &Path) -> AppResult<usize> {
+    let mut removed_count = 0usize;
+    for subdir in config::OUTPUT_SUBDIRS {
+        let dir_path = base_dir.join(subdir);
+        if !fs::try_exists(&dir_path).await.unwrap_or(false) {
+            continue;
+        }
+        let kept: Vec<&str> = config::CLEAN_KEPT_FILES
+            .iter()
+            .filter_map(|(dir, file)| (dir == subdir).then_some(*file))
+            .collect();
+        if kept.is_empty() {
+            fs::remove_dir_all(&dir_path)
+                .await
+                .map_err(|e| map_io_error(e, &dir_path))?;
+        } else {
+            remove_dir_entries_except(&dir_path, &kept).await?;
+        }
+        log(
+            LogLevel::Info,
+            &format!("Removed output directory: {}", dir_path.display()),
+        );
+        removed_count += 1;
+    }
+    Ok(removed_count)
+}
+
+async fn remove_dir_entries_except(dir_path: &Path, kept: &[&str]) -> AppResult<()> {
+    let mut entries = fs::read_dir(dir_path)
+        .await
+        .map_err(|e| map_io_error(e, dir_path))?;
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| map_io_error(e, dir_path))?
+    {
+        let path = entry.path();
+        let is_dir = entry.file_type().await.is_ok_and(|ft| ft.is_dir());
+        if is_dir {
+            fs::remove_dir_all(&path).await
+        } else if kept.contains(&entry.file_name().to_string_lossy().as_ref()) {
+            continue;
+        } else {
+            fs::remove_file(&path).await
+        }
+        .map_err(|e| map_io_error(e, &path))?;
+    }
+    Ok(())
+}
+
+pub async fn warn_if_oversized(fpath: &Path, limit_mb: f64, log_ctx: &str) {
+    if let Ok(metadata) = fs::metadata(fpath).await {
+        let size_mb = metadata.len() as f64 / (1024.0 * 1024.0);
+        if size_mb > limit_mb {
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "{} file is {:.2} MB, exceeding the {:.2} MB limit: '{}'",
+                    log_ctx,
+                    size_mb,
+                    limit_mb,
+                    fpath.display()
+                ),
+            );
+        }
+    }
+}
+
 fn map_io_error(error: std::io::Error, path: &Path) -> AppError {
     AppError::Io(format!("I/O error at path '{}': {}", path.display(), error))
 }
@@ -59,41 +156,149 @@ async fn write_file_async(fpath: &Path, data: &[u8]) -> AppResult<()> {
     file.write_all(data)
         .await
         .map_err(|e| map_io_error(e, fpath))?;
+    // tokio finishes the write in the background; flush so the file is complete on return.
+    file.flush().await.map_err(|e| map_io_error(e, fpath))?;
 
     Ok(())
 }
 
+/// Writes a raw Unknown component payload to `<dir>/<component_id>/<page_id>_<lang>.json`
+/// for discovery. Failures are logged and never affect the transform.
+pub async fn dump_unknown_component(
+    dir: &Path,
+    component_id: &str,
+    lang: &str,
+    page_id: EntryId,
+    data: &Value,
+) {
+    let component_dir = dir.join(clean_filename(component_id));
+    if let Err(e) = fs::create_dir_all(&component_dir).await {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Failed to create unknown component dump directory {}: {}",
+                component_dir.display(),
+                e
+            ),
+        );
+        return;
+    }
+    let payload = json!({
+        "componentId": component_id,
+        "lang": lang,
+        "pageId": page_id,
+        "data": data,
+    });
+    let fpath = component_dir.join(format!("{}_{}.json", page_id, lang));
+    let ctx = format!("Unknown component dump {}", component_id);
+    let _ = save_json(fpath, payload, ctx).await;
+}
+
+/// One element of a shared vector. It serializes as the element itself, so a
+/// save task can own its page without copying it out of the vector.
+pub struct SharedItem<T> {
+    items: Arc<Vec<T>>,
+    index: usize,
+}
+
+impl<T> SharedItem<T> {
+    pub fn new(items: Arc<Vec<T>>, index: usize) -> Self {
+        assert!(index < items.len(), "SharedItem index out of bounds");
+        SharedItem { items, index }
+    }
+}
+
+impl<T> Borrow<T> for SharedItem<T> {
+    fn borrow(&self) -> &T {
+        &self.items[self.index]
+    }
+}
+
+impl<T: Serialize> Serialize for SharedItem<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.items[self.index].serialize(serializer)
+    }
+}
+
+pub async fn save_detail_page<P>(fpath: PathBuf, page: P, log_ctx: String) -> AppResult<bool>
+where
+    P: Borrow<OutputDetailPage> + Serialize + Send + Sync + 'static,
+{
+    let page_ref: &OutputDetailPage = page.borrow();
+    if config::components_as_array() {
+        let array_view = page_ref.components_array_view(config::debug_component_ids())?;
+        save_json(fpath, array_view, log_ctx).await
+    } else if config::debug_component_ids() {
+        let annotated = page_ref.component_id_view()?;
+        save_json(fpath, annotated, log_ctx).await
+    } else {
+        save_json(fpath, page, log_ctx).await
+    }
+}
+
+/// Compact JSON for `page` in the same view [`save_detail_page`] writes.
+pub fn detail_page_json_bytes(page: &OutputDetailPage) -> AppResult<Vec<u8>> {
+    let bytes = if config::components_as_array() {
+        serde_json::to_vec(&page.components_array_view(config::debug_component_ids())?)?
+    } else if config::debug_component_ids() {
+        serde_json::to_vec(&page.component_id_view()?)?
+    } else {
+        serde_json::to_vec(page)?
+    };
+    Ok(bytes)
+}
+
+/// Pretty-prints `data` with the configured `--indent`.
+pub fn to_json_writer<W, T>(writer: W, data: &T) -> serde_json::Result<()>
+where
+    W: std::io::Write,
+    T: Serialize + ?Sized,
+{
+    let formatter = PrettyFormatter::with_indent(config::json_indent());
+    let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
+    data.serialize(&mut serializer)
+}
+
+pub fn to_json_string<T>(data: &T) -> AppResult<String>
+where
+    T: Serialize + ?Sized,
+{
+    let mut buf = Vec::with_capacity(128);
+    to_json_writer(&mut buf, data)?;
+    String::from_utf8(buf).map_err(|e| AppError::SerdeSerialize(e.to_string()))
+}
+
+/// Pretty JSON for a saved file, or compact JSON when `--compact-threshold` is set
+/// and the pretty form would exceed it. The compact form is a lower bound on the
+/// pretty size, so it is serialized first and files already over the threshold
+/// are serialized only once.
+pub fn to_json_string_for_save<T>(data: &T) -> AppResult<String>
+where
+    T: Serialize + ?Sized,
+{
+    let Some(threshold) = config::compact_threshold() else {
+        return to_json_string(data);
+    };
+    let compact = serde_json::to_string(data)?;
+    if compact.len() as u64 > threshold {
+        return Ok(compact);
+    }
+    let pretty = to_json_string(data)?;
+    Ok(if pretty.len() as u64 > threshold {
+        compact
+    } else {
+        pretty
+    })
+}
+
 pub async fn save_json<T>(fpath: PathBuf, data: T, log_ctx: String) -> AppResult<bool>
 where
     T: Serialize + Send + Sync + 'static,
 {
-    let json_string_result =
-        utils::run_blocking(move || serde_json::to_string_pretty(&data).map_err(AppError::from)).await;
+    let json_string_result = utils::run_blocking(move || to_json_string_for_save(&data)).await;
 
     match json_string_result {
-        Ok(json_string) => {
-            let json_bytes = json_string.into_bytes();
-            match write_file_async(&fpath, &json_bytes).await {
-                Ok(_) => Ok(true),
-                Err(e) => {
-                    log(
-                        LogLevel::Error,
-                        &format!(
-                            "Save JSON ({}) FAIL - Write Error: {}. File: '{}'",
-                            log_ctx,
-                            e,
-                            fpath.display()
-                        ),
-                    );
-
-                    if fs::try_exists(&fpath).await.unwrap_or(false) {
-                        let _ = fs::remove_file(&fpath).await;
-                    }
-
-                    Err(e)
-                }
-            }
-        }
+        Ok(json_string) => save_json_string(fpath, json_string, log_ctx).await,
         Err(e) => {
             log(
                 LogLevel::Error,
@@ -107,4 +312,32 @@ where
             Err(e)
         }
     }
-}
\ No newline at end of file
+}
+
+pub async fn save_json_string(
+    fpath: PathBuf,
+    json_string: String,
+    log_ctx: String,
+) -> AppResult<bool> {
+    let json_bytes = json_string.into_bytes();
+    match write_file_async(&fpath, &json_bytes).await {
+        Ok(_) => Ok(true),
+        Err(e) => {
+            log(
+                LogLevel::Error,
+                &format!(
+                    "Save JSON ({}) FAIL - Write Error: {}. File: '{}'",
+                    log_ctx,
+                    e,
+                    fpath.display()
+                ),
+            );
+
+            if fs::try_exists(&fpath).await.unwrap_or(false) {
+                let _ = fs::remove_file(&fpath).await;
+            }
+
+            Err(e)
+        }
+    }
+}
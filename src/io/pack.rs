@@ -0,0 +1,378 @@
+//! Single-file detail packs (`packs/<lang>.pack`) for clients that would rather
+//! memory-map one file than sync thousands of detail JSON files.
+//!
+//! Layout, all integers little-endian:
+//!
+//! ```text
+//! header  magic "GWPK" | version u16 | reserved u16 | entry count u32
+//! index   entry count x (id i64 | offset u64 | length u32 | raw length u32 | crc32 u32)
+//! data    one brotli stream per entry, concatenated
+//! ```
+//!
+//! `offset` is relative to the start of the data section, and `crc32` covers the
+//! uncompressed JSON so a reader can verify each entry on its own.
+
+use crate::error::{AppError, AppResult};
+use crate::logging::{log, LogLevel};
+use crate::model::common::EntryId;
+use crate::model::output::OutputDetailPage;
+use crate::utils;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs;
+
+pub const PACK_MAGIC: &[u8; 4] = b"GWPK";
+pub const PACK_VERSION: u16 = 1;
+pub const PACK_DIR: &str = "packs";
+
+const HEADER_LEN: usize = 12;
+const INDEX_ENTRY_LEN: usize = 28;
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_QUALITY: u32 = 9;
+const BROTLI_LG_WINDOW: u32 = 22;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackIndexEntry {
+    pub id: EntryId,
+    pub offset: u64,
+    pub length: u32,
+    pub raw_length: u32,
+    pub crc32: u32,
+}
+
+/// Side copy of a pack's index, written as `packs/<lang>.index.json`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackIndex {
+    pub version: u16,
+    pub entries: Vec<PackIndexEntry>,
+}
+
+/// Builds a pack from `(id, json)` pairs, keeping their order.
+pub fn encode_pack(entries: &[(EntryId, Vec<u8>)]) -> AppResult<(Vec<u8>, PackIndex)> {
+    let mut index = Vec::with_capacity(entries.len());
+    let mut data = Vec::new();
+    for (id, json) in entries {
+        let compressed = compress(json)?;
+        index.push(PackIndexEntry {
+            id: *id,
+            offset: data.len() as u64,
+            length: to_u32(compressed.len(), *id)?,
+            raw_length: to_u32(json.len(), *id)?,
+            crc32: crc32fast::hash(json),
+        });
+        data.extend_from_slice(&compressed);
+    }
+
+    let entry_count = u32::try_from(index.len())
+        .map_err(|_| AppError::Unexpected("Too many entries for a pack".to_string()))?;
+    let mut bytes = Vec::with_capacity(HEADER_LEN + index.len() * INDEX_ENTRY_LEN + data.len());
+    bytes.extend_from_slice(PACK_MAGIC);
+    bytes.extend_from_slice(&PACK_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(&entry_count.to_le_bytes());
+    for entry in &index {
+        bytes.extend_from_slice(&entry.id.to_le_bytes());
+        bytes.extend_from_slice(&entry.offset.to_le_bytes());
+        bytes.extend_from_slice(&entry.length.to_le_bytes());
+        bytes.extend_from_slice(&entry.raw_length.to_le_bytes());
+        bytes.extend_from_slice(&entry.crc32.to_le_bytes());
+    }
+    bytes.extend_from_slice(&data);
+
+    Ok((
+        bytes,
+        PackIndex {
+            version: PACK_VERSION,
+            entries: index,
+        },
+    ))
+}
+
+/// Random-access view over an encoded pack.
+///
+/// # Examples
+///
+/// ```
+/// use wiki_update::io::pack::{encode_pack, PackReader};
+///
+/// # fn example() -> wiki_update::error::AppResult<()> {
+/// let entries = vec![(1, br#"{"id":1}"#.to_vec()), (7, br#"{"id":7}"#.to_vec())];
+/// let (bytes, _index) = encode_pack(&entries)?;
+/// let reader = PackReader::open(&bytes)?;
+/// assert_eq!(reader.read(7)?.as_deref(), Some(&br#"{"id":7}"#[..]));
+/// assert_eq!(reader.read(2)?, None);
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub struct PackReader<'a> {
+    data: &'a [u8],
+    entries: HashMap<EntryId, PackIndexEntry>,
+}
+
+impl<'a> PackReader<'a> {
+    pub fn open(bytes: &'a [u8]) -> AppResult<Self> {
+        if bytes.len() < HEADER_LEN || &bytes[..4] != PACK_MAGIC {
+            return Err(invalid("missing GWPK header"));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != PACK_VERSION {
+            return Err(invalid(&format!("unsupported version {}", version)));
+        }
+        let entry_count = read_u32(bytes, 8) as usize;
+        let data_start = entry_count
+            .checked_mul(INDEX_ENTRY_LEN)
+            .and_then(|index_len| index_len.checked_add(HEADER_LEN))
+            .filter(|data_start| *data_start <= bytes.len())
+            .ok_or_else(|| invalid("index extends past end of file"))?;
+
+        let data = &bytes[data_start..];
+        let mut entries = HashMap::with_capacity(entry_count);
+        for position in 0..entry_count {
+            let at = HEADER_LEN + position * INDEX_ENTRY_LEN;
+            let entry = PackIndexEntry {
+                id: i64::from_le_bytes(bytes[at..at + 8].try_into().unwrap_or_default()),
+                offset: u64::from_le_bytes(bytes[at + 8..at + 16].try_into().unwrap_or_default()),
+                length: read_u32(bytes, at + 16),
+                raw_length: read_u32(bytes, at + 20),
+                crc32: read_u32(bytes, at + 24),
+            };
+            let end = entry.offset.checked_add(u64::from(entry.length));
+            if end.is_none_or(|end| end > data.len() as u64) {
+                return Err(invalid(&format!(
+                    "entry {} extends past end of file",
+                    entry.id
+                )));
+            }
+            entries.insert(entry.id, entry);
+        }
+        Ok(PackReader { data, entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entry ids in the pack, in no particular order.
+    pub fn ids(&self) -> impl Iterator<Item = EntryId> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Decompresses one entry's JSON, checking its length and checksum.
+    pub fn read(&self, id: EntryId) -> AppResult<Option<Vec<u8>>> {
+        let Some(entry) = self.entries.get(&id) else {
+            return Ok(None);
+        };
+        let start = entry.offset as usize;
+        let compressed = &self.data[start..start + entry.length as usize];
+        let mut json = Vec::with_capacity(entry.raw_length as usize);
+        brotli::Decompressor::new(compressed, BROTLI_BUFFER_SIZE).read_to_end(&mut json)?;
+        if json.len() != entry.raw_length as usize || crc32fast::hash(&json) != entry.crc32 {
+            return Err(invalid(&format!("checksum mismatch for entry {}", id)));
+        }
+        Ok(Some(json))
+    }
+}
+
+/// Writes `packs/<lang>.pack` and its side index.
+pub async fn save_detail_pack(
+    out_dir: PathBuf,
+    lang: String,
+    pages: Arc<Vec<OutputDetailPage>>,
+) -> AppResult<bool> {
+    let pack_dir = out_dir.join(PACK_DIR);
+    fs::create_dir_all(&pack_dir).await?;
+    let pack_path = pack_dir.join(format!("{}.pack", lang));
+    let index_path = pack_dir.join(format!("{}.index.json", lang));
+
+    let (pack_bytes, index) = utils::run_blocking(move || {
+        let mut entries = pages
+            .iter()
+            .map(|page| Ok((page.id, super::detail_page_json_bytes(page)?)))
+            .collect::<AppResult<Vec<_>>>()?;
+        entries.sort_by_key(|(id, _)| *id);
+        encode_pack(&entries)
+    })
+    .await?;
+    let entry_count = index.entries.len();
+
+    super::write_file_async(&pack_path, &pack_bytes).await?;
+    super::save_json(index_path, index, format!("Pack index [{}]", lang)).await?;
+    log(
+        LogLevel::Info,
+        &format!(
+            "Wrote pack [{}]: {} entries, {:.1} KB.",
+            lang,
+            entry_count,
+            pack_bytes.len() as f64 / 1024.0
+        ),
+    );
+    Ok(true)
+}
+
+fn compress(json: &[u8]) -> AppResult<Vec<u8>> {
+    let mut writer = brotli::CompressorWriter::new(
+        Vec::with_capacity(json.len() / 4),
+        BROTLI_BUFFER_SIZE,
+        BROTLI_QUALITY,
+        BROTLI_LG_WINDOW,
+    );
+    writer.write_all(json)?;
+    Ok(writer.into_inner())
+}
+
+fn to_u32(len: usize, id: EntryId) -> AppResult<u32> {
+    u32::try_from(len).map_err(|_| AppError::Unexpected(format!("Pack entry {} exceeds 4 GiB", id)))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap_or_default())
+}
+
+fn invalid(message: &str) -> AppError {
+    AppError::SerdeParse(format!("Invalid pack: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entries() -> Vec<(EntryId, Vec<u8>)> {
+        (1..=20)
+            .map(|id| {
+                (
+                    id * 3,
+                    format!(r#"{{"epId":{},"name":"Entry {}"}}"#, id * 3, id).into_bytes(),
+                )
+            })
+            .collect()
+    }
+
+    fn index_field_at(position: usize, field_offset: usize) -> usize {
+        HEADER_LEN + position * INDEX_ENTRY_LEN + field_offset
+    }
+
+    fn open_error(bytes: &[u8]) -> String {
+        match PackReader::open(bytes) {
+            Ok(_) => panic!("corrupt pack opened"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn every_entry_reads_back_in_any_order() {
+        let entries = sample_entries();
+        let (bytes, index) = encode_pack(&entries).unwrap();
+        assert_eq!(index.entries.len(), entries.len());
+
+        let reader = PackReader::open(&bytes).unwrap();
+        assert_eq!(reader.len(), entries.len());
+        for (id, json) in entries.iter().rev() {
+            assert_eq!(reader.read(*id).unwrap().as_deref(), Some(json.as_slice()));
+        }
+        assert_eq!(reader.read(4).unwrap(), None);
+        let mut ids: Vec<EntryId> = reader.ids().collect();
+        ids.sort_unstable();
+        assert_eq!(ids, entries.iter().map(|(id, _)| *id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty_pack_opens() {
+        let (bytes, _) = encode_pack(&[]).unwrap();
+        assert_eq!(bytes.len(), HEADER_LEN);
+        assert!(PackReader::open(&bytes).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_header_is_rejected() {
+        let (mut bytes, _) = encode_pack(&sample_entries()).unwrap();
+        assert!(open_error(&bytes[..HEADER_LEN - 1]).contains("header"));
+        bytes[4] = 9;
+        assert!(open_error(&bytes).contains("unsupported version 9"));
+        bytes[0] = b'X';
+        assert!(open_error(&bytes).contains("header"));
+    }
+
+    #[test]
+    fn truncated_index_is_rejected() {
+        let (bytes, _) = encode_pack(&sample_entries()).unwrap();
+        assert!(open_error(&bytes[..HEADER_LEN + INDEX_ENTRY_LEN]).contains("index extends"));
+    }
+
+    #[test]
+    fn entry_past_the_end_is_rejected() {
+        let (bytes, _) = encode_pack(&sample_entries()).unwrap();
+        assert!(open_error(&bytes[..bytes.len() - 1]).contains("past end of file"));
+    }
+
+    #[test]
+    fn overflowing_offset_is_rejected() {
+        let (mut bytes, _) = encode_pack(&sample_entries()).unwrap();
+        let at = index_field_at(2, 8);
+        bytes[at..at + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(open_error(&bytes).contains("past end of file"));
+    }
+
+    #[test]
+    fn checksum_mismatch_is_reported_on_read() {
+        let entries = sample_entries();
+        let (mut bytes, _) = encode_pack(&entries).unwrap();
+        let at = index_field_at(5, 24);
+        bytes[at] ^= 0xFF;
+        let reader = PackReader::open(&bytes).unwrap();
+        assert!(reader.read(entries[4].0).is_ok());
+        let err = reader.read(entries[5].0).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+    }
+
+    #[tokio::test]
+    async fn saved_pack_reads_back_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let pages: Vec<OutputDetailPage> = [9, 2, 5]
+            .into_iter()
+            .map(|id| OutputDetailPage {
+                id,
+                name: Some(format!("Entry {}", id)),
+                ..Default::default()
+            })
+            .collect();
+        let expected: Vec<(EntryId, Vec<u8>)> = pages
+            .iter()
+            .map(|page| (page.id, super::super::detail_page_json_bytes(page).unwrap()))
+            .collect();
+        save_detail_pack(
+            dir.path().to_path_buf(),
+            "en-us".to_string(),
+            Arc::new(pages),
+        )
+        .await
+        .unwrap();
+
+        let bytes = std::fs::read(dir.path().join(PACK_DIR).join("en-us.pack")).unwrap();
+        let reader = PackReader::open(&bytes).unwrap();
+        assert_eq!(reader.len(), expected.len());
+        for (id, json) in &expected {
+            assert_eq!(reader.read(*id).unwrap().as_deref(), Some(json.as_slice()));
+        }
+        let index: serde_json::Value = serde_json::from_slice(
+            &std::fs::read(dir.path().join(PACK_DIR).join("en-us.index.json")).unwrap(),
+        )
+        .unwrap();
+        let ids: Vec<i64> = index["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(ids, [2, 5, 9]);
+    }
+}
@@ -6,10 +6,15 @@ pub mod error;
 pub mod io;
 pub mod logging;
 pub mod model;
+pub mod serve;
+pub mod telemetry;
 pub mod testing;
 pub mod transform;
 pub mod utils;
 
+pub use core::processor::{fetch_and_transform, save, FetchOptions, SaveOptions};
+pub use core::updater::WikiUpdater;
+
 #[cfg(not(target_env = "msvc"))]
 use tikv_jemallocator::Jemalloc;
 
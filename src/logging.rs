@@ -1,6 +1,13 @@
 use colored::*;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LogLevel {
@@ -80,7 +87,14 @@ static LOG_PREFIXES: Lazy<HashMap<LogLevel, String>> = Lazy::new(|| {
         .collect()
 });
 
-pub fn setup_logging() {
+#[derive(Debug, Clone)]
+pub struct LogFileConfig {
+    pub path: PathBuf,
+    pub max_bytes: u64,
+    pub keep: usize,
+}
+
+pub fn setup_logging(log_file: Option<LogFileConfig>) {
     if std::env::var("RUST_LOG").is_err() {
         std::env::set_var("RUST_LOG", "info");
     }
@@ -91,11 +105,157 @@ pub fn setup_logging() {
         .with_target(false)
         .compact();
 
-    tracing_subscriber::fmt()
-        .event_format(format)
-        .with_ansi(true)
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+    let console_layer = tracing_subscriber::fmt::layer()
+        .event_format(format.clone())
+        .with_ansi(true);
+
+    let mut file_open_error = None;
+    let file_layer = log_file.and_then(|cfg| match RotatingFileWriter::open(cfg.clone()) {
+        Ok(writer) => Some(
+            tracing_subscriber::fmt::layer()
+                .event_format(format)
+                .with_ansi(false)
+                .with_writer(writer),
+        ),
+        Err(e) => {
+            file_open_error = Some(format!(
+                "Failed to open log file {}: {}",
+                cfg.path.display(),
+                e
+            ));
+            None
+        }
+    });
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(console_layer)
+        .with(file_layer)
         .init();
+
+    if let Some(message) = file_open_error {
+        log(LogLevel::Warning, &message);
+    }
+}
+
+#[derive(Debug)]
+struct RotatingFileState {
+    cfg: LogFileConfig,
+    file: File,
+    written: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RotatingFileWriter(Arc<Mutex<RotatingFileState>>);
+
+impl RotatingFileWriter {
+    pub fn open(cfg: LogFileConfig) -> io::Result<Self> {
+        if let Some(parent) = cfg.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&cfg.path)?;
+        let written = file.metadata()?.len();
+        Ok(RotatingFileWriter(Arc::new(Mutex::new(
+            RotatingFileState { cfg, file, written },
+        ))))
+    }
+}
+
+impl RotatingFileState {
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let path = self.cfg.path.clone();
+        if self.cfg.keep == 0 {
+            remove_if_exists(&path)?;
+        } else {
+            remove_if_exists(&rotated_path(&path, self.cfg.keep))?;
+            for index in (1..self.cfg.keep).rev() {
+                let from = rotated_path(&path, index);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&path, index + 1))?;
+                }
+            }
+            fs::rename(&path, rotated_path(&path, 1))?;
+        }
+        self.file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// Our prefixes carry ANSI colour codes baked in (and tracing escapes them as a literal
+// `\x1b`), so strip both forms for the file copy.
+fn strip_ansi(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while !rest.is_empty() {
+        let escape_len = if rest.starts_with("\x1b[") {
+            1
+        } else if rest.starts_with("\\x1b[") {
+            4
+        } else {
+            0
+        };
+        if escape_len == 0 {
+            let mut chars = rest.chars();
+            output.extend(chars.next());
+            rest = chars.as_str();
+            continue;
+        }
+        let sequence = &rest[escape_len + 1..];
+        match sequence.find(|c: char| ('@'..='~').contains(&c)) {
+            Some(end) => rest = &sequence[end + 1..],
+            None => rest = "",
+        }
+    }
+    output
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let plain = strip_ansi(&String::from_utf8_lossy(buf));
+        let mut state = self
+            .0
+            .lock()
+            .map_err(|_| io::Error::other("log file lock poisoned"))?;
+        if state.written > 0 && state.written + plain.len() as u64 > state.cfg.max_bytes {
+            state.rotate()?;
+        }
+        state.file.write_all(plain.as_bytes())?;
+        state.written += plain.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.0.lock() {
+            Ok(mut state) => state.file.flush(),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFileWriter {
+    type Writer = RotatingFileWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
 }
 
 pub fn log(level: LogLevel, message: &str) {
@@ -112,3 +272,75 @@ pub fn log(level: LogLevel, message: &str) {
         LogLevel::Error => tracing::error!("{}", format!("{}{}", prefix, message)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_lines(cfg: &LogFileConfig, lines: usize) {
+        let mut writer = RotatingFileWriter::open(cfg.clone()).unwrap();
+        for i in 0..lines {
+            writeln!(
+                writer,
+                "\x1b[32mline {:04}\x1b[0m padded to a fixed width",
+                i
+            )
+            .unwrap();
+        }
+        writer.flush().unwrap();
+    }
+
+    #[test]
+    fn rotation_keeps_the_configured_number_of_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = LogFileConfig {
+            path: dir.path().join("logs").join("run.log"),
+            max_bytes: 256,
+            keep: 3,
+        };
+        write_lines(&cfg, 100);
+
+        for index in 1..=cfg.keep {
+            let rotated = rotated_path(&cfg.path, index);
+            assert!(rotated.exists(), "{} missing", rotated.display());
+            assert!(fs::metadata(&rotated).unwrap().len() <= cfg.max_bytes);
+        }
+        assert!(!rotated_path(&cfg.path, cfg.keep + 1).exists());
+        let current = fs::read_to_string(&cfg.path).unwrap();
+        assert!(current.len() as u64 <= cfg.max_bytes);
+        assert!(current.ends_with("line 0099 padded to a fixed width\n"));
+        assert!(!current.contains('\x1b'));
+    }
+
+    #[test]
+    fn rotation_with_keep_zero_truncates_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = LogFileConfig {
+            path: dir.path().join("run.log"),
+            max_bytes: 256,
+            keep: 0,
+        };
+        write_lines(&cfg, 100);
+
+        assert!(!rotated_path(&cfg.path, 1).exists());
+        let current = fs::read_to_string(&cfg.path).unwrap();
+        assert!(current.len() as u64 <= cfg.max_bytes);
+        assert!(current.ends_with("line 0099 padded to a fixed width\n"));
+    }
+
+    #[test]
+    fn reopening_continues_from_the_existing_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = LogFileConfig {
+            path: dir.path().join("run.log"),
+            max_bytes: 256,
+            keep: 1,
+        };
+        write_lines(&cfg, 4);
+        assert!(!rotated_path(&cfg.path, 1).exists());
+        write_lines(&cfg, 4);
+
+        assert!(rotated_path(&cfg.path, 1).exists());
+        assert!(!rotated_path(&cfg.path, 2).exists());
+    }
+}
@@ -1,19 +1,44 @@
 use clap::{CommandFactory, Parser};
+use std::future::Future;
+use std::io::{BufRead, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::runtime::Builder;
-use wiki_update::cli::CliArgs;
-use wiki_update::core::processor;
+use tokio::time::timeout;
+use wiki_update::cli::{CliArgs, Command};
+use wiki_update::config;
+use wiki_update::core::{pipeline, processor, verify};
 use wiki_update::error::{AppError, AppResult};
+use wiki_update::io;
 use wiki_update::logging::{log, setup_logging, LogLevel};
+use wiki_update::serve;
+use wiki_update::telemetry;
 use wiki_update::testing;
 
 fn main() -> ExitCode {
-    setup_logging();
-
     let cli_args = match CliArgs::try_parse() {
-        Ok(args) => args,
+        Ok(args) => {
+            if let Some(Command::Completions { shell }) = args.get_command() {
+                clap_complete::generate(
+                    *shell,
+                    &mut CliArgs::command(),
+                    env!("CARGO_BIN_NAME"),
+                    &mut std::io::stdout(),
+                );
+                return ExitCode::SUCCESS;
+            }
+            if args.print_config() {
+                // Keep stdout to the JSON document.
+                std::env::set_var("RUST_LOG", "error");
+            }
+            setup_logging(args.get_log_file_config());
+            args
+        }
         Err(e) => {
+            setup_logging(None);
             log(LogLevel::Error, &format!("CLI Argument Error: {}", e));
             let _ = CliArgs::command().print_help();
             return ExitCode::from(2);
@@ -36,11 +61,113 @@ fn main() -> ExitCode {
         }
     };
 
+    let runtime_settings = cli_args.runtime_settings();
+    if runtime_settings != config::RuntimeSettings::default() {
+        log(
+            LogLevel::Info,
+            &format!("Runtime settings: {:?}", runtime_settings),
+        );
+    }
+    if let Err(e) = runtime_settings.apply() {
+        log(LogLevel::Error, &format!("FATAL: {}", e));
+        return ExitCode::FAILURE;
+    }
+    config::set_icon_fallback_langs(&cli_args.get_icon_fallback_langs());
+    config::set_max_recursion_depth(cli_args.get_max_recursion_depth());
+    if let Some(page_size) = cli_args.get_page_size() {
+        config::set_page_size(page_size);
+    }
+    config::set_depth_limit_placeholder(cli_args.depth_limit_placeholder());
+    config::set_debug_component_ids(cli_args.debug_component_ids());
+    config::set_components_as_array(cli_args.components_as_array());
+    config::set_strip_color(cli_args.strip_color());
+    config::set_search_index_keyword_chars(cli_args.get_search_index_chars());
+    config::set_search_index_components(&cli_args.get_search_index_components());
+    config::set_bundle_entries(&cli_args.bundle_entries());
+    config::set_export_pack(cli_args.export_pack());
+    config::set_strict_language(cli_args.strict_language());
+    config::set_detail_transform_fail_limit(cli_args.fail_fast());
+    config::set_extra_filter_fields(&cli_args.get_extra_filter_fields());
+    config::set_abort_on_consistency_warning(cli_args.abort_on_consistency_warning());
+    config::set_require_all_langs(cli_args.require_all_langs());
+    config::set_skip_preflight(cli_args.skip_preflight());
+    config::set_stats_report(cli_args.stats_report());
+    config::set_report_unresolved(cli_args.report_unresolved());
+    config::set_save_failed_ids(cli_args.save_failed_ids());
+    config::set_derive_progression(cli_args.derive_progression());
+    config::set_derive_event_windows(cli_args.derive_event_windows());
+    config::set_event_menu_ids(&cli_args.event_menu_ids());
+    config::set_parse_bulk_desc(cli_args.parse_bulk_desc());
+    config::set_keep_raw_filters(cli_args.keep_raw_filters());
+    config::set_emit_filter_vocabulary(cli_args.emit_filter_vocabulary());
+    if let Some(mode) = cli_args.header_gallery_dedup() {
+        config::set_header_gallery_dedup(mode);
+    }
+    if let Some(mode) = cli_args.alias_detection() {
+        config::set_alias_detection(mode);
+    }
+    config::set_include_abstract_modules(cli_args.include_abstract_modules());
+    config::set_flatten_html(cli_args.flatten_html());
+    config::set_intern_nodes(cli_args.intern_nodes());
+    if let Some(template) = cli_args.entry_url_template() {
+        config::set_entry_url_template(template);
+    }
+    config::set_oversized_component_kb(cli_args.component_size_warn_kb());
+    config::set_truncate_oversized(cli_args.truncate_oversized());
+    if let Some(url) = cli_args.api_base_url() {
+        log(LogLevel::Info, &format!("Using API base URL: {}", url));
+        if let Err(e) = config::set_api_base_url(url) {
+            log(LogLevel::Error, &format!("FATAL: {}", e));
+            return ExitCode::FAILURE;
+        }
+    }
+    let (max_response_mb, per_endpoint_mb) = cli_args.max_response_mb();
+    config::set_max_response_mb(max_response_mb, &per_endpoint_mb);
+    config::set_retry_post_parse_failures(cli_args.retry_post_parse_failures());
+    if let Some(identity) = cli_args.device_identity() {
+        config::set_device_identity(identity);
+    }
+    if let Some(user_agent) = cli_args.user_agent() {
+        config::set_user_agent(user_agent);
+    }
+    if let Some(dir) = cli_args.schema_watch_dir() {
+        config::set_schema_watch_dir(dir);
+    }
+    if let Some(dir) = cli_args.bulk_cache_dir() {
+        config::set_bulk_cache_dir(dir);
+    }
+    if let Some(dir) = cli_args.dump_unknown_components_dir() {
+        config::set_dump_unknown_components_dir(dir);
+    }
+    if let Some(indent) = cli_args.json_indent() {
+        config::set_json_indent(indent);
+    }
+    config::set_compact_threshold(cli_args.compact_threshold());
+    if cli_args.serde_telemetry() {
+        telemetry::enable();
+    }
+    let max_runtime = cli_args.get_max_runtime();
+    if cli_args.print_config() {
+        return print_config(&cli_args);
+    }
     let cli_args_arc = Arc::new(cli_args);
 
-    let main_result: AppResult<i32> = runtime.block_on(async {
+    let main_future = async {
         let args = cli_args_arc;
 
+        match args.get_command() {
+            Some(Command::Clean { yes }) => return run_clean(&args.get_out_dir(), *yes).await,
+            Some(Command::Serve { port, host }) => {
+                let addr = SocketAddr::new(*host, *port);
+                return serve::serve_output(&args.get_out_dir(), addr).await;
+            }
+            Some(Command::Verify { dir, report }) => {
+                let dir = dir.clone().unwrap_or_else(|| args.get_out_dir());
+                return run_verify(&dir, report.clone()).await;
+            }
+            Some(Command::Completions { .. }) | None => {}
+        }
+
         if let Some(test_file_path) = args.get_test_detail_file() {
             let output_path = args.get_test_output_file();
 
@@ -52,7 +179,34 @@ fn main() -> ExitCode {
                 return Err(AppError::Argument("Test input file not found.".to_string()));
             }
 
-            match testing::test_detail_transform(&test_file_path, output_path).await {
+            let nav_path = args.get_test_nav_file();
+            match testing::test_detail_transform(&test_file_path, output_path, nav_path.as_deref())
+                .await
+            {
+                Ok(_) => Ok(0),
+                Err(e) => {
+                    log(LogLevel::Error, &format!("Test mode failed: {:?}", e));
+                    Ok(1)
+                }
+            }
+        } else if let Some(test_file_path) = args.get_test_list_file() {
+            let output_path = args.get_test_output_file();
+
+            if !test_file_path.exists() {
+                log(
+                    LogLevel::Error,
+                    &format!("Test input file not found: {}", test_file_path.display()),
+                );
+                return Err(AppError::Argument("Test input file not found.".to_string()));
+            }
+
+            match testing::test_list_transform(
+                &test_file_path,
+                output_path,
+                args.get_test_menu_id(),
+            )
+            .await
+            {
                 Ok(_) => Ok(0),
                 Err(e) => {
                     log(LogLevel::Error, &format!("Test mode failed: {:?}", e));
@@ -71,7 +225,27 @@ fn main() -> ExitCode {
 
             let out_dir = args.get_out_dir();
 
-            processor::run(target_langs, out_dir).await
+            if args.use_pipeline() {
+                let conflicts = args.pipeline_conflicts();
+                if !conflicts.is_empty() {
+                    let message = format!(
+                        "{} cannot be used with --pipeline, which does not keep all transformed data for the save phase.",
+                        conflicts.join(", ")
+                    );
+                    log(LogLevel::Error, &message);
+                    return Err(AppError::Argument(message));
+                }
+                pipeline::run(target_langs, out_dir).await
+            } else {
+                processor::run(target_langs, out_dir).await
+            }
+        }
+    };
+
+    let main_result: AppResult<i32> = runtime.block_on(async {
+        match max_runtime {
+            Some(limit) => run_with_watchdog(main_future, limit).await,
+            None => main_future.await,
         }
     });
 
@@ -85,3 +259,143 @@ fn main() -> ExitCode {
         }
     }
 }
+
+fn print_config(args: &CliArgs) -> ExitCode {
+    // Languages are optional here; without a valid -l the list is printed empty.
+    let languages = args.get_languages().unwrap_or_default();
+    let effective = config::EffectiveConfig::resolve(
+        &languages,
+        &args.get_out_dir(),
+        args.use_pipeline(),
+        args.get_max_runtime().map(|limit| limit.as_secs()),
+    );
+    match serde_json::to_string_pretty(&effective) {
+        Ok(json) => {
+            println!("{}", json);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            log(
+                LogLevel::Error,
+                &format!("Failed to serialize configuration: {}", e),
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run_with_watchdog<F>(main_future: F, limit: Duration) -> AppResult<i32>
+where
+    F: Future<Output = AppResult<i32>>,
+{
+    tokio::pin!(main_future);
+    if let Ok(result) = timeout(limit, &mut main_future).await {
+        return result;
+    }
+
+    log(
+        LogLevel::Error,
+        &format!(
+            "Max runtime of {}s exceeded during phase '{}'. Stopping new work and saving completed output...",
+            limit.as_secs(),
+            config::current_phase()
+        ),
+    );
+    config::request_shutdown();
+
+    let grace = Duration::from_secs(config::MAX_RUNTIME_GRACE_SECS);
+    match timeout(grace, &mut main_future).await {
+        Ok(Ok(_)) => log(
+            LogLevel::Warning,
+            "Run wound down after max runtime; output is partial.",
+        ),
+        Ok(Err(e)) => log(
+            LogLevel::Error,
+            &format!("Run failed while winding down after max runtime: {:?}", e),
+        ),
+        Err(_) => log(
+            LogLevel::Error,
+            &format!(
+                "Run did not wind down within {}s (stuck in phase '{}'); aborting.",
+                grace.as_secs(),
+                config::current_phase()
+            ),
+        ),
+    }
+    Ok(config::EXIT_CODE_TIMEOUT)
+}
+
+async fn run_clean(out_dir: &Path, skip_confirm: bool) -> AppResult<i32> {
+    if !out_dir.exists() {
+        log(
+            LogLevel::Info,
+            &format!(
+                "Output directory '{}' does not exist. Nothing to clean.",
+                out_dir.display()
+            ),
+        );
+        return Ok(0);
+    }
+
+    let resolved_dir = match io::check_clean_target(out_dir) {
+        Ok(dir) => dir,
+        Err(e) => {
+            log(LogLevel::Error, &e.to_string());
+            return Err(e);
+        }
+    };
+
+    if !skip_confirm && !confirm_clean(&resolved_dir)? {
+        log(LogLevel::Warning, "Clean aborted by user.");
+        return Ok(1);
+    }
+
+    let removed_count = io::clean_output_directories(&resolved_dir).await?;
+    log(
+        LogLevel::Success,
+        &format!(
+            "Clean complete: removed {} director(ies) under {}",
+            removed_count,
+            resolved_dir.display()
+        ),
+    );
+    Ok(0)
+}
+
+async fn run_verify(dir: &Path, report_path: Option<PathBuf>) -> AppResult<i32> {
+    log(
+        LogLevel::Step,
+        &format!("Verifying output under {}", dir.display()),
+    );
+    let report = match verify::verify_output_dir(dir).await {
+        Ok(report) => report,
+        Err(e) => {
+            log(LogLevel::Error, &e.to_string());
+            return Err(e);
+        }
+    };
+    verify::log_verify_report(&report);
+    let exit_code = if report.is_ok() { 0 } else { 1 };
+    if let Some(path) = report_path {
+        io::save_json(path, report, "Verify report".to_string()).await?;
+    }
+    Ok(exit_code)
+}
+
+fn confirm_clean(out_dir: &Path) -> AppResult<bool> {
+    let kept: Vec<String> = config::CLEAN_KEPT_FILES
+        .iter()
+        .map(|(dir, file)| format!("{}/{}", dir, file))
+        .collect();
+    print!(
+        "This will delete [{}] under '{}' (keeping {}). Continue? [y/N] ",
+        config::OUTPUT_SUBDIRS.join(", "),
+        out_dir.display(),
+        kept.join(", ")
+    );
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
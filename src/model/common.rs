@@ -1,10 +1,244 @@
+use crate::error::{AppError, AppResult};
+use crate::telemetry;
+use crate::transform::util::normalize_lang_code;
 use serde::de::{self, Deserializer, MapAccess, SeqAccess, Unexpected, Visitor};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 use std::fmt;
 
 pub type EntryId = i64;
 pub type MenuId = i64;
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LangCode(String);
+
+impl LangCode {
+    pub fn parse(raw: &str) -> AppResult<Self> {
+        normalize_lang_code(raw)
+            .map(LangCode)
+            .ok_or_else(|| AppError::Argument(format!("Unsupported language code: '{}'", raw)))
+    }
+
+    pub fn all_supported() -> Vec<LangCode> {
+        crate::config::SUPPORTED_LANGS
+            .iter()
+            .map(|lang| LangCode(lang.clone()))
+            .collect()
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for LangCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for LangCode {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The wiki menus this crate knows by id. Any other menu id is carried through
+/// as `Unknown` so converting back to a [`MenuId`] never loses information.
+///
+/// Serializes as its slug (`"characters"`, `"weapons"`, ...), or as the bare
+/// menu id for `Unknown`.
+///
+/// # Examples
+///
+/// ```
+/// use wiki_update::model::common::{MenuCategory, MenuId};
+///
+/// let ids: Vec<MenuId> = MenuCategory::KNOWN.iter().map(|c| c.menu_id()).collect();
+/// assert_eq!(ids, [2, 4, 5, 7, 9, 231, 39]);
+/// for category in MenuCategory::KNOWN {
+///     assert_eq!(MenuCategory::from(category.menu_id()), category);
+///     assert_eq!(MenuCategory::from_slug(category.slug().unwrap()), Some(category));
+/// }
+///
+/// assert!(MenuCategory::from(4).is_equipment());
+/// assert!(!MenuCategory::Characters.is_equipment());
+///
+/// let other = MenuCategory::from(1234);
+/// assert_eq!(other, MenuCategory::Unknown(1234));
+/// assert_eq!(MenuId::from(other), 1234);
+/// assert_eq!(other.slug(), None);
+///
+/// assert_eq!(serde_json::to_string(&MenuCategory::Tcg).unwrap(), r#""tcg""#);
+/// assert_eq!(serde_json::to_string(&other).unwrap(), "1234");
+/// let parsed: Vec<MenuCategory> = serde_json::from_str(r#"["weapons", 1234, 2]"#).unwrap();
+/// assert_eq!(parsed, [MenuCategory::Weapons, other, MenuCategory::Characters]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MenuCategory {
+    Characters,
+    Weapons,
+    Artifacts,
+    Enemies,
+    Materials,
+    Tcg,
+    Events,
+    Unknown(MenuId),
+}
+
+impl MenuCategory {
+    /// Every category except `Unknown`.
+    pub const KNOWN: [MenuCategory; 7] = [
+        MenuCategory::Characters,
+        MenuCategory::Weapons,
+        MenuCategory::Artifacts,
+        MenuCategory::Enemies,
+        MenuCategory::Materials,
+        MenuCategory::Tcg,
+        MenuCategory::Events,
+    ];
+
+    pub fn menu_id(self) -> MenuId {
+        match self {
+            MenuCategory::Characters => crate::config::CHARACTER_MENU_ID,
+            MenuCategory::Weapons => crate::config::WEAPON_MENU_ID,
+            MenuCategory::Artifacts => crate::config::ARTIFACT_MENU_ID,
+            MenuCategory::Enemies => crate::config::ENEMY_MENU_ID,
+            MenuCategory::Materials => crate::config::MATERIAL_MENU_ID,
+            MenuCategory::Tcg => crate::config::TCG_MENU_ID,
+            MenuCategory::Events => crate::config::EVENTS_MENU_ID,
+            MenuCategory::Unknown(id) => id,
+        }
+    }
+
+    /// Stable lowercase name, or `None` for `Unknown`.
+    pub fn slug(self) -> Option<&'static str> {
+        match self {
+            MenuCategory::Characters => Some("characters"),
+            MenuCategory::Weapons => Some("weapons"),
+            MenuCategory::Artifacts => Some("artifacts"),
+            MenuCategory::Enemies => Some("enemies"),
+            MenuCategory::Materials => Some("materials"),
+            MenuCategory::Tcg => Some("tcg"),
+            MenuCategory::Events => Some("events"),
+            MenuCategory::Unknown(_) => None,
+        }
+    }
+
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        Self::KNOWN
+            .into_iter()
+            .find(|category| category.slug() == Some(slug))
+    }
+
+    /// True when `menu_id` or any of `menu_ids` (a page's list menus) is this category.
+    pub fn contains_any(self, menu_id: MenuId, menu_ids: &[MenuId]) -> bool {
+        std::iter::once(&menu_id)
+            .chain(menu_ids)
+            .any(|&id| MenuCategory::from(id) == self)
+    }
+
+    /// Weapons and artifacts: what a character equips.
+    pub fn is_equipment(self) -> bool {
+        matches!(self, MenuCategory::Weapons | MenuCategory::Artifacts)
+    }
+
+    pub fn is_known(self) -> bool {
+        !matches!(self, MenuCategory::Unknown(_))
+    }
+
+    /// Filter key list cards and calendar abstracts read this menu's rarity from.
+    pub fn rarity_key(self) -> &'static str {
+        match self {
+            MenuCategory::Weapons => crate::config::KEY_WEAPON_RARITY,
+            _ => crate::config::KEY_CHAR_RARITY,
+        }
+    }
+
+    /// Filter keys list cards and calendar abstracts show for this menu's entries.
+    pub fn card_filter_keys(self) -> &'static [&'static str] {
+        match self {
+            MenuCategory::Characters => &[
+                crate::config::KEY_CHAR_VISION,
+                crate::config::KEY_CHAR_RARITY,
+            ],
+            MenuCategory::Weapons => &[crate::config::KEY_WEAPON_RARITY],
+            _ => &[],
+        }
+    }
+}
+
+impl From<MenuId> for MenuCategory {
+    fn from(menu_id: MenuId) -> Self {
+        Self::KNOWN
+            .into_iter()
+            .find(|category| category.menu_id() == menu_id)
+            .unwrap_or(MenuCategory::Unknown(menu_id))
+    }
+}
+
+impl From<MenuCategory> for MenuId {
+    fn from(category: MenuCategory) -> Self {
+        category.menu_id()
+    }
+}
+
+impl fmt::Display for MenuCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.slug() {
+            Some(slug) => f.write_str(slug),
+            None => write!(f, "{}", self.menu_id()),
+        }
+    }
+}
+
+impl Serialize for MenuCategory {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.slug() {
+            Some(slug) => serializer.serialize_str(slug),
+            None => serializer.serialize_i64(self.menu_id()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MenuCategory {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MenuCategoryVisitor;
+        impl<'de> Visitor<'de> for MenuCategoryVisitor {
+            type Value = MenuCategory;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("menu category slug or menu id")
+            }
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(MenuCategory::from(v))
+            }
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                i64::try_from(v)
+                    .map(MenuCategory::from)
+                    .map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                MenuCategory::from_slug(v)
+                    .or_else(|| v.trim().parse::<MenuId>().ok().map(MenuCategory::from))
+                    .ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))
+            }
+        }
+        deserializer.deserialize_any(MenuCategoryVisitor)
+    }
+}
+
 pub fn deserialize_flexible_i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
 where
     D: Deserializer<'de>,
@@ -53,11 +287,18 @@ where
     deserializer.deserialize_any(FlexibleI64Visitor)
 }
 
-pub fn deserialize_optional_flexible_i64<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+/// Like [`deserialize_flexible_i64`] but lenient: empty and unusable values become
+/// `None`, and each fallback is counted against `field` in the coercion report.
+pub fn deserialize_optional_flexible_i64<'de, D>(
+    deserializer: D,
+    field: &'static str,
+) -> Result<Option<i64>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    struct OptionalFlexibleI64Visitor;
+    struct OptionalFlexibleI64Visitor {
+        field: &'static str,
+    }
     impl<'de> Visitor<'de> for OptionalFlexibleI64Visitor {
         type Value = Option<i64>;
         fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -85,6 +326,7 @@ where
             if v.fract() == 0.0 && v >= i64::MIN as f64 && v <= i64::MAX as f64 {
                 Ok(Some(v as i64))
             } else {
+                telemetry::record_coercion(self.field, "fractional_float");
                 Ok(None)
             }
         }
@@ -97,7 +339,11 @@ where
             if t.is_empty() {
                 Ok(None)
             } else {
-                Ok(t.parse::<i64>().ok())
+                let parsed = t.parse::<i64>().ok();
+                if parsed.is_none() {
+                    telemetry::record_coercion(self.field, "unparsable_string");
+                }
+                Ok(parsed)
             }
         }
         #[inline]
@@ -110,6 +356,7 @@ where
         }
         #[inline]
         fn visit_bool<E>(self, _v: bool) -> Result<Self::Value, E> {
+            telemetry::record_coercion(self.field, "bool");
             Ok(None)
         }
         #[inline]
@@ -117,6 +364,7 @@ where
         where
             A: MapAccess<'de>,
         {
+            telemetry::record_coercion(self.field, "map");
             Ok(None)
         }
         #[inline]
@@ -124,10 +372,11 @@ where
         where
             A: SeqAccess<'de>,
         {
+            telemetry::record_coercion(self.field, "seq");
             Ok(None)
         }
     }
-    deserializer.deserialize_any(OptionalFlexibleI64Visitor)
+    deserializer.deserialize_any(OptionalFlexibleI64Visitor { field })
 }
 
 pub fn deserialize_optional_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
@@ -138,11 +387,18 @@ where
     Ok(v.filter(|s| !s.trim().is_empty()))
 }
 
-pub fn deserialize_string_or_default<'de, D>(deserializer: D) -> Result<String, D::Error>
+/// Reads a string, treating null and arrays as empty; arrays are counted against
+/// `field` in the coercion report.
+pub fn deserialize_string_or_default<'de, D>(
+    deserializer: D,
+    field: &'static str,
+) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
 {
-    struct StringOrDefaultVisitor;
+    struct StringOrDefaultVisitor {
+        field: &'static str,
+    }
 
     impl<'de> Visitor<'de> for StringOrDefaultVisitor {
         type Value = String;
@@ -183,6 +439,7 @@ where
         where
             A: SeqAccess<'de>,
         {
+            telemetry::record_coercion(self.field, "seq");
             if seq.next_element::<de::IgnoredAny>()?.is_none() {
                 Ok(String::new())
             } else {
@@ -191,5 +448,5 @@ where
         }
     }
 
-    deserializer.deserialize_any(StringOrDefaultVisitor)
+    deserializer.deserialize_any(StringOrDefaultVisitor { field })
 }
@@ -1,3 +1,4 @@
+use crate::config;
 use crate::model::common::{EntryId, MenuId};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -37,6 +38,8 @@ pub enum HtmlNode {
         display_style: String,
         #[serde(default, skip_serializing_if = "Option::is_none", rename = "menuId")]
         menu_id: Option<MenuId>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        link: Option<String>,
     },
     CustomImage {
         url: String,
@@ -56,6 +59,8 @@ pub enum HtmlNode {
         name: String,
         #[serde(default, skip_serializing_if = "String::is_empty")]
         icon_url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        link: Option<String>,
     },
     CustomVideo {
         url: String,
@@ -63,6 +68,10 @@ pub enum HtmlNode {
     CustomMap {
         url: String,
     },
+    /// `--intern-nodes`: stands for the page's `nodeDefs[id]` (see `transform::intern`).
+    Ref {
+        id: usize,
+    },
 }
 
 #[inline]
@@ -90,3 +99,160 @@ impl HtmlNode {
         }
     }
 }
+
+/// Plain text of `nodes` with color markup removed; entries, posts and ruby contribute
+/// their visible names. Whitespace is collapsed to single spaces.
+pub fn extract_text(nodes: &[HtmlNode]) -> String {
+    render(nodes, false)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Readable plain text of `nodes` (`--flatten-html`): headings become `## text` lines,
+/// entries `name xAmount`, ruby its base text; images, videos and maps are dropped and
+/// color markup is stripped. Line breaks inside rich text are kept.
+pub fn render_plain(nodes: &[HtmlNode]) -> String {
+    render(nodes, true)
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn render(nodes: &[HtmlNode], markup: bool) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            HtmlNode::RichText { text, .. } => push_inline(&mut out, text),
+            HtmlNode::Heading { level, text, .. } if markup => {
+                let text = text.trim();
+                if text.is_empty() {
+                    continue;
+                }
+                if !out.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str(&"#".repeat(usize::from((*level).clamp(1, 6))));
+                out.push(' ');
+                out.push_str(text);
+                out.push('\n');
+            }
+            HtmlNode::Heading { text, .. } => push_inline(&mut out, text),
+            HtmlNode::CustomEntry { name, amount, .. } if markup && *amount > 0 => {
+                if !name.trim().is_empty() {
+                    push_inline(&mut out, &format!("{} x{}", name.trim(), amount));
+                }
+            }
+            HtmlNode::CustomEntry { name, .. } | HtmlNode::CustomPost { name, .. } => {
+                push_inline(&mut out, name)
+            }
+            HtmlNode::CustomRuby { rb, .. } => push_inline(&mut out, rb),
+            HtmlNode::CustomImage { .. }
+            | HtmlNode::CustomVideo { .. }
+            | HtmlNode::CustomMap { .. }
+            | HtmlNode::Ref { .. } => {}
+        }
+    }
+    config::RE_COLOR_TAG.replace_all(&out, "").into_owned()
+}
+
+// Adjacent inline pieces (text, entry names) need a separating space unless one side
+// already provides whitespace or the next piece opens with closing punctuation.
+fn push_inline(out: &mut String, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let closes = text.starts_with(|c: char| {
+        matches!(
+            c,
+            '.' | ',' | ';' | ':' | '!' | '?' | ')' | '。' | '，' | '、' | '！' | '？' | '）'
+        )
+    });
+    if !out.is_empty()
+        && !out.ends_with(char::is_whitespace)
+        && !text.starts_with(char::is_whitespace)
+        && !closes
+    {
+        out.push(' ');
+    }
+    out.push_str(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_value, json, Value};
+
+    fn nodes(value: Value) -> Vec<HtmlNode> {
+        from_value(value).unwrap()
+    }
+
+    #[test]
+    fn rich_text_keeps_line_breaks_and_drops_color() {
+        let text = nodes(json!([
+            { "type": "RichText", "text": "<color=#ffd780ff>Pyro</color> DMG  \nLine two" }
+        ]));
+        assert_eq!(render_plain(&text), "Pyro DMG\nLine two");
+        assert_eq!(extract_text(&text), "Pyro DMG Line two");
+    }
+
+    #[test]
+    fn headings_get_their_own_marked_line() {
+        let text = nodes(json!([
+            { "type": "RichText", "text": "Intro" },
+            { "type": "Heading", "level": 2, "text": " Skills " },
+            { "type": "RichText", "text": "Body" },
+            { "type": "Heading", "level": 9, "text": "Deep" },
+            { "type": "Heading", "level": 1, "text": "  " }
+        ]));
+        assert_eq!(render_plain(&text), "Intro\n## Skills\nBody\n###### Deep");
+        assert_eq!(extract_text(&text), "Intro Skills Body Deep");
+    }
+
+    #[test]
+    fn entries_show_their_amount() {
+        let text = nodes(json!([
+            { "type": "CustomEntry", "epId": 1, "name": " Mora ", "amount": 20000 },
+            { "type": "CustomEntry", "epId": 2, "name": "Hero's Wit" },
+            { "type": "CustomEntry", "epId": 3, "amount": 4 }
+        ]));
+        assert_eq!(render_plain(&text), "Mora x20000 Hero's Wit");
+        assert_eq!(extract_text(&text), "Mora Hero's Wit");
+    }
+
+    #[test]
+    fn posts_and_ruby_contribute_their_visible_text() {
+        let text = nodes(json!([
+            { "type": "CustomPost", "postId": 7, "name": "Fan art" },
+            { "type": "CustomRuby", "rb": "稲妻", "rt": "いなずま" }
+        ]));
+        assert_eq!(render_plain(&text), "Fan art 稲妻");
+        assert_eq!(extract_text(&text), "Fan art 稲妻");
+    }
+
+    #[test]
+    fn media_and_refs_are_dropped() {
+        let text = nodes(json!([
+            { "type": "CustomImage", "url": "https://example.test/a.png" },
+            { "type": "CustomVideo", "url": "https://example.test/a.mp4" },
+            { "type": "CustomMap", "url": "https://example.test/map" },
+            { "type": "Ref", "id": 0 }
+        ]));
+        assert_eq!(render_plain(&text), "");
+        assert_eq!(extract_text(&text), "");
+    }
+
+    #[test]
+    fn closing_punctuation_is_not_spaced() {
+        let text = nodes(json!([
+            { "type": "CustomEntry", "epId": 1, "name": "Amber" },
+            { "type": "RichText", "text": ", a scout." },
+            { "type": "CustomEntry", "epId": 2, "name": "Kaeya" },
+            { "type": "RichText", "text": "。" }
+        ]));
+        assert_eq!(render_plain(&text), "Amber, a scout. Kaeya。");
+    }
+}
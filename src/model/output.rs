@@ -27,6 +27,10 @@ pub struct OutputListItem {
     pub desc: Option<String>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub filter_values: HashMap<String, FilterValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_filters: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
@@ -43,10 +47,55 @@ pub struct OutputListFile {
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub menu_name: String,
     pub total_items: usize,
+    /// Distinct sorted values per filter key across `list`, for building filter
+    /// dropdowns without scanning every item. Only set with `--emit-filter-vocabulary`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub filter_vocabulary: HashMap<String, Vec<String>>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub list: Vec<OutputListItem>,
 }
 
+/// `list/<lang>/index.json`: the list files written for a language.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputListIndex {
+    pub language: String,
+    pub total_menus: usize,
+    pub menus: Vec<OutputListIndexEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputListIndexEntry {
+    pub menu_id: MenuId,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub menu_name: String,
+    pub total_items: usize,
+}
+
+/// `detail/<lang>/index.json`: a slim projection of every detail page written
+/// for a language, ordered by id.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputDetailIndex {
+    pub language: String,
+    pub total_entries: usize,
+    pub entries: Vec<OutputDetailIndexEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputDetailIndexEntry {
+    #[serde(rename = "epId")]
+    pub id: EntryId,
+    pub menu_id: MenuId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    pub version: i64,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct OutputNavMenuItem {
@@ -57,6 +106,25 @@ pub struct OutputNavMenuItem {
     pub icon_url: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputBulkEntry {
+    #[serde(rename = "epId")]
+    pub id: EntryId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_lang: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct OutputBaseInfoItem {
@@ -65,6 +133,8 @@ pub struct OutputBaseInfoItem {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub value: Option<Vec<HtmlNode>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub is_material: Option<bool>,
 }
 
@@ -78,6 +148,8 @@ pub struct OutputAscensionItem {
     pub combat_stats: Value,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub materials: Option<Vec<HtmlNode>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub materials_text: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
@@ -91,11 +163,15 @@ pub struct OutputTalentItem {
     pub icon_url: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_text: Option<String>,
     #[serde(default = "default_value_null")]
     pub attributes: Value,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub materials: Option<Vec<Option<Vec<HtmlNode>>>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub materials_text: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub talent_img: Option<String>,
 }
 
@@ -108,6 +184,8 @@ pub struct OutputSummaryItem {
     pub name: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_text: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
@@ -117,6 +195,8 @@ pub struct OutputStoryItem {
     pub title: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_text: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
@@ -148,6 +228,8 @@ pub struct OutputGalleryCharacterItem {
     pub img: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub img_desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub img_desc_text: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
@@ -159,6 +241,8 @@ pub struct OutputArtifactListItem {
     pub position: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_text: Option<String>,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub icon_url: String,
 }
@@ -166,6 +250,10 @@ pub struct OutputArtifactListItem {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct OutputReliquaryEffect {
+    // Effect text by piece count, including counts other than 2 and 4.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub effects: HashMap<u8, String>,
+    // Copies of `effects[2]` and `effects[4]` for clients that read the fixed fields.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub two_set_effect: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -179,6 +267,8 @@ pub struct OutputTextualResearchItem {
     pub title: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_text: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
@@ -192,6 +282,8 @@ pub struct OutputTimelineEvent {
     pub bg_url: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub contents: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents_text: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
@@ -229,6 +321,179 @@ pub struct OutputTcgData {
     pub header_imgs: Vec<OutputTcgHeaderImage>,
     #[serde(default, skip_serializing_if = "is_zero_i64")]
     pub hp: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub costs: Vec<OutputTcgCost>,
+}
+
+/// One entry of a TCG cost field that lists several costs, e.g. "energy:2,dice:1".
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputTcgCost {
+    pub icon_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<i64>,
+    // Set for costs taken from `cost_icon_type_any`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub any: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputGiftRewardItem {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub icon_url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ep_id: Option<EntryId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputGiftRewardData {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub liked: Vec<OutputGiftRewardItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disliked: Vec<OutputGiftRewardItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMapPoint {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub icon_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, rename = "epId", skip_serializing_if = "Option::is_none")]
+    pub ep_id: Option<EntryId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMapData {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    pub points: Vec<OutputMapPoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMapResourceData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_count: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputBuildReference {
+    #[serde(rename = "epId")]
+    pub ep_id: EntryId,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub icon_url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputBuildSection {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weapons: Vec<OutputBuildReference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<OutputBuildReference>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputEventCalendarData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rewards: Option<Vec<HtmlNode>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rewards_text: Option<String>,
+    pub description: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputAchievementItem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ep_id: Option<EntryId>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_text: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub icon_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_hidden: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputAchievementGroupData {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub group_name: String,
+    pub items: Vec<OutputAchievementItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputProgressionEntry {
+    pub index: u8,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub desc: Vec<HtmlNode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ProgressionKind {
+    #[default]
+    Constellation,
+    Refinement,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputProgressionData {
+    pub kind: ProgressionKind,
+    pub entries: Vec<OutputProgressionEntry>,
+}
+
+/// Event start and end dates (`YYYY-MM-DD`) found in an event page's text.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct OutputEventWindow {
+    pub start: String,
+    pub end: String,
 }
 
 fn is_zero_i64(num: &i64) -> bool {
@@ -246,14 +511,22 @@ pub enum ComponentData {
     Voice(Vec<OutputVoiceItem>),
     GalleryCharacter(Vec<OutputGalleryCharacterItem>),
     ArtifactList(HashMap<String, OutputArtifactListItem>),
+    // These must precede ReliquarySetEffect: its all-optional fields would swallow any object.
+    Map(OutputMapData),
+    EventCalendar(OutputEventCalendarData),
+    AchievementGroup(OutputAchievementGroupData),
+    Progression(OutputProgressionData),
     ReliquarySetEffect(OutputReliquaryEffect),
     MapUrl(String),
+    MapResource(OutputMapResourceData),
     TextualResearch(Vec<OutputTextualResearchItem>),
     Timeline(Vec<OutputTimelineEvent>),
     VideoCollection(Vec<OutputVideoCollectionItem>),
     Customize(Vec<HtmlNode>),
     Tcg(OutputTcgData),
     DropMaterial(Vec<HtmlNode>),
+    GiftReward(OutputGiftRewardData),
+    Build(Vec<OutputBuildSection>),
     Unknown(Value),
 }
 
@@ -270,14 +543,123 @@ pub struct OutputDetailPage {
     pub icon_url: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub header_img_url: Option<String>,
+    // The one image to show for the page: header, then Character Card, then icon.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_image: Option<String>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub filter_values: HashMap<String, FilterValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_filters: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
     pub menu_id: MenuId,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub menu_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub menu_ids: Vec<MenuId>,
     pub version: i64,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub components: HashMap<String, ComponentData>,
+    // --flatten-html text for components that are a bare node list (customize, dropMaterial).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub components_text: HashMap<String, String>,
+    // Component keys whose modules are popped (collapsed) on the web wiki.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub popped_components: Vec<String>,
+    // Component keys whose HTML was too deep or broken for the regular parser and
+    // was recovered as flat text.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded_components: Vec<String>,
+    // --intern-nodes definitions referenced by `Ref` nodes in `components`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub node_defs: Vec<Vec<HtmlNode>>,
+    // --derive-event-windows: the single date range found on an event page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_window: Option<OutputEventWindow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias_of: Option<EntryId>,
+    #[serde(skip)]
+    pub component_ids: HashMap<String, String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentWrapper<'a> {
+    pub component_id: &'a str,
+    pub data: &'a ComponentData,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentArrayEntry<'a> {
+    pub id: &'a str,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component_id: Option<&'a str>,
+    pub data: &'a ComponentData,
+}
+
+impl OutputDetailPage {
+    /// Serializes the page with `components` as an array of `{ id, type, data }`
+    /// sorted by id. Ids are unique today since components are merged per id,
+    /// but consumers should not rely on it: a module-preserving output mode
+    /// (`--preserve-modules`) would repeat ids across modules.
+    pub fn components_array_view(&self, include_component_ids: bool) -> serde_json::Result<Value> {
+        let mut entries: Vec<ComponentArrayEntry<'_>> = self
+            .components
+            .iter()
+            .map(|(key, data)| ComponentArrayEntry {
+                id: key.as_str(),
+                kind: data.discriminant_str(),
+                component_id: include_component_ids.then(|| {
+                    self.component_ids
+                        .get(key)
+                        .map_or(key.as_str(), String::as_str)
+                }),
+                data,
+            })
+            .collect();
+        entries.sort_by(|a, b| a.id.cmp(b.id));
+
+        let mut page_value = serde_json::to_value(self)?;
+        if let Value::Object(map) = &mut page_value {
+            if !entries.is_empty() {
+                map.insert("components".to_string(), serde_json::to_value(entries)?);
+            }
+        }
+        Ok(page_value)
+    }
+
+    pub fn component_id_view(&self) -> serde_json::Result<Value> {
+        let wrapped: HashMap<&str, ComponentWrapper<'_>> = self
+            .components
+            .iter()
+            .map(|(key, data)| {
+                let component_id = self
+                    .component_ids
+                    .get(key)
+                    .map_or(key.as_str(), String::as_str);
+                (key.as_str(), ComponentWrapper { component_id, data })
+            })
+            .collect();
+
+        let mut page_value = serde_json::to_value(self)?;
+        if let Value::Object(map) = &mut page_value {
+            if !wrapped.is_empty() {
+                map.insert("components".to_string(), serde_json::to_value(wrapped)?);
+            }
+        }
+        Ok(page_value)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputAliasEntry {
+    #[serde(rename = "epId")]
+    pub id: EntryId,
+    pub alias_of: EntryId,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
@@ -0,0 +1,210 @@
+use crate::error::{AppError, AppResult};
+use crate::logging::{log, LogLevel};
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderValue};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde_json::json;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+use tokio::net::TcpListener;
+
+/// Serves the files under `root` read-only, with permissive CORS so a local frontend
+/// on another port can fetch them. Directories answer with a JSON listing.
+pub async fn serve_output(root: &Path, addr: SocketAddr) -> AppResult<i32> {
+    let root =
+        Arc::new(fs::canonicalize(root).await.map_err(|e| {
+            AppError::Argument(format!("Cannot serve '{}': {}", root.display(), e))
+        })?);
+    let listener = TcpListener::bind(addr).await?;
+    log(
+        LogLevel::Success,
+        &format!(
+            "Serving {} at http://{} (Ctrl+C to stop)",
+            root.display(),
+            listener.local_addr().unwrap_or(addr)
+        ),
+    );
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                log(LogLevel::Warning, &format!("Accept failed: {}", e));
+                continue;
+            }
+        };
+        let root = Arc::clone(&root);
+        tokio::spawn(async move {
+            let service = service_fn(move |req| handle_request(Arc::clone(&root), req));
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                log(
+                    LogLevel::Warning,
+                    &format!("Connection from {} failed: {}", peer, e),
+                );
+            }
+        });
+    }
+}
+
+async fn handle_request(
+    root: Arc<PathBuf>,
+    req: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let response = match *req.method() {
+        Method::OPTIONS => respond(StatusCode::NO_CONTENT, Bytes::new(), None),
+        Method::GET | Method::HEAD => {
+            let mut response = serve_path(&root, req.uri().path()).await;
+            if req.method() == Method::HEAD {
+                *response.body_mut() = Full::new(Bytes::new());
+            }
+            response
+        }
+        _ => respond(
+            StatusCode::METHOD_NOT_ALLOWED,
+            Bytes::from_static(b"Method not allowed"),
+            Some("text/plain; charset=utf-8"),
+        ),
+    };
+    log(
+        LogLevel::Info,
+        &format!(
+            "{} {} -> {}",
+            req.method(),
+            req.uri().path(),
+            response.status().as_u16()
+        ),
+    );
+    Ok(response)
+}
+
+async fn serve_path(root: &Path, uri_path: &str) -> Response<Full<Bytes>> {
+    let not_found = || {
+        respond(
+            StatusCode::NOT_FOUND,
+            Bytes::from_static(b"Not found"),
+            Some("text/plain; charset=utf-8"),
+        )
+    };
+    let Some(path) = resolve_path(root, uri_path) else {
+        return not_found();
+    };
+    // Symlinks may point outside the served directory.
+    let Ok(path) = fs::canonicalize(&path).await else {
+        return not_found();
+    };
+    if !path.starts_with(root) {
+        return not_found();
+    }
+
+    let is_dir = fs::metadata(&path).await.is_ok_and(|meta| meta.is_dir());
+    if is_dir {
+        return match list_dir(&path).await {
+            Ok(entries) => respond(
+                StatusCode::OK,
+                Bytes::from(json!(entries).to_string()),
+                Some("application/json; charset=utf-8"),
+            ),
+            Err(_) => not_found(),
+        };
+    }
+    match fs::read(&path).await {
+        Ok(contents) => respond(
+            StatusCode::OK,
+            Bytes::from(contents),
+            Some(content_type(&path)),
+        ),
+        Err(_) => not_found(),
+    }
+}
+
+// Maps a request path onto `root`, refusing anything that climbs out of it.
+fn resolve_path(root: &Path, uri_path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(uri_path)?;
+    let mut path = root.to_path_buf();
+    for component in Path::new(decoded.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+fn percent_decode(raw: &str) -> Option<String> {
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = raw.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+async fn list_dir(dir: &Path) -> std::io::Result<Vec<String>> {
+    let mut entries = Vec::new();
+    let mut read_dir = fs::read_dir(dir).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        let mut name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type().await?.is_dir() {
+            name.push('/');
+        }
+        entries.push(name);
+    }
+    entries.sort_unstable();
+    Ok(entries)
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => "application/json; charset=utf-8",
+        Some("html") => "text/html; charset=utf-8",
+        Some("txt" | "log") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+fn respond(
+    status: StatusCode,
+    body: Bytes,
+    content_type: Option<&'static str>,
+) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(body));
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, HEAD, OPTIONS"),
+    );
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static("*"),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    if let Some(content_type) = content_type {
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    }
+    response
+}
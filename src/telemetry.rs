@@ -0,0 +1,52 @@
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::RwLock;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static COERCION_COUNTERS: Lazy<RwLock<HashMap<(&'static str, &'static str), AtomicU64>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Counts one fallback taken while reading `field` (e.g. `ApiDetailPage.menu_id`);
+/// `kind` says which fallback it was.
+#[cold]
+pub fn record_coercion(field: &'static str, kind: &'static str) {
+    if !is_enabled() {
+        return;
+    }
+    let id = (field, kind);
+    if let Ok(counters) = COERCION_COUNTERS.read() {
+        if let Some(counter) = counters.get(&id) {
+            counter.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    }
+    if let Ok(mut counters) = COERCION_COUNTERS.write() {
+        counters
+            .entry(id)
+            .or_insert_with(|| AtomicU64::new(0))
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Non-zero counts grouped by field, then by kind.
+pub fn coercion_snapshot() -> BTreeMap<&'static str, BTreeMap<&'static str, u64>> {
+    let mut snapshot: BTreeMap<&'static str, BTreeMap<&'static str, u64>> = BTreeMap::new();
+    if let Ok(counters) = COERCION_COUNTERS.read() {
+        for (&(field, kind), counter) in counters.iter() {
+            let count = counter.load(Ordering::Relaxed);
+            if count > 0 {
+                snapshot.entry(field).or_default().insert(kind, count);
+            }
+        }
+    }
+    snapshot
+}
@@ -1,14 +1,23 @@
-use crate::api::model::{ApiDetailResponse, ApiWrapper};
+use crate::api::model::{
+    ApiDetailResponse, ApiListItem, ApiListResponse, ApiNavResponse, ApiWrapper,
+};
 use crate::error::{AppError, AppResult};
 use crate::io;
 use crate::logging::{log, LogLevel};
+use crate::model::common::MenuId;
 use crate::transform;
 use crate::transform::bulk::BulkStore;
+use crate::transform::common::DetailFallbacks;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
 
-pub async fn test_detail_transform(input_path: &Path, output_path: PathBuf) -> AppResult<()> {
+pub async fn test_detail_transform(
+    input_path: &Path,
+    output_path: PathBuf,
+    nav_path: Option<&Path>,
+) -> AppResult<()> {
     log(
         LogLevel::Info,
         &format!("--- Running Detail Transform Test ---"),
@@ -46,11 +55,18 @@ pub async fn test_detail_transform(input_path: &Path, output_path: PathBuf) -> A
 
     let bulk_store = Arc::new(BulkStore::default());
     let lang = "test-lang";
+    let fallbacks = match nav_path {
+        Some(path) => Arc::new(DetailFallbacks {
+            nav: Arc::new(load_nav_lookup(path).await?),
+            ..Default::default()
+        }),
+        None => Default::default(),
+    };
 
     log(LogLevel::Info, "Starting transformation...");
 
     let transform_result =
-        transform::detail::transform_detail_page(raw_page, bulk_store, lang).await;
+        transform::detail::transform_detail_page(raw_page, bulk_store, fallbacks, lang).await;
     log(LogLevel::Info, "Transformation finished.");
 
     match transform_result {
@@ -58,7 +74,7 @@ pub async fn test_detail_transform(input_path: &Path, output_path: PathBuf) -> A
             log(LogLevel::Success, "Transformation successful.");
             let log_ctx = format!("Test Detail File (Entry: {})", output_page.id);
 
-            match io::save_json(output_path.clone(), output_page, log_ctx).await {
+            match io::save_detail_page(output_path.clone(), output_page, log_ctx).await {
                 Ok(true) => {
                     log(
                         LogLevel::Success,
@@ -96,3 +112,68 @@ pub async fn test_detail_transform(input_path: &Path, output_path: PathBuf) -> A
         }
     }
 }
+
+pub async fn test_list_transform(
+    input_path: &Path,
+    output_path: PathBuf,
+    menu_id: MenuId,
+) -> AppResult<()> {
+    log(LogLevel::Info, "--- Running List Transform Test ---");
+    log(
+        LogLevel::Info,
+        &format!("Input file: {} (menu {})", input_path.display(), menu_id),
+    );
+
+    let json_content = fs::read_to_string(input_path)
+        .await
+        .map_err(AppError::from)?;
+    let wrapper: ApiWrapper<ApiListResponse> =
+        serde_json::from_str(&json_content).map_err(AppError::from)?;
+    let list_response = wrapper.data.ok_or_else(|| {
+        AppError::response_invalid("Missing 'data' field", "test list", "test-lang")
+    })?;
+    let raw_items = list_response
+        .list
+        .into_iter()
+        .map(serde_json::from_value::<ApiListItem>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(AppError::from)?;
+
+    let bulk_store = BulkStore::default();
+    let Some(list_file) = transform::list::transform_list_file(
+        raw_items,
+        &bulk_store,
+        "test-lang",
+        menu_id,
+        String::new(),
+    ) else {
+        log(
+            LogLevel::Warning,
+            "Transformation resulted in no output. No file generated.",
+        );
+        return Ok(());
+    };
+
+    let log_ctx = format!("Test List File (Menu: {})", menu_id);
+    io::save_json(output_path.clone(), list_file, log_ctx).await?;
+    log(
+        LogLevel::Success,
+        &format!(
+            "Successfully saved transformed data to {}",
+            output_path.display()
+        ),
+    );
+    Ok(())
+}
+
+// Menu names from a local nav response, as the detail transform gets them from Phase 1.
+async fn load_nav_lookup(path: &Path) -> AppResult<HashMap<MenuId, String>> {
+    log(LogLevel::Info, &format!("Nav file: {}", path.display()));
+    let content = fs::read_to_string(path).await.map_err(AppError::from)?;
+    let wrapper: ApiWrapper<ApiNavResponse> =
+        serde_json::from_str(&content).map_err(AppError::from)?;
+    let nav = wrapper.data.ok_or_else(|| {
+        AppError::response_invalid("Missing 'data' field", "test nav", "test-lang")
+    })?;
+    Ok(transform::build_nav_lookup(&nav.nav))
+}
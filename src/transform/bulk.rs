@@ -1,6 +1,11 @@
-use crate::api::model::ApiBulkPage;
+use crate::api::model::{ApiBulkPage, ApiDetailPage, ApiPostPreview};
+use crate::config;
 use crate::error::AppResult;
+use crate::logging::{log, LogLevel};
 use crate::model::common::EntryId;
+use crate::model::html;
+use crate::model::output::OutputBulkEntry;
+use crate::transform::{html_parser, util};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
@@ -9,83 +14,272 @@ pub struct BulkInfo {
     pub name: Option<String>,
     pub desc: Option<String>,
     pub best_icon_url: Option<String>,
+    // Language each field was taken from; only fallbacks differ from the store's language.
+    pub name_lang: Option<String>,
+    pub desc_lang: Option<String>,
+    pub icon_lang: Option<String>,
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct BulkStore(Arc<HashMap<EntryId, BulkInfo>>);
+pub struct BulkStore {
+    entries: Arc<HashMap<EntryId, BulkInfo>>,
+    posts: Arc<HashMap<EntryId, BulkInfo>>,
+}
 
 impl BulkStore {
+    pub fn from_map(map: HashMap<EntryId, BulkInfo>) -> Self {
+        BulkStore {
+            entries: Arc::new(map),
+            posts: Arc::default(),
+        }
+    }
+
+    pub fn with_post_previews(mut self, posts: HashMap<EntryId, BulkInfo>) -> Self {
+        self.posts = Arc::new(posts);
+        self
+    }
+
+    pub fn into_inner(self) -> HashMap<EntryId, BulkInfo> {
+        Arc::try_unwrap(self.entries).unwrap_or_else(|arc| (*arc).clone())
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&EntryId, &BulkInfo)> {
+        self.entries.iter()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
     #[inline]
     pub fn get(&self, id: &EntryId) -> Option<&BulkInfo> {
-        self.0.get(id)
+        self.entries.get(id)
     }
     #[inline]
     pub fn contains_key(&self, id: &EntryId) -> bool {
-        self.0.contains_key(id)
+        self.entries.contains_key(id)
     }
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.entries.is_empty()
     }
 
     #[inline]
     pub fn get_name(&self, id: EntryId) -> Option<&str> {
-        self.0.get(&id).and_then(|info| info.name.as_deref())
+        self.entries.get(&id).and_then(|info| info.name.as_deref())
     }
 
     #[inline]
     pub fn get_icon(&self, id: EntryId) -> Option<&str> {
-        self.0
+        self.entries
             .get(&id)
             .and_then(|info| info.best_icon_url.as_deref())
     }
 
     #[inline]
     pub fn get_desc(&self, id: EntryId) -> Option<&str> {
-        self.0.get(&id).and_then(|info| info.desc.as_deref())
+        self.entries.get(&id).and_then(|info| info.desc.as_deref())
+    }
+
+    #[inline]
+    pub fn get_post_name(&self, post_id: EntryId) -> Option<&str> {
+        self.posts
+            .get(&post_id)
+            .and_then(|info| info.name.as_deref())
     }
+
+    #[inline]
+    pub fn get_post_icon(&self, post_id: EntryId) -> Option<&str> {
+        self.posts
+            .get(&post_id)
+            .and_then(|info| info.best_icon_url.as_deref())
+    }
+}
+
+pub fn build_detail_name_map<'a>(
+    details: impl IntoIterator<Item = &'a ApiDetailPage>,
+) -> HashMap<EntryId, String> {
+    details
+        .into_iter()
+        .filter_map(|page| {
+            let name = util::sanitize_display_string(&page.name)?;
+            match page.id {
+                Some(id) if id > 0 => Some((id, name)),
+                _ => None,
+            }
+        })
+        .collect()
 }
 
 pub fn process_bulk_data(
     primary_bulk: HashMap<EntryId, ApiBulkPage>,
     fallback_map: HashMap<String, HashMap<EntryId, ApiBulkPage>>,
     all_ids_for_primary_lang: &HashSet<EntryId>,
+    detail_name_map: &HashMap<EntryId, String>,
+    lang: &str,
 ) -> AppResult<BulkStore> {
-    let mut store_map = HashMap::with_capacity(all_ids_for_primary_lang.len());
-
-    for &id in all_ids_for_primary_lang {
-        let mut info = BulkInfo::default();
-        let mut primary_icon_valid = false;
-
-        if let Some(primary_page) = primary_bulk.get(&id) {
-            info.name = primary_page.name.clone();
-            info.desc = primary_page.desc.clone();
-            if let Some(icon) = primary_page.icon_url.as_deref() {
-                if !icon.is_empty() && !icon.contains("invalid-file") {
-                    info.best_icon_url = Some(icon.to_string());
+    let parse_desc = config::parse_bulk_desc();
+    let primary_bulk = retain_requested(primary_bulk, all_ids_for_primary_lang, lang);
+    let primary_names: HashMap<&str, EntryId> = primary_bulk
+        .values()
+        .filter_map(|page| {
+            let name = page.name.as_deref()?.trim();
+            (!name.is_empty()).then_some((name, page.id))
+        })
+        .collect();
+    let mut fallback_bulks: Vec<(String, HashMap<EntryId, ApiBulkPage>)> = fallback_map
+        .into_iter()
+        .map(|(fallback_lang, pages)| {
+            let pages = retain_requested(pages, all_ids_for_primary_lang, &fallback_lang);
+            let pages = drop_name_collisions(pages, &primary_names, &fallback_lang);
+            (fallback_lang, pages)
+        })
+        .collect();
+    fallback_bulks.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+    let store_map: HashMap<EntryId, BulkInfo> = all_ids_for_primary_lang
+        .iter()
+        .filter_map(|&id| {
+            let mut info = BulkInfo::default();
+            let mut primary_icon_valid = false;
+
+            if let Some(primary_page) = primary_bulk.get(&id) {
+                info.name = primary_page
+                    .name
+                    .as_deref()
+                    .and_then(util::sanitize_display_string);
+                info.name_lang = info.name.as_ref().map(|_| lang.to_string());
+                info.desc = if parse_desc {
+                    primary_page
+                        .desc
+                        .as_deref()
+                        .map(|desc| desc_to_text(desc, id, lang))
+                } else {
+                    primary_page.desc.clone()
+                }
+                .and_then(|desc| util::sanitize_display_string(&desc));
+                info.desc_lang = info.desc.as_ref().map(|_| lang.to_string());
+                if let Some(icon) = primary_page
+                    .icon_url
+                    .as_deref()
+                    .and_then(util::normalize_asset_url)
+                {
+                    info.best_icon_url = Some(icon);
+                    info.icon_lang = Some(lang.to_string());
                     primary_icon_valid = true;
                 }
             }
-        }
 
-        if !primary_icon_valid {
-            for fallback_bulk in fallback_map.values() {
-                if let Some(fallback_page) = fallback_bulk.get(&id) {
-                    if let Some(fallback_icon) = fallback_page.icon_url.as_deref() {
-                        if !fallback_icon.is_empty() && !fallback_icon.contains("invalid-file") {
-                            info.best_icon_url = Some(fallback_icon.to_string());
+            if !primary_icon_valid {
+                for (fallback_lang, fallback_bulk) in &fallback_bulks {
+                    if let Some(fallback_page) = fallback_bulk.get(&id) {
+                        if let Some(fallback_icon) = fallback_page
+                            .icon_url
+                            .as_deref()
+                            .and_then(util::normalize_asset_url)
+                        {
+                            info.best_icon_url = Some(fallback_icon);
+                            info.icon_lang = Some(fallback_lang.clone());
                             break;
                         }
                     }
                 }
             }
-        }
 
-        if info.name.is_some() || info.desc.is_some() || info.best_icon_url.is_some() {
-            store_map.insert(id, info);
+            if info.name.is_none() {
+                if let Some(detail_name) = detail_name_map.get(&id) {
+                    info.name = Some(detail_name.clone());
+                    info.name_lang = Some(lang.to_string());
+                }
+            }
+
+            (info.name.is_some() || info.desc.is_some() || info.best_icon_url.is_some())
+                .then_some((id, info))
+        })
+        .collect();
+
+    Ok(BulkStore::from_map(store_map))
+}
+
+// Pages keyed under an id we never asked for (or whose own id disagrees with the
+// key) can't be trusted to describe the requested entry.
+fn retain_requested(
+    pages: HashMap<EntryId, ApiBulkPage>,
+    requested: &HashSet<EntryId>,
+    lang: &str,
+) -> HashMap<EntryId, ApiBulkPage> {
+    let received = pages.len();
+    let kept: HashMap<EntryId, ApiBulkPage> = pages
+        .into_iter()
+        .filter(|(id, page)| page.id == *id && requested.contains(id))
+        .collect();
+    if kept.len() < received {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Bulk [{}]: dropped {} page(s) for ids that were not requested.",
+                lang,
+                received - kept.len()
+            ),
+        );
+    }
+    kept
+}
+
+// A fallback page carrying exactly the primary name of a *different* entry was most
+// likely returned for the wrong id; using its icon would mix two entries.
+fn drop_name_collisions(
+    pages: HashMap<EntryId, ApiBulkPage>,
+    primary_names: &HashMap<&str, EntryId>,
+    fallback_lang: &str,
+) -> HashMap<EntryId, ApiBulkPage> {
+    pages
+        .into_iter()
+        .filter(|(id, page)| {
+            let Some(other_id) = page
+                .name
+                .as_deref()
+                .and_then(|name| primary_names.get(name.trim()))
+                .filter(|&&other_id| other_id != *id)
+            else {
+                return true;
+            };
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "Bulk fallback [{}]: entry {} has the primary name of entry {} ('{}'); ignoring it.",
+                    fallback_lang,
+                    id,
+                    other_id,
+                    page.name.as_deref().unwrap_or_default().trim()
+                ),
+            );
+            false
+        })
+        .collect()
+}
+
+// Bulk descriptions occasionally carry markup; flatten it to plain text so it
+// doesn't render literally. Plain strings pass through untouched.
+fn desc_to_text(desc: &str, id: EntryId, lang: &str) -> String {
+    if !(desc.contains('<') && desc.contains('>')) {
+        return desc.to_string();
+    }
+    match html_parser::parse_html_content(desc, id, lang) {
+        Ok(nodes) => html::extract_text(&nodes),
+        Err(e) => {
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "Bulk desc [{} / {}] could not be parsed as HTML, keeping raw: {}",
+                    lang, id, e
+                ),
+            );
+            desc.to_string()
         }
     }
-    Ok(BulkStore(Arc::new(store_map)))
 }
 
 #[inline]
@@ -98,7 +292,262 @@ pub fn resolve_name(id: EntryId, bulk_store: &BulkStore) -> Option<String> {
     bulk_store.get_name(id).map(String::from)
 }
 
+#[inline]
+pub fn resolve_post_icon(post_id: EntryId, bulk_store: &BulkStore) -> Option<String> {
+    bulk_store.get_post_icon(post_id).map(String::from)
+}
+
+#[inline]
+pub fn resolve_post_name(post_id: EntryId, bulk_store: &BulkStore) -> Option<String> {
+    bulk_store.get_post_name(post_id).map(String::from)
+}
+
 #[inline]
 pub fn resolve_desc(id: EntryId, bulk_store: &BulkStore) -> Option<String> {
     bulk_store.get_desc(id).map(String::from)
 }
+
+pub fn process_post_previews(
+    previews: HashMap<EntryId, ApiPostPreview>,
+) -> HashMap<EntryId, BulkInfo> {
+    previews
+        .into_iter()
+        .filter_map(|(post_id, preview)| {
+            let info = BulkInfo {
+                name: preview
+                    .name
+                    .as_deref()
+                    .and_then(util::sanitize_display_string),
+                desc: None,
+                best_icon_url: preview
+                    .icon_url
+                    .as_deref()
+                    .and_then(util::normalize_asset_url),
+                ..Default::default()
+            };
+            (post_id > 0 && (info.name.is_some() || info.best_icon_url.is_some()))
+                .then_some((post_id, info))
+        })
+        .collect()
+}
+
+pub fn build_bulk_entries(bulk_store: &BulkStore, lang: &str) -> Vec<OutputBulkEntry> {
+    let non_empty = |v: &Option<String>| v.as_deref().and_then(util::sanitize_display_string);
+    // Provenance is only written when it differs from the file's language.
+    let foreign = |v: &Option<String>| v.clone().filter(|source| source != lang);
+
+    let mut entries: Vec<OutputBulkEntry> = bulk_store
+        .iter()
+        .filter_map(|(&id, info)| {
+            let entry = OutputBulkEntry {
+                id,
+                name: non_empty(&info.name),
+                desc: non_empty(&info.desc),
+                icon_url: non_empty(&info.best_icon_url),
+                name_lang: foreign(&info.name_lang),
+                desc_lang: foreign(&info.desc_lang),
+                icon_lang: foreign(&info.icon_lang),
+            };
+            (entry.name.is_some() || entry.desc.is_some() || entry.icon_url.is_some())
+                .then_some(entry)
+        })
+        .collect();
+    entries.sort_unstable_by_key(|entry| entry.id);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(name: &str, icon: Option<&str>, desc: Option<&str>) -> BulkInfo {
+        BulkInfo {
+            name: Some(name.to_string()),
+            best_icon_url: icon.map(str::to_string),
+            desc: desc.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn store() -> BulkStore {
+        BulkStore::from_map(HashMap::from([
+            (
+                1001,
+                info(
+                    "Amber",
+                    Some("https://example.test/1001.png"),
+                    Some("Outrider"),
+                ),
+            ),
+            (1002, info("Kaeya", None, None)),
+        ]))
+    }
+
+    #[test]
+    fn lookups_read_the_entry_map() {
+        let store = store();
+        assert_eq!(store.len(), 2);
+        assert!(!store.is_empty());
+        assert!(store.contains_key(&1001));
+        assert!(!store.contains_key(&9999));
+        assert_eq!(
+            store.get(&1002).and_then(|i| i.name.as_deref()),
+            Some("Kaeya")
+        );
+        assert_eq!(store.get_name(1001), Some("Amber"));
+        assert_eq!(store.get_icon(1001), Some("https://example.test/1001.png"));
+        assert_eq!(store.get_desc(1001), Some("Outrider"));
+        assert_eq!(store.get_icon(1002), None);
+        assert_eq!(store.get_desc(1002), None);
+        assert_eq!(store.get_name(9999), None);
+
+        let mut ids: Vec<EntryId> = store.iter().map(|(&id, _)| id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, [1001, 1002]);
+    }
+
+    #[test]
+    fn default_store_is_empty() {
+        let store = BulkStore::default();
+        assert!(store.is_empty());
+        assert_eq!(store.len(), 0);
+        assert_eq!(store.get_name(1001), None);
+        assert_eq!(store.get_post_name(1001), None);
+    }
+
+    #[test]
+    fn post_previews_are_kept_apart_from_entries() {
+        let store = store().with_post_previews(HashMap::from([(
+            1001,
+            info("Fan art", Some("https://example.test/post.png"), None),
+        )]));
+        assert_eq!(store.get_post_name(1001), Some("Fan art"));
+        assert_eq!(
+            store.get_post_icon(1001),
+            Some("https://example.test/post.png")
+        );
+        assert_eq!(store.get_name(1001), Some("Amber"));
+        assert_eq!(store.get_post_name(1002), None);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn into_inner_with_a_single_reference_moves_the_map() {
+        let store = store();
+        assert_eq!(Arc::strong_count(&store.entries), 1);
+        let name_buffer = store.get_name(1001).unwrap().as_ptr();
+
+        let map = store.into_inner();
+        assert_eq!(map.len(), 2);
+        // Same heap buffer: the strings were moved out, not cloned.
+        assert_eq!(map[&1001].name.as_deref().unwrap().as_ptr(), name_buffer);
+    }
+
+    #[test]
+    fn into_inner_with_shared_references_clones_the_map() {
+        let store = store();
+        let shared = store.clone();
+        assert_eq!(Arc::strong_count(&store.entries), 2);
+
+        let name_buffer = store.get_name(1002).unwrap().as_ptr();
+
+        let map = store.into_inner();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[&1002].name.as_deref(), Some("Kaeya"));
+        assert_ne!(map[&1002].name.as_deref().unwrap().as_ptr(), name_buffer);
+        // The other handle still sees the untouched store.
+        assert_eq!(Arc::strong_count(&shared.entries), 1);
+        assert_eq!(shared.get_name(1002), Some("Kaeya"));
+    }
+
+    fn bulk_page(id: EntryId, name: Option<&str>, icon_url: Option<&str>) -> ApiBulkPage {
+        ApiBulkPage {
+            id,
+            name: name.map(str::to_string),
+            desc: None,
+            icon_url: icon_url.map(str::to_string),
+        }
+    }
+
+    fn bulk_map(pages: Vec<(EntryId, ApiBulkPage)>) -> HashMap<EntryId, ApiBulkPage> {
+        pages.into_iter().collect()
+    }
+
+    const ICON: &str = "https://act-upload.hoyoverse.com/wiki-user-upload/";
+
+    #[test]
+    fn pages_that_were_not_requested_are_dropped() {
+        let primary = bulk_map(vec![
+            (1001, bulk_page(1001, Some("Amber"), None)),
+            (9999, bulk_page(9999, Some("Stray"), None)),
+            // Keyed under a requested id but describing another entry.
+            (1002, bulk_page(1003, Some("Lisa"), None)),
+        ]);
+        let fallback = HashMap::from([(
+            "ja-jp".to_string(),
+            bulk_map(vec![(9998, bulk_page(9998, None, Some(ICON)))]),
+        )]);
+        let requested = HashSet::from([1001, 1002]);
+        let store =
+            process_bulk_data(primary, fallback, &requested, &HashMap::new(), "en-us").unwrap();
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get_name(1001), Some("Amber"));
+        assert!(!store.contains_key(&1002));
+        assert!(!store.contains_key(&9998));
+        assert!(!store.contains_key(&9999));
+    }
+
+    #[test]
+    fn fallback_fields_record_their_language() {
+        let amber_icon = format!("{}amber.png", ICON);
+        let primary = bulk_map(vec![
+            (
+                1001,
+                ApiBulkPage {
+                    desc: Some("Outrider".to_string()),
+                    ..bulk_page(1001, Some("Amber"), Some(""))
+                },
+            ),
+            (1004, bulk_page(1004, Some("Lisa"), Some(ICON))),
+        ]);
+        let fallback = HashMap::from([
+            (
+                "ja-jp".to_string(),
+                bulk_map(vec![
+                    (1001, bulk_page(1001, None, Some(ICON))),
+                    // Carries the primary name of 1001, so it was returned for the
+                    // wrong id and its icon is ignored.
+                    (1003, bulk_page(1003, Some("Amber"), Some(ICON))),
+                ]),
+            ),
+            (
+                "de-de".to_string(),
+                bulk_map(vec![(1001, bulk_page(1001, None, Some(&amber_icon)))]),
+            ),
+        ]);
+        let requested = HashSet::from([1001, 1002, 1003, 1004]);
+        let detail_names = HashMap::from([(1002, "Kaeya".to_string())]);
+        let store =
+            process_bulk_data(primary, fallback, &requested, &detail_names, "en-us").unwrap();
+
+        let amber = store.get(&1001).unwrap();
+        assert_eq!(amber.name_lang.as_deref(), Some("en-us"));
+        assert_eq!(amber.desc_lang.as_deref(), Some("en-us"));
+        // Fallback languages are tried in sorted order.
+        assert_eq!(amber.best_icon_url.as_deref(), Some(amber_icon.as_str()));
+        assert_eq!(amber.icon_lang.as_deref(), Some("de-de"));
+
+        let kaeya = store.get(&1002).unwrap();
+        assert_eq!(kaeya.name.as_deref(), Some("Kaeya"));
+        assert_eq!(kaeya.name_lang.as_deref(), Some("en-us"));
+        assert_eq!(
+            (kaeya.desc_lang.as_deref(), kaeya.icon_lang.as_deref()),
+            (None, None)
+        );
+
+        assert!(!store.contains_key(&1003));
+        let lisa = store.get(&1004).unwrap();
+        assert_eq!(lisa.icon_lang.as_deref(), Some("en-us"));
+    }
+}
@@ -7,7 +7,7 @@ use crate::model::output::{
 };
 use crate::transform::bulk::BulkStore;
 use crate::transform::util;
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -21,6 +21,7 @@ pub fn transform_calendar(
 ) -> AppResult<Option<OutputCalendarFile>> {
     let mut output_calendar_items = Vec::new();
     let mut output_op_items = Vec::new();
+    let today = Utc::now().date_naive();
 
     for item_val in raw.calendar {
         if let Value::Object(map) = &item_val {
@@ -41,8 +42,7 @@ pub fn transform_calendar(
             let obtain_method = map
                 .get("obtain_method")
                 .and_then(Value::as_str)
-                .filter(|s| !s.is_empty())
-                .map(String::from);
+                .and_then(util::sanitize_display_string);
             let has_abstracts = !character_abstracts.is_empty()
                 || !material_abstracts.is_empty()
                 || !ep_abstracts.is_empty();
@@ -70,26 +70,25 @@ pub fn transform_calendar(
         if let Value::Object(map) = &item_val {
             let ep_abstracts =
                 process_abstracts_value(map.get("ep_abstracts"), &bulk_store, metadata_map);
-            let start_time = util::format_calendar_date_value(map.get("start_time"));
-            let end_time = util::format_calendar_date_value(map.get("end_time"));
+            let (start_date, end_date) =
+                util::resolve_calendar_range(map.get("start_time"), map.get("end_time"), today);
+            let iso = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
             let text = map
                 .get("text")
                 .and_then(Value::as_str)
-                .filter(|s| !s.is_empty())
-                .map(String::from);
+                .and_then(util::sanitize_display_string);
             let title = map
                 .get("title")
                 .and_then(Value::as_str)
-                .filter(|s| !s.is_empty())
-                .map(String::from);
+                .and_then(util::sanitize_display_string);
             let is_birth = map.get("is_birth").and_then(Value::as_bool);
 
             let item = OutputCalendarOpItem {
                 is_birth,
                 text,
                 title,
-                start_time,
-                end_time,
+                start_time: start_date.map(iso),
+                end_time: end_date.map(iso),
                 ep_abstracts: ep_abstracts.clone(),
             };
 
@@ -100,12 +99,14 @@ pub fn transform_calendar(
                 || item.end_time.is_some()
                 || !item.ep_abstracts.is_empty()
             {
-                output_op_items.push(item);
+                output_op_items.push(((start_date, end_date), item));
             }
         }
     }
 
-    output_op_items.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+    output_op_items.sort_by_key(|(sort_key, _)| *sort_key);
+    let output_op_items: Vec<OutputCalendarOpItem> =
+        output_op_items.into_iter().map(|(_, item)| item).collect();
 
     if output_calendar_items.is_empty() && output_op_items.is_empty() {
         Ok(None)
@@ -135,15 +136,23 @@ fn process_abstracts_value(
                         .unwrap_or(&Value::Null),
                 ) {
                     if id > 0 {
-                        let name = bulk_store.get_name(id).unwrap_or_default().to_string();
-                        let icon_url = bulk_store.get_icon(id).unwrap_or_default().to_string();
-                        let desc = bulk_store.get_desc(id).map(String::from);
+                        let name = bulk_store
+                            .get_name(id)
+                            .and_then(util::sanitize_display_string)
+                            .unwrap_or_default();
+                        let icon_url = bulk_store
+                            .get_icon(id)
+                            .and_then(util::normalize_asset_url)
+                            .unwrap_or_default();
+                        let desc = bulk_store
+                            .get_desc(id)
+                            .and_then(util::sanitize_display_string);
 
                         let item_metadata = metadata_map.get(&id);
 
                         let vision = item_metadata
                             .and_then(|m| m.get(config::KEY_CHAR_VISION))
-                            .cloned();
+                            .and_then(|vision| util::sanitize_display_string(vision));
 
                         let char_rarity = item_metadata
                             .and_then(|m| m.get(config::KEY_CHAR_RARITY))
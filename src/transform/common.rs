@@ -1,11 +1,14 @@
 use crate::api;
 use crate::config;
+use crate::logging::{log, LogLevel};
 use crate::model::{
-    common::EntryId,
+    common::{EntryId, MenuCategory, MenuId},
     output::{FilterValue, OutputListFile, OutputNavMenuItem},
 };
+use crate::transform::util;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 pub fn transform_nav_item(entry: &api::model::ApiNavEntry) -> Option<OutputNavMenuItem> {
     match (&entry.menu, &entry.name) {
@@ -18,30 +21,216 @@ pub fn transform_nav_item(entry: &api::model::ApiNavEntry) -> Option<OutputNavMe
     }
 }
 
+/// A nav entry dropped because an earlier entry already claimed its menu id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavDuplicate {
+    pub menu_id: MenuId,
+    pub kept_name: String,
+    pub dropped_name: String,
+}
+
+/// Transforms nav entries keeping the first entry per menu id. Lists are fetched
+/// by menu id, so a later duplicate would only refetch the same items under
+/// another name; it is dropped and reported instead.
+pub fn transform_nav_items(
+    entries: &[api::model::ApiNavEntry],
+) -> (Vec<OutputNavMenuItem>, Vec<NavDuplicate>) {
+    let mut items: Vec<OutputNavMenuItem> = Vec::with_capacity(entries.len());
+    let mut first_index: HashMap<MenuId, usize> = HashMap::with_capacity(entries.len());
+    let mut duplicates = Vec::new();
+    for item in entries.iter().filter_map(transform_nav_item) {
+        match first_index.get(&item.menu_id) {
+            Some(&index) => duplicates.push(NavDuplicate {
+                menu_id: item.menu_id,
+                kept_name: items[index].name.clone(),
+                dropped_name: item.name,
+            }),
+            None => {
+                first_index.insert(item.menu_id, items.len());
+                items.push(item);
+            }
+        }
+    }
+    (items, duplicates)
+}
+
+pub fn log_nav_duplicates(duplicates: &[NavDuplicate], lang: &str) {
+    for duplicate in duplicates {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Duplicate nav menu {} [{}]: keeping '{}', dropping '{}'.",
+                duplicate.menu_id, lang, duplicate.kept_name, duplicate.dropped_name
+            ),
+        );
+    }
+}
+
+/// Name and icon of an entry as shown in its menu list, the last resort
+/// when neither bulk data nor the detail page provide one, plus every menu
+/// whose list contains the entry (ascending; `menu_name` names the first).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListEntryFallback {
+    pub name: Option<String>,
+    pub icon_url: Option<String>,
+    pub menu_ids: Vec<MenuId>,
+    pub menu_name: Option<String>,
+}
+
+pub type ListFallbackMap = HashMap<EntryId, ListEntryFallback>;
+
+/// Per-language lookups a detail page falls back on for metadata the detail
+/// endpoint leaves out: its list entry and the localized nav menu names.
+#[derive(Debug, Clone, Default)]
+pub struct DetailFallbacks {
+    pub list: ListFallbackMap,
+    pub nav: Arc<HashMap<MenuId, String>>,
+}
+
+/// Trimmed menu name with inner whitespace runs collapsed; None when blank.
+pub fn normalize_menu_name(name: &str) -> Option<String> {
+    let normalized = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    util::sanitize_display_string(&normalized)
+}
+
+pub fn build_list_fallback_map(
+    lists: &HashMap<MenuId, Vec<api::model::ApiListItem>>,
+    nav_lookup: &HashMap<MenuId, String>,
+) -> ListFallbackMap {
+    let mut menu_ids: Vec<&MenuId> = lists.keys().collect();
+    menu_ids.sort_unstable();
+
+    let mut fallbacks = ListFallbackMap::new();
+    for &menu_id in menu_ids {
+        for item in &lists[&menu_id] {
+            if item.entry_page_id <= 0 {
+                continue;
+            }
+            let fallback = fallbacks.entry(item.entry_page_id).or_default();
+            if fallback.name.is_none() {
+                let name = item.name.trim();
+                fallback.name = (!name.is_empty()).then(|| name.to_string());
+            }
+            if fallback.icon_url.is_none() {
+                fallback.icon_url = crate::transform::util::normalize_asset_url(&item.icon_url);
+            }
+            if fallback.menu_ids.last() != Some(&menu_id) {
+                if fallback.menu_ids.is_empty() {
+                    fallback.menu_name = nav_lookup.get(&menu_id).cloned();
+                }
+                fallback.menu_ids.push(menu_id);
+            }
+        }
+    }
+    fallbacks
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuName {
+    Nav(String),
+    Fallback(String),
+}
+
+impl MenuName {
+    pub fn resolve(nav_lookup: &HashMap<MenuId, String>, menu_id: MenuId, lang: &str) -> Self {
+        match nav_lookup.get(&menu_id) {
+            Some(name) => MenuName::Nav(name.clone()),
+            None => MenuName::Fallback(format!("{} {}", fallback_menu_label(lang), menu_id)),
+        }
+    }
+
+    pub fn is_fallback(&self) -> bool {
+        matches!(self, MenuName::Fallback(_))
+    }
+
+    pub fn into_string(self) -> String {
+        match self {
+            MenuName::Nav(name) | MenuName::Fallback(name) => name,
+        }
+    }
+}
+
+fn fallback_menu_label(lang: &str) -> &'static str {
+    match lang {
+        "de-de" | "tr-tr" => "Menü",
+        "es-es" => "Menú",
+        "ja-jp" => "メニュー",
+        "ko-kr" => "메뉴",
+        "ru-ru" => "Меню",
+        "th-th" => "เมนู",
+        "vi-vn" => "Danh mục",
+        "zh-cn" => "菜单",
+        "zh-tw" => "選單",
+
+        _ => "Menu",
+    }
+}
+
+/// `snake_case` -> `camelCase`. Runs of underscores count as one separator and leading
+/// or trailing underscores are dropped, so `_a__b_` becomes `aB`.
 pub fn to_camel_case(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
-    let mut capitalize_next = false;
-    for c in s.chars() {
-        if c == '_' {
-            capitalize_next = true;
-        } else if capitalize_next {
-            result.push(c.to_ascii_uppercase());
-            capitalize_next = false;
-        } else {
-            result.push(c);
+    for segment in s.split('_').filter(|segment| !segment.is_empty()) {
+        let mut chars = segment.chars();
+        if let Some(first) = chars.next() {
+            if result.is_empty() {
+                result.push(first);
+            } else {
+                result.push(first.to_ascii_uppercase());
+            }
+            result.extend(chars);
         }
     }
     result
 }
 
+/// The untouched `filter_values` object for `--keep-raw-filters`; None when the flag is
+/// off or there is nothing to keep.
+pub fn keep_raw_filters(raw_filters_val: &Value) -> Option<Value> {
+    if !config::keep_raw_filters() {
+        return None;
+    }
+    match raw_filters_val {
+        Value::Object(map) if !map.is_empty() => Some(raw_filters_val.clone()),
+        _ => None,
+    }
+}
+
+/// Deep link for an entry or post id from `--entry-url-template`; None when no template
+/// is set. Substituted values are percent-encoded as URL components.
+pub fn entry_link(id: EntryId, lang: &str) -> Option<String> {
+    config::entry_url_template().map(|template| fill_url_template(template, id, lang))
+}
+
+fn fill_url_template(template: &str, id: EntryId, lang: &str) -> String {
+    template
+        .replace("{id}", &encode_url_component(&id.to_string()))
+        .replace("{lang}", &encode_url_component(lang))
+}
+
+fn encode_url_component(raw: &str) -> String {
+    let mut encoded = String::with_capacity(raw.len());
+    for byte in raw.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
 pub fn process_filters_value(raw_filters_val: &Value) -> HashMap<String, FilterValue> {
     let mut processed_map = HashMap::new();
     if let Value::Object(raw_filters) = raw_filters_val {
-        for &snake_key in config::LIST_FILTER_FIELDS.iter() {
+        for snake_key in config::list_filter_fields() {
             if let Some(field_data) = raw_filters.get(snake_key) {
                 let camel_key = to_camel_case(snake_key);
-                let is_rarity_key =
-                    snake_key == config::KEY_CHAR_RARITY || snake_key == config::KEY_WEAPON_RARITY;
+                let is_extra_key = config::extra_filter_fields().contains(&snake_key);
+                let is_rarity_key = snake_key == config::KEY_CHAR_RARITY
+                    || snake_key == config::KEY_WEAPON_RARITY
+                    || (is_extra_key && snake_key.ends_with("_rarity"));
 
                 let extracted_string_values = extract_filter_field_values(field_data);
 
@@ -64,7 +253,9 @@ pub fn process_filters_value(raw_filters_val: &Value) -> HashMap<String, FilterV
                             extracted_string_values.into_iter().collect();
                         sorted_values.sort_unstable();
 
-                        if config::MULTI_VALUE_FILTER_FIELDS.contains(snake_key) {
+                        if config::MULTI_VALUE_FILTER_FIELDS.contains(snake_key)
+                            || (is_extra_key && sorted_values.len() > 1)
+                        {
                             processed_map.insert(camel_key, FilterValue::Multiple(sorted_values));
                         } else if let Some(first_val) = sorted_values.into_iter().next() {
                             processed_map.insert(camel_key, FilterValue::Single(first_val));
@@ -134,11 +325,12 @@ pub fn build_metadata_map(
 ) -> HashMap<EntryId, HashMap<&'static str, String>> {
     let mut metadata_map = HashMap::new();
 
-    let keys_to_extract = [
-        config::KEY_CHAR_VISION,
-        config::KEY_CHAR_RARITY,
-        config::KEY_WEAPON_RARITY,
-    ];
+    let keys_to_extract: Vec<&'static str> = [MenuCategory::Characters, MenuCategory::Weapons]
+        .into_iter()
+        .flat_map(MenuCategory::card_filter_keys)
+        .copied()
+        .chain(config::extra_filter_fields().iter().copied())
+        .collect();
 
     for list_file in output_lists {
         for item in &list_file.list {
@@ -161,3 +353,92 @@ pub fn build_metadata_map(
     }
     metadata_map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camel_case_collapses_and_trims_underscores() {
+        assert_eq!(to_camel_case("base_info"), "baseInfo");
+        assert_eq!(to_camel_case("gallery_character"), "galleryCharacter");
+        assert_eq!(to_camel_case("a__b"), "aB");
+        assert_eq!(to_camel_case("_ab"), "ab");
+        assert_eq!(to_camel_case("ab_"), "ab");
+        assert_eq!(to_camel_case("ab__"), "ab");
+        assert_eq!(to_camel_case("_a__b_"), "aB");
+        assert_eq!(to_camel_case("summaryList"), "summaryList");
+        assert_eq!(to_camel_case("___"), "");
+        assert_eq!(to_camel_case(""), "");
+    }
+
+    #[test]
+    fn url_template_fills_every_placeholder() {
+        assert_eq!(
+            fill_url_template(
+                "https://wiki.example/{lang}/entry/{id}?from={lang}",
+                1001,
+                "en-us"
+            ),
+            "https://wiki.example/en-us/entry/1001?from=en-us"
+        );
+        assert_eq!(
+            fill_url_template("https://wiki.example/static", 1001, "en-us"),
+            "https://wiki.example/static"
+        );
+        assert_eq!(
+            fill_url_template("https://wiki.example/{ID}/{other}", 1001, "en-us"),
+            "https://wiki.example/{ID}/{other}"
+        );
+        assert_eq!(
+            fill_url_template("https://wiki.example/{id}", -5, "en-us"),
+            "https://wiki.example/-5"
+        );
+    }
+
+    #[test]
+    fn url_template_values_are_percent_encoded() {
+        assert_eq!(
+            fill_url_template("https://wiki.example/{lang}/{id}", 7, "zh cn/é?&"),
+            "https://wiki.example/zh%20cn%2F%C3%A9%3F%26/7"
+        );
+        // A substituted value can't inject another placeholder.
+        assert_eq!(
+            fill_url_template("https://wiki.example/{lang}/{id}", 7, "{id}"),
+            "https://wiki.example/%7Bid%7D/7"
+        );
+        assert_eq!(encode_url_component("a-b_c.d~e"), "a-b_c.d~e");
+    }
+
+    fn fixture_nav(name: &str) -> Vec<api::model::ApiNavEntry> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join(name);
+        let text = std::fs::read_to_string(path).unwrap();
+        let wrapper: api::model::ApiWrapper<api::model::ApiNavResponse> =
+            serde_json::from_str(&text).unwrap();
+        wrapper.data.unwrap().nav
+    }
+
+    #[test]
+    fn duplicate_nav_menus_keep_the_first_entry() {
+        let (items, duplicates) = transform_nav_items(&fixture_nav("nav_duplicate_menu_ids.json"));
+        let kept: Vec<(MenuId, &str)> = items
+            .iter()
+            .map(|item| (item.menu_id, item.name.as_str()))
+            .collect();
+        assert_eq!(kept, [(2, "Characters"), (4, "Weapons")]);
+        assert_eq!(
+            items[0].icon_url,
+            "https://act-upload.mihoyo.com/wiki-user-upload/characters.png"
+        );
+        assert_eq!(
+            duplicates,
+            [NavDuplicate {
+                menu_id: 2,
+                kept_name: "Characters".to_string(),
+                dropped_name: "Playable Characters".to_string(),
+            }]
+        );
+    }
+}
@@ -1,85 +1,221 @@
 use crate::api::model;
 use crate::api::model::{
-    ApiComponentData, ApiDropMaterialData, ApiTcgData, ApiTimelineListData,
+    ApiAchievementGroupData, ApiComponentData, ApiDropMaterialData, ApiGiftRewardData,
+    ApiGiftRewardItem, ApiMapResourceData, ApiTcgData, ApiTimelineListData,
     ApiVideoCollectionDataList,
 };
+use crate::config;
+use crate::core::statistics;
+use crate::core::stats::DEGRADED_COMPONENTS;
 use crate::error::AppResult;
+use crate::io;
 use crate::logging::{log, LogLevel};
-use crate::model::common::EntryId;
+use crate::model::common::{EntryId, MenuCategory, MenuId};
 use crate::model::html;
 use crate::model::html::HtmlNode;
 use crate::model::output::{
-    self, AudioInfo, ComponentData, OutputArtifactListItem, OutputAscensionItem,
-    OutputBaseInfoItem, OutputDetailPage, OutputGalleryCharacterItem, OutputReliquaryEffect,
-    OutputStoryItem, OutputSummaryItem, OutputTalentItem, OutputTcgData, OutputTcgHeaderImage,
+    self, AudioInfo, ComponentData, OutputAchievementGroupData, OutputAchievementItem,
+    OutputAliasEntry, OutputArtifactListItem, OutputAscensionItem, OutputBaseInfoItem,
+    OutputBuildReference, OutputBuildSection, OutputDetailPage, OutputEventCalendarData,
+    OutputGalleryCharacterItem, OutputGiftRewardData, OutputGiftRewardItem, OutputMapData,
+    OutputMapPoint, OutputMapResourceData, OutputReliquaryEffect, OutputStoryItem,
+    OutputSummaryItem, OutputTalentItem, OutputTcgCost, OutputTcgData, OutputTcgHeaderImage,
     OutputTextualResearchItem, OutputTimelineEvent, OutputVideoCollectionItem, OutputVoiceItem,
 };
+use crate::telemetry;
 use crate::transform::bulk::BulkStore;
-use crate::transform::{common, html_parser};
+use crate::transform::{common, event_window, html_parser, intern, progression, util};
 use crate::utils;
 use async_recursion::async_recursion;
 use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::Serialize;
 use serde_json::Value;
-use serde_json::{from_str, from_value};
-use std::collections::{hash_map::Entry, HashMap, HashSet};
-use std::ops::Not;
-use std::sync::Arc;
+use serde_json::{from_str, from_value, json};
+use std::cell::Cell;
+use std::collections::{hash_map::Entry, BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
 use tokio::task::JoinSet;
 
+tokio::task_local! {
+    static HTML_PARSE_STATE: HtmlParseState;
+}
+
+// Per-component HTML parsing state: `flat` forces the fallback parser for a retry,
+// `degraded` records that some of the component's HTML went through it.
+struct HtmlParseState {
+    flat: bool,
+    degraded: Cell<bool>,
+}
+
+static SEEN_COMPONENT_IDS: Lazy<Mutex<HashSet<(String, String)>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
+
+pub const COMPONENT_KEY_COLLISIONS_FILE: &str = "component_key_collisions.json";
+
+static COMPONENT_KEY_COLLISIONS: Lazy<Mutex<Vec<ComponentKeyCollision>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Distinct component ids on one page that camelCase to the same output key.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentKeyCollision {
+    pub page_id: EntryId,
+    pub lang: String,
+    pub key: String,
+    pub component_ids: Vec<String>,
+}
+
+/// Drains the collisions recorded so far, ordered by language, page and key.
+pub fn take_component_key_collisions() -> Vec<ComponentKeyCollision> {
+    let mut collisions = COMPONENT_KEY_COLLISIONS
+        .lock()
+        .map(|mut collisions| std::mem::take(&mut *collisions))
+        .unwrap_or_default();
+    collisions.sort_unstable_by(|a, b| {
+        a.lang
+            .cmp(&b.lang)
+            .then_with(|| a.page_id.cmp(&b.page_id))
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    collisions
+}
+
 #[async_recursion]
 pub async fn transform_detail_page(
     raw_page: model::ApiDetailPage,
     bulk_store: Arc<BulkStore>,
+    fallbacks: Arc<common::DetailFallbacks>,
     lang: &str,
 ) -> AppResult<Option<OutputDetailPage>> {
     let page_id = match raw_page.id {
         Some(id) if id > 0 => id,
-        _ => return Ok(None),
+        _ => {
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "Detail [{}]: dropping page '{}' without a valid id.",
+                    lang, raw_page.name
+                ),
+            );
+            return Ok(None);
+        }
     };
+    let list_fallback = fallbacks.list.get(&page_id);
     let version = raw_page.version.unwrap_or_else(|| Utc::now().timestamp());
-    let menu_id = raw_page.menu_id.unwrap_or(0);
+    // The detail endpoint sometimes reports menu 0 for entries reachable from filter menus.
+    let list_menu_ids = list_fallback.map_or(&[][..], |f| f.menu_ids.as_slice());
+    let menu_id = raw_page
+        .menu_id
+        .filter(|&id| id > 0)
+        .or_else(|| list_menu_ids.first().copied())
+        .unwrap_or(0);
+    let menu_name = raw_page
+        .menu_name
+        .as_deref()
+        .and_then(common::normalize_menu_name)
+        .or_else(|| {
+            list_fallback
+                .filter(|f| f.menu_ids.first() == Some(&menu_id))
+                .and_then(|f| f.menu_name.as_deref())
+                .and_then(common::normalize_menu_name)
+        })
+        .or_else(|| {
+            (menu_id > 0)
+                .then(|| fallbacks.nav.get(&menu_id))
+                .flatten()
+                .and_then(|name| common::normalize_menu_name(name))
+        });
 
     let final_name = bulk_store
         .get_name(page_id)
-        .map(String::from)
-        .filter(|s| !s.is_empty())
+        .and_then(util::sanitize_display_string)
+        .or_else(|| util::sanitize_display_string(&raw_page.name))
         .or_else(|| {
-            raw_page
-                .name
-                .is_empty()
-                .not()
-                .then_some(raw_page.name.clone())
+            list_fallback
+                .and_then(|f| f.name.as_deref())
+                .and_then(util::sanitize_display_string)
         });
 
     let final_icon_url = bulk_store
         .get_icon(page_id)
-        .map(String::from)
-        .filter(|s| !s.is_empty())
-        .or_else(|| raw_page.icon_url.clone());
+        .and_then(util::sanitize_display_string)
+        .or_else(|| {
+            raw_page
+                .icon_url
+                .as_deref()
+                .and_then(util::sanitize_display_string)
+        })
+        .or_else(|| {
+            list_fallback
+                .and_then(|f| f.icon_url.as_deref())
+                .and_then(util::sanitize_display_string)
+        });
 
     let final_desc = bulk_store
         .get_desc(page_id)
-        .map(String::from)
-        .or_else(|| raw_page.desc.clone());
+        .or(raw_page.desc.as_deref())
+        .and_then(util::sanitize_display_string);
 
     let filter_values = common::process_filters_value(&raw_page.filter_values);
+    let raw_filters = common::keep_raw_filters(&raw_page.filter_values);
+    // Weapon entries reached through filter menus can report another menu; their
+    // components still need weapon handling.
+    let content_menu_id = if is_weapon_page(menu_id, list_menu_ids, &raw_page.filter_values) {
+        MenuCategory::Weapons.menu_id()
+    } else {
+        menu_id
+    };
 
     let mut component_tasks = JoinSet::new();
-    let all_components = flatten_modules_components(raw_page.modules);
+    let all_components = flatten_modules_components(raw_page.modules, false);
 
-    for component in all_components {
+    for (component, popped) in all_components {
         let bulk_store_clone = bulk_store.clone();
         let lang_clone = lang.to_string();
         component_tasks.spawn(async move {
-            transform_component_content(component, page_id, bulk_store_clone, &lang_clone).await
+            let (result, degraded) = transform_component_degradable(
+                component,
+                page_id,
+                content_menu_id,
+                bulk_store_clone,
+                &lang_clone,
+            )
+            .await;
+            (popped, degraded, result)
         });
     }
 
     let mut final_components: HashMap<String, ComponentData> = HashMap::new();
+    let mut component_ids: HashMap<String, String> = HashMap::new();
+    let mut key_sources: HashMap<String, BTreeSet<String>> = HashMap::new();
+    // A key counts as popped only when every component merged into it came from a
+    // popped module.
+    let mut popped_keys: BTreeSet<String> = BTreeSet::new();
+    let mut shown_keys: HashSet<String> = HashSet::new();
+    let mut degraded_keys: BTreeSet<String> = BTreeSet::new();
     while let Some(result) = component_tasks.join_next().await {
         match result {
-            Ok(Ok(Some((id, new_content)))) => {
+            Ok((popped, degraded, Ok(Some((id, new_content))))) => {
                 let camel_case_id = common::to_camel_case(&id);
+                if degraded {
+                    degraded_keys.insert(camel_case_id.clone());
+                }
+                if popped {
+                    popped_keys.insert(camel_case_id.clone());
+                } else {
+                    shown_keys.insert(camel_case_id.clone());
+                }
+                key_sources
+                    .entry(camel_case_id.clone())
+                    .or_default()
+                    .insert(id.clone());
+                if let Entry::Vacant(entry) = component_ids.entry(camel_case_id.clone()) {
+                    log_component_id_mapping(lang, &id, &camel_case_id);
+                    entry.insert(id);
+                }
                 match final_components.entry(camel_case_id.clone()) {
                     Entry::Occupied(mut entry) => {
                         let existing_content_mut = entry.get_mut();
@@ -118,23 +254,21 @@ pub async fn transform_detail_page(
                                 ComponentData::DropMaterial(existing),
                                 ComponentData::DropMaterial(new),
                             ) => existing.extend(new),
+                            (ComponentData::Build(existing), ComponentData::Build(new)) => {
+                                existing.extend(new)
+                            }
+                            (
+                                ComponentData::GiftReward(existing),
+                                ComponentData::GiftReward(new),
+                            ) => {
+                                existing.liked.extend(new.liked);
+                                existing.disliked.extend(new.disliked);
+                            }
 
                             (
                                 ComponentData::GalleryCharacter(existing_items),
                                 ComponentData::GalleryCharacter(new_items),
-                            ) => {
-                                let existing_keys: HashSet<String> =
-                                    existing_items.iter().map(|item| item.key.clone()).collect();
-                                let existing_img: HashSet<String> =
-                                    existing_items.iter().map(|item| item.img.clone()).collect();
-                                for new_item in new_items {
-                                    if !existing_img.contains(&new_item.img) {
-                                        if !existing_keys.contains(&new_item.key) {
-                                            existing_items.push(new_item.clone());
-                                        }
-                                    }
-                                }
-                            }
+                            ) => merge_gallery_items(existing_items, new_items),
 
                             (
                                 ComponentData::VideoCollection(existing_videos),
@@ -159,6 +293,11 @@ pub async fn transform_detail_page(
                                     new,
                                     ComponentData::ReliquarySetEffect(_)
                                         | ComponentData::MapUrl(_)
+                                        | ComponentData::Map(_)
+                                        | ComponentData::EventCalendar(_)
+                                        | ComponentData::AchievementGroup(_)
+                                        | ComponentData::Progression(_)
+                                        | ComponentData::MapResource(_)
                                         | ComponentData::Tcg(_)
                                         | ComponentData::Unknown(_)
                                 ) {
@@ -173,8 +312,8 @@ pub async fn transform_detail_page(
                     }
                 }
             }
-            Ok(Ok(None)) => {}
-            Ok(Err(e)) => log(
+            Ok((_, _, Ok(None))) => {}
+            Ok((_, _, Err(e))) => log(
                 LogLevel::Warning,
                 &format!("Comp transform error [{} / {}]: {:?}", lang, page_id, e),
             ),
@@ -188,30 +327,311 @@ pub async fn transform_detail_page(
         }
     }
 
+    record_component_key_collisions(key_sources, page_id, lang);
     final_components.retain(
         |_key, value| !matches!(value, ComponentData::Customize(nodes) if nodes.is_empty()),
     );
 
-    if final_name.is_none() && final_components.is_empty() {
+    let has_bulk_name = bulk_store
+        .get_name(page_id)
+        .is_some_and(|name| !name.is_empty());
+    let alias_of = raw_page
+        .alias_of
+        .filter(|&id| id > 0 && id != page_id)
+        .or_else(|| {
+            detect_entry_link_alias(
+                page_id,
+                has_bulk_name,
+                &final_components,
+                config::alias_detection(),
+            )
+        });
+    if config::truncate_oversized() {
+        truncate_oversized_components(
+            &mut final_components,
+            config::oversized_component_bytes(),
+            page_id,
+            lang,
+        );
+    }
+
+    if alias_of.is_some() {
+        final_components.clear();
+    } else if config::derive_progression() {
+        if let Some(progression) =
+            progression::derive_progression(&final_components, menu_id, list_menu_ids, lang)
+        {
+            final_components.insert(
+                config::COMPONENT_PROGRESSION.to_string(),
+                ComponentData::Progression(progression),
+            );
+        }
+    }
+
+    let event_window = if alias_of.is_none() && config::derive_event_windows() {
+        event_window::derive_event_window(&final_components, menu_id, list_menu_ids, page_id, lang)
+    } else {
+        None
+    };
+
+    let mut header_img_url = raw_page.header_img_url;
+    if let Some(mode) = config::header_gallery_dedup() {
+        dedup_header_gallery(&mut header_img_url, &mut final_components, mode);
+    }
+    let primary_image = select_primary_image(
+        header_img_url.as_deref(),
+        &final_components,
+        final_icon_url.as_deref(),
+        lang,
+    );
+
+    let popped_components = popped_keys
+        .into_iter()
+        .filter(|key| !shown_keys.contains(key) && final_components.contains_key(key))
+        .collect();
+    let degraded_components: Vec<String> = degraded_keys
+        .into_iter()
+        .filter(|key| final_components.contains_key(key))
+        .collect();
+
+    let components_text = if config::flatten_html() {
+        flatten_component_html(&mut final_components)
+    } else {
+        HashMap::new()
+    };
+    // After flattening, so the `*_text` siblings see the full node lists.
+    let node_defs = if config::intern_nodes() {
+        intern::intern_node_lists(&mut final_components)
+    } else {
+        Vec::new()
+    };
+
+    if final_name.is_none() && final_components.is_empty() && alias_of.is_none() {
         Ok(None)
     } else {
+        DEGRADED_COMPONENTS.fetch_add(degraded_components.len() as u64, Ordering::Relaxed);
         Ok(Some(OutputDetailPage {
             id: page_id,
             name: final_name,
             desc: final_desc,
             icon_url: final_icon_url,
-            header_img_url: raw_page.header_img_url,
+            header_img_url,
+            primary_image,
             components: final_components,
             filter_values,
+            raw_filters,
+            link: common::entry_link(page_id, lang),
             menu_id,
-            menu_name: raw_page.menu_name,
+            menu_name,
+            menu_ids: list_menu_ids.to_vec(),
             version,
+            alias_of,
+            event_window,
+            component_ids,
+            components_text,
+            popped_components,
+            degraded_components,
+            node_defs,
         }))
     }
 }
 
+// Fills the `*_text` siblings of every HtmlNode field. Components that are a bare node
+// list have no sibling to fill; their text is returned keyed by component.
+fn flatten_component_html(
+    components: &mut HashMap<String, ComponentData>,
+) -> HashMap<String, String> {
+    let plain = |nodes: &[HtmlNode]| Some(html::render_plain(nodes)).filter(|t| !t.is_empty());
+    let plain_opt = |nodes: &Option<Vec<HtmlNode>>| nodes.as_deref().and_then(plain);
+    let mut bare_text = HashMap::new();
+    for (key, component) in components.iter_mut() {
+        match component {
+            ComponentData::BaseInfo(items) => {
+                for item in items {
+                    item.value_text = plain_opt(&item.value);
+                }
+            }
+            ComponentData::Ascension(items) => {
+                for item in items {
+                    item.materials_text = plain_opt(&item.materials);
+                }
+            }
+            ComponentData::Talent(items) => {
+                for item in items {
+                    item.desc_text = plain(&item.desc);
+                    item.materials_text = item.materials.as_ref().map(|levels| {
+                        levels
+                            .iter()
+                            .map(|level| plain_opt(level).unwrap_or_default())
+                            .collect()
+                    });
+                }
+            }
+            ComponentData::SummaryList(items) => {
+                for item in items {
+                    item.desc_text = plain(&item.desc);
+                }
+            }
+            ComponentData::Story(items) => {
+                for item in items {
+                    item.desc_text = plain(&item.desc);
+                }
+            }
+            ComponentData::GalleryCharacter(items) => {
+                for item in items {
+                    item.img_desc_text = plain(&item.img_desc);
+                }
+            }
+            ComponentData::ArtifactList(items) => {
+                for item in items.values_mut() {
+                    item.desc_text = plain(&item.desc);
+                }
+            }
+            ComponentData::TextualResearch(items) => {
+                for item in items {
+                    item.desc_text = plain(&item.desc);
+                }
+            }
+            ComponentData::Timeline(events) => {
+                for event in events {
+                    event.contents_text = plain(&event.contents);
+                }
+            }
+            ComponentData::Build(sections) => {
+                for section in sections {
+                    section.desc_text = plain(&section.desc);
+                }
+            }
+            ComponentData::EventCalendar(event) => {
+                event.rewards_text = plain_opt(&event.rewards);
+                event.description_text = plain(&event.description);
+            }
+            ComponentData::AchievementGroup(group) => {
+                for item in &mut group.items {
+                    item.desc_text = plain(&item.desc);
+                }
+            }
+            ComponentData::Progression(progression) => {
+                for entry in &mut progression.entries {
+                    entry.desc_text = plain(&entry.desc);
+                }
+            }
+            ComponentData::Customize(nodes) | ComponentData::DropMaterial(nodes) => {
+                if let Some(text) = plain(nodes) {
+                    bare_text.insert(key.clone(), text);
+                }
+            }
+            ComponentData::Voice(_)
+            | ComponentData::Map(_)
+            | ComponentData::ReliquarySetEffect(_)
+            | ComponentData::MapUrl(_)
+            | ComponentData::MapResource(_)
+            | ComponentData::VideoCollection(_)
+            | ComponentData::Tcg(_)
+            | ComponentData::GiftReward(_)
+            | ComponentData::Unknown(_) => {}
+        }
+    }
+    bare_text
+}
+
+fn record_component_key_collisions(
+    key_sources: HashMap<String, BTreeSet<String>>,
+    page_id: EntryId,
+    lang: &str,
+) {
+    let mut collisions: Vec<ComponentKeyCollision> = key_sources
+        .into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .map(|(key, sources)| ComponentKeyCollision {
+            page_id,
+            lang: lang.to_string(),
+            key,
+            component_ids: sources.into_iter().collect(),
+        })
+        .collect();
+    if collisions.is_empty() {
+        return;
+    }
+    collisions.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+    for collision in &collisions {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Component key collision [{} / {}]: {} <- [{}].",
+                lang,
+                page_id,
+                collision.key,
+                collision.component_ids.join(", ")
+            ),
+        );
+    }
+    if let Ok(mut recorded) = COMPONENT_KEY_COLLISIONS.lock() {
+        recorded.extend(collisions);
+    }
+}
+
+fn log_component_id_mapping(lang: &str, component_id: &str, camel_case_id: &str) {
+    if !config::debug_component_ids() {
+        return;
+    }
+    let first_seen = SEEN_COMPONENT_IDS
+        .lock()
+        .map(|mut seen| seen.insert((lang.to_string(), component_id.to_string())))
+        .unwrap_or(false);
+    if first_seen {
+        log(
+            LogLevel::Info,
+            &format!(
+                "Component mapping [{}]: {} -> {}",
+                lang, component_id, camel_case_id
+            ),
+        );
+    }
+}
+
+fn detect_entry_link_alias(
+    page_id: EntryId,
+    has_bulk_name: bool,
+    components: &HashMap<String, ComponentData>,
+    detection: config::AliasDetection,
+) -> Option<EntryId> {
+    let enabled = match detection {
+        config::AliasDetection::ExplicitOnly => false,
+        config::AliasDetection::EntryLink => !has_bulk_name,
+        config::AliasDetection::EntryLinkAnyName => true,
+    };
+    if !enabled || components.len() != 1 {
+        return None;
+    }
+    let Some(ComponentData::Customize(nodes)) = components.values().next() else {
+        return None;
+    };
+    let mut meaningful_nodes = nodes.iter().filter(|node| !node.is_empty_text());
+    match (meaningful_nodes.next(), meaningful_nodes.next()) {
+        (Some(HtmlNode::CustomEntry { ep_id, .. }), None) if *ep_id > 0 && *ep_id != page_id => {
+            Some(*ep_id)
+        }
+        _ => None,
+    }
+}
+
+pub fn collect_alias_entries(details: &[OutputDetailPage]) -> Vec<OutputAliasEntry> {
+    let mut aliases: Vec<OutputAliasEntry> = details
+        .iter()
+        .filter_map(|page| {
+            page.alias_of.map(|alias_of| OutputAliasEntry {
+                id: page.id,
+                alias_of,
+            })
+        })
+        .collect();
+    aliases.sort_unstable_by_key(|entry| entry.id);
+    aliases
+}
+
 impl ComponentData {
-    fn discriminant_str(&self) -> &'static str {
+    pub(crate) fn discriminant_str(&self) -> &'static str {
         match self {
             ComponentData::BaseInfo(_) => "BaseInfo",
             ComponentData::Ascension(_) => "Ascension",
@@ -223,32 +643,97 @@ impl ComponentData {
             ComponentData::ArtifactList(_) => "ArtifactList",
             ComponentData::ReliquarySetEffect(_) => "ReliquarySetEffect",
             ComponentData::MapUrl(_) => "MapUrl",
+            ComponentData::Map(_) => "Map",
+            ComponentData::MapResource(_) => "MapResource",
             ComponentData::TextualResearch(_) => "TextualResearch",
             ComponentData::Timeline(_) => "Timeline",
             ComponentData::VideoCollection(_) => "VideoCollection",
             ComponentData::Customize(_) => "Customize",
             ComponentData::Tcg(_) => "Tcg",
             ComponentData::DropMaterial(_) => "DropMaterial",
+            ComponentData::GiftReward(_) => "GiftReward",
+            ComponentData::Build(_) => "Build",
+            ComponentData::EventCalendar(_) => "EventCalendar",
+            ComponentData::AchievementGroup(_) => "AchievementGroup",
+            ComponentData::Progression(_) => "Progression",
             ComponentData::Unknown(_) => "Unknown",
         }
     }
 }
 
-fn flatten_modules_components(modules: Vec<model::ApiModule>) -> Vec<model::ApiComponent> {
+// Components paired with whether they sit in a popped module (directly or through a
+// parent). Abstract modules are not rendered by the web wiki and are skipped with their
+// children unless `--include-abstract-modules` is set.
+fn flatten_modules_components(
+    modules: Vec<model::ApiModule>,
+    parent_popped: bool,
+) -> Vec<(model::ApiComponent, bool)> {
     let mut components = Vec::new();
     for module in modules {
-        components.extend(module.components);
+        if module.is_abstract == Some(true) && !config::include_abstract_modules() {
+            continue;
+        }
+        let popped = parent_popped || module.is_poped == Some(true);
+        components.extend(module.components.into_iter().map(|c| (c, popped)));
         if !module.modules.is_empty() {
-            components.extend(flatten_modules_components(module.modules));
+            components.extend(flatten_modules_components(module.modules, popped));
         }
     }
     components
 }
 
 #[async_recursion]
+/// Runs `transform_component_content`, retrying once with the flat HTML parser
+/// when it fails. The flag is `true` when the result relied on the flat parse.
+async fn transform_component_degradable(
+    api_comp: model::ApiComponent,
+    page_id: EntryId,
+    menu_id: MenuId,
+    bulk_store: Arc<BulkStore>,
+    lang: &str,
+) -> (AppResult<Option<(String, ComponentData)>>, bool) {
+    let retry_comp = api_comp.clone();
+    let first_attempt = with_html_parse_state(
+        false,
+        transform_component_content(api_comp, page_id, menu_id, bulk_store.clone(), lang),
+    )
+    .await;
+    let (Err(e), _) = &first_attempt else {
+        return first_attempt;
+    };
+    log(
+        LogLevel::Warning,
+        &format!(
+            "Comp transform error [{} / {}]: {:?}. Retrying with flat HTML parsing.",
+            lang, page_id, e
+        ),
+    );
+    let (result, _) = with_html_parse_state(
+        true,
+        transform_component_content(retry_comp, page_id, menu_id, bulk_store, lang),
+    )
+    .await;
+    (result, true)
+}
+
+async fn with_html_parse_state<T>(flat: bool, transform: impl Future<Output = T>) -> (T, bool) {
+    let state = HtmlParseState {
+        flat,
+        degraded: Cell::new(false),
+    };
+    HTML_PARSE_STATE
+        .scope(state, async move {
+            let output = transform.await;
+            let degraded = HTML_PARSE_STATE.with(|state| state.degraded.get());
+            (output, degraded)
+        })
+        .await
+}
+
 async fn transform_component_content(
     api_comp: model::ApiComponent,
     page_id: EntryId,
+    menu_id: MenuId,
     bulk_store: Arc<BulkStore>,
     lang: &str,
 ) -> AppResult<Option<(String, ComponentData)>> {
@@ -262,7 +747,7 @@ async fn transform_component_content(
                 .map(|res| (!res.is_empty()).then_some(ComponentData::BaseInfo(res)))
         }
         ApiComponentData::AscensionList(items) => {
-            transform_ascension_list(items, page_id, lang, &bulk_store)
+            transform_ascension_list(items, page_id, menu_id, lang, &bulk_store)
                 .await
                 .map(|res| (!res.is_empty()).then_some(ComponentData::Ascension(res)))
         }
@@ -314,8 +799,12 @@ async fn transform_component_content(
         ApiComponentData::ReliquarySetEffect(effect) => {
             Ok(transform_reliquary_effect(effect).map(ComponentData::ReliquarySetEffect))
         }
-        ApiComponentData::Map(map_data) => {
-            Ok((!map_data.url.is_empty()).then_some(ComponentData::MapUrl(map_data.url)))
+        ApiComponentData::Map(map_data) => Ok(transform_map(map_data, &bulk_store)),
+        ApiComponentData::MapResource(resource_data) => {
+            Ok(
+                transform_map_resource(resource_data, page_id, lang)
+                    .map(ComponentData::MapResource),
+            )
         }
         ApiComponentData::Customize(cust_data_val) => {
             let mut html_string_to_parse: Option<String> = None;
@@ -386,7 +875,30 @@ async fn transform_component_content(
                 .await
                 .map(|nodes| (!nodes.is_empty()).then_some(ComponentData::DropMaterial(nodes)))
         }
+        ApiComponentData::GiftReward(gift_data) => {
+            let gift = transform_gift_reward(gift_data, &bulk_store);
+            Ok((!gift.liked.is_empty() || !gift.disliked.is_empty())
+                .then_some(ComponentData::GiftReward(gift)))
+        }
+        ApiComponentData::BuildList(sections) => {
+            transform_build_sections(sections, page_id, lang, &bulk_store)
+                .await
+                .map(|res| (!res.is_empty()).then_some(ComponentData::Build(res)))
+        }
+        ApiComponentData::EventCalendar(event_data) => {
+            transform_event_calendar(event_data, page_id, lang, &bulk_store)
+                .await
+                .map(|res| res.map(ComponentData::EventCalendar))
+        }
+        ApiComponentData::AchievementGroup(group_data) => {
+            transform_achievement_group(group_data, page_id, lang, &bulk_store)
+                .await
+                .map(|res| res.map(ComponentData::AchievementGroup))
+        }
         ApiComponentData::Unknown(val) => {
+            if let Some(dump_dir) = config::dump_unknown_components_dir() {
+                io::dump_unknown_component(dump_dir, &component_id, lang, page_id, &val).await;
+            }
             if val.is_null() {
                 Ok(None)
             } else {
@@ -397,7 +909,9 @@ async fn transform_component_content(
                         component_id, lang, page_id
                     ),
                 );
-                Ok(Some(ComponentData::Unknown(val)))
+                Ok(Some(ComponentData::Unknown(util::normalize_unknown_value(
+                    val,
+                ))))
             }
         }
     };
@@ -427,6 +941,7 @@ async fn transform_timeline_component(
     let mut output_events = Vec::new();
 
     for event in list_data.list {
+        let title = display_string(&event.title);
         let mut event_contents = Vec::new();
         for module_content in event.modules {
             let content_nodes = parse_value_to_html_nodes(
@@ -439,23 +954,24 @@ async fn transform_timeline_component(
             event_contents.extend(content_nodes);
         }
 
-        if !event_contents.is_empty() && !event.title.is_empty() {
+        if !event_contents.is_empty() && !title.is_empty() {
             let mut final_contents = Vec::with_capacity(event_contents.len() + 1);
             final_contents.push(HtmlNode::Heading {
                 level: 3,
-                text: event.title.clone(),
+                text: title.clone(),
                 alignment: None,
             });
             final_contents.extend(event_contents);
             event_contents = final_contents;
         }
 
-        if !event_contents.is_empty() || !event.title.is_empty() {
+        if !event_contents.is_empty() || !title.is_empty() {
             output_events.push(OutputTimelineEvent {
-                title: event.title,
-                sub_title: event.sub_title,
-                bg_url: event.icon_url,
+                title,
+                sub_title: display_string(&event.sub_title),
+                bg_url: display_string(&event.icon_url),
                 contents: event_contents,
+                ..Default::default()
             });
         }
     }
@@ -482,9 +998,9 @@ async fn transform_video_collection(
                         for video_item in category.videos {
                             output_videos.push(OutputVideoCollectionItem {
                                 video_id: video_item.video_id.unwrap_or_default(),
-                                title: video_item.title,
-                                url: video_item.url,
-                                cover_url: video_item.cover,
+                                title: display_string(&video_item.title),
+                                url: display_string(&video_item.url),
+                                cover_url: display_string(&video_item.cover),
                                 duration: video_item.duration,
                             });
                         }
@@ -503,9 +1019,9 @@ async fn transform_video_collection(
                         for video_item in category.videos {
                             output_videos.push(OutputVideoCollectionItem {
                                 video_id: video_item.video_id.unwrap_or_default(),
-                                title: video_item.title,
-                                url: video_item.url,
-                                cover_url: video_item.cover,
+                                title: display_string(&video_item.title),
+                                url: display_string(&video_item.url),
+                                cover_url: display_string(&video_item.cover),
                                 duration: video_item.duration,
                             });
                         }
@@ -539,16 +1055,137 @@ async fn transform_video_collection(
 }
 
 fn transform_reliquary_effect(effect: model::ApiReliquaryEffect) -> Option<OutputReliquaryEffect> {
-    if effect.two_set_effect.is_some() || effect.four_set_effect.is_some() {
-        Some(OutputReliquaryEffect {
-            two_set_effect: effect.two_set_effect,
-            four_set_effect: effect.four_set_effect,
+    let effects: HashMap<u8, String> = effect
+        .effects
+        .iter()
+        .filter_map(|(key, value)| {
+            let pieces = effect_piece_count(key)?;
+            let text = value.as_str().and_then(util::sanitize_display_string)?;
+            Some((pieces, text))
         })
-    } else {
-        None
+        .collect();
+    if effects.is_empty() {
+        return None;
+    }
+    Some(OutputReliquaryEffect {
+        two_set_effect: effects.get(&2).cloned(),
+        four_set_effect: effects.get(&4).cloned(),
+        effects,
+    })
+}
+
+/// Piece count of a `<count>_set_effect` key, with the count spelled out
+/// (`one_set_effect`) or in digits (`1_set_effect`).
+fn effect_piece_count(key: &str) -> Option<u8> {
+    match key.strip_suffix("_set_effect")? {
+        "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        "four" => Some(4),
+        "five" => Some(5),
+        digits => digits.parse().ok().filter(|&pieces| pieces > 0),
+    }
+}
+
+fn transform_map(
+    api_data: model::ApiMapData,
+    bulk_store: &Arc<BulkStore>,
+) -> Option<ComponentData> {
+    let url = display_string(&api_data.url);
+    let points: Vec<OutputMapPoint> = api_data
+        .points
+        .iter()
+        .filter_map(|point| transform_map_point(point, bulk_store))
+        .collect();
+
+    match (url.is_empty(), points.is_empty()) {
+        (true, true) => None,
+        (false, true) => Some(ComponentData::MapUrl(url)),
+        _ => Some(ComponentData::Map(OutputMapData { url, points })),
     }
 }
 
+fn transform_map_point(point: &Value, bulk_store: &Arc<BulkStore>) -> Option<OutputMapPoint> {
+    let map = point.as_object()?;
+    let coord = |keys: &[&str]| {
+        keys.iter().find_map(|key| match map.get(*key)? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse::<f64>().ok(),
+            _ => None,
+        })
+    };
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| map.get(*key).and_then(Value::as_str))
+            .and_then(util::sanitize_display_string)
+    };
+
+    let x = coord(&["x", "lng", "lon"])?;
+    let y = coord(&["y", "lat"])?;
+    let ep_id = map
+        .get("ep_id")
+        .or_else(|| map.get("entry_page_id"))
+        .and_then(crate::transform::util::parse_value_as_optional_i64)
+        .filter(|&id| id > 0);
+
+    let name = ep_id
+        .and_then(|id| bulk_store.get_name(id))
+        .and_then(util::sanitize_display_string)
+        .or_else(|| text(&["name", "title"]))
+        .unwrap_or_default();
+    let icon_url = ep_id
+        .and_then(|id| bulk_store.get_icon(id))
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .or_else(|| {
+            text(&["icon_url", "icon"])
+                .as_deref()
+                .and_then(crate::transform::util::normalize_asset_url)
+        })
+        .unwrap_or_default();
+
+    Some(OutputMapPoint {
+        name,
+        x,
+        y,
+        icon_url,
+        category: text(&["category", "type"]),
+        ep_id,
+    })
+}
+
+fn transform_map_resource(
+    api_data: ApiMapResourceData,
+    page_id: EntryId,
+    lang: &str,
+) -> Option<OutputMapResourceData> {
+    let map_url = api_data
+        .map_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| {
+            let valid = url.starts_with("https://");
+            if !valid && !url.is_empty() {
+                log(
+                    LogLevel::Warning,
+                    &format!(
+                        "Dropping non-https map_resource url [{} / {}]: '{}'",
+                        lang, page_id, url
+                    ),
+                );
+            }
+            valid
+        });
+
+    let output = OutputMapResourceData {
+        map_id: api_data.map_id.filter(|&id| id > 0),
+        resource_count: api_data.resource_count.filter(|&count| count >= 0),
+        map_url,
+    };
+
+    (output.map_id.is_some() || output.resource_count.is_some() || output.map_url.is_some())
+        .then_some(output)
+}
+
 #[async_recursion]
 async fn transform_textual_research(
     items: Vec<model::ApiTextualResearchItem>,
@@ -559,10 +1196,12 @@ async fn transform_textual_research(
     let mut results = Vec::with_capacity(items.len());
     for item in items {
         let desc_nodes = parse_value_to_html_nodes(&item.desc, page_id, lang, bulk_store).await?;
-        if !desc_nodes.is_empty() || !item.title.is_empty() {
+        let title = display_string(&item.title);
+        if !desc_nodes.is_empty() || !title.is_empty() {
             results.push(OutputTextualResearchItem {
-                title: item.title,
+                title,
                 desc: desc_nodes,
+                ..Default::default()
             });
         }
     }
@@ -570,21 +1209,57 @@ async fn transform_textual_research(
 }
 
 fn transform_tcg_data(api_data: ApiTcgData) -> OutputTcgData {
+    // Upstream order is kept; a repeated image only contributes a missing description.
+    let mut header_imgs: Vec<OutputTcgHeaderImage> = Vec::new();
+    for header in api_data.header_imgs {
+        let Some(img_url) = util::normalize_asset_url(&header.img_url) else {
+            continue;
+        };
+        let img_desc = display_string(&header.img_desc);
+        match header_imgs.iter_mut().find(|h| h.img_url == img_url) {
+            Some(existing) => {
+                if existing.img_desc.is_empty() {
+                    existing.img_desc = img_desc;
+                }
+            }
+            None => header_imgs.push(OutputTcgHeaderImage { img_url, img_desc }),
+        }
+    }
+
+    let base_costs = parse_tcg_costs(&api_data.cost_icon_type, None);
+    let any_costs = parse_tcg_costs(&api_data.cost_icon_type_any, Some(true));
+    let costs = if base_costs.len() > 1 || any_costs.len() > 1 {
+        base_costs.into_iter().chain(any_costs).collect()
+    } else {
+        Vec::new()
+    };
+
     OutputTcgData {
-        cost_icon_type: api_data.cost_icon_type,
-        cost_icon_type_any: api_data.cost_icon_type_any,
-        header_imgs: api_data
-            .header_imgs
-            .into_iter()
-            .map(|h| OutputTcgHeaderImage {
-                img_url: h.img_url,
-                img_desc: h.img_desc,
-            })
-            .collect(),
+        cost_icon_type: display_string(&api_data.cost_icon_type),
+        cost_icon_type_any: display_string(&api_data.cost_icon_type_any),
+        header_imgs,
         hp: api_data.hp,
+        costs,
     }
 }
 
+// Splits a cost field on ',', '|' or ';'; each part is "type", "type:count" or "type*count".
+fn parse_tcg_costs(raw: &str, any: Option<bool>) -> Vec<OutputTcgCost> {
+    raw.split([',', '|', ';'])
+        .filter_map(|part| {
+            let (icon_type, count) = match part.split_once([':', '*']) {
+                Some((icon_type, count)) => (icon_type, count.trim().parse::<i64>().ok()),
+                None => (part, None),
+            };
+            Some(OutputTcgCost {
+                icon_type: util::sanitize_display_string(icon_type)?,
+                count,
+                any,
+            })
+        })
+        .collect()
+}
+
 async fn transform_drop_material(
     api_data: ApiDropMaterialData,
     page_id: EntryId,
@@ -600,6 +1275,201 @@ async fn transform_drop_material(
     Ok(results)
 }
 
+fn transform_gift_reward(
+    api_data: ApiGiftRewardData,
+    bulk_store: &Arc<BulkStore>,
+) -> OutputGiftRewardData {
+    let mut output = OutputGiftRewardData::default();
+
+    let tagged_items = api_data
+        .liked
+        .into_iter()
+        .map(|item| (true, item))
+        .chain(api_data.disliked.into_iter().map(|item| (false, item)));
+
+    for (in_liked_list, item) in tagged_items {
+        let is_liked = item.liked.unwrap_or(in_liked_list);
+        if let Some(output_item) = transform_gift_reward_item(item, bulk_store) {
+            if is_liked {
+                output.liked.push(output_item);
+            } else {
+                output.disliked.push(output_item);
+            }
+        }
+    }
+    output
+}
+
+fn transform_gift_reward_item(
+    item: ApiGiftRewardItem,
+    bulk_store: &Arc<BulkStore>,
+) -> Option<OutputGiftRewardItem> {
+    let ep_id = item.ep_id.filter(|&id| id > 0);
+    let name = ep_id
+        .and_then(|id| bulk_store.get_name(id))
+        .unwrap_or(&item.name);
+    let name = display_string(name);
+    let icon_url = ep_id
+        .and_then(|id| bulk_store.get_icon(id))
+        .unwrap_or(&item.icon_url);
+    let icon_url = display_string(icon_url);
+
+    (!name.is_empty() || !icon_url.is_empty()).then_some(OutputGiftRewardItem {
+        icon_url,
+        name,
+        ep_id,
+    })
+}
+
+#[async_recursion]
+async fn transform_build_sections(
+    sections: Vec<model::ApiBuildSection>,
+    page_id: EntryId,
+    lang: &str,
+    bulk_store: &Arc<BulkStore>,
+) -> AppResult<Vec<OutputBuildSection>> {
+    let mut results = Vec::with_capacity(sections.len());
+    for section in sections {
+        let desc_nodes =
+            parse_value_to_html_nodes(&section.desc, page_id, lang, bulk_store).await?;
+        let weapons = resolve_build_references(&section.weapons, bulk_store);
+        let artifacts = resolve_build_references(&section.artifacts, bulk_store);
+        let title = display_string(&section.title);
+        if !title.is_empty()
+            || !desc_nodes.is_empty()
+            || !weapons.is_empty()
+            || !artifacts.is_empty()
+        {
+            results.push(OutputBuildSection {
+                title,
+                desc: desc_nodes,
+                weapons,
+                artifacts,
+                ..Default::default()
+            });
+        }
+    }
+    Ok(results)
+}
+
+async fn transform_event_calendar(
+    event_data: model::ApiEventCalendarData,
+    page_id: EntryId,
+    lang: &str,
+    bulk_store: &Arc<BulkStore>,
+) -> AppResult<Option<OutputEventCalendarData>> {
+    let start_value = event_data.start_time.clone().map(Value::String);
+    let end_value = event_data.end_time.clone().map(Value::String);
+    let start_timestamp = start_value.as_ref().and_then(util::parse_value_as_date);
+    let end_timestamp = end_value.as_ref().and_then(util::parse_value_as_date);
+    if event_data.start_time.is_some() && start_timestamp.is_none() {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Unparseable event start time [{} / {}]: {:?}",
+                lang, page_id, event_data.start_time
+            ),
+        );
+    }
+    if event_data.end_time.is_some() && end_timestamp.is_none() {
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Unparseable event end time [{} / {}]: {:?}",
+                lang, page_id, event_data.end_time
+            ),
+        );
+    }
+    // Unparseable values are kept verbatim so the source text isn't lost.
+    let normalize_time = |value: Option<&Value>, raw: Option<String>| {
+        util::format_calendar_datetime_value(value)
+            .or_else(|| util::format_calendar_date_value(value))
+            .or_else(|| raw.as_deref().and_then(util::sanitize_display_string))
+    };
+    let start_time = normalize_time(start_value.as_ref(), event_data.start_time);
+    let end_time = normalize_time(end_value.as_ref(), event_data.end_time);
+
+    let rewards = if event_data.rewards.is_null() {
+        Vec::new()
+    } else {
+        parse_materials_value(&event_data.rewards, page_id, lang, bulk_store).await?
+    };
+    let description =
+        parse_value_to_html_nodes(&event_data.description, page_id, lang, bulk_store).await?;
+
+    if start_time.is_none() && end_time.is_none() && rewards.is_empty() && description.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(OutputEventCalendarData {
+        start_time,
+        end_time,
+        start_timestamp,
+        end_timestamp,
+        rewards: (!rewards.is_empty()).then_some(rewards),
+        description,
+        ..Default::default()
+    }))
+}
+
+async fn transform_achievement_group(
+    group_data: ApiAchievementGroupData,
+    page_id: EntryId,
+    lang: &str,
+    bulk_store: &Arc<BulkStore>,
+) -> AppResult<Option<OutputAchievementGroupData>> {
+    let mut items = Vec::with_capacity(group_data.items.len());
+    for item in group_data.items {
+        let ep_id = item.ep_id.filter(|&id| id > 0);
+        let icon_url = ep_id
+            .and_then(|id| bulk_store.get_icon(id))
+            .or(item.icon_url.as_deref())
+            .map(display_string)
+            .unwrap_or_default();
+        let title = display_string(&item.title);
+        let desc = parse_value_to_html_nodes(&item.desc, page_id, lang, bulk_store).await?;
+        if title.is_empty() && desc.is_empty() && ep_id.is_none() {
+            continue;
+        }
+        items.push(OutputAchievementItem {
+            ep_id,
+            title,
+            desc,
+            icon_url,
+            is_hidden: item.is_hidden,
+            ..Default::default()
+        });
+    }
+
+    let group_name = display_string(&group_data.group_name);
+    if group_name.is_empty() && items.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(OutputAchievementGroupData { group_name, items }))
+}
+
+fn resolve_build_references(
+    refs: &[Value],
+    bulk_store: &Arc<BulkStore>,
+) -> Vec<OutputBuildReference> {
+    let mut seen = HashSet::new();
+    refs.iter()
+        .filter_map(|ref_val| match ref_val {
+            Value::Object(map) => map
+                .get("ep_id")
+                .or_else(|| map.get("entry_page_id"))
+                .and_then(crate::transform::util::parse_value_as_optional_i64),
+            other => crate::transform::util::parse_value_as_optional_i64(other),
+        })
+        .filter(|&ep_id| ep_id > 0 && seen.insert(ep_id))
+        .map(|ep_id| OutputBuildReference {
+            ep_id,
+            name: bulk_store.get_name(ep_id).unwrap_or_default().to_string(),
+            icon_url: bulk_store.get_icon(ep_id).unwrap_or_default().to_string(),
+        })
+        .collect()
+}
+
 #[async_recursion]
 async fn parse_value_to_html_nodes(
     value: &Value,
@@ -621,14 +1491,28 @@ async fn parse_value_to_html_nodes(
                 let html_owned = trimmed.to_string();
                 let lang_owned = lang.to_string();
                 let bulk_store_clone = bulk_store.clone();
+                let flat = HTML_PARSE_STATE
+                    .try_with(|state| state.flat)
+                    .unwrap_or(false);
                 let parse_result = utils::run_blocking(move || {
-                    html_parser::parse_html_content(&html_owned, page_id, &lang_owned)
+                    if flat {
+                        Ok((html_parser::parse_html_content_flat(&html_owned), true))
+                    } else {
+                        html_parser::parse_html_content_degradable(
+                            &html_owned,
+                            page_id,
+                            &lang_owned,
+                        )
+                    }
                 })
                 .await;
 
                 match parse_result {
-                    Ok(nodes) => {
-                        html_parser::post_process_html_nodes(nodes, &bulk_store_clone).await
+                    Ok((nodes, degraded)) => {
+                        if degraded {
+                            let _ = HTML_PARSE_STATE.try_with(|state| state.degraded.set(true));
+                        }
+                        html_parser::post_process_html_nodes(nodes, &bulk_store_clone, lang).await
                     }
                     Err(e) => Err(e),
                 }
@@ -648,20 +1532,49 @@ async fn parse_value_to_html_nodes(
             Ok(all_nodes)
         }
         Value::Object(obj) => {
-            log(
-                LogLevel::Warning,
-                &format!(
-                    "Unexpected object during HTML node parsing [{} / {}]: {:?}",
-                    lang, page_id, obj
-                ),
-            );
-            Ok(vec![])
+            // Shapes seen in customize payloads: `{ text, align }` and `{ content: [...] }`.
+            let inner = obj
+                .get("text")
+                .filter(|v| v.is_string())
+                .or_else(|| obj.get("content").filter(|v| v.is_array() || v.is_string()));
+            let Some(inner) = inner else {
+                log(
+                    LogLevel::Warning,
+                    &format!(
+                        "Unexpected object during HTML node parsing [{} / {}]: {:?}",
+                        lang, page_id, obj
+                    ),
+                );
+                return Ok(vec![]);
+            };
+            let mut nodes = parse_value_to_html_nodes(inner, page_id, lang, bulk_store).await?;
+            let align = obj
+                .get("align")
+                .or_else(|| obj.get("alignment"))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|a| !a.is_empty());
+            if let Some(align) = align {
+                apply_default_alignment(&mut nodes, align);
+            }
+            Ok(nodes)
         }
         Value::Null => Ok(vec![]),
         _ => Ok(vec![]),
     }
 }
 
+fn apply_default_alignment(nodes: &mut [HtmlNode], align: &str) {
+    for node in nodes {
+        if let HtmlNode::RichText { alignment, .. }
+        | HtmlNode::Heading { alignment, .. }
+        | HtmlNode::CustomImage { alignment, .. } = node
+        {
+            alignment.get_or_insert_with(|| align.to_string());
+        }
+    }
+}
+
 async fn parse_materials_value(
     value: &Value,
     page_id: EntryId,
@@ -680,10 +1593,19 @@ async fn parse_materials_value(
                 if !inner_json_str.is_empty() {
                     match from_str::<Vec<serde_json::Map<String, Value>>>(inner_json_str) {
                         Ok(list) => material_list.extend(list),
-                        Err(_) => match from_str::<serde_json::Map<String, Value>>(inner_json_str) {
-                            Ok(map) => material_list.push(map),
-                            Err(e_inner) => log(LogLevel::Warning, &format!("Failed to parse inner material JSON string '{}' as Vec or Map: {}", inner_json_str, e_inner)),
-                        },
+                        Err(_) => {
+                            match from_str::<serde_json::Map<String, Value>>(inner_json_str) {
+                                Ok(map) => material_list.push(map),
+                                Err(e_inner) => util::report_parse_failure(
+                                    "material_json",
+                                    "unparsable",
+                                    lang,
+                                    Some(page_id),
+                                    inner_json_str,
+                                    &e_inner,
+                                ),
+                            }
+                        }
                     }
                 }
             }
@@ -705,13 +1627,23 @@ async fn parse_materials_value(
                                     material_list.extend(list);
                                     successfully_parsed_from_array = true;
                                 }
-                                Err(_) => match from_str::<serde_json::Map<String, Value>>(inner_json_str) {
-                                    Ok(map) => {
-                                        material_list.push(map);
-                                        successfully_parsed_from_array = true;
+                                Err(_) => {
+                                    match from_str::<serde_json::Map<String, Value>>(inner_json_str)
+                                    {
+                                        Ok(map) => {
+                                            material_list.push(map);
+                                            successfully_parsed_from_array = true;
+                                        }
+                                        Err(e_inner) => util::report_parse_failure(
+                                            "material_json",
+                                            "unparsable",
+                                            lang,
+                                            Some(page_id),
+                                            inner_json_str,
+                                            &e_inner,
+                                        ),
                                     }
-                                    Err(e_inner) => log(LogLevel::Warning, &format!("Failed to parse inner material JSON string '{}' from array element: {}", inner_json_str, e_inner)),
-                                },
+                                }
                             }
                         }
                     }
@@ -758,12 +1690,13 @@ async fn parse_materials_value(
                 });
 
             if !is_simple_or_empty {
-                log(
-                    LogLevel::Warning,
-                    &format!(
-                        "Failed fallback material parse for value: {:?} on page {}/{}",
-                        value, lang, page_id
-                    ),
+                util::report_parse_failure(
+                    "material_value",
+                    "unrecognized",
+                    lang,
+                    Some(page_id),
+                    &value.to_string(),
+                    &"not a material list or entry",
                 );
             }
         }
@@ -772,12 +1705,13 @@ async fn parse_materials_value(
     if material_list.is_empty() {
         Ok(vec![])
     } else {
-        process_material_list(material_list, bulk_store).await
+        process_material_list(material_list, lang, bulk_store).await
     }
 }
 
 async fn process_material_list(
     material_list: Vec<serde_json::Map<String, Value>>,
+    lang: &str,
     bulk_store: &Arc<BulkStore>,
 ) -> AppResult<Vec<HtmlNode>> {
     let mut processed_mats = Vec::with_capacity(material_list.len());
@@ -794,7 +1728,8 @@ async fn process_material_list(
                         .get("icon")
                         .or_else(|| mat_map.get("icon_url"))
                         .or_else(|| mat_map.get("img"))
-                        .and_then(Value::as_str);
+                        .and_then(Value::as_str)
+                        .and_then(crate::transform::util::normalize_asset_url);
                     let desc_from_map = mat_map.get("desc").and_then(Value::as_str);
                     let menu_id_from_map = mat_map
                         .get("menu_id")
@@ -811,7 +1746,7 @@ async fn process_material_list(
                         .get_icon(ep_id)
                         .filter(|s| !s.is_empty())
                         .map(String::from)
-                        .or_else(|| icon_from_map.map(String::from))
+                        .or(icon_from_map)
                         .unwrap_or_default();
 
                     let desc = bulk_store
@@ -833,6 +1768,7 @@ async fn process_material_list(
                         amount,
                         display_style,
                         menu_id: menu_id_from_map,
+                        link: common::entry_link(ep_id, lang),
                     });
                 }
             }
@@ -855,11 +1791,13 @@ async fn transform_base_info_list(
         } else {
             parse_value_to_html_nodes(&item.value, page_id, lang, bulk_store).await?
         };
-        if !item.key.is_empty() || !nodes.is_empty() {
+        let key = display_string(&item.key);
+        if !key.is_empty() || !nodes.is_empty() {
             results.push(OutputBaseInfoItem {
-                key: item.key,
+                key,
                 value: if nodes.is_empty() { None } else { Some(nodes) },
                 is_material: item.is_material,
+                ..Default::default()
             });
         }
     }
@@ -870,27 +1808,147 @@ async fn transform_base_info_list(
 async fn transform_ascension_list(
     items: Vec<model::ApiAscensionItem>,
     page_id: EntryId,
+    menu_id: MenuId,
     lang: &str,
     bulk_store: &Arc<BulkStore>,
 ) -> AppResult<Vec<OutputAscensionItem>> {
+    let is_weapon = MenuCategory::from(menu_id) == MenuCategory::Weapons;
     let mut results = Vec::with_capacity(items.len());
     for item in items {
         let materials = parse_materials_value(&item.materials, page_id, lang, bulk_store).await?;
-        if !item.combat_list.is_null() || !materials.is_empty() || !item.key.is_empty() {
+        let combat_list = decode_combat_list(item.combat_list, page_id, lang);
+        let key = display_string(&item.key);
+        if !combat_list.is_null() || !materials.is_empty() || !key.is_empty() {
             results.push(OutputAscensionItem {
-                key: item.key,
-                combat_stats: item.combat_list.clone(),
+                key,
+                combat_stats: if is_weapon {
+                    normalize_weapon_combat_list(&combat_list)
+                } else {
+                    combat_list
+                },
                 materials: if materials.is_empty() {
                     None
                 } else {
                     Some(materials)
                 },
+                ..Default::default()
             });
         }
     }
     Ok(results)
 }
 
+// Only flat node-list components are cut; the marker keeps the truncation visible to readers.
+fn truncate_oversized_components(
+    components: &mut HashMap<String, ComponentData>,
+    threshold: usize,
+    page_id: EntryId,
+    lang: &str,
+) {
+    for (key, component) in components.iter_mut() {
+        if statistics::component_size(component) <= threshold {
+            continue;
+        }
+        let (ComponentData::Customize(nodes) | ComponentData::DropMaterial(nodes)) = component
+        else {
+            continue;
+        };
+        if nodes.len() <= config::OVERSIZED_NODE_LIMIT {
+            continue;
+        }
+        let dropped = nodes.len() - config::OVERSIZED_NODE_LIMIT;
+        nodes.truncate(config::OVERSIZED_NODE_LIMIT);
+        nodes.push(HtmlNode::RichText {
+            text: format!("[{} more item(s) truncated]", dropped),
+            alignment: None,
+        });
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Component '{}' [{} / {}] truncated: dropped {} of {} node(s).",
+                key,
+                lang,
+                page_id,
+                dropped,
+                dropped + config::OVERSIZED_NODE_LIMIT
+            ),
+        );
+    }
+}
+
+fn is_weapon_page(menu_id: MenuId, list_menu_ids: &[MenuId], filter_values: &Value) -> bool {
+    MenuCategory::Weapons.contains_any(menu_id, list_menu_ids)
+        || [config::KEY_WEAPON_TYPE, config::KEY_WEAPON_RARITY]
+            .iter()
+            .any(|key| filter_values.get(key).is_some_and(|v| !v.is_null()))
+}
+
+// Weapon combat lists mix a header row ("Before/After Ascension"), numeric and string
+// values, and `name`/`value` spellings. Normalize to `[{ key, values: [String] }]`.
+// Some responses send combat_list as a JSON-encoded string instead of an array.
+fn decode_combat_list(combat_list: Value, page_id: EntryId, lang: &str) -> Value {
+    let Value::String(raw) = &combat_list else {
+        return combat_list;
+    };
+    let Some(embedded) = util::EmbeddedJson::detect(raw) else {
+        return combat_list;
+    };
+    match embedded.parse() {
+        Ok(decoded) => {
+            telemetry::record_coercion("combat_list", "stringified");
+            decoded
+        }
+        Err(e) => {
+            util::report_parse_failure(
+                "combat_list",
+                "unparsable",
+                lang,
+                Some(page_id),
+                embedded.payload(),
+                &e,
+            );
+            combat_list
+        }
+    }
+}
+
+fn normalize_weapon_combat_list(combat_list: &Value) -> Value {
+    let Value::Array(rows) = combat_list else {
+        return combat_list.clone();
+    };
+    let stats: Vec<Value> = rows
+        .iter()
+        .filter_map(|row| {
+            let key = row
+                .get("key")
+                .or_else(|| row.get("name"))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|key| !key.is_empty())?;
+            let values: Vec<String> = match row.get("values").or_else(|| row.get("value")) {
+                Some(Value::Array(values)) => values.iter().filter_map(stat_value_string).collect(),
+                Some(value) => stat_value_string(value).into_iter().collect(),
+                None => Vec::new(),
+            };
+            Some(json!({ "key": key, "values": values }))
+        })
+        .collect();
+    Value::Array(stats)
+}
+
+// Display strings for `String` output fields, which are skipped when empty.
+fn display_string(value: &str) -> String {
+    util::sanitize_display_string(value).unwrap_or_default()
+}
+
+fn stat_value_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => util::sanitize_display_string(s),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
 #[async_recursion]
 async fn transform_talent_list(
     items: Vec<model::ApiTalentItem>,
@@ -924,20 +1982,31 @@ async fn transform_talent_list(
             }
         }
 
+        let title = item
+            .title
+            .as_deref()
+            .map(display_string)
+            .unwrap_or_default();
+        let icon_url = item
+            .icon_url
+            .as_deref()
+            .map(display_string)
+            .unwrap_or_default();
         if !desc_nodes.is_empty()
             || !item.attributes.is_null()
             || processed_levels.is_some()
-            || item.title.is_some()
-            || item.icon_url.is_some()
+            || !title.is_empty()
+            || !icon_url.is_empty()
         {
             results.push(OutputTalentItem {
-                key: item.key,
-                title: item.title.unwrap_or_default(),
-                icon_url: item.icon_url.unwrap_or_default(),
+                key: item.key.as_deref().and_then(util::sanitize_display_string),
+                title,
+                icon_url,
                 desc: desc_nodes,
                 attributes: item.attributes.clone(),
                 materials: processed_levels,
                 talent_img: item.talent_img,
+                ..Default::default()
             });
         }
     }
@@ -954,11 +2023,14 @@ async fn transform_summary_list(
     let mut results = Vec::with_capacity(items.len());
     for item in items {
         let desc_nodes = parse_value_to_html_nodes(&item.desc, page_id, lang, bulk_store).await?;
-        if !desc_nodes.is_empty() || !item.name.is_empty() || !item.icon_url.is_empty() {
+        let name = display_string(&item.name);
+        let icon_url = display_string(&item.icon_url);
+        if !desc_nodes.is_empty() || !name.is_empty() || !icon_url.is_empty() {
             results.push(OutputSummaryItem {
-                icon_url: item.icon_url,
-                name: item.name,
+                icon_url,
+                name,
                 desc: desc_nodes,
+                ..Default::default()
             });
         }
     }
@@ -975,10 +2047,12 @@ async fn transform_story_list(
     let mut results = Vec::with_capacity(items.len());
     for item in items {
         let desc_nodes = parse_value_to_html_nodes(&item.desc, page_id, lang, bulk_store).await?;
-        if !desc_nodes.is_empty() || !item.title.is_empty() {
+        let title = display_string(&item.title);
+        if !desc_nodes.is_empty() || !title.is_empty() {
             results.push(OutputStoryItem {
-                title: item.title,
+                title,
                 desc: desc_nodes,
+                ..Default::default()
             });
         }
     }
@@ -1006,17 +2080,235 @@ fn transform_voice_item(item: model::ApiVoiceItem) -> Option<OutputVoiceItem> {
             }
         }
     }
-    if item.title.is_empty() && item.desc.is_empty() && output_audios.is_empty() {
+    let title = display_string(&item.title);
+    let desc = display_string(&item.desc);
+    if title.is_empty() && desc.is_empty() && output_audios.is_empty() {
         None
     } else {
         Some(OutputVoiceItem {
-            title: item.title,
-            desc: item.desc,
+            title,
+            desc,
             audios: output_audios,
         })
     }
 }
 
+/// Gallery key given to the `pic` image of a `gallery_character` component.
+fn character_card_key(lang: &str) -> &'static str {
+    match lang {
+        "id-id" => "Kartu Karakter",
+
+        _ => "Character Card",
+    }
+}
+
+fn gallery_items(
+    components: &HashMap<String, ComponentData>,
+) -> impl Iterator<Item = &OutputGalleryCharacterItem> {
+    components.values().flat_map(|component| match component {
+        ComponentData::GalleryCharacter(items) => items.as_slice(),
+        _ => &[],
+    })
+}
+
+/// Removes one copy of the header image when a gallery item shows the same image
+/// after URL normalization. Gallery components left empty are removed.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use wiki_update::config::HeaderGalleryDedup;
+/// use wiki_update::model::output::{ComponentData, OutputGalleryCharacterItem};
+/// use wiki_update::transform::detail::dedup_header_gallery;
+///
+/// let card = |img: &str| OutputGalleryCharacterItem {
+///     key: "Character Card".to_string(),
+///     img: img.to_string(),
+///     ..Default::default()
+/// };
+/// let gallery = || {
+///     HashMap::from([(
+///         "galleryCharacter".to_string(),
+///         ComponentData::GalleryCharacter(vec![card("https://cdn.example/card.png")]),
+///     )])
+/// };
+///
+/// // Protocol-relative and https URLs compare equal.
+/// let mut header = Some("//cdn.example/card.png".to_string());
+/// let mut components = gallery();
+/// dedup_header_gallery(&mut header, &mut components, HeaderGalleryDedup::DropGalleryItem);
+/// assert!(header.is_some());
+/// assert!(components.is_empty());
+///
+/// let mut components = gallery();
+/// dedup_header_gallery(&mut header, &mut components, HeaderGalleryDedup::ClearHeader);
+/// assert_eq!(header, None);
+/// assert_eq!(components, gallery());
+///
+/// // Different images are left alone.
+/// let mut header = Some("https://cdn.example/header.png".to_string());
+/// let mut components = gallery();
+/// dedup_header_gallery(&mut header, &mut components, HeaderGalleryDedup::ClearHeader);
+/// assert!(header.is_some());
+/// assert_eq!(components, gallery());
+/// ```
+pub fn dedup_header_gallery(
+    header_img_url: &mut Option<String>,
+    components: &mut HashMap<String, ComponentData>,
+    mode: config::HeaderGalleryDedup,
+) {
+    let Some(header_key) = header_img_url
+        .as_deref()
+        .and_then(util::normalize_asset_url)
+    else {
+        return;
+    };
+    let is_header = |item: &OutputGalleryCharacterItem| {
+        util::normalize_asset_url(&item.img).is_some_and(|img| img == header_key)
+    };
+
+    match mode {
+        config::HeaderGalleryDedup::DropGalleryItem => {
+            for component in components.values_mut() {
+                if let ComponentData::GalleryCharacter(items) = component {
+                    items.retain(|item| !is_header(item));
+                }
+            }
+            components.retain(
+                |_key, component| !matches!(component, ComponentData::GalleryCharacter(items) if items.is_empty()),
+            );
+        }
+        config::HeaderGalleryDedup::ClearHeader => {
+            if gallery_items(components).any(is_header) {
+                *header_img_url = None;
+            }
+        }
+    }
+}
+
+/// The page's `primaryImage`: the header image, else the Character Card gallery
+/// image, else the icon.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use wiki_update::model::output::{ComponentData, OutputGalleryCharacterItem};
+/// use wiki_update::transform::detail::select_primary_image;
+///
+/// let item = |key: &str, img: &str| OutputGalleryCharacterItem {
+///     key: key.to_string(),
+///     img: img.to_string(),
+///     ..Default::default()
+/// };
+/// let components = HashMap::from([(
+///     "galleryCharacter".to_string(),
+///     ComponentData::GalleryCharacter(vec![
+///         item("Outfit", "https://cdn.example/outfit.png"),
+///         item("Character Card", "https://cdn.example/card.png"),
+///     ]),
+/// )]);
+/// let icon = Some("//cdn.example/icon.png");
+///
+/// assert_eq!(
+///     select_primary_image(Some("//cdn.example/header.png"), &components, icon, "en-us").as_deref(),
+///     Some("https://cdn.example/header.png")
+/// );
+/// assert_eq!(
+///     select_primary_image(None, &components, icon, "en-us").as_deref(),
+///     Some("https://cdn.example/card.png")
+/// );
+/// // A placeholder header counts as missing.
+/// assert_eq!(
+///     select_primary_image(Some("null"), &HashMap::new(), icon, "en-us").as_deref(),
+///     Some("https://cdn.example/icon.png")
+/// );
+/// assert_eq!(select_primary_image(None, &HashMap::new(), None, "en-us"), None);
+/// ```
+pub fn select_primary_image(
+    header_img_url: Option<&str>,
+    components: &HashMap<String, ComponentData>,
+    icon_url: Option<&str>,
+    lang: &str,
+) -> Option<String> {
+    let card_key = character_card_key(lang);
+    header_img_url
+        .and_then(util::normalize_asset_url)
+        .or_else(|| {
+            gallery_items(components)
+                .filter(|item| item.key == card_key)
+                .find_map(|item| util::normalize_asset_url(&item.img))
+        })
+        .or_else(|| icon_url.and_then(util::normalize_asset_url))
+}
+
+fn merge_gallery_items(
+    existing_items: &mut Vec<OutputGalleryCharacterItem>,
+    new_items: Vec<OutputGalleryCharacterItem>,
+) {
+    let gallery_img_key = |img: &str| {
+        crate::transform::util::normalize_asset_url(img).unwrap_or_else(|| img.trim().to_string())
+    };
+
+    for new_item in new_items {
+        let new_img = gallery_img_key(&new_item.img);
+        let duplicate = existing_items.iter().position(|item| {
+            if new_img.is_empty() {
+                item.img.trim().is_empty() && item.key == new_item.key
+            } else {
+                gallery_img_key(&item.img) == new_img
+            }
+        });
+
+        match duplicate {
+            Some(index) => {
+                let current_key = existing_items[index].key.trim();
+                let new_key = new_item.key.trim();
+                let prefer_new_key = !new_key.is_empty()
+                    && new_key != current_key
+                    && (current_key.is_empty()
+                        || new_key.chars().count() > current_key.chars().count());
+                if prefer_new_key {
+                    let key = unique_gallery_key(existing_items, new_key, Some(index));
+                    existing_items[index].key = key;
+                }
+                if existing_items[index].img_desc.is_empty() && !new_item.img_desc.is_empty() {
+                    existing_items[index].img_desc = new_item.img_desc;
+                }
+            }
+            None => {
+                let key = unique_gallery_key(existing_items, new_item.key.trim(), None);
+                existing_items.push(OutputGalleryCharacterItem { key, ..new_item });
+            }
+        }
+    }
+}
+
+fn unique_gallery_key(
+    items: &[OutputGalleryCharacterItem],
+    base_key: &str,
+    skip_index: Option<usize>,
+) -> String {
+    let is_taken = |candidate: &str| {
+        items
+            .iter()
+            .enumerate()
+            .any(|(i, item)| Some(i) != skip_index && item.key == candidate)
+    };
+    if base_key.is_empty() || !is_taken(base_key) {
+        return base_key.to_string();
+    }
+    let stem = base_key
+        .rsplit_once(' ')
+        .filter(|(_, suffix)| suffix.parse::<u32>().is_ok_and(|n| n >= 2))
+        .map_or(base_key, |(stem, _)| stem);
+    (2..)
+        .map(|n| format!("{} {}", stem, n))
+        .find(|candidate| !is_taken(candidate))
+        .unwrap_or_else(|| base_key.to_string())
+}
+
 #[async_recursion]
 async fn transform_gallery_list(
     items: Vec<model::ApiGalleryCharacterItem>,
@@ -1024,18 +2316,23 @@ async fn transform_gallery_list(
     lang: &str,
     bulk_store: &Arc<BulkStore>,
 ) -> AppResult<Vec<OutputGalleryCharacterItem>> {
-    let mut results = Vec::with_capacity(items.len());
+    let mut transformed = Vec::with_capacity(items.len());
     for item in items {
         let desc_nodes =
             parse_value_to_html_nodes(&item.img_desc, page_id, lang, bulk_store).await?;
-        if !item.img.is_empty() || !item.key.is_empty() {
-            results.push(OutputGalleryCharacterItem {
-                key: item.key,
-                img: item.img,
+        let img = crate::transform::util::normalize_asset_url(&item.img).unwrap_or_default();
+        let key = display_string(&item.key);
+        if !img.is_empty() || !key.is_empty() {
+            transformed.push(OutputGalleryCharacterItem {
+                key,
+                img,
                 img_desc: desc_nodes,
+                ..Default::default()
             });
         }
     }
+    let mut results = Vec::with_capacity(transformed.len());
+    merge_gallery_items(&mut results, transformed);
     Ok(results)
 }
 
@@ -1046,25 +2343,23 @@ async fn transform_gallery_character_wrapper(
     lang: &str,
     bulk_store: &Arc<BulkStore>,
 ) -> AppResult<Vec<OutputGalleryCharacterItem>> {
-    let mut transformed_list =
-        transform_gallery_list(wrapper.list, page_id, lang, bulk_store).await?;
-
-    if let Some(pic_url) = wrapper.pic {
-        if !pic_url.is_empty() {
-            let character_card_key = match lang {
-                "id-id" => "Kartu Karakter",
-
-                _ => "Character Card",
-            };
-
-            let pic_item = OutputGalleryCharacterItem {
-                key: character_card_key.to_string(),
-                img: pic_url,
-                img_desc: vec![],
-            };
+    let transformed_list = transform_gallery_list(wrapper.list, page_id, lang, bulk_store).await?;
+
+    if let Some(pic_url) = wrapper
+        .pic
+        .as_deref()
+        .and_then(crate::transform::util::normalize_asset_url)
+    {
+        let pic_item = OutputGalleryCharacterItem {
+            key: character_card_key(lang).to_string(),
+            img: pic_url,
+            img_desc: vec![],
+            ..Default::default()
+        };
 
-            transformed_list.insert(0, pic_item);
-        }
+        let mut items = vec![pic_item];
+        merge_gallery_items(&mut items, transformed_list);
+        return Ok(items);
     }
 
     Ok(transformed_list)
@@ -1080,17 +2375,663 @@ async fn transform_artifact_map(
     let mut results = HashMap::with_capacity(item_map.len());
     for (key, item) in item_map {
         let desc_nodes = parse_value_to_html_nodes(&item.desc, page_id, lang, bulk_store).await?;
-        if !desc_nodes.is_empty() || !item.icon_url.is_empty() || item.title.is_some() {
+        let title = item
+            .title
+            .as_deref()
+            .and_then(util::sanitize_display_string);
+        let icon_url = display_string(&item.icon_url);
+        if !desc_nodes.is_empty() || !icon_url.is_empty() || title.is_some() {
             results.insert(
                 key.clone(),
                 OutputArtifactListItem {
-                    title: item.title,
-                    position: item.position,
+                    title,
+                    position: display_string(&item.position),
                     desc: desc_nodes,
-                    icon_url: item.icon_url,
+                    icon_url,
+                    ..Default::default()
                 },
             );
         }
     }
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::model::{ApiDetailResponse, ApiWrapper};
+    use crate::core::data_store::TransformedData;
+    use crate::model::output::{OutputEventWindow, OutputProgressionData, ProgressionKind};
+    use crate::transform::bulk::BulkInfo;
+    use std::path::Path;
+
+    fn fixture_page(name: &str) -> model::ApiDetailPage {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join(name);
+        let text = std::fs::read_to_string(path).unwrap();
+        let wrapper: ApiWrapper<ApiDetailResponse> = from_str(&text).unwrap();
+        wrapper.data.unwrap().page
+    }
+
+    async fn transform_fixture(name: &str) -> OutputDetailPage {
+        transform_fixture_in(name, "en-us").await
+    }
+
+    async fn transform_fixture_in(name: &str, lang: &str) -> OutputDetailPage {
+        transform_detail_page(
+            fixture_page(name),
+            Arc::new(BulkStore::default()),
+            Default::default(),
+            lang,
+        )
+        .await
+        .unwrap()
+        .unwrap()
+    }
+
+    fn entry_link_components(ep_id: EntryId) -> HashMap<String, ComponentData> {
+        let nodes: Vec<HtmlNode> = from_value(json!([
+            { "type": "RichText", "text": " " },
+            { "type": "CustomEntry", "epId": ep_id, "name": "Traveler" }
+        ]))
+        .unwrap();
+        HashMap::from([("customize".to_string(), ComponentData::Customize(nodes))])
+    }
+
+    #[tokio::test]
+    async fn single_entry_link_page_becomes_an_alias() {
+        let page = transform_fixture("detail_alias_entry_link.json").await;
+        assert_eq!(page.id, 7001);
+        assert_eq!(page.alias_of, Some(7000));
+        assert!(page.components.is_empty());
+        assert_eq!(
+            collect_alias_entries(&[page]),
+            [OutputAliasEntry {
+                id: 7001,
+                alias_of: 7000
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn explicit_redirect_field_becomes_an_alias() {
+        let page = transform_fixture("detail_alias_redirect.json").await;
+        assert_eq!(page.alias_of, Some(7000));
+        assert!(page.components.is_empty());
+    }
+
+    #[test]
+    fn alias_detection_modes() {
+        use config::AliasDetection::*;
+        let components = entry_link_components(7000);
+        let detect = |has_bulk_name, detection| {
+            detect_entry_link_alias(7001, has_bulk_name, &components, detection)
+        };
+        assert_eq!(detect(false, ExplicitOnly), None);
+        assert_eq!(detect(false, EntryLink), Some(7000));
+        assert_eq!(detect(true, EntryLink), None);
+        assert_eq!(detect(true, EntryLinkAnyName), Some(7000));
+    }
+
+    #[test]
+    fn self_links_and_extra_components_are_not_aliases() {
+        let detection = config::AliasDetection::EntryLinkAnyName;
+        let self_link = entry_link_components(7001);
+        assert_eq!(
+            detect_entry_link_alias(7001, false, &self_link, detection),
+            None
+        );
+
+        let mut extra = entry_link_components(7000);
+        extra.insert("baseInfo".to_string(), ComponentData::Customize(Vec::new()));
+        assert_eq!(
+            detect_entry_link_alias(7001, false, &extra, detection),
+            None
+        );
+    }
+
+    fn gallery_item(key: &str, img: &str) -> OutputGalleryCharacterItem {
+        OutputGalleryCharacterItem {
+            key: key.to_string(),
+            img: img.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn gallery_keys(items: &[OutputGalleryCharacterItem]) -> Vec<(&str, &str)> {
+        items
+            .iter()
+            .map(|item| (item.key.as_str(), item.img.as_str()))
+            .collect()
+    }
+
+    const IMG_A: &str = "https://example.test/a.png";
+    const IMG_B: &str = "https://example.test/b.png";
+    const IMG_C: &str = "https://example.test/c.png";
+
+    #[test]
+    fn gallery_same_url_keeps_the_longer_key() {
+        let mut items = vec![gallery_item("Outfit", IMG_A)];
+        merge_gallery_items(&mut items, vec![gallery_item("Outfit: Default", IMG_A)]);
+        assert_eq!(gallery_keys(&items), [("Outfit: Default", IMG_A)]);
+
+        merge_gallery_items(&mut items, vec![gallery_item("Outfit", IMG_A)]);
+        assert_eq!(gallery_keys(&items), [("Outfit: Default", IMG_A)]);
+    }
+
+    #[test]
+    fn gallery_same_url_with_an_empty_key() {
+        let mut items = vec![gallery_item("Namecard", IMG_A)];
+        merge_gallery_items(&mut items, vec![gallery_item("", IMG_A)]);
+        assert_eq!(gallery_keys(&items), [("Namecard", IMG_A)]);
+
+        let mut items = vec![gallery_item("", IMG_A)];
+        merge_gallery_items(&mut items, vec![gallery_item("Namecard", IMG_A)]);
+        assert_eq!(gallery_keys(&items), [("Namecard", IMG_A)]);
+    }
+
+    #[test]
+    fn gallery_key_collision_on_a_new_url_is_numbered() {
+        let mut items = vec![gallery_item("Outfit", IMG_A)];
+        merge_gallery_items(
+            &mut items,
+            vec![gallery_item("Outfit", IMG_B), gallery_item("Outfit", IMG_C)],
+        );
+        assert_eq!(
+            gallery_keys(&items),
+            [("Outfit", IMG_A), ("Outfit 2", IMG_B), ("Outfit 3", IMG_C)]
+        );
+    }
+
+    #[test]
+    fn unique_gallery_key_skips_the_item_being_renamed() {
+        let items = vec![
+            gallery_item("Outfit", IMG_A),
+            gallery_item("Outfit 2", IMG_B),
+        ];
+        assert_eq!(unique_gallery_key(&items, "Outfit 2", Some(1)), "Outfit 2");
+        assert_eq!(unique_gallery_key(&items, "Outfit 2", None), "Outfit 3");
+        assert_eq!(unique_gallery_key(&items, "Outfit", Some(1)), "Outfit 2");
+        assert_eq!(unique_gallery_key(&items, "Outfit", Some(0)), "Outfit");
+        assert_eq!(unique_gallery_key(&items, "", None), "");
+    }
+
+    #[tokio::test]
+    async fn gallery_outfits_fixture_merges_modules() {
+        let page = transform_fixture("detail_gallery_outfits.json").await;
+        let items = page
+            .components
+            .values()
+            .find_map(|component| match component {
+                ComponentData::GalleryCharacter(items) => Some(items),
+                _ => None,
+            })
+            .expect("gallery component");
+        let upload = "https://act-upload.hoyoverse.com/wiki-user-upload/";
+        let expected: Vec<(String, String)> = [
+            (character_card_key("en-us"), "jean_card.png"),
+            ("Outfit: Default", "jean_default.png"),
+            ("Namecard", "jean_namecard.png"),
+            // Numbered while the default outfit still held "Outfit"; it only takes
+            // its longer key once the Outfits module reaches it.
+            ("Outfit 2", "jean_gunnhildr.png"),
+            ("Outfit 3", "jean_sea_breeze.png"),
+        ]
+        .iter()
+        .map(|(key, file)| (key.to_string(), format!("{}{}", upload, file)))
+        .collect();
+        let actual: Vec<(String, String)> = items
+            .iter()
+            .map(|item| (item.key.clone(), item.img.clone()))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    fn list_fallbacks(items: Value) -> Arc<common::DetailFallbacks> {
+        let items: Vec<model::ApiListItem> = from_value(items).unwrap();
+        Arc::new(common::DetailFallbacks {
+            list: common::build_list_fallback_map(&HashMap::from([(2, items)]), &HashMap::new()),
+            ..Default::default()
+        })
+    }
+
+    const LIST_ICON: &str = "https://act-upload.hoyoverse.com/wiki-user-upload/amber_list.png";
+
+    fn amber_list_item() -> Arc<common::DetailFallbacks> {
+        list_fallbacks(json!([
+            {
+                "entry_page_id": "7100",
+                "name": " Amber ",
+                "icon_url": "//act-upload.hoyoverse.com/wiki-user-upload/amber_list.png"
+            },
+            { "entry_page_id": "7101", "name": "Kaeya", "icon_url": "" }
+        ]))
+    }
+
+    #[tokio::test]
+    async fn list_item_fills_in_a_missing_name_and_icon() {
+        let page = transform_detail_page(
+            fixture_page("detail_list_fallback.json"),
+            Arc::new(BulkStore::default()),
+            amber_list_item(),
+            "en-us",
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(page.id, 7100);
+        assert_eq!(page.name.as_deref(), Some("Amber"));
+        assert_eq!(page.icon_url.as_deref(), Some(LIST_ICON));
+    }
+
+    #[tokio::test]
+    async fn page_and_bulk_values_win_over_the_list_item() {
+        let mut raw = fixture_page("detail_list_fallback.json");
+        raw.name = "Amber (page)".to_string();
+        raw.icon_url = Some("https://example.test/page.png".to_string());
+        let page = transform_detail_page(
+            raw,
+            Arc::new(BulkStore::default()),
+            amber_list_item(),
+            "en-us",
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(page.name.as_deref(), Some("Amber (page)"));
+        assert_eq!(
+            page.icon_url.as_deref(),
+            Some("https://example.test/page.png")
+        );
+
+        let bulk = BulkStore::from_map(HashMap::from([(
+            7100,
+            BulkInfo {
+                name: Some("Amber (bulk)".to_string()),
+                best_icon_url: Some("https://example.test/bulk.png".to_string()),
+                ..Default::default()
+            },
+        )]));
+        let page = transform_detail_page(
+            fixture_page("detail_list_fallback.json"),
+            Arc::new(bulk),
+            amber_list_item(),
+            "en-us",
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(page.name.as_deref(), Some("Amber (bulk)"));
+        assert_eq!(
+            page.icon_url.as_deref(),
+            Some("https://example.test/bulk.png")
+        );
+    }
+
+    fn gift_item(ep_id: Option<EntryId>, name: &str, icon_url: &str) -> OutputGiftRewardItem {
+        OutputGiftRewardItem {
+            icon_url: icon_url.to_string(),
+            name: name.to_string(),
+            ep_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn gift_reward_resolves_linked_items_through_bulk() {
+        let bulk_item = |name: &str, id: EntryId| BulkInfo {
+            name: Some(name.to_string()),
+            best_icon_url: Some(format!("https://example.test/bulk/{}.png", id)),
+            ..Default::default()
+        };
+        let bulk = BulkStore::from_map(HashMap::from([
+            (501, bulk_item("Sweet Madame", 501)),
+            (502, bulk_item("Adeptus' Temptation", 502)),
+        ]));
+        let page = transform_detail_page(
+            fixture_page("detail_gift_reward.json"),
+            Arc::new(bulk),
+            Default::default(),
+            "en-us",
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let Some(ComponentData::GiftReward(gift)) = page.components.get("giftReward") else {
+            panic!("giftReward missing: {:?}", page.components.keys());
+        };
+        assert_eq!(
+            gift.liked,
+            [
+                gift_item(
+                    Some(501),
+                    "Sweet Madame",
+                    "https://example.test/bulk/501.png"
+                ),
+                gift_item(
+                    None,
+                    "Plain Tea",
+                    "https://act-upload.hoyoverse.com/wiki-user-upload/plain_tea.png"
+                ),
+            ]
+        );
+        assert_eq!(
+            gift.disliked,
+            [
+                gift_item(
+                    Some(502),
+                    "Adeptus' Temptation",
+                    "https://example.test/bulk/502.png"
+                ),
+                gift_item(
+                    Some(503),
+                    "Fried Radish Balls",
+                    "https://act-upload.hoyoverse.com/wiki-user-upload/radish.png"
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn map_resource_fixture() {
+        let page = transform_fixture("detail_map_resource.json").await;
+        assert_eq!(
+            page.components.get("mapResource"),
+            Some(&ComponentData::MapResource(OutputMapResourceData {
+                map_id: Some(2),
+                resource_count: Some(37),
+                map_url: Some(
+                    "https://act.hoyolab.com/ys/app/interactive-map/index.html#/map/2?shown_types=395"
+                        .to_string()
+                ),
+            }))
+        );
+    }
+
+    #[test]
+    fn map_resource_drops_invalid_fields() {
+        let resource = |data: Value| {
+            transform_map_resource(from_value::<ApiMapResourceData>(data).unwrap(), 1, "en-us")
+        };
+        assert_eq!(
+            resource(json!({
+                "map_id": 0,
+                "resource_count": 12,
+                "map_url": "http://act.hoyolab.com/ys/app/interactive-map/"
+            })),
+            Some(OutputMapResourceData {
+                resource_count: Some(12),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            resource(json!({ "map_id": "-1", "resource_count": -3, "map_url": "  " })),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn event_calendar_fixture_parses_times_and_rewards() {
+        let page = transform_fixture("detail_event_calendar.json").await;
+        let Some(ComponentData::EventCalendar(event)) = page.components.get("eventCalendar") else {
+            panic!("expected an event calendar, got {:?}", page.components);
+        };
+        assert_eq!(event.start_time.as_deref(), Some("2024-01-01T10:00:00"));
+        assert_eq!(event.end_time.as_deref(), Some("2024-01-15T03:59:59"));
+        assert_eq!(event.start_timestamp, Some(1704103200));
+        assert_eq!(event.end_timestamp, Some(1705291199));
+        let rewards: Vec<HtmlNode> = from_value(json!([
+            {
+                "type": "CustomEntry",
+                "epId": 1,
+                "name": "Primogem",
+                "iconUrl": "https://act-upload.mihoyo.com/wiki-user-upload/primogem.png",
+                "amount": 420
+            },
+            { "type": "CustomEntry", "epId": 2, "name": "Hero's Wit", "amount": 3 }
+        ]))
+        .unwrap();
+        assert_eq!(event.rewards.as_ref(), Some(&rewards));
+        assert!(matches!(
+            event.description.as_slice(),
+            [HtmlNode::RichText { text, .. }] if text.starts_with("Complete challenges")
+        ));
+    }
+
+    /// Progression as `--derive-progression` would add it, without flipping the
+    /// process-wide flag.
+    async fn fixture_progression(name: &str, lang: &str) -> Option<OutputProgressionData> {
+        let page = transform_fixture_in(name, lang).await;
+        progression::derive_progression(&page.components, page.menu_id, &page.menu_ids, lang)
+    }
+
+    fn progression_names(progression: &OutputProgressionData) -> Vec<(u8, &str)> {
+        progression
+            .entries
+            .iter()
+            .map(|entry| (entry.index, entry.name.as_str()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn constellations_fixture_derives_six_entries() {
+        let progression = fixture_progression("detail_character_constellations.json", "en-us")
+            .await
+            .expect("constellation progression");
+        assert_eq!(progression.kind, ProgressionKind::Constellation);
+        let names = progression_names(&progression);
+        assert_eq!(names.len(), 6);
+        assert_eq!(names[0], (1, "Crowned Crystalline Pot"));
+        assert_eq!(names[2], (3, "Secret Pattern of the Thunderbird"));
+        assert!(progression
+            .entries
+            .iter()
+            .all(|entry| !entry.desc.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn refinement_fixture_keeps_only_ranked_rows() {
+        let progression = fixture_progression("detail_weapon_refinement_zh.json", "zh-cn")
+            .await
+            .expect("refinement progression");
+        assert_eq!(progression.kind, ProgressionKind::Refinement);
+        assert_eq!(
+            progression_names(&progression),
+            [
+                (1, "精炼1"),
+                (2, "精炼2"),
+                (3, "精炼3"),
+                (4, "精炼4"),
+                (5, "精炼5")
+            ]
+        );
+        assert!(matches!(
+            progression.entries[4].desc.as_slice(),
+            [HtmlNode::RichText { text, .. }] if text == "攻击力提高22.5%。"
+        ));
+
+        // An English label set finds no refinement rows on the Chinese page.
+        assert_eq!(
+            fixture_progression("detail_weapon_refinement_zh.json", "en-us").await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn pathological_nesting_fixture_recovers_without_placeholder() {
+        let page = transform_fixture("detail_pathological_nesting.json").await;
+        let Some(ComponentData::Story(items)) = page.components.get("body") else {
+            panic!("expected a body list, got {:?}", page.components);
+        };
+        let texts: Vec<(&str, Vec<&str>)> = items
+            .iter()
+            .map(|item| {
+                let texts = item
+                    .desc
+                    .iter()
+                    .filter_map(|node| match node {
+                        HtmlNode::RichText { text, .. } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                (item.title.as_str(), texts)
+            })
+            .collect();
+        // The flat parse recovers the deep item's text, so the opt-in placeholder
+        // never replaces it.
+        assert_eq!(
+            texts,
+            [
+                ("Shallow", vec!["Shallow paragraph."]),
+                ("Deep", vec!["Too deep"])
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_fixture_is_reported_and_truncated() {
+        let page = transform_fixture("detail_oversized_customize.json").await;
+        let size = statistics::component_size(&page.components["customize"]);
+        let data = TransformedData {
+            details: HashMap::from([("en-us".to_string(), Arc::new(vec![page.clone()]))]),
+            ..Default::default()
+        };
+        assert!(statistics::collect_oversized_components(&data, size).is_empty());
+        let oversized = statistics::collect_oversized_components(&data, size - 1);
+        assert_eq!(oversized.len(), 1);
+        assert_eq!(
+            (oversized[0].page_id, oversized[0].component.as_str()),
+            (910003, "customize")
+        );
+
+        let mut components = page.components;
+        truncate_oversized_components(&mut components, size, 910003, "en-us");
+        assert_eq!(
+            statistics::component_size(&components["customize"]),
+            size,
+            "a component at the threshold is left alone"
+        );
+        truncate_oversized_components(&mut components, size - 1, 910003, "en-us");
+        let ComponentData::Customize(nodes) = &components["customize"] else {
+            panic!("expected customize nodes");
+        };
+        assert_eq!(nodes.len(), config::OVERSIZED_NODE_LIMIT + 1);
+        assert!(matches!(
+            nodes.last(),
+            Some(HtmlNode::RichText { text, .. }) if text.ends_with("more item(s) truncated]")
+        ));
+    }
+
+    async fn fixture_event_window(name: &str, lang: &str) -> Option<OutputEventWindow> {
+        let page = transform_fixture_in(name, lang).await;
+        event_window::derive_event_window(
+            &page.components,
+            page.menu_id,
+            &page.menu_ids,
+            page.id,
+            lang,
+        )
+    }
+
+    fn window(start: &str, end: &str) -> Option<OutputEventWindow> {
+        Some(OutputEventWindow {
+            start: start.to_string(),
+            end: end.to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn event_window_fixtures_derive_dates() {
+        assert_eq!(
+            fixture_event_window("detail_event_window_en.json", "en-us").await,
+            window("2024-05-01", "2024-05-21")
+        );
+        assert_eq!(
+            fixture_event_window("detail_event_window_month_names_en.json", "en-us").await,
+            window("2024-05-29", "2024-06-17")
+        );
+        assert_eq!(
+            fixture_event_window("detail_event_window_ja.json", "ja-jp").await,
+            window("2024-05-01", "2024-05-21")
+        );
+        // The end date omits its year and rolls over into the next one.
+        assert_eq!(
+            fixture_event_window("detail_event_window_zh.json", "zh-cn").await,
+            window("2024-12-20", "2025-01-06")
+        );
+
+        assert_eq!(
+            fixture_event_window("detail_event_window_multiple.json", "en-us").await,
+            None
+        );
+        let conflicts = event_window::take_event_window_conflicts();
+        assert_eq!(conflicts.len(), 1, "{:?}", conflicts);
+        assert_eq!(
+            (conflicts[0].page_id, conflicts[0].reason),
+            (5105, "multiple")
+        );
+        assert_eq!(conflicts[0].matches.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn junk_display_values_fixture_is_scrubbed() {
+        let page = transform_fixture("detail_junk_display_values.json").await;
+        let kept = |text: &str| json!([{ "type": "RichText", "text": text }]);
+        assert_eq!(
+            serde_json::to_value(&page).unwrap(),
+            json!({
+                "epId": 4301,
+                "name": "Junk Value Sample",
+                "menuId": 2,
+                "version": 1,
+                "components": {
+                    "baseInfo": [
+                        { "value": kept("Kept value") },
+                        { "key": "Region", "value": kept("Mondstadt") }
+                    ],
+                    "summaryList": [{ "desc": kept("Kept summary") }],
+                    "story": [{ "desc": kept("Kept story") }],
+                    "voice": [{ "title": "Hello" }]
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn entry_in_two_menus_records_both() {
+        let amber = |name: &str| -> model::ApiListItem {
+            from_value(json!({ "entry_page_id": "7100", "name": name })).unwrap()
+        };
+        let nav = HashMap::from([(2, "Characters".to_string()), (4, "Weapons".to_string())]);
+        let lists = HashMap::from([
+            (4, vec![amber("Amber")]),
+            (2, vec![amber(" Amber "), amber("Amber")]),
+        ]);
+        let fallbacks = Arc::new(common::DetailFallbacks {
+            list: common::build_list_fallback_map(&lists, &nav),
+            nav: Arc::new(nav),
+        });
+        let transform = |menu_id: MenuId| {
+            let mut raw = fixture_page("detail_list_fallback.json");
+            raw.menu_id = Some(menu_id);
+            raw.menu_name = None;
+            transform_detail_page(
+                raw,
+                Arc::new(BulkStore::default()),
+                fallbacks.clone(),
+                "en-us",
+            )
+        };
+
+        // Menu 0 is backfilled from the lowest containing menu, with its nav name.
+        let page = transform(0).await.unwrap().unwrap();
+        assert_eq!(page.menu_id, 2);
+        assert_eq!(page.menu_ids, [2, 4]);
+        assert_eq!(page.menu_name.as_deref(), Some("Characters"));
+
+        let page = transform(4).await.unwrap().unwrap();
+        assert_eq!(page.menu_id, 4);
+        assert_eq!(page.menu_ids, [2, 4]);
+    }
+}
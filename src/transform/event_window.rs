@@ -0,0 +1,172 @@
+use crate::config;
+use crate::logging::{log, LogLevel};
+use crate::model::common::{EntryId, MenuId};
+use crate::model::html::{self, HtmlNode};
+use crate::model::output::{ComponentData, OutputEventWindow};
+use chrono::{Datelike, NaiveDate};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
+pub const EVENT_WINDOW_CONFLICTS_FILE: &str = "event_window_conflicts.json";
+
+static EVENT_WINDOW_CONFLICTS: Lazy<Mutex<Vec<EventWindowConflict>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+/// An event page whose text did not yield exactly one usable date range.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventWindowConflict {
+    pub page_id: EntryId,
+    pub lang: String,
+    pub reason: &'static str,
+    /// The matched text of every candidate range.
+    pub matches: Vec<String>,
+}
+
+/// Drains the conflicts recorded so far, ordered by language and page.
+pub fn take_event_window_conflicts() -> Vec<EventWindowConflict> {
+    let mut conflicts = EVENT_WINDOW_CONFLICTS
+        .lock()
+        .map(|mut conflicts| std::mem::take(&mut *conflicts))
+        .unwrap_or_default();
+    conflicts.sort_unstable_by(|a, b| a.lang.cmp(&b.lang).then_with(|| a.page_id.cmp(&b.page_id)));
+    conflicts
+}
+
+// Scans the RichText of customize components on pages in `config::event_menu_ids()`.
+// Only a single distinct, valid range becomes the window; several distinct ranges or an
+// unparseable one are recorded as a conflict and yield None.
+pub fn derive_event_window(
+    components: &HashMap<String, ComponentData>,
+    menu_id: MenuId,
+    menu_ids: &[MenuId],
+    page_id: EntryId,
+    lang: &str,
+) -> Option<OutputEventWindow> {
+    let event_menus = config::event_menu_ids();
+    if !event_menus
+        .iter()
+        .any(|&id| menu_id == id || menu_ids.contains(&id))
+    {
+        return None;
+    }
+    let patterns = patterns_for(lang);
+
+    let mut windows: BTreeSet<(NaiveDate, NaiveDate)> = BTreeSet::new();
+    let mut matches: BTreeSet<String> = BTreeSet::new();
+    let mut invalid = false;
+    let customize = components.values().filter_map(|data| match data {
+        ComponentData::Customize(nodes) => Some(nodes),
+        _ => None,
+    });
+    for nodes in customize {
+        for node in nodes {
+            if !matches!(node, HtmlNode::RichText { .. }) {
+                continue;
+            }
+            let text = html::render_plain(std::slice::from_ref(node));
+            for pattern in &patterns {
+                for caps in pattern.captures_iter(&text) {
+                    matches.insert(caps[0].trim().to_string());
+                    match parse_range(&caps) {
+                        Some(range) => {
+                            windows.insert(range);
+                        }
+                        None => invalid = true,
+                    }
+                }
+            }
+        }
+    }
+
+    let reason = match (windows.len(), invalid) {
+        (0, false) => return None,
+        (1, false) => {
+            let (start, end) = windows.into_iter().next()?;
+            return Some(OutputEventWindow {
+                start: start.format("%Y-%m-%d").to_string(),
+                end: end.format("%Y-%m-%d").to_string(),
+            });
+        }
+        (_, true) => "invalid",
+        _ => "multiple",
+    };
+    record_conflict(EventWindowConflict {
+        page_id,
+        lang: lang.to_string(),
+        reason,
+        matches: matches.into_iter().collect(),
+    });
+    None
+}
+
+// Unknown languages (e.g. test mode) try every known pattern.
+fn patterns_for(lang: &str) -> Vec<&'static Regex> {
+    let patterns = config::event_window_patterns(lang);
+    if !patterns.is_empty() {
+        return patterns;
+    }
+    let mut all: Vec<&'static Regex> = config::SUPPORTED_LANGS
+        .iter()
+        .flat_map(|lang| config::event_window_patterns(lang))
+        .collect();
+    all.sort_unstable_by_key(|re| re.as_str());
+    all.dedup_by_key(|re| re.as_str());
+    all
+}
+
+// A missing end year means the start year, or the next one when the end month/day
+// comes earlier ("2024/12/20 ~ 01/05").
+fn parse_range(caps: &Captures) -> Option<(NaiveDate, NaiveDate)> {
+    let start = date_from(&caps["sy"], &caps["sm"], &caps["sd"])?;
+    let end = match caps.name("ey") {
+        Some(year) => date_from(year.as_str(), &caps["em"], &caps["ed"])?,
+        None => {
+            let end = date_from(&start.year().to_string(), &caps["em"], &caps["ed"])?;
+            if end < start {
+                end.with_year(start.year() + 1)?
+            } else {
+                end
+            }
+        }
+    };
+    (end >= start).then_some((start, end))
+}
+
+fn date_from(year: &str, month: &str, day: &str) -> Option<NaiveDate> {
+    let month = month
+        .parse::<u32>()
+        .ok()
+        .or_else(|| month_from_name(month))?;
+    NaiveDate::from_ymd_opt(year.parse().ok()?, month, day.parse().ok()?)
+}
+
+fn month_from_name(name: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let prefix = name.get(..3)?.to_ascii_lowercase();
+    MONTHS
+        .iter()
+        .position(|&m| m == prefix)
+        .map(|i| i as u32 + 1)
+}
+
+fn record_conflict(conflict: EventWindowConflict) {
+    log(
+        LogLevel::Warning,
+        &format!(
+            "Event window skipped [{} / {}]: {} date range(s) [{}].",
+            conflict.lang,
+            conflict.page_id,
+            conflict.reason,
+            conflict.matches.join(" | ")
+        ),
+    );
+    if let Ok(mut recorded) = EVENT_WINDOW_CONFLICTS.lock() {
+        recorded.push(conflict);
+    }
+}
@@ -1,16 +1,23 @@
 use crate::config;
+use crate::core::stats::{COLOR_TAG_REPAIRS, DEPTH_LIMIT_WARNINGS};
 use crate::error::{AppError, AppResult};
+use crate::logging::{log, LogLevel};
 use crate::model::common::EntryId;
 use crate::model::html::HtmlNode;
-use crate::transform::bulk::{resolve_icon, resolve_name, BulkStore};
-use crate::transform::util;
+use crate::transform::bulk::{
+    resolve_icon, resolve_name, resolve_post_icon, resolve_post_name, BulkStore,
+};
+use crate::transform::{common, util};
 use once_cell::sync::Lazy;
 use scraper::{ElementRef, Html, Node, Selector};
 use serde_json::Value;
 use std::fmt::Write as FmtWrite;
+use std::sync::atomic::Ordering;
 
 use super::bulk::resolve_desc;
 
+const INLINE_DEPTH_MARKER: &str = "[Inline Depth Limit]";
+
 static RB_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse("rb").expect("Invalid rb selector"));
 static RT_SELECTOR: Lazy<Selector> =
@@ -25,9 +32,17 @@ fn clean_consecutive_slashes(text: &str) -> String {
     cleaned
 }
 
+// Also drops U+FFFD left behind by lossy UTF-8 recovery or unpaired surrogate escapes.
 #[inline]
 pub(crate) fn normalize_whitespace(text: &str) -> String {
-    text.split_whitespace().collect::<Vec<_>>().join(" ")
+    if !text.contains(char::REPLACEMENT_CHARACTER) {
+        return text.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    text.split_whitespace()
+        .map(|word| word.replace(char::REPLACEMENT_CHARACTER, ""))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 fn parse_css_color_to_hex(css_color: &str) -> Option<String> {
@@ -63,6 +78,7 @@ struct RichTextBuilder {
     buffer: String,
     color_stack: Vec<Option<String>>,
     needs_space: bool,
+    strip_color: bool,
 }
 
 impl RichTextBuilder {
@@ -71,6 +87,7 @@ impl RichTextBuilder {
             buffer: String::with_capacity(256),
             color_stack: vec![None],
             needs_space: false,
+            strip_color: config::strip_color(),
         }
     }
 
@@ -84,6 +101,16 @@ impl RichTextBuilder {
         self.color_stack.push(effective_color);
     }
 
+    // With --strip-color the style attribute is never parsed and the stack stays colorless.
+    fn push_element_color(&mut self, element_ref: &ElementRef<'_>) {
+        let color_hex = if self.strip_color {
+            None
+        } else {
+            get_element_style_color_hex(element_ref)
+        };
+        self.push_color(color_hex);
+    }
+
     fn pop_color(&mut self) {
         if self.color_stack.len() > 1 {
             self.color_stack.pop();
@@ -122,7 +149,7 @@ impl RichTextBuilder {
     }
 
     fn flush(mut self, alignment: Option<String>) -> Option<HtmlNode> {
-        loop {
+        while !self.strip_color {
             let before_len = self.buffer.len();
             let mut optimized_pass1 = String::with_capacity(self.buffer.len());
             let mut last_end = 0;
@@ -191,6 +218,7 @@ fn process_custom_element(element_ref: ElementRef<'_>) -> Option<HtmlNode> {
                         .trim()
                         .to_string(),
                     menu_id: el_val.attr("menuid").and_then(|s| s.trim().parse().ok()),
+                    link: None,
                 })
         }
         "custom-image" => el_val
@@ -228,6 +256,7 @@ fn process_custom_element(element_ref: ElementRef<'_>) -> Option<HtmlNode> {
                     post_id: id,
                     name: extract_plain_text(element_ref),
                     icon_url: String::new(),
+                    link: None,
                 })
         }
         "custom-video" => el_val
@@ -283,6 +312,63 @@ fn merge_consecutive_rich_text_nodes(nodes: Vec<HtmlNode>) -> Vec<HtmlNode> {
     merged
 }
 
+// Drops closers with no open tag and appends closers for tags left open.
+// Returns the repaired text and the number of tags dropped or added.
+fn balance_color_tags(text: &str) -> Option<(String, usize)> {
+    if !text.contains("color") {
+        return None;
+    }
+    let mut balanced = String::with_capacity(text.len() + 16);
+    let mut depth = 0usize;
+    let mut repairs = 0usize;
+    let mut last_end = 0;
+    for tag in config::RE_COLOR_TAG.find_iter(text) {
+        balanced.push_str(&text[last_end..tag.start()]);
+        last_end = tag.end();
+        if tag.as_str().starts_with("</") {
+            if depth == 0 {
+                repairs += 1;
+                continue;
+            }
+            depth -= 1;
+        } else if tag.as_str() == "<color>" {
+            repairs += 1;
+            continue;
+        } else {
+            depth += 1;
+        }
+        balanced.push_str(tag.as_str());
+    }
+    balanced.push_str(&text[last_end..]);
+    repairs += depth;
+    for _ in 0..depth {
+        balanced.push_str("</color>");
+    }
+    (repairs > 0).then_some((balanced, repairs))
+}
+
+fn repair_color_markup(nodes: &mut [HtmlNode], page_id: EntryId, lang: &str) {
+    let mut total_repairs = 0;
+    for node in nodes.iter_mut() {
+        if let HtmlNode::RichText { text, .. } = node {
+            if let Some((balanced, repairs)) = balance_color_tags(text) {
+                *text = balanced;
+                total_repairs += repairs;
+            }
+        }
+    }
+    if total_repairs > 0 {
+        COLOR_TAG_REPAIRS.fetch_add(total_repairs as u64, Ordering::Relaxed);
+        log(
+            LogLevel::Warning,
+            &format!(
+                "Repaired {} unbalanced color tag(s) [{} / {}].",
+                total_repairs, lang, page_id
+            ),
+        );
+    }
+}
+
 fn process_nested_inline_children(
     inline_element: ElementRef<'_>,
     builder: &mut RichTextBuilder,
@@ -290,8 +376,8 @@ fn process_nested_inline_children(
     page_id: EntryId,
     lang: &str,
 ) -> AppResult<()> {
-    if depth > config::MAX_RECURSION_DEPTH {
-        builder.add_text(" [Inline Depth Limit] ");
+    if depth > config::max_recursion_depth() {
+        builder.add_text(&format!(" {} ", INLINE_DEPTH_MARKER));
         return Ok(());
     }
 
@@ -310,8 +396,7 @@ fn process_nested_inline_children(
                     match tag_name.as_str() {
                         "br" | "hr" => builder.add_newline(),
                         tag if config::HTML_INLINE_TAGS.contains(tag) => {
-                            let color_hex = get_element_style_color_hex(&child_element_ref);
-                            builder.push_color(color_hex);
+                            builder.push_element_color(&child_element_ref);
                             process_nested_inline_children(
                                 child_element_ref,
                                 builder,
@@ -353,11 +438,12 @@ fn parse_element_recursive(
     page_id: EntryId,
     lang: &str,
 ) -> AppResult<Vec<HtmlNode>> {
-    if depth > config::MAX_RECURSION_DEPTH {
-        return Ok(vec![HtmlNode::RichText {
-            text: "[HTML Depth Limit Exceeded]".to_string(),
-            alignment: None,
-        }]);
+    let depth_limit = config::max_recursion_depth();
+    if depth > depth_limit {
+        return Err(AppError::RecursionLimit {
+            context: format!("HTML parsing page {}/{}", lang, page_id),
+            limit: depth_limit,
+        });
     }
 
     let mut results: Vec<HtmlNode> = Vec::new();
@@ -427,8 +513,7 @@ fn parse_element_recursive(
                             current_rich_text_builder.add_newline();
                         }
                         tag if config::HTML_INLINE_TAGS.contains(tag) => {
-                            let color_hex = get_element_style_color_hex(&child_element_ref);
-                            current_rich_text_builder.push_color(color_hex);
+                            current_rich_text_builder.push_element_color(&child_element_ref);
                             process_nested_inline_children(
                                 child_element_ref,
                                 &mut current_rich_text_builder,
@@ -461,25 +546,172 @@ fn parse_element_recursive(
     Ok(merge_consecutive_rich_text_nodes(results))
 }
 
+// Degraded traversal for markup too deep for `parse_element_recursive`: walks the
+// tree with an explicit stack, keeping text and custom tags in document order and
+// breaking lines at block boundaries. Colors, alignment and headings are dropped.
+fn parse_element_flat(root: ElementRef<'_>) -> Vec<HtmlNode> {
+    let mut results: Vec<HtmlNode> = Vec::new();
+    let mut builder = RichTextBuilder::new();
+    // `(node, closing)`: a closing entry marks the end of a block element.
+    let mut stack: Vec<_> = root.children().rev().map(|child| (child, false)).collect();
+
+    while let Some((node, closing)) = stack.pop() {
+        if closing {
+            builder.add_newline();
+            continue;
+        }
+        match node.value() {
+            Node::Text(text_node) => builder.add_text(&text_node.text),
+            Node::Element(el_data) => {
+                let Some(element_ref) = ElementRef::wrap(node) else {
+                    continue;
+                };
+                let tag_name = el_data.name().to_lowercase();
+                let tag = tag_name.as_str();
+                if config::HTML_STRIP_TAGS.contains(tag) {
+                    continue;
+                }
+                if config::TARGET_HTML_CUSTOM_TAGS.contains(tag) {
+                    results.extend(builder.flush(None));
+                    builder = RichTextBuilder::new();
+                    results.extend(process_custom_element(element_ref));
+                    continue;
+                }
+                if tag == "br" || tag == "hr" {
+                    builder.add_newline();
+                } else if config::HTML_BLOCK_TAGS.contains(tag)
+                    || config::HEADING_TAGS.contains(tag)
+                    || tag == "li"
+                {
+                    builder.add_newline();
+                    stack.push((node, true));
+                }
+                stack.extend(node.children().rev().map(|child| (child, false)));
+            }
+            _ => {}
+        }
+    }
+
+    results.extend(builder.flush(None));
+    results.retain(|node| !node.is_empty_text());
+    merge_consecutive_rich_text_nodes(results)
+}
+
+fn has_inline_depth_marker(nodes: &[HtmlNode]) -> bool {
+    nodes.iter().any(
+        |node| matches!(node, HtmlNode::RichText { text, .. } if text.contains(INLINE_DEPTH_MARKER)),
+    )
+}
+
 pub fn parse_html_content(
     html_string: &str,
     page_id: EntryId,
     lang: &str,
 ) -> AppResult<Vec<HtmlNode>> {
+    parse_html_content_degradable(html_string, page_id, lang).map(|(nodes, _degraded)| nodes)
+}
+
+/// Parses rich text like [`parse_html_content`], falling back to a flat parse that
+/// ignores nesting when the markup exceeds the recursion limit. The flag is `true`
+/// when the returned nodes came from that fallback.
+///
+/// # Examples
+///
+/// ```
+/// use wiki_update::model::html::HtmlNode;
+/// use wiki_update::transform::html_parser::parse_html_content_degradable;
+///
+/// let html = format!(
+///     "<p>Intro</p>{}Buried text{}",
+///     "<div>".repeat(40),
+///     "</div>".repeat(40)
+/// );
+/// let (nodes, degraded) = parse_html_content_degradable(&html, 1, "en-us").unwrap();
+/// assert!(degraded);
+/// assert_eq!(
+///     nodes,
+///     vec![HtmlNode::RichText {
+///         text: "Intro\nBuried text".to_string(),
+///         alignment: None,
+///     }]
+/// );
+///
+/// let (_, degraded) = parse_html_content_degradable("<p>Shallow</p>", 1, "en-us").unwrap();
+/// assert!(!degraded);
+/// ```
+pub fn parse_html_content_degradable(
+    html_string: &str,
+    page_id: EntryId,
+    lang: &str,
+) -> AppResult<(Vec<HtmlNode>, bool)> {
     let trimmed_html = html_string.trim();
     if trimmed_html.is_empty() {
-        return Ok(vec![]);
+        return Ok((vec![], false));
     }
     let cleaned_html = clean_consecutive_slashes(trimmed_html);
     let fragment = Html::parse_fragment(&cleaned_html);
-    parse_element_recursive(fragment.root_element(), 0, page_id, lang).map_err(|e| {
-        AppError::HtmlParseError(format!("HTML Parse Err [{} / {}]: {}", lang, page_id, e))
-    })
+    match parse_element_recursive(fragment.root_element(), 0, page_id, lang) {
+        Ok(nodes) if has_inline_depth_marker(&nodes) => {
+            DEPTH_LIMIT_WARNINGS.fetch_add(1, Ordering::Relaxed);
+            log(
+                LogLevel::Warning,
+                &format!(
+                    "Inline markup too deep [{} / {}]. Recovered content with a flat parse.",
+                    lang, page_id
+                ),
+            );
+            Ok((parse_element_flat(fragment.root_element()), true))
+        }
+        Ok(mut nodes) => {
+            repair_color_markup(&mut nodes, page_id, lang);
+            Ok((nodes, false))
+        }
+        Err(e @ AppError::RecursionLimit { .. }) => {
+            DEPTH_LIMIT_WARNINGS.fetch_add(1, Ordering::Relaxed);
+            let flat_nodes = parse_element_flat(fragment.root_element());
+            if !flat_nodes.is_empty() {
+                log(
+                    LogLevel::Warning,
+                    &format!("{}. Recovered content with a flat parse.", e),
+                );
+                return Ok((flat_nodes, true));
+            }
+            if config::depth_limit_placeholder() {
+                log(LogLevel::Warning, &format!("{}. Emitting placeholder.", e));
+                return Ok((
+                    vec![HtmlNode::RichText {
+                        text: config::DEPTH_LIMIT_PLACEHOLDER.to_string(),
+                        alignment: None,
+                    }],
+                    false,
+                ));
+            }
+            log(LogLevel::Warning, &format!("{}. Skipping HTML content.", e));
+            Ok((vec![], false))
+        }
+        Err(e) => Err(AppError::HtmlParseError(format!(
+            "HTML Parse Err [{} / {}]: {}",
+            lang, page_id, e
+        ))),
+    }
+}
+
+/// The flat fallback parse on its own, for retrying a component whose regular
+/// transform failed.
+pub fn parse_html_content_flat(html_string: &str) -> Vec<HtmlNode> {
+    let trimmed_html = html_string.trim();
+    if trimmed_html.is_empty() {
+        return vec![];
+    }
+    let cleaned_html = clean_consecutive_slashes(trimmed_html);
+    let fragment = Html::parse_fragment(&cleaned_html);
+    parse_element_flat(fragment.root_element())
 }
 
 pub async fn post_process_html_nodes(
     nodes: Vec<HtmlNode>,
     bulk_store: &BulkStore,
+    lang: &str,
 ) -> AppResult<Vec<HtmlNode>> {
     let mut processed_nodes = Vec::with_capacity(nodes.len());
     for node in nodes {
@@ -492,6 +724,7 @@ pub async fn post_process_html_nodes(
                 amount,
                 display_style,
                 menu_id,
+                link: _,
             } => {
                 processed_nodes.push(HtmlNode::CustomEntry {
                     ep_id,
@@ -501,21 +734,170 @@ pub async fn post_process_html_nodes(
                     amount,
                     display_style,
                     menu_id,
+                    link: common::entry_link(ep_id, lang),
                 });
             }
             HtmlNode::CustomPost {
                 post_id,
                 name: _,
                 icon_url: _,
+                link: _,
             } => {
                 processed_nodes.push(HtmlNode::CustomPost {
                     post_id,
-                    name: resolve_name(post_id, bulk_store).unwrap_or_default(),
-                    icon_url: resolve_icon(post_id, bulk_store).unwrap_or_default(),
+                    name: resolve_post_name(post_id, bulk_store).unwrap_or_default(),
+                    icon_url: resolve_post_icon(post_id, bulk_store).unwrap_or_default(),
+                    link: common::entry_link(post_id, lang),
                 });
             }
             _ => processed_nodes.push(node),
         }
     }
     Ok(processed_nodes)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    const PALETTE: [&str; 3] = ["#ff0000", "#00ff00", "#0000ff"];
+
+    /// `blocks` runs of spans nested `depth` deep, each level recoloured.
+    fn deeply_colored_html(blocks: usize, depth: usize) -> String {
+        let mut html = String::from("<p>");
+        for block in 0..blocks {
+            for level in 0..depth {
+                let color = PALETTE[(block + level) % PALETTE.len()];
+                let _ = write!(html, "<span style=\"color:{}\">w{} ", color, level);
+            }
+            html.push_str(&"</span>".repeat(depth));
+        }
+        html.push_str("</p>");
+        html
+    }
+
+    fn paragraph(fragment: &Html) -> ElementRef<'_> {
+        fragment
+            .select(&Selector::parse("p").unwrap())
+            .next()
+            .unwrap()
+    }
+
+    fn build_rich_text(paragraph: ElementRef<'_>, strip_color: bool) -> String {
+        let mut builder = RichTextBuilder {
+            strip_color,
+            ..RichTextBuilder::new()
+        };
+        process_nested_inline_children(paragraph, &mut builder, 0, 1, "en-us").unwrap();
+        match builder.flush(None) {
+            Some(HtmlNode::RichText { text, .. }) => text,
+            other => panic!("expected rich text, got {:?}", other),
+        }
+    }
+
+    /// Best of `runs` builds over an already parsed fragment, so only the
+    /// builder and its flush are timed.
+    fn fastest_of(runs: usize, paragraph: ElementRef<'_>, strip_color: bool) -> Duration {
+        (0..runs)
+            .map(|_| {
+                let started = Instant::now();
+                std::hint::black_box(build_rich_text(paragraph, strip_color));
+                started.elapsed()
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn strip_color_writes_no_tags() {
+        let fragment = Html::parse_fragment(&deeply_colored_html(50, 12));
+        let tagged = build_rich_text(paragraph(&fragment), false);
+        assert!(tagged.contains("<color=#ff0000>"), "{}", tagged);
+
+        let plain = build_rich_text(paragraph(&fragment), true);
+        assert!(!plain.contains(['<', '>']), "{}", plain);
+        assert_eq!(plain, config::RE_COLOR_TAG.replace_all(&tagged, ""));
+    }
+
+    #[test]
+    fn strip_color_skips_the_cleanup_passes() {
+        let fragment = Html::parse_fragment(&deeply_colored_html(200, 12));
+        let tagged = fastest_of(5, paragraph(&fragment), false);
+        let plain = fastest_of(5, paragraph(&fragment), true);
+        assert!(plain < tagged, "strip {:?} vs tags {:?}", plain, tagged);
+    }
+
+    fn nested_divs(depth: usize) -> Html {
+        Html::parse_fragment(&format!(
+            "{}Buried text{}",
+            "<div>".repeat(depth),
+            "</div>".repeat(depth)
+        ))
+    }
+
+    #[test]
+    fn nesting_past_the_limit_is_an_error() {
+        let limit = config::max_recursion_depth();
+        let fragment = nested_divs(limit as usize + 2);
+        match parse_element_recursive(fragment.root_element(), 0, 7, "en-us") {
+            Err(AppError::RecursionLimit {
+                context,
+                limit: reported,
+            }) => {
+                assert_eq!(reported, limit);
+                assert_eq!(context, "HTML parsing page en-us/7");
+            }
+            other => panic!("expected a recursion limit error, got {:?}", other),
+        }
+
+        let fragment = nested_divs(limit as usize - 2);
+        assert!(parse_element_recursive(fragment.root_element(), 0, 7, "en-us").is_ok());
+    }
+
+    #[test]
+    fn stray_and_unclosed_color_tags_are_balanced() {
+        assert_eq!(
+            balance_color_tags("Deals </color>DMG"),
+            Some(("Deals DMG".to_string(), 1))
+        );
+        assert_eq!(
+            balance_color_tags("<color=#ffd780>Pyro DMG"),
+            Some(("<color=#ffd780>Pyro DMG</color>".to_string(), 1))
+        );
+        assert_eq!(
+            balance_color_tags("<color>a</color> <color=#ffffff>b</color></color>"),
+            Some(("a <color=#ffffff>b</color>".to_string(), 3))
+        );
+        assert_eq!(balance_color_tags("<color=#ffffff>ok</color>"), None);
+        assert_eq!(balance_color_tags("no tags"), None);
+    }
+
+    #[test]
+    fn repair_touches_only_rich_text() {
+        let mut nodes = vec![
+            HtmlNode::RichText {
+                text: "<color=#ffd780>open".to_string(),
+                alignment: None,
+            },
+            HtmlNode::RichText {
+                text: "plain".to_string(),
+                alignment: None,
+            },
+        ];
+        repair_color_markup(&mut nodes, 1, "en-us");
+        assert_eq!(
+            nodes,
+            [
+                HtmlNode::RichText {
+                    text: "<color=#ffd780>open</color>".to_string(),
+                    alignment: None,
+                },
+                HtmlNode::RichText {
+                    text: "plain".to_string(),
+                    alignment: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! `--intern-nodes`: per-page deduplication of repeated rich-text node lists.
+//!
+//! Only whole field-level lists are compared, by their serialized JSON: a
+//! component field whose entire node list equals another field's is interned.
+//! Subtrees are not: a run of nodes repeated inside two otherwise different
+//! lists stays inline in both.
+//!
+//! Rehydration contract for consumers of an interned detail page:
+//! - `nodeDefs` is an array of node lists; a `{ "type": "Ref", "id": n }` node stands
+//!   for the nodes of `nodeDefs[n]`, spliced in its place.
+//! - A Ref always replaces a whole node list, so an interned field holds exactly one
+//!   Ref node. Definitions never contain Refs themselves, so one lookup is enough.
+//! - Ids are page-local and numbered in order of first use, visiting components by
+//!   key. Pages without duplicates carry no `nodeDefs` and no Refs.
+
+use crate::config;
+use crate::model::html::HtmlNode;
+use crate::model::output::ComponentData;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Replaces every whole node list that occurs more than once on the page (and
+/// serializes to at least `config::INTERN_MIN_BYTES`) with a Ref, returning the
+/// definitions table.
+pub fn intern_node_lists(components: &mut HashMap<String, ComponentData>) -> Vec<Vec<HtmlNode>> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for_each_node_list(components, &mut |nodes| {
+        if let Some(key) = intern_key(nodes) {
+            *occurrences.entry(key).or_default() += 1;
+        }
+    });
+    occurrences.retain(|_, count| *count > 1);
+    if occurrences.is_empty() {
+        return Vec::new();
+    }
+
+    let mut defs: Vec<Vec<HtmlNode>> = Vec::new();
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    for_each_node_list(components, &mut |nodes| {
+        let Some(key) = intern_key(nodes).filter(|key| occurrences.contains_key(key)) else {
+            return;
+        };
+        let id = *ids.entry(key).or_insert_with(|| {
+            defs.push(nodes.clone());
+            defs.len() - 1
+        });
+        *nodes = vec![HtmlNode::Ref { id }];
+    });
+    defs
+}
+
+/// `nodes` with Refs replaced by their definitions; borrowed when there are none.
+pub fn resolve_refs<'a>(nodes: &'a [HtmlNode], defs: &'a [Vec<HtmlNode>]) -> Cow<'a, [HtmlNode]> {
+    if !nodes
+        .iter()
+        .any(|node| matches!(node, HtmlNode::Ref { .. }))
+    {
+        return Cow::Borrowed(nodes);
+    }
+    let mut resolved = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        match node {
+            HtmlNode::Ref { id } => resolved.extend(defs.get(*id).into_iter().flatten().cloned()),
+            _ => resolved.push(node.clone()),
+        }
+    }
+    Cow::Owned(resolved)
+}
+
+fn intern_key(nodes: &[HtmlNode]) -> Option<String> {
+    if nodes.is_empty() {
+        return None;
+    }
+    serde_json::to_string(nodes)
+        .ok()
+        .filter(|key| key.len() >= config::INTERN_MIN_BYTES)
+}
+
+// Visits node lists in a fixed order (components sorted by key) so Ref ids are stable
+// across runs.
+fn for_each_node_list(
+    components: &mut HashMap<String, ComponentData>,
+    visit: &mut dyn FnMut(&mut Vec<HtmlNode>),
+) {
+    let mut keys: Vec<String> = components.keys().cloned().collect();
+    keys.sort_unstable();
+    for key in keys {
+        let Some(component) = components.get_mut(&key) else {
+            continue;
+        };
+        match component {
+            ComponentData::BaseInfo(items) => {
+                items
+                    .iter_mut()
+                    .filter_map(|i| i.value.as_mut())
+                    .for_each(&mut *visit);
+            }
+            ComponentData::Ascension(items) => {
+                items
+                    .iter_mut()
+                    .filter_map(|i| i.materials.as_mut())
+                    .for_each(&mut *visit);
+            }
+            ComponentData::Talent(items) => {
+                for item in items {
+                    visit(&mut item.desc);
+                    for level in item.materials.iter_mut().flatten().flatten() {
+                        visit(level);
+                    }
+                }
+            }
+            ComponentData::SummaryList(items) => {
+                items.iter_mut().for_each(|i| visit(&mut i.desc));
+            }
+            ComponentData::Story(items) => items.iter_mut().for_each(|i| visit(&mut i.desc)),
+            ComponentData::GalleryCharacter(items) => {
+                items.iter_mut().for_each(|i| visit(&mut i.img_desc));
+            }
+            ComponentData::ArtifactList(items) => {
+                let mut entries: Vec<_> = items.iter_mut().collect();
+                entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                entries.into_iter().for_each(|(_, i)| visit(&mut i.desc));
+            }
+            ComponentData::TextualResearch(items) => {
+                items.iter_mut().for_each(|i| visit(&mut i.desc));
+            }
+            ComponentData::Timeline(events) => {
+                events.iter_mut().for_each(|e| visit(&mut e.contents));
+            }
+            ComponentData::Build(sections) => {
+                sections.iter_mut().for_each(|s| visit(&mut s.desc));
+            }
+            ComponentData::EventCalendar(event) => {
+                if let Some(rewards) = event.rewards.as_mut() {
+                    visit(rewards);
+                }
+                visit(&mut event.description);
+            }
+            ComponentData::AchievementGroup(group) => {
+                group.items.iter_mut().for_each(|i| visit(&mut i.desc));
+            }
+            ComponentData::Progression(progression) => {
+                progression
+                    .entries
+                    .iter_mut()
+                    .for_each(|e| visit(&mut e.desc));
+            }
+            ComponentData::Customize(nodes) | ComponentData::DropMaterial(nodes) => visit(nodes),
+            ComponentData::Voice(_)
+            | ComponentData::Map(_)
+            | ComponentData::ReliquarySetEffect(_)
+            | ComponentData::MapUrl(_)
+            | ComponentData::MapResource(_)
+            | ComponentData::VideoCollection(_)
+            | ComponentData::Tcg(_)
+            | ComponentData::GiftReward(_)
+            | ComponentData::Unknown(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::output::OutputSummaryItem;
+
+    fn text(text: &str) -> HtmlNode {
+        HtmlNode::RichText {
+            text: text.to_string(),
+            alignment: None,
+        }
+    }
+
+    fn entry(ep_id: i64) -> HtmlNode {
+        HtmlNode::CustomEntry {
+            ep_id,
+            name: String::new(),
+            desc: None,
+            icon_url: String::new(),
+            amount: 3,
+            display_style: "link".to_string(),
+            menu_id: None,
+            link: None,
+        }
+    }
+
+    /// Long enough to clear `INTERN_MIN_BYTES`.
+    fn shared_nodes() -> Vec<HtmlNode> {
+        vec![
+            text("Gained from the weekly boss and the Spiral Abyss."),
+            entry(7),
+        ]
+    }
+
+    fn summary(desc: Vec<HtmlNode>) -> OutputSummaryItem {
+        OutputSummaryItem {
+            desc,
+            ..Default::default()
+        }
+    }
+
+    fn page_components() -> HashMap<String, ComponentData> {
+        HashMap::from([
+            (
+                "customize".to_string(),
+                ComponentData::Customize(shared_nodes()),
+            ),
+            (
+                "dropMaterial".to_string(),
+                ComponentData::DropMaterial(shared_nodes()),
+            ),
+            (
+                "summaryList".to_string(),
+                ComponentData::SummaryList(vec![
+                    summary(vec![text("Too short to intern.")]),
+                    summary(vec![text("Too short to intern.")]),
+                    summary(shared_nodes()),
+                ]),
+            ),
+        ])
+    }
+
+    fn node_lists(components: &HashMap<String, ComponentData>) -> Vec<Vec<HtmlNode>> {
+        let mut components = components.clone();
+        let mut lists = Vec::new();
+        for_each_node_list(&mut components, &mut |nodes| lists.push(nodes.clone()));
+        lists
+    }
+
+    #[test]
+    fn resolving_refs_restores_every_list() {
+        let original = page_components();
+        let mut interned = original.clone();
+        let defs = intern_node_lists(&mut interned);
+
+        assert_eq!(defs, vec![shared_nodes()]);
+        let interned_lists = node_lists(&interned);
+        assert_eq!(
+            interned_lists
+                .iter()
+                .filter(|nodes| nodes.as_slice() == [HtmlNode::Ref { id: 0 }])
+                .count(),
+            3
+        );
+        let resolved: Vec<Vec<HtmlNode>> = interned_lists
+            .iter()
+            .map(|nodes| resolve_refs(nodes, &defs).into_owned())
+            .collect();
+        assert_eq!(resolved, node_lists(&original));
+    }
+
+    #[test]
+    fn ref_ids_follow_component_key_order() {
+        let other_nodes = vec![text(
+            "A second passage long enough to be worth interning too.",
+        )];
+        let build = |keys: &[&str]| {
+            let mut components: HashMap<String, ComponentData> = HashMap::new();
+            for key in keys {
+                let nodes = if key.starts_with('b') {
+                    shared_nodes()
+                } else {
+                    other_nodes.clone()
+                };
+                components.insert(key.to_string(), ComponentData::Customize(nodes));
+            }
+            components
+        };
+
+        let mut forward = build(&["a1", "a2", "b1", "b2"]);
+        let mut backward = build(&["b2", "b1", "a2", "a1"]);
+        let forward_defs = intern_node_lists(&mut forward);
+        let backward_defs = intern_node_lists(&mut backward);
+
+        assert_eq!(forward_defs, vec![other_nodes, shared_nodes()]);
+        assert_eq!(forward_defs, backward_defs);
+        assert_eq!(forward, backward);
+        assert!(matches!(
+            &forward["b1"],
+            ComponentData::Customize(nodes) if nodes.as_slice() == [HtmlNode::Ref { id: 1 }]
+        ));
+    }
+
+    #[test]
+    fn pages_without_repeats_are_left_alone() {
+        let mut components = HashMap::from([(
+            "customize".to_string(),
+            ComponentData::Customize(shared_nodes()),
+        )]);
+        let before = components.clone();
+        assert!(intern_node_lists(&mut components).is_empty());
+        assert_eq!(components, before);
+
+        let nodes = shared_nodes();
+        assert!(matches!(resolve_refs(&nodes, &[]), Cow::Borrowed(_)));
+    }
+}
@@ -1,10 +1,19 @@
 use crate::api;
+use crate::config;
 use crate::model::{
-    common::MenuId,
-    output::{OutputListFile, OutputListItem},
+    common::{MenuCategory, MenuId},
+    output::{FilterValue, OutputListFile, OutputListItem},
 };
-use crate::transform::{bulk, common};
+use crate::transform::{bulk, common, util};
 use chrono::Utc;
+use serde_json::Value;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+
+/// `display_field` keys that carry a card's rarity or vision when the filter key
+/// itself is absent.
+const DISPLAY_FIELD_RARITY_KEYS: [&str; 2] = ["rarity", "star"];
+const DISPLAY_FIELD_VISION_KEYS: [&str; 2] = ["vision", "element"];
 
 pub fn transform_list_file(
     raw_items: Vec<api::model::ApiListItem>,
@@ -23,19 +32,36 @@ pub fn transform_list_file(
     for item in raw_items {
         let item_id = item.entry_page_id;
 
-        let name = bulk_store.get_name(item_id).unwrap_or(&item.name);
-        let icon_url = bulk_store.get_icon(item_id).unwrap_or(&item.icon_url);
-        let desc = bulk_store.get_desc(item_id).map(String::from).or(item.desc);
+        let name = bulk_store
+            .get_name(item_id)
+            .and_then(util::sanitize_display_string)
+            .or_else(|| util::sanitize_display_string(&item.name))
+            .unwrap_or_default();
+        let icon_url = bulk_store
+            .get_icon(item_id)
+            .and_then(util::sanitize_display_string)
+            .or_else(|| util::sanitize_display_string(&item.icon_url))
+            .unwrap_or_default();
+        let desc = bulk_store
+            .get_desc(item_id)
+            .or(item.desc.as_deref())
+            .and_then(util::sanitize_display_string);
 
-        let filter_values = common::process_filters_value(&item.filter_values);
+        let mut filter_values = common::process_filters_value(&item.filter_values);
+        if let Some(display_field) = &item.display_field {
+            fill_card_fields_from_display_field(display_field, menu_id, &mut filter_values);
+        }
+        let raw_filters = common::keep_raw_filters(&item.filter_values);
 
         if !name.is_empty() || !icon_url.is_empty() {
             output_items.push(OutputListItem {
                 id: item_id,
-                name: name.to_string(),
-                icon_url: icon_url.to_string(),
+                name,
+                icon_url,
                 desc,
                 filter_values,
+                raw_filters,
+                link: common::entry_link(item_id, lang),
             });
         }
     }
@@ -45,13 +71,103 @@ pub fn transform_list_file(
         None
     } else {
         output_items.sort_unstable_by_key(|item| item.id);
+        let filter_vocabulary = if config::emit_filter_vocabulary() {
+            filter_vocabulary(&output_items)
+        } else {
+            HashMap::new()
+        };
         Some(OutputListFile {
             version: Utc::now(),
             language: lang.to_string(),
             menu_id,
             menu_name,
             total_items: final_count,
+            filter_vocabulary,
             list: output_items,
         })
     }
 }
+
+/// Every filter key seen on `items` mapped to its distinct values, sorted.
+/// Multi-value filters contribute each of their values.
+fn filter_vocabulary(items: &[OutputListItem]) -> HashMap<String, Vec<String>> {
+    let mut vocabulary: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for item in items {
+        for (key, value) in &item.filter_values {
+            let values = vocabulary.entry(key.clone()).or_default();
+            match value {
+                FilterValue::Single(s) => {
+                    values.insert(s.clone());
+                }
+                FilterValue::Multiple(many) => values.extend(many.iter().cloned()),
+                FilterValue::Integer(n) => {
+                    values.insert(n.to_string());
+                }
+            }
+        }
+    }
+    vocabulary
+        .into_iter()
+        .map(|(key, values)| (key, values.into_iter().collect()))
+        .collect()
+}
+
+/// Fills the rarity and vision list cards render from `display_field` when
+/// `filter_values` lacks them; filter values stay authoritative when present.
+fn fill_card_fields_from_display_field(
+    display_field: &Value,
+    menu_id: MenuId,
+    filter_values: &mut HashMap<String, FilterValue>,
+) {
+    let Value::Object(fields) = display_field else {
+        return;
+    };
+    let category = MenuCategory::from(menu_id);
+    let rarity_key = category.rarity_key();
+    let lookup = |filter_key: &str, fallback_keys: &[&str]| {
+        std::iter::once(filter_key)
+            .chain(fallback_keys.iter().copied())
+            .find_map(|key| fields.get(key).and_then(display_field_text))
+    };
+
+    if let Entry::Vacant(slot) = filter_values.entry(common::to_camel_case(rarity_key)) {
+        if let Some(rarity) = lookup(rarity_key, &DISPLAY_FIELD_RARITY_KEYS)
+            .as_deref()
+            .and_then(parse_rarity)
+        {
+            slot.insert(FilterValue::Integer(rarity));
+        }
+    }
+
+    if category != MenuCategory::Characters {
+        return;
+    }
+    if let Entry::Vacant(slot) = filter_values.entry(common::to_camel_case(config::KEY_CHAR_VISION))
+    {
+        if let Some(vision) = lookup(config::KEY_CHAR_VISION, &DISPLAY_FIELD_VISION_KEYS) {
+            slot.insert(FilterValue::Single(vision));
+        }
+    }
+}
+
+fn display_field_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => util::sanitize_display_string(s),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Array(values) => values.iter().find_map(display_field_text),
+        Value::Object(map) => map
+            .get("value")
+            .or_else(|| map.get("values"))
+            .and_then(display_field_text),
+        _ => None,
+    }
+}
+
+/// "5", "5-Star", "5星" and "★★★★★" all read as 5.
+fn parse_rarity(text: &str) -> Option<i64> {
+    if let Some(digit) = text.chars().find_map(|c| c.to_digit(10)) {
+        return Some(i64::from(digit));
+    }
+    let stars = text.chars().filter(|&c| c == '★').count();
+    (stars > 0).then_some(stars as i64)
+}
@@ -2,47 +2,46 @@ pub mod bulk;
 pub mod calendar;
 pub mod common;
 pub mod detail;
+pub mod event_window;
 pub mod html_parser;
+pub mod intern;
 pub mod list;
+pub mod progression;
+pub mod search_index;
+pub mod taxonomy;
 pub mod util;
 
 use crate::api::model::{self, ApiComponentData};
+use crate::config;
 use crate::core::data_store::{RawData, TransformedData};
+use crate::core::stats::CategoryStats;
 use crate::error::{AppError, AppResult};
 use crate::logging::{log, LogLevel};
 use crate::model as output_model;
-use crate::model::common::{EntryId, MenuId};
+use crate::model::common::{EntryId, LangCode, MenuId};
 use crate::transform::bulk::BulkStore;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::task::JoinSet;
 
 pub async fn transform_all_data(
     raw_data: Arc<RawData>,
     all_bulk_stores: HashMap<String, BulkStore>,
-    languages: &[String],
-) -> AppResult<TransformedData> {
+    languages: &[LangCode],
+    save_sink: Option<UnboundedSender<LangTransformResult>>,
+) -> AppResult<(TransformedData, CategoryStats)> {
     log(LogLevel::Info, "--- Transforming all fetched data ---");
     let start_time = Instant::now();
     let mut transformed = TransformedData::default();
+    let mut transform_stats = CategoryStats::default();
 
     let nav_lookup_maps: HashMap<String, Arc<HashMap<MenuId, String>>> = raw_data
         .navigation
         .iter()
-        .map(|(lang, entries)| {
-            let lookup = entries
-                .iter()
-                .filter_map(|entry| match (&entry.menu, &entry.name) {
-                    (Some(menu), Some(name)) if !name.is_empty() => {
-                        Some((menu.menu_id, name.trim().to_string()))
-                    }
-                    _ => None,
-                })
-                .collect::<HashMap<_, _>>();
-            (lang.clone(), Arc::new(lookup))
-        })
+        .map(|(lang, entries)| (lang.clone(), Arc::new(build_nav_lookup(entries))))
         .collect();
 
     let mut transformation_tasks: JoinSet<Result<LangTransformResult, AppError>> = JoinSet::new();
@@ -51,10 +50,13 @@ pub async fn transform_all_data(
     let raw_data_arc = raw_data;
 
     for lang in languages {
-        let lang_clone = lang.clone();
-        let bulk_store = all_bulk_stores_arc.get(lang).cloned().unwrap_or_default();
+        let lang_clone = lang.to_string();
+        let bulk_store = all_bulk_stores_arc
+            .get(lang.as_str())
+            .cloned()
+            .unwrap_or_default();
         let nav_lookup = nav_lookup_maps
-            .get(lang)
+            .get(lang.as_str())
             .cloned()
             .unwrap_or_else(|| Arc::new(HashMap::new()));
         let raw_data_for_lang = Arc::clone(&raw_data_arc);
@@ -65,8 +67,21 @@ pub async fn transform_all_data(
     }
 
     while let Some(result) = transformation_tasks.join_next().await {
+        transform_stats.total_tasks += 1;
         match result {
             Ok(Ok(lang_result)) => {
+                transform_stats.add_ok();
+                if let Some(sink) = &save_sink {
+                    if sink.send(lang_result.clone()).is_err() {
+                        log(
+                            LogLevel::Warning,
+                            &format!(
+                                "Save queue closed; output for [{}] will not be written.",
+                                lang_result.lang
+                            ),
+                        );
+                    }
+                }
                 transformed
                     .navigation
                     .insert(lang_result.lang.clone(), lang_result.navigation);
@@ -76,17 +91,22 @@ pub async fn transform_all_data(
                 transformed
                     .details
                     .insert(lang_result.lang.clone(), lang_result.details);
+                transformed
+                    .bulk
+                    .insert(lang_result.lang.clone(), lang_result.bulk);
                 if let Some(cal) = lang_result.calendar {
                     transformed.calendars.insert(lang_result.lang.clone(), cal);
                 }
             }
             Ok(Err(e)) => {
+                transform_stats.add_fail();
                 log(
                     LogLevel::Error,
                     &format!("Language transformation task failed: {:?}", e),
                 );
             }
             Err(e) => {
+                transform_stats.add_fail();
                 log(
                     LogLevel::Error,
                     &format!("Language transformation task panicked: {}", e),
@@ -102,18 +122,31 @@ pub async fn transform_all_data(
             start_time.elapsed()
         ),
     );
-    Ok(transformed)
+    Ok((transformed, transform_stats))
 }
 
-struct LangTransformResult {
-    lang: String,
-    navigation: Vec<output_model::output::OutputNavMenuItem>,
-    lists: Vec<output_model::output::OutputListFile>,
-    details: Vec<output_model::output::OutputDetailPage>,
-    calendar: Option<output_model::output::OutputCalendarFile>,
+// First entry wins on duplicate menu ids, matching `common::transform_nav_items`.
+pub fn build_nav_lookup(entries: &[model::ApiNavEntry]) -> HashMap<MenuId, String> {
+    common::transform_nav_items(entries)
+        .0
+        .into_iter()
+        .map(|menu| (menu.menu_id, menu.name))
+        .collect()
 }
 
-async fn transform_language_data(
+/// One language's transformed output. The large vectors sit behind `Arc`s so
+/// handing a copy to the save queue doesn't duplicate the pages.
+#[derive(Debug, Clone, Default)]
+pub struct LangTransformResult {
+    pub lang: String,
+    pub navigation: Vec<output_model::output::OutputNavMenuItem>,
+    pub lists: Arc<Vec<output_model::output::OutputListFile>>,
+    pub details: Arc<Vec<output_model::output::OutputDetailPage>>,
+    pub calendar: Option<output_model::output::OutputCalendarFile>,
+    pub bulk: Arc<Vec<output_model::output::OutputBulkEntry>>,
+}
+
+pub async fn transform_language_data(
     lang: &str,
     raw_data: Arc<RawData>,
     bulk_store: BulkStore,
@@ -124,20 +157,22 @@ async fn transform_language_data(
     let output_nav = raw_data
         .navigation
         .get(lang)
-        .map_or_else(Vec::new, |entries| {
-            entries
-                .iter()
-                .filter_map(common::transform_nav_item)
-                .collect()
-        });
+        .map_or_else(Vec::new, |entries| common::transform_nav_items(entries).0);
 
     let mut output_lists = Vec::new();
     if let Some(lang_list_map) = raw_data.lists.get(lang) {
         for (&menu_id, items) in lang_list_map {
-            let menu_name = nav_lookup
-                .get(&menu_id)
-                .cloned()
-                .unwrap_or_else(|| format!("Menu {}", menu_id));
+            let menu_name = common::MenuName::resolve(&nav_lookup, menu_id, lang);
+            if menu_name.is_fallback() {
+                log(
+                    LogLevel::Warning,
+                    &format!(
+                        "No navigation name for menu {} [{}]; using fallback name.",
+                        menu_id, lang
+                    ),
+                );
+            }
+            let menu_name = menu_name.into_string();
 
             if let Some(lf) =
                 list::transform_list_file(items.clone(), &bulk_store_arc, lang, menu_id, menu_name)
@@ -147,34 +182,67 @@ async fn transform_language_data(
         }
     }
 
+    let detail_fallbacks = Arc::new(common::DetailFallbacks {
+        list: raw_data
+            .lists
+            .get(lang)
+            .map(|lists| common::build_list_fallback_map(lists, &nav_lookup))
+            .unwrap_or_default(),
+        nav: Arc::clone(&nav_lookup),
+    });
     let mut detail_tasks = JoinSet::new();
     let mut output_details = Vec::new();
+    let mut detail_failures = 0usize;
     if let Some(detail_pages) = raw_data.details.get(lang) {
         output_details.reserve(detail_pages.len());
         for detail_page in detail_pages.clone() {
             let bulk_store_c = bulk_store_arc.clone();
+            let detail_fallbacks_c = detail_fallbacks.clone();
             let lang_c = lang.to_string();
             detail_tasks.spawn(async move {
-                detail::transform_detail_page(detail_page, bulk_store_c, &lang_c).await
+                detail::transform_detail_page(
+                    detail_page,
+                    bulk_store_c,
+                    detail_fallbacks_c,
+                    &lang_c,
+                )
+                .await
             });
         }
         while let Some(result) = detail_tasks.join_next().await {
             match result {
                 Ok(Ok(Some(od))) => output_details.push(od),
                 Ok(Ok(None)) => {}
-                Ok(Err(e)) => log(
-                    LogLevel::Warning,
-                    &format!("Detail transform error [{}]: {:?}", lang, e),
-                ),
-                Err(e) => log(
-                    LogLevel::Error,
-                    &format!("Detail transform task panicked [{}]: {}", lang, e),
-                ),
+                Ok(Err(e)) => {
+                    detail_failures += 1;
+                    log(
+                        LogLevel::Warning,
+                        &format!("Detail transform error [{}]: {:?}", lang, e),
+                    );
+                }
+                Err(e) => {
+                    detail_failures += 1;
+                    log(
+                        LogLevel::Error,
+                        &format!("Detail transform task panicked [{}]: {}", lang, e),
+                    );
+                }
+            }
+            if let Some(limit) = config::detail_transform_fail_limit() {
+                if detail_failures > limit {
+                    detail_tasks.abort_all();
+                    return Err(AppError::TransformError(format!(
+                        "{} detail transforms failed for [{}], more than the --fail-fast limit of {}; aborting the language",
+                        detail_failures, lang, limit
+                    )));
+                }
             }
         }
     }
     output_details.sort_unstable_by_key(|d| d.id);
 
+    let output_bulk = bulk::build_bulk_entries(&bulk_store_arc, lang);
+
     let output_calendar = if let Some(cal_resp) = raw_data.calendars.get(lang) {
         let metadata_map = common::build_metadata_map(&output_lists);
         match calendar::transform_calendar(cal_resp.clone(), bulk_store_arc, &metadata_map, lang) {
@@ -194,51 +262,89 @@ async fn transform_language_data(
     Ok(LangTransformResult {
         lang: lang.to_string(),
         navigation: output_nav,
-        lists: output_lists,
-        details: output_details,
+        lists: Arc::new(output_lists),
+        details: Arc::new(output_details),
         calendar: output_calendar,
+        bulk: Arc::new(output_bulk),
     })
 }
 
-pub fn collect_all_ids(raw_data: &RawData) -> HashMap<String, HashSet<EntryId>> {
-    let mut all_ids_map: HashMap<String, HashSet<EntryId>> = HashMap::new();
+pub type LangIdMap = HashMap<String, HashSet<EntryId>>;
+
+/// Returns `(entry_ids, post_ids)` per language. Post IDs reference community
+/// posts rather than wiki entries, so they are kept out of the bulk fetch.
+pub fn collect_all_ids(raw_data: &RawData) -> (LangIdMap, LangIdMap) {
+    let mut all_ids_map: LangIdMap = HashMap::new();
+    let mut all_post_ids_map: LangIdMap = HashMap::new();
 
-    let mut collector = IdCollector::new();
+    let mut collector = IdCollector::new("");
 
     for (lang, lists_map) in &raw_data.lists {
+        collector.set_lang(lang);
         let lang_ids = all_ids_map.entry(lang.clone()).or_default();
         for items in lists_map.values() {
             for item in items {
                 collector.collect_from_list_item(item, lang_ids);
             }
         }
+        collector.drain_post_ids_into(all_post_ids_map.entry(lang.clone()).or_default());
     }
 
     for (lang, details) in &raw_data.details {
+        collector.set_lang(lang);
         let lang_ids = all_ids_map.entry(lang.clone()).or_default();
         for detail in details {
             collector.collect_from_detail_page(detail, lang_ids);
         }
+        collector.drain_post_ids_into(all_post_ids_map.entry(lang.clone()).or_default());
     }
 
     for (lang, calendar) in &raw_data.calendars {
+        collector.set_lang(lang);
         let lang_ids = all_ids_map.entry(lang.clone()).or_default();
         for item in calendar.calendar.iter().chain(calendar.op.iter()) {
             collector.collect_from_value(item, lang_ids);
         }
+        collector.drain_post_ids_into(all_post_ids_map.entry(lang.clone()).or_default());
     }
 
-    all_ids_map
+    all_post_ids_map.retain(|_, ids| !ids.is_empty());
+    (all_ids_map, all_post_ids_map)
 }
 
-struct IdCollector;
+pub fn collect_detail_ids(detail: &model::ApiDetailPage, lang: &str) -> HashSet<EntryId> {
+    let mut ids = HashSet::new();
+    IdCollector::new(lang).collect_from_detail_page(detail, &mut ids);
+    ids
+}
+
+struct IdCollector {
+    post_ids: HashSet<EntryId>,
+    // Context for parse failure warnings.
+    lang: String,
+    page_id: Option<EntryId>,
+}
 
 impl IdCollector {
-    fn new() -> Self {
-        Self
+    fn new(lang: &str) -> Self {
+        Self {
+            post_ids: HashSet::new(),
+            lang: lang.to_string(),
+            page_id: None,
+        }
+    }
+
+    fn set_lang(&mut self, lang: &str) {
+        self.lang = lang.to_string();
+        self.page_id = None;
+    }
+
+    fn drain_post_ids_into(&mut self, target: &mut HashSet<EntryId>) {
+        target.extend(self.post_ids.drain());
     }
 
     fn collect_from_list_item(&mut self, item: &model::ApiListItem, ids: &mut HashSet<EntryId>) {
+        self.page_id = Some(item.entry_page_id).filter(|&id| id > 0);
         if item.entry_page_id > 0 {
             ids.insert(item.entry_page_id);
         }
@@ -253,6 +359,7 @@ impl IdCollector {
         detail: &model::ApiDetailPage,
         ids: &mut HashSet<EntryId>,
     ) {
+        self.page_id = detail.id.filter(|&id| id > 0);
         if let Some(id) = detail.id {
             if id > 0 {
                 ids.insert(id);
@@ -351,10 +458,49 @@ impl IdCollector {
                     self.collect_from_value(&Value::String(mat_str.clone()), ids);
                 }
             }
+            ApiComponentData::GiftReward(data) => {
+                for id in data
+                    .liked
+                    .iter()
+                    .chain(&data.disliked)
+                    .filter_map(|item| item.ep_id)
+                {
+                    if id > 0 {
+                        ids.insert(id);
+                    }
+                }
+            }
+            ApiComponentData::BuildList(sections) => {
+                for section in sections {
+                    self.collect_from_value(&section.desc, ids);
+                    for ref_val in section.weapons.iter().chain(&section.artifacts) {
+                        match util::parse_value_as_optional_i64(ref_val) {
+                            Some(id) if id > 0 => {
+                                ids.insert(id);
+                            }
+                            _ => self.collect_from_value(ref_val, ids),
+                        }
+                    }
+                }
+            }
+            ApiComponentData::EventCalendar(data) => self.collect_from_value(&data.rewards, ids),
+            ApiComponentData::AchievementGroup(data) => {
+                for item in &data.items {
+                    if let Some(id) = item.ep_id.filter(|&id| id > 0) {
+                        ids.insert(id);
+                    }
+                    self.collect_from_value(&item.desc, ids);
+                }
+            }
+            ApiComponentData::Map(map_data) => {
+                for point in &map_data.points {
+                    self.collect_from_value(point, ids);
+                }
+            }
             ApiComponentData::Unknown(v) => self.collect_from_value(v, ids),
             ApiComponentData::VoiceList(_)
             | ApiComponentData::ReliquarySetEffect(_)
-            | ApiComponentData::Map(_)
+            | ApiComponentData::MapResource(_)
             | ApiComponentData::Tcg(_) => {}
         }
     }
@@ -376,7 +522,7 @@ impl IdCollector {
                     .and_then(util::parse_value_as_optional_i64)
                 {
                     if id > 0 {
-                        ids.insert(id);
+                        self.post_ids.insert(id);
                     }
                 }
                 if let Some(id) = map.get("id").and_then(util::parse_value_as_optional_i64) {
@@ -396,49 +542,16 @@ impl IdCollector {
                 }
             }
             Value::String(s) => {
-                let trimmed = s.trim();
-                if (trimmed.starts_with("$[") && trimmed.ends_with("]$"))
-                    || (trimmed.starts_with("\"$[") && trimmed.ends_with("]$\""))
-                {
-                    let json_str = if trimmed.starts_with("\"$[") {
-                        trimmed
-                            .get(3..trimmed.len().saturating_sub(3))
-                            .unwrap_or("")
-                    } else {
-                        trimmed
-                            .get(2..trimmed.len().saturating_sub(2))
-                            .unwrap_or("")
-                    };
-
-                    if !json_str.is_empty() {
-                        match serde_json::from_str::<Value>(json_str) {
-                            Ok(parsed_value) => self.collect_from_value(&parsed_value, ids),
-                            Err(e) => {
-                                if json_str.starts_with('{') && json_str.ends_with('}') {
-                                    match serde_json::from_str::<Value>(&format!("[{}]", json_str)) {
-                                        Ok(wrapped_value) => self.collect_from_value(&wrapped_value, ids),
-                                        Err(e2) => log(LogLevel::Warning, &format!("Failed secondary parse of material string '{}': {}", json_str, e2)),
-                                    }
-                                } else {
-                                    log(
-                                        LogLevel::Warning,
-                                        &format!(
-                                            "Failed to parse material string '{}': {}",
-                                            json_str, e
-                                        ),
-                                    );
-                                }
-                            }
-                        }
-                    }
-                } else if (trimmed.starts_with('{') && trimmed.ends_with('}'))
-                    || (trimmed.starts_with('[') && trimmed.ends_with(']'))
-                {
-                    match serde_json::from_str::<Value>(trimmed) {
+                if let Some(embedded) = util::EmbeddedJson::detect(s) {
+                    match embedded.parse() {
                         Ok(parsed_value) => self.collect_from_value(&parsed_value, ids),
-                        Err(e) => log(
-                            LogLevel::Warning,
-                            &format!("Failed to parse direct JSON string '{}': {}", trimmed, e),
+                        Err(e) => util::report_parse_failure(
+                            embedded.failure_field(),
+                            "unparsable",
+                            &self.lang,
+                            self.page_id,
+                            embedded.payload(),
+                            &e,
                         ),
                     }
                 }
@@ -0,0 +1,114 @@
+use crate::config;
+use crate::model::common::{MenuCategory, MenuId};
+use crate::model::output::{
+    ComponentData, OutputProgressionData, OutputProgressionEntry, ProgressionKind,
+};
+use std::collections::HashMap;
+
+// Character pages list their constellations C1..C6 in summaryList; weapon pages
+// carry refinement ranks as baseInfo rows keyed by a localized label. Anything
+// that doesn't fit these shapes yields None rather than a partial component.
+pub fn derive_progression(
+    components: &HashMap<String, ComponentData>,
+    menu_id: MenuId,
+    menu_ids: &[MenuId],
+    lang: &str,
+) -> Option<OutputProgressionData> {
+    if MenuCategory::Characters.contains_any(menu_id, menu_ids) {
+        derive_constellations(components.get(config::COMPONENT_SUMMARY_LIST)?)
+    } else if MenuCategory::Weapons.contains_any(menu_id, menu_ids) {
+        derive_refinements(components.get(config::COMPONENT_BASE_INFO)?, lang)
+    } else {
+        None
+    }
+}
+
+fn derive_constellations(summary: &ComponentData) -> Option<OutputProgressionData> {
+    let ComponentData::SummaryList(items) = summary else {
+        return None;
+    };
+    if items.is_empty() || items.len() > config::MAX_CONSTELLATIONS {
+        return None;
+    }
+    let entries = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| OutputProgressionEntry {
+            index: i as u8 + 1,
+            name: item.name.trim().to_string(),
+            desc: item.desc.clone(),
+            ..Default::default()
+        })
+        .collect();
+    Some(OutputProgressionData {
+        kind: ProgressionKind::Constellation,
+        entries,
+    })
+}
+
+fn derive_refinements(base_info: &ComponentData, lang: &str) -> Option<OutputProgressionData> {
+    let ComponentData::BaseInfo(rows) = base_info else {
+        return None;
+    };
+    let labels = refinement_labels_for(lang);
+    let mut entries: Vec<OutputProgressionEntry> = rows
+        .iter()
+        .filter(|row| labels.iter().any(|label| row.key.contains(label)))
+        .filter_map(|row| {
+            Some(OutputProgressionEntry {
+                index: 0,
+                name: row.key.trim().to_string(),
+                desc: row.value.clone().filter(|nodes| !nodes.is_empty())?,
+                ..Default::default()
+            })
+        })
+        .collect();
+    if entries.is_empty() {
+        return None;
+    }
+
+    // Prefer the rank written in the key ("Refinement Rank 3"); fall back to row order.
+    let ranks: Vec<Option<u8>> = entries.iter().map(|e| trailing_rank(&e.name)).collect();
+    let use_ranks = ranks.iter().all(Option::is_some);
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.index = if use_ranks {
+            ranks[i].unwrap_or_default()
+        } else {
+            i as u8 + 1
+        };
+    }
+    entries.sort_by_key(|entry| entry.index);
+    entries.dedup_by_key(|entry| entry.index);
+    Some(OutputProgressionData {
+        kind: ProgressionKind::Refinement,
+        entries,
+    })
+}
+
+// Unknown languages (e.g. test mode) accept any known label.
+fn refinement_labels_for(lang: &str) -> Vec<&'static str> {
+    let labels = config::refinement_labels(lang);
+    if !labels.is_empty() {
+        return labels.to_vec();
+    }
+    let mut all: Vec<&'static str> = config::SUPPORTED_LANGS
+        .iter()
+        .flat_map(|lang| config::refinement_labels(lang).iter().copied())
+        .collect();
+    all.sort_unstable();
+    all.dedup();
+    all
+}
+
+fn trailing_rank(key: &str) -> Option<u8> {
+    let digits: String = key
+        .trim()
+        .chars()
+        .rev()
+        .take_while(char::is_ascii_digit)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    digits.parse().ok().filter(|&rank| rank > 0)
+}
@@ -0,0 +1,111 @@
+use crate::model::common::EntryId;
+use crate::model::html::{extract_text, HtmlNode};
+use crate::model::output::{ComponentData, OutputDetailPage};
+use crate::transform::common::to_camel_case;
+use crate::transform::intern::resolve_refs;
+use serde::Serialize;
+use std::collections::HashSet;
+
+pub const SEARCH_INDEX_DIR: &str = "search_index";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexEntry<'a> {
+    #[serde(rename = "epId")]
+    pub ep_id: EntryId,
+    pub name: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    pub menu_name: &'a str,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub keywords: String,
+}
+
+// `components` accepts API (snake_case) or output (camelCase) component ids; their text is
+// concatenated in the given order and cut to `max_chars` characters.
+pub fn build_search_index<'a>(
+    details: &'a [OutputDetailPage],
+    components: &[String],
+    max_chars: usize,
+) -> Vec<SearchIndexEntry<'a>> {
+    let mut keys: Vec<String> = Vec::with_capacity(components.len());
+    let mut seen = HashSet::new();
+    for key in components.iter().map(|c| to_camel_case(c)) {
+        if seen.insert(key.clone()) {
+            keys.push(key);
+        }
+    }
+
+    let mut entries: Vec<SearchIndexEntry<'a>> = details
+        .iter()
+        .filter(|page| page.id > 0 && page.alias_of.is_none())
+        .filter_map(|page| {
+            let name = page.name.as_deref().map(str::trim).unwrap_or_default();
+            if name.is_empty() {
+                return None;
+            }
+            let text = keys
+                .iter()
+                .filter_map(|key| page.components.get(key))
+                .map(|component| component_text(component, &page.node_defs))
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(SearchIndexEntry {
+                ep_id: page.id,
+                name,
+                menu_name: page.menu_name.as_deref().unwrap_or_default(),
+                keywords: truncate_chars(&text, max_chars),
+            })
+        })
+        .collect();
+    entries.sort_unstable_by_key(|entry| entry.ep_id);
+    entries
+}
+
+fn component_text(data: &ComponentData, node_defs: &[Vec<HtmlNode>]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut push_nodes = |label: &str, nodes: &[HtmlNode]| {
+        let text = extract_text(&resolve_refs(nodes, node_defs));
+        let label = label.trim();
+        match (label.is_empty(), text.is_empty()) {
+            (false, false) => parts.push(format!("{} {}", label, text)),
+            (false, true) => parts.push(label.to_string()),
+            (true, false) => parts.push(text),
+            (true, true) => {}
+        }
+    };
+    match data {
+        ComponentData::BaseInfo(items) => {
+            for item in items {
+                push_nodes(&item.key, item.value.as_deref().unwrap_or_default());
+            }
+        }
+        ComponentData::SummaryList(items) => {
+            for item in items {
+                push_nodes(&item.name, &item.desc);
+            }
+        }
+        ComponentData::Story(items) => {
+            for item in items {
+                push_nodes(&item.title, &item.desc);
+            }
+        }
+        ComponentData::Talent(items) => {
+            for item in items {
+                push_nodes(&item.title, &item.desc);
+            }
+        }
+        ComponentData::Customize(nodes) | ComponentData::DropMaterial(nodes) => {
+            push_nodes("", nodes)
+        }
+        _ => {}
+    }
+    parts.join(" ")
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => text[..end].trim_end().to_string(),
+        None => text.to_string(),
+    }
+}
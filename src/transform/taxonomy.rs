@@ -0,0 +1,141 @@
+use crate::model::common::EntryId;
+use crate::model::output::{FilterValue, OutputDetailPage, OutputListFile};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// filterKey -> value -> epIds. Keys and string values borrow from the transformed
+// pages; only integer values (e.g. rarity) are stringified into owned strings.
+pub type Taxonomy<'a> = BTreeMap<&'a str, BTreeMap<Cow<'a, str>, BTreeSet<EntryId>>>;
+
+pub fn build_taxonomy<'a>(
+    lists: &'a [OutputListFile],
+    details: &'a [OutputDetailPage],
+) -> Taxonomy<'a> {
+    let mut taxonomy = Taxonomy::new();
+
+    let list_sources = lists
+        .iter()
+        .flat_map(|file| file.list.iter())
+        .map(|item| (item.id, &item.filter_values));
+    let detail_sources = details.iter().map(|page| (page.id, &page.filter_values));
+
+    for (id, filter_values) in list_sources.chain(detail_sources) {
+        if id > 0 {
+            add_filter_values(&mut taxonomy, id, filter_values);
+        }
+    }
+
+    taxonomy.retain(|_, values| !values.is_empty());
+    taxonomy
+}
+
+fn add_filter_values<'a>(
+    taxonomy: &mut Taxonomy<'a>,
+    id: EntryId,
+    filter_values: &'a HashMap<String, FilterValue>,
+) {
+    for (key, filter_value) in filter_values {
+        let values = taxonomy.entry(key.as_str()).or_default();
+        match filter_value {
+            FilterValue::Single(value) => insert_value(values, Cow::Borrowed(value.trim()), id),
+            FilterValue::Multiple(list) => {
+                for value in list {
+                    insert_value(values, Cow::Borrowed(value.trim()), id);
+                }
+            }
+            FilterValue::Integer(value) => insert_value(values, Cow::Owned(value.to_string()), id),
+        }
+    }
+}
+
+fn insert_value<'a>(
+    values: &mut BTreeMap<Cow<'a, str>, BTreeSet<EntryId>>,
+    value: Cow<'a, str>,
+    id: EntryId,
+) {
+    if !value.is_empty() {
+        values.entry(value).or_default().insert(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_value, json, Value};
+
+    fn list_file(items: Value) -> OutputListFile {
+        from_value(json!({
+            "version": 0,
+            "language": "en-us",
+            "menuId": 2,
+            "totalItems": items.as_array().map_or(0, Vec::len),
+            "list": items
+        }))
+        .unwrap()
+    }
+
+    fn detail(id: EntryId, filter_values: Value) -> OutputDetailPage {
+        OutputDetailPage {
+            id,
+            filter_values: from_value(filter_values).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn ids(taxonomy: &Taxonomy, key: &str, value: &str) -> Vec<EntryId> {
+        taxonomy[key][value].iter().copied().collect()
+    }
+
+    #[test]
+    fn integer_values_are_stringified() {
+        let lists = [list_file(json!([
+            { "epId": 1001, "filterValues": { "rarity": 5 } },
+            { "epId": 1002, "filterValues": { "rarity": 4 } }
+        ]))];
+        let taxonomy = build_taxonomy(&lists, &[]);
+        assert_eq!(ids(&taxonomy, "rarity", "5"), [1001]);
+        assert_eq!(ids(&taxonomy, "rarity", "4"), [1002]);
+        assert!(matches!(
+            taxonomy["rarity"].keys().next(),
+            Some(Cow::Owned(_))
+        ));
+    }
+
+    #[test]
+    fn values_are_trimmed_and_blanks_dropped() {
+        let details = [detail(
+            1001,
+            json!({ "element": "  Pyro ", "region": ["Mondstadt ", " ", ""], "blank": "  " }),
+        )];
+        let taxonomy = build_taxonomy(&[], &details);
+        assert_eq!(ids(&taxonomy, "element", "Pyro"), [1001]);
+        assert_eq!(taxonomy["region"].keys().collect::<Vec<_>>(), ["Mondstadt"]);
+        assert!(!taxonomy.contains_key("blank"));
+    }
+
+    #[test]
+    fn list_and_detail_ids_are_deduplicated() {
+        let lists = [list_file(json!([
+            { "epId": 1001, "filterValues": { "element": "Pyro" } },
+            { "epId": 1002, "filterValues": { "element": "Cryo" } }
+        ]))];
+        let details = [
+            detail(1001, json!({ "element": "Pyro" })),
+            detail(1003, json!({ "element": "Pyro" })),
+        ];
+        let taxonomy = build_taxonomy(&lists, &details);
+        assert_eq!(ids(&taxonomy, "element", "Pyro"), [1001, 1003]);
+        assert_eq!(ids(&taxonomy, "element", "Cryo"), [1002]);
+    }
+
+    #[test]
+    fn non_positive_ids_are_skipped() {
+        let lists = [list_file(json!([
+            { "epId": 0, "filterValues": { "element": "Pyro" } },
+            { "epId": -5, "filterValues": { "element": "Hydro" } }
+        ]))];
+        let details = [detail(0, json!({ "weapon": "Sword" }))];
+        let taxonomy = build_taxonomy(&lists, &details);
+        assert!(taxonomy.is_empty());
+    }
+}
@@ -1,5 +1,141 @@
+use crate::config;
+use crate::logging::{log, LogLevel};
+use crate::model::common::EntryId;
+use crate::telemetry;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use once_cell::sync::Lazy;
 use scraper::ElementRef;
-use serde_json::Value;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+static PARSE_FAILURES_SEEN: Lazy<Mutex<HashMap<(&'static str, &'static str), usize>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// JSON the API embeds in string fields: `$[...]$` material markers (optionally wrapped
+/// in quotes) or a bare `{...}` / `[...]` document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddedJson<'a> {
+    Marker(&'a str),
+    Direct(&'a str),
+}
+
+impl<'a> EmbeddedJson<'a> {
+    pub fn detect(s: &'a str) -> Option<Self> {
+        let trimmed = s.trim();
+        let marker = trimmed
+            .strip_prefix("\"$[")
+            .and_then(|rest| rest.strip_suffix("]$\""))
+            .or_else(|| {
+                trimmed
+                    .strip_prefix("$[")
+                    .and_then(|rest| rest.strip_suffix("]$"))
+            });
+        match marker {
+            Some("") => None,
+            Some(payload) => Some(EmbeddedJson::Marker(payload)),
+            None if (trimmed.starts_with('{') && trimmed.ends_with('}'))
+                || (trimmed.starts_with('[') && trimmed.ends_with(']')) =>
+            {
+                Some(EmbeddedJson::Direct(trimmed))
+            }
+            None => None,
+        }
+    }
+
+    pub fn payload(self) -> &'a str {
+        match self {
+            EmbeddedJson::Marker(payload) | EmbeddedJson::Direct(payload) => payload,
+        }
+    }
+
+    /// Coercion report field for a payload that does not parse.
+    pub fn failure_field(self) -> &'static str {
+        match self {
+            EmbeddedJson::Marker(_) => "material_string",
+            EmbeddedJson::Direct(_) => "embedded_json",
+        }
+    }
+
+    /// Marker payloads holding bare comma-separated objects are retried as a list.
+    pub fn parse(self) -> serde_json::Result<Value> {
+        match self {
+            EmbeddedJson::Marker(payload) => serde_json::from_str(payload).or_else(|e| {
+                if payload.starts_with('{') && payload.ends_with('}') {
+                    serde_json::from_str(&format!("[{}]", payload)).map_err(|_| e)
+                } else {
+                    Err(e)
+                }
+            }),
+            EmbeddedJson::Direct(payload) => serde_json::from_str(payload),
+        }
+    }
+}
+
+/// Warns that an embedded JSON/material string failed to parse. Only the first
+/// `PARSE_FAILURE_LOG_SAMPLES` failures of each `field`/`kind` pair are logged, with the
+/// payload cut short; every failure is counted under that pair in the coercion report.
+pub fn report_parse_failure(
+    field: &'static str,
+    kind: &'static str,
+    lang: &str,
+    page_id: Option<EntryId>,
+    payload: &str,
+    error: &dyn Display,
+) {
+    telemetry::record_coercion(field, kind);
+    let seen = PARSE_FAILURES_SEEN
+        .lock()
+        .map(|mut seen| {
+            let count = seen.entry((field, kind)).or_default();
+            *count += 1;
+            *count
+        })
+        .unwrap_or(1);
+    if let Some(message) = parse_failure_message(field, kind, lang, page_id, payload, error, seen) {
+        log(LogLevel::Warning, &message);
+    }
+}
+
+// The warning for the `seen`-th failure of a field/kind pair; None once past the samples.
+fn parse_failure_message(
+    field: &str,
+    kind: &str,
+    lang: &str,
+    page_id: Option<EntryId>,
+    payload: &str,
+    error: &dyn Display,
+    seen: usize,
+) -> Option<String> {
+    if seen > config::PARSE_FAILURE_LOG_SAMPLES {
+        return None;
+    }
+    let mut preview: String = payload
+        .chars()
+        .take(config::PARSE_FAILURE_PAYLOAD_CHARS)
+        .collect();
+    if preview.len() < payload.len() {
+        preview.push('…');
+    }
+    let page = page_id.map_or_else(|| "-".to_string(), |id| id.to_string());
+    let suppressed = if seen == config::PARSE_FAILURE_LOG_SAMPLES {
+        " Further failures of this kind are only counted."
+    } else {
+        ""
+    };
+    Some(format!(
+        "Parse failure {}:{} [{} / {}]: '{}': {}.{}",
+        field, kind, lang, page, preview, error, suppressed
+    ))
+}
+
+pub fn normalize_lang_code(raw: &str) -> Option<String> {
+    let normalized = raw.trim().to_lowercase().replace('_', "-");
+    config::SUPPORTED_LANGS
+        .contains(&normalized)
+        .then_some(normalized)
+}
 
 #[inline]
 pub fn parse_value_as_optional_i64(value: &Value) -> Option<i64> {
@@ -10,21 +146,229 @@ pub fn parse_value_as_optional_i64(value: &Value) -> Option<i64> {
     }
 }
 
+/// Trims a user-visible string (name, desc, title, key, icon URL) and drops the
+/// placeholders upstream sends for "no value": blanks, "null", "undefined" and "-".
+pub fn sanitize_display_string(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    let is_placeholder = trimmed.is_empty()
+        || trimmed == "-"
+        || trimmed.eq_ignore_ascii_case("null")
+        || trimmed.eq_ignore_ascii_case("undefined");
+    (!is_placeholder).then(|| trimmed.to_string())
+}
+
+// Prefixes protocol-relative and scheme-less URLs with https; drops empty/placeholder values.
+pub fn normalize_asset_url(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty()
+        || trimmed.contains("invalid-file")
+        || trimmed.contains(char::is_whitespace)
+    {
+        return None;
+    }
+
+    let lower = trimmed.to_ascii_lowercase();
+    if lower.starts_with("https://") || lower.starts_with("http://") {
+        return Some(trimmed.to_string());
+    }
+    if let Some(rest) = trimmed.strip_prefix("//") {
+        return is_plausible_host_path(rest).then(|| format!("https://{}", rest));
+    }
+    if trimmed.contains("://") {
+        return None;
+    }
+    is_plausible_host_path(trimmed).then(|| format!("https://{}", trimmed))
+}
+
+fn is_plausible_host_path(value: &str) -> bool {
+    let host = value.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    host.contains('.')
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+// A parsed calendar value, whether the source carried a time of day, and whether
+// that time is known to be UTC (timestamps and RFC 3339) rather than zone-less.
+struct CalendarInstant {
+    datetime: NaiveDateTime,
+    has_time: bool,
+    is_utc: bool,
+}
+
+// Accepts unix seconds/milliseconds, RFC 3339, and the date strings seen in calendar data.
+// Zone-less values keep their wall-clock time; a bare "MM-DD" is placed in the current year.
+fn parse_calendar_instant(value: &Value) -> Option<CalendarInstant> {
+    const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+    const DATETIME_FORMATS: [&str; 4] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y/%m/%d %H:%M",
+    ];
+    const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y/%m/%d", "%m/%d/%Y"];
+
+    let from_number = |n: i64| {
+        let secs = if n >= MILLIS_THRESHOLD { n / 1000 } else { n };
+        (n > 0)
+            .then(|| DateTime::from_timestamp(secs, 0))
+            .flatten()
+            .map(|dt| CalendarInstant {
+                datetime: dt.naive_utc(),
+                has_time: true,
+                is_utc: true,
+            })
+    };
+    let date_only = |d: NaiveDate| CalendarInstant {
+        datetime: d.and_time(NaiveTime::MIN),
+        has_time: false,
+        is_utc: false,
+    };
+
+    let trimmed = match value {
+        Value::Number(n) => return n.as_i64().and_then(from_number),
+        Value::String(s) => s.trim(),
+        _ => return None,
+    };
+    if trimmed.is_empty() {
+        return None;
+    }
+    if let Ok(n) = trimmed.parse::<i64>() {
+        return from_number(n);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(trimmed) {
+        return Some(CalendarInstant {
+            datetime: dt.naive_utc(),
+            has_time: true,
+            is_utc: true,
+        });
+    }
+    if let Some(datetime) = DATETIME_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(trimmed, fmt).ok())
+    {
+        return Some(CalendarInstant {
+            datetime,
+            has_time: true,
+            is_utc: false,
+        });
+    }
+    if let Some(date) = DATE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(trimmed, fmt).ok())
+    {
+        return Some(date_only(date));
+    }
+    let (month, day) = parse_month_day(trimmed)?;
+    NaiveDate::from_ymd_opt(Utc::now().year(), month, day).map(date_only)
+}
+
+fn parse_month_day(value: &str) -> Option<(u32, u32)> {
+    match value.split('-').collect::<Vec<_>>().as_slice() {
+        [m, d] if m.len() == 2 && d.len() == 2 => Some((m.parse().ok()?, d.parse().ok()?)),
+        _ => None,
+    }
+}
+
+/// A calendar date as the API wrote it: a full date, or an "MM-DD" without a year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CalendarDate {
+    Full(NaiveDate),
+    MonthDay { month: u32, day: u32 },
+}
+
+impl CalendarDate {
+    fn parse(value: &Value) -> Option<Self> {
+        if let Some((month, day)) = value.as_str().and_then(|s| parse_month_day(s.trim())) {
+            return Some(CalendarDate::MonthDay { month, day });
+        }
+        parse_calendar_instant(value).map(|instant| CalendarDate::Full(instant.datetime.date()))
+    }
+
+    // The first occurrence of a month/day on or after `from`, or on or before it when
+    // `backwards`. A Feb 29 skips ahead (or back) to the nearest leap year.
+    fn resolve_from(self, from: NaiveDate, backwards: bool) -> Option<NaiveDate> {
+        let (month, day) = match self {
+            CalendarDate::Full(date) => return Some(date),
+            CalendarDate::MonthDay { month, day } => (month, day),
+        };
+        let step = if backwards { -1 } else { 1 };
+        (0..=8)
+            .filter_map(|offset| NaiveDate::from_ymd_opt(from.year() + offset * step, month, day))
+            .find(|&date| {
+                if backwards {
+                    date <= from
+                } else {
+                    date >= from
+                }
+            })
+    }
+}
+
+/// Resolves an op event's start/end to full dates. A yearless side is placed next to
+/// the dated one, so "12-20" .. "01-05" spans New Year; when both are yearless the start
+/// takes the year that puts it closest to `today`.
+pub fn resolve_calendar_range(
+    start: Option<&Value>,
+    end: Option<&Value>,
+    today: NaiveDate,
+) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    let start = start.and_then(CalendarDate::parse);
+    let end = end.and_then(CalendarDate::parse);
+    let start_date = match (start, end) {
+        (Some(start @ CalendarDate::MonthDay { .. }), Some(CalendarDate::Full(end_date))) => {
+            start.resolve_from(end_date, true)
+        }
+        (Some(start), _) => nearest_to(start, today),
+        (None, _) => None,
+    };
+    let end_date = match (end, start_date) {
+        (Some(end), Some(start_date)) => end.resolve_from(start_date, false),
+        (Some(end), None) => nearest_to(end, today),
+        (None, _) => None,
+    };
+    (start_date, end_date)
+}
+
+fn nearest_to(date: CalendarDate, today: NaiveDate) -> Option<NaiveDate> {
+    let before = date.resolve_from(today, true);
+    let after = date.resolve_from(today, false);
+    match (before, after) {
+        (Some(before), Some(after)) if today - before <= after - today => Some(before),
+        (_, Some(after)) => Some(after),
+        (before, None) => before,
+    }
+}
+
+pub fn parse_value_as_date(value: &Value) -> Option<i64> {
+    parse_calendar_instant(value).map(|instant| instant.datetime.and_utc().timestamp())
+}
+
+// Normalises any supported calendar value to ISO 8601 "YYYY-MM-DD".
 pub fn format_calendar_date_value(raw_date_val: Option<&Value>) -> Option<String> {
     raw_date_val
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-        .and_then(|s| {
-            let parts: Vec<&str> = s.split('-').collect();
-            match parts.as_slice() {
-                [y, m, d] if y.len() == 4 && m.len() == 2 && d.len() == 2 => {
-                    Some(format!("{}-{}", m, d))
-                }
+        .and_then(parse_calendar_instant)
+        .map(|instant| instant.datetime.format("%Y-%m-%d").to_string())
+}
 
-                [m, d] if m.len() == 2 && d.len() == 2 => Some(s.to_string()),
-                _ => None,
-            }
+// Full ISO 8601 "YYYY-MM-DDTHH:MM:SS"; None when the value only carries a date.
+// The "Z" suffix is only added for timestamps and RFC 3339 input. The API's
+// "YYYY/MM/DD HH:MM:SS" strings carry no zone, so they stay local wall-clock times.
+pub fn format_calendar_datetime_value(raw_date_val: Option<&Value>) -> Option<String> {
+    raw_date_val
+        .and_then(parse_calendar_instant)
+        .filter(|instant| instant.has_time)
+        .map(|instant| {
+            let format = if instant.is_utc {
+                "%Y-%m-%dT%H:%M:%SZ"
+            } else {
+                "%Y-%m-%dT%H:%M:%S"
+            };
+            instant.datetime.format(format).to_string()
         })
 }
 
@@ -68,3 +412,324 @@ pub fn remove_internal_ids_recursive(value: &mut Value) {
         _ => {}
     }
 }
+
+/// Makes raw Unknown component data readable: JSON-encoded strings are decoded
+/// (up to `UNKNOWN_JSON_DECODE_DEPTH` nested encodings) and oversized base64-like
+/// blobs become `{"__truncated": true, "length": N}`.
+pub fn normalize_unknown_value(value: Value) -> Value {
+    normalize_unknown_value_at(value, config::UNKNOWN_JSON_DECODE_DEPTH)
+}
+
+fn normalize_unknown_value_at(value: Value, decode_depth: u32) -> Value {
+    match value {
+        Value::String(s) => {
+            if decode_depth > 0 {
+                if let Some(decoded) = decode_embedded_json(&s) {
+                    telemetry::record_coercion("unknown_component", "embedded_json");
+                    return normalize_unknown_value_at(decoded, decode_depth - 1);
+                }
+            }
+            if is_opaque_blob(&s) {
+                telemetry::record_coercion("unknown_component", "truncated_blob");
+                return json!({ "__truncated": true, "length": s.len() });
+            }
+            Value::String(s)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| normalize_unknown_value_at(item, decode_depth))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, val)| (key, normalize_unknown_value_at(val, decode_depth)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+fn decode_embedded_json(s: &str) -> Option<Value> {
+    let trimmed = s.trim();
+    if !(trimmed.starts_with('{') || trimmed.starts_with('[') || trimmed.starts_with('"')) {
+        return None;
+    }
+    serde_json::from_str(trimmed).ok()
+}
+
+fn is_opaque_blob(s: &str) -> bool {
+    s.len() > config::UNKNOWN_BLOB_MAX_CHARS
+        && s.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(b, b'+' | b'/' | b'=' | b'-' | b'_' | b':' | b';' | b',')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(raw: Value) -> Option<String> {
+        format_calendar_date_value(Some(&raw))
+    }
+
+    fn datetime(raw: Value) -> Option<String> {
+        format_calendar_datetime_value(Some(&raw))
+    }
+
+    #[test]
+    fn unix_seconds_string() {
+        assert_eq!(date(json!("1700000000")).as_deref(), Some("2023-11-14"));
+        assert_eq!(
+            datetime(json!("1700000000")).as_deref(),
+            Some("2023-11-14T22:13:20Z")
+        );
+    }
+
+    #[test]
+    fn unix_seconds_number() {
+        assert_eq!(
+            datetime(json!(1700000000)).as_deref(),
+            Some("2023-11-14T22:13:20Z")
+        );
+    }
+
+    #[test]
+    fn unix_milliseconds() {
+        assert_eq!(
+            datetime(json!("1700000000123")).as_deref(),
+            Some("2023-11-14T22:13:20Z")
+        );
+        assert_eq!(
+            datetime(json!(1700000000123_i64)).as_deref(),
+            Some("2023-11-14T22:13:20Z")
+        );
+    }
+
+    #[test]
+    fn non_positive_timestamps_are_rejected() {
+        assert_eq!(date(json!("0")), None);
+        assert_eq!(date(json!(-86400)), None);
+    }
+
+    #[test]
+    fn timestamp_across_the_year_boundary() {
+        assert_eq!(
+            datetime(json!(1704067199)).as_deref(),
+            Some("2023-12-31T23:59:59Z")
+        );
+        assert_eq!(
+            datetime(json!(1704067200)).as_deref(),
+            Some("2024-01-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn rfc3339_is_converted_to_utc() {
+        assert_eq!(
+            datetime(json!("2024-01-01T02:30:00+08:00")).as_deref(),
+            Some("2023-12-31T18:30:00Z")
+        );
+        assert_eq!(
+            date(json!("2024-01-01T02:30:00+08:00")).as_deref(),
+            Some("2023-12-31")
+        );
+    }
+
+    #[test]
+    fn iso_date() {
+        assert_eq!(date(json!("2024-03-05")).as_deref(), Some("2024-03-05"));
+    }
+
+    #[test]
+    fn dashed_datetime_has_no_zone() {
+        assert_eq!(
+            datetime(json!("2024-03-05 10:00:00")).as_deref(),
+            Some("2024-03-05T10:00:00")
+        );
+        assert_eq!(
+            datetime(json!("2024-03-05 10:00")).as_deref(),
+            Some("2024-03-05T10:00:00")
+        );
+    }
+
+    #[test]
+    fn slashed_datetime_has_no_zone() {
+        assert_eq!(
+            datetime(json!("2023/12/31 23:59:59")).as_deref(),
+            Some("2023-12-31T23:59:59")
+        );
+        assert_eq!(
+            date(json!("2023/12/31 23:59:59")).as_deref(),
+            Some("2023-12-31")
+        );
+    }
+
+    #[test]
+    fn slashed_date() {
+        assert_eq!(date(json!("2024/01/01")).as_deref(), Some("2024-01-01"));
+    }
+
+    #[test]
+    fn us_date() {
+        assert_eq!(date(json!("12/31/2023")).as_deref(), Some("2023-12-31"));
+    }
+
+    #[test]
+    fn month_day_uses_the_current_year() {
+        let expected = format!("{}-07-15", Utc::now().year());
+        assert_eq!(date(json!("07-15")), Some(expected));
+    }
+
+    #[test]
+    fn date_only_values_have_no_datetime() {
+        assert_eq!(datetime(json!("2024-03-05")), None);
+        assert_eq!(datetime(json!("2024/03/05")), None);
+        assert_eq!(datetime(json!("07-15")), None);
+    }
+
+    #[test]
+    fn leap_days() {
+        assert_eq!(date(json!("2024-02-29")).as_deref(), Some("2024-02-29"));
+        assert_eq!(date(json!("2023-02-29")), None);
+    }
+
+    #[test]
+    fn invalid_month_day() {
+        assert_eq!(date(json!("13-01")), None);
+        assert_eq!(date(json!("02-30")), None);
+        assert_eq!(date(json!("7-15")), None);
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_ignored() {
+        assert_eq!(date(json!("  2024-03-05\n")).as_deref(), Some("2024-03-05"));
+    }
+
+    #[test]
+    fn empty_and_garbage_strings_are_rejected() {
+        assert_eq!(date(json!("")), None);
+        assert_eq!(date(json!("   ")), None);
+        assert_eq!(date(json!("soon")), None);
+        assert_eq!(date(json!("2024-13-01")), None);
+    }
+
+    #[test]
+    fn non_string_values_are_rejected() {
+        assert_eq!(date(json!(null)), None);
+        assert_eq!(date(json!(true)), None);
+        assert_eq!(date(json!(["2024-03-05"])), None);
+        assert_eq!(format_calendar_date_value(None), None);
+    }
+
+    #[test]
+    fn parse_value_as_date_reads_zone_less_values_as_utc() {
+        assert_eq!(
+            parse_value_as_date(&json!("2023/11/14 22:13:20")),
+            Some(1700000000)
+        );
+        assert_eq!(parse_value_as_date(&json!("nope")), None);
+    }
+
+    #[test]
+    fn yearless_range_spans_new_year() {
+        let today = NaiveDate::from_ymd_opt(2023, 12, 25).unwrap();
+        let (start, end) =
+            resolve_calendar_range(Some(&json!("12-20")), Some(&json!("01-05")), today);
+        assert_eq!(start, NaiveDate::from_ymd_opt(2023, 12, 20));
+        assert_eq!(end, NaiveDate::from_ymd_opt(2024, 1, 5));
+
+        let (start, end) =
+            resolve_calendar_range(Some(&json!("12-20")), Some(&json!("2024-01-05")), today);
+        assert_eq!(start, NaiveDate::from_ymd_opt(2023, 12, 20));
+        assert_eq!(end, NaiveDate::from_ymd_opt(2024, 1, 5));
+    }
+
+    #[test]
+    fn string_wrapped_objects_are_decoded() {
+        assert_eq!(
+            normalize_unknown_value(json!({ "data": "{\"a\": 1, \"b\": [\"x\"]}" })),
+            json!({ "data": { "a": 1, "b": ["x"] } })
+        );
+        assert_eq!(
+            normalize_unknown_value(json!(["[1, 2]", "hello", "[not json"])),
+            json!([[1, 2], "hello", "[not json"])
+        );
+    }
+
+    #[test]
+    fn double_wrapped_strings_are_decoded_up_to_the_depth() {
+        let once = json!({ "a": 1 }).to_string();
+        let twice = Value::String(once.clone()).to_string();
+        let thrice = Value::String(twice.clone()).to_string();
+        assert_eq!(normalize_unknown_value(json!(twice)), json!({ "a": 1 }));
+        assert_eq!(normalize_unknown_value(json!(thrice)), json!(once));
+    }
+
+    #[test]
+    fn only_long_opaque_blobs_are_truncated() {
+        let limit = config::UNKNOWN_BLOB_MAX_CHARS;
+        let blob = "QUJD".repeat(limit / 4) + "==";
+        assert_eq!(
+            normalize_unknown_value(json!({ "img": blob })),
+            json!({ "img": { "__truncated": true, "length": limit + 2 } })
+        );
+        let at_limit = "A".repeat(limit);
+        assert_eq!(normalize_unknown_value(json!(at_limit)), json!(at_limit));
+        let prose = "word ".repeat(limit);
+        assert_eq!(normalize_unknown_value(json!(prose)), json!(prose));
+    }
+
+    fn failure_message(page_id: Option<EntryId>, payload: &str, seen: usize) -> Option<String> {
+        parse_failure_message(
+            "material_string",
+            "unparsable",
+            "ja-jp",
+            page_id,
+            payload,
+            &"expected value",
+            seen,
+        )
+    }
+
+    #[test]
+    fn parse_failures_carry_their_page_context() {
+        assert_eq!(
+            failure_message(Some(4242), "{oops", 1).as_deref(),
+            Some(
+                "Parse failure material_string:unparsable [ja-jp / 4242]: '{oops': expected value."
+            )
+        );
+        assert!(failure_message(None, "{oops", 1)
+            .unwrap()
+            .contains("[ja-jp / -]"));
+    }
+
+    #[test]
+    fn parse_failure_payloads_are_cut_short() {
+        let limit = config::PARSE_FAILURE_PAYLOAD_CHARS;
+        let exact = "稲".repeat(limit);
+        assert!(failure_message(None, &exact, 1)
+            .unwrap()
+            .contains(&format!("'{}'", exact)));
+        let long = "稲".repeat(limit + 1);
+        assert!(failure_message(None, &long, 1)
+            .unwrap()
+            .contains(&format!("'{}…'", exact)));
+    }
+
+    #[test]
+    fn only_the_first_parse_failures_are_logged() {
+        let samples = config::PARSE_FAILURE_LOG_SAMPLES;
+        for seen in 1..samples {
+            assert!(!failure_message(None, "x", seen)
+                .unwrap()
+                .contains("only counted"));
+        }
+        assert!(failure_message(None, "x", samples)
+            .unwrap()
+            .ends_with("Further failures of this kind are only counted."));
+        assert_eq!(failure_message(None, "x", samples + 1), None);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::core::stats;
 use crate::error::{AppError, AppResult};
+use std::time::Instant;
 use tokio::sync::{Semaphore, SemaphorePermit};
 use tokio::task;
 
@@ -25,12 +27,19 @@ where
     }
 }
 
+/// Acquires a permit, recording the wait under `context` when it is one of
+/// `stats::SEMAPHORE_LABELS` (see `stats::WaitHistogram` for reading the numbers).
 pub async fn acquire_semaphore<'a>(
     semaphore: &'a Semaphore,
     context: &str,
 ) -> AppResult<SemaphorePermit<'a>> {
-    semaphore
+    let started = Instant::now();
+    let permit = semaphore
         .acquire()
         .await
-        .map_err(|e| AppError::SemaphoreAcquire(format!("Failed for '{}': {}", context, e)))
+        .map_err(|e| AppError::SemaphoreAcquire(format!("Failed for '{}': {}", context, e)))?;
+    if let Some(histogram) = stats::semaphore_wait_histogram(context) {
+        histogram.record(started.elapsed());
+    }
+    Ok(permit)
 }
@@ -0,0 +1,211 @@
+//! `ApiClient::fetch` against the mock API: response size caps and the
+//! served-language check, plus the id backfill in `fetch_entry_detail`.
+//!
+//! The detail endpoint picks the scenario by entry id.
+
+mod common;
+
+use common::{MockApi, MockRequest, MockResponse};
+use reqwest::Method;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Semaphore;
+use wiki_update::api::client::ApiClient;
+use wiki_update::api::fetchers;
+use wiki_update::api::model::ApiDetailResponse;
+use wiki_update::config;
+use wiki_update::core::stats;
+use wiki_update::error::{AppError, AppResult};
+use wiki_update::logging::{self, LogFileConfig};
+use wiki_update::model::common::LangCode;
+use wiki_update::transform::bulk::BulkStore;
+use wiki_update::transform::detail::transform_detail_page;
+
+const CAP_MB: u64 = 1;
+const CAP_BYTES: u64 = CAP_MB * 1024 * 1024;
+const OVERSIZED_WITH_LENGTH: i64 = 1;
+const OVERSIZED_CHUNKED: i64 = 2;
+/// Always answers in `SERVED_LANG`, whatever was asked for.
+const SERVED_IN_ENGLISH: i64 = 3;
+const SERVED_LANG: &str = "en-us";
+/// The page comes back without an `id` field.
+const PAGE_WITHOUT_ID: i64 = 4;
+/// The page comes back with `"id": "0"`.
+const PAGE_WITH_ZERO_ID: i64 = 5;
+
+static MOCK: OnceLock<MockApi> = OnceLock::new();
+static LOG_DIR: OnceLock<tempfile::TempDir> = OnceLock::new();
+
+fn mock_api() -> &'static MockApi {
+    MOCK.get_or_init(|| {
+        let log_dir = LOG_DIR.get_or_init(|| tempfile::tempdir().unwrap());
+        logging::setup_logging(Some(LogFileConfig {
+            path: log_dir.path().join("run.log"),
+            max_bytes: u64::MAX,
+            keep: 1,
+        }));
+        let api = MockApi::start(route);
+        config::set_api_base_url(api.base_url()).unwrap();
+        config::set_max_response_mb(CAP_MB, &[]);
+        api
+    })
+}
+
+fn log_path() -> PathBuf {
+    mock_api();
+    LOG_DIR.get().unwrap().path().join("run.log")
+}
+
+fn route(req: &MockRequest) -> MockResponse {
+    let id = req.query_i64("entry_page_id").unwrap_or_default();
+    match (req.endpoint.as_str(), id) {
+        ("entry_page", OVERSIZED_WITH_LENGTH) => MockResponse::raw(200, oversized_body()),
+        ("entry_page", OVERSIZED_CHUNKED) => MockResponse::raw(200, oversized_body()).chunked(),
+        ("entry_page", SERVED_IN_ENGLISH) => MockResponse::ok(common::detail_page(id, "Amber", 2))
+            .with_header(common::LANGUAGE_HEADER, SERVED_LANG),
+        ("entry_page", PAGE_WITHOUT_ID) => {
+            let mut data = common::detail_page(id, "Kaeya", 2);
+            data["page"].as_object_mut().unwrap().remove("id");
+            MockResponse::ok(data)
+        }
+        ("entry_page", PAGE_WITH_ZERO_ID) => {
+            let mut data = common::detail_page(id, "Lisa", 2);
+            data["page"]["id"] = json!("0");
+            MockResponse::ok(data)
+        }
+        _ => MockResponse::status(404),
+    }
+}
+
+fn oversized_body() -> Vec<u8> {
+    vec![b' '; CAP_BYTES as usize * 2]
+}
+
+async fn fetch_detail(id: i64, lang: &str) -> AppResult<ApiDetailResponse> {
+    mock_api();
+    let params = HashMap::from([("entry_page_id".to_string(), id.to_string())]);
+    ApiClient::new()?
+        .fetch(
+            Method::GET,
+            "detail",
+            &LangCode::parse(lang).unwrap(),
+            Some(&params),
+            None,
+        )
+        .await
+}
+
+fn requests_for(id: i64) -> usize {
+    mock_api()
+        .requests()
+        .iter()
+        .filter(|req| req.query_i64("entry_page_id") == Some(id))
+        .count()
+}
+
+#[tokio::test]
+async fn oversized_body_with_content_length_is_rejected_up_front() {
+    let error = fetch_detail(OVERSIZED_WITH_LENGTH, "en-us")
+        .await
+        .unwrap_err();
+    match error {
+        AppError::ResponseTooLarge {
+            bytes,
+            limit,
+            endpoint,
+            ..
+        } => {
+            assert_eq!(bytes, CAP_BYTES * 2);
+            assert_eq!(limit, CAP_BYTES);
+            assert_eq!(endpoint, "detail");
+        }
+        other => panic!("expected ResponseTooLarge, got {:?}", other),
+    }
+    assert_eq!(requests_for(OVERSIZED_WITH_LENGTH), 1, "must not retry");
+}
+
+#[tokio::test]
+async fn oversized_chunked_body_is_cut_off_at_the_cap() {
+    let error = fetch_detail(OVERSIZED_CHUNKED, "en-us").await.unwrap_err();
+    match error {
+        AppError::ResponseTooLarge { bytes, limit, .. } => {
+            assert_eq!(limit, CAP_BYTES);
+            assert!(bytes > CAP_BYTES, "stopped before the cap: {}", bytes);
+            assert!(bytes < CAP_BYTES * 2, "read the whole body: {}", bytes);
+        }
+        other => panic!("expected ResponseTooLarge, got {:?}", other),
+    }
+    assert_eq!(requests_for(OVERSIZED_CHUNKED), 1, "must not retry");
+}
+
+fn mismatch_warnings(lang: &str) -> usize {
+    let needle = format!("Language mismatch [{}] detail", lang);
+    std::fs::read_to_string(log_path())
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| line.contains(&needle))
+        .count()
+}
+
+#[tokio::test]
+async fn language_mismatch_warns_once_and_fails_when_strict() {
+    let before = stats::LANGUAGE_MISMATCHES.load(Ordering::Relaxed);
+    let page = fetch_detail(SERVED_IN_ENGLISH, SERVED_LANG).await.unwrap();
+    assert_eq!(page.page.name, "Amber");
+    assert_eq!(stats::LANGUAGE_MISMATCHES.load(Ordering::Relaxed), before);
+
+    for _ in 0..3 {
+        fetch_detail(SERVED_IN_ENGLISH, "ja-jp").await.unwrap();
+    }
+    assert_eq!(
+        stats::LANGUAGE_MISMATCHES.load(Ordering::Relaxed),
+        before + 3
+    );
+    assert_eq!(mismatch_warnings("ja-jp"), 1);
+
+    config::set_strict_language(true);
+    let result = fetch_detail(SERVED_IN_ENGLISH, "ja-jp").await;
+    config::set_strict_language(false);
+    match result.unwrap_err() {
+        AppError::LanguageMismatch {
+            served,
+            endpoint,
+            lang,
+        } => {
+            assert_eq!(served, SERVED_LANG);
+            assert_eq!(endpoint, "detail");
+            assert_eq!(lang, "ja-jp");
+        }
+        other => panic!("expected LanguageMismatch, got {:?}", other),
+    }
+    assert_eq!(mismatch_warnings("ja-jp"), 1);
+}
+
+#[tokio::test]
+async fn missing_page_id_is_backfilled_from_the_request() {
+    mock_api();
+    let client = ApiClient::new().unwrap();
+    let lang = LangCode::parse("en-us").unwrap();
+    for (id, name) in [(PAGE_WITHOUT_ID, "Kaeya"), (PAGE_WITH_ZERO_ID, "Lisa")] {
+        let page = fetchers::fetch_entry_detail(&client, Arc::new(Semaphore::new(1)), &lang, id)
+            .await
+            .unwrap()
+            .expect("page is kept");
+        assert_eq!(page.id, Some(id));
+
+        let output = transform_detail_page(
+            page,
+            Arc::new(BulkStore::default()),
+            Arc::default(),
+            "en-us",
+        )
+        .await
+        .unwrap()
+        .expect("transform keeps the backfilled page");
+        assert_eq!(output.id, id);
+        assert_eq!(output.name.as_deref(), Some(name));
+    }
+}
@@ -0,0 +1,323 @@
+//! In-process mock of the wiki API for integration tests.
+//!
+//! The process-wide API base URL can only be set once, so each test binary starts
+//! a single [`MockApi`] and points the crate at it before the first request.
+//! Scenarios are told apart by what the request carries (language header,
+//! menu id, entry id) rather than by restarting the server.
+
+#![allow(dead_code)]
+
+use bytes::Bytes;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+pub const LANGUAGE_HEADER: &str = "x-rpc-language";
+
+/// One request as the mock saw it.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    /// Last path segment, e.g. `entry_page` or `navigation`.
+    pub endpoint: String,
+    pub lang: String,
+    pub query: HashMap<String, String>,
+    pub body: Value,
+}
+
+impl MockRequest {
+    pub fn query_i64(&self, key: &str) -> Option<i64> {
+        self.query.get(key).and_then(|v| v.parse().ok())
+    }
+
+    pub fn query_ids(&self, key: &str) -> Vec<i64> {
+        self.query
+            .get(key)
+            .map(|v| v.split(',').filter_map(|id| id.parse().ok()).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: Bytes,
+    pub headers: Vec<(String, String)>,
+    /// Send the body as chunks without a `Content-Length` header.
+    pub chunked: bool,
+}
+
+impl MockResponse {
+    /// A successful API envelope around `data`.
+    pub fn ok(data: Value) -> Self {
+        Self::json(json!({ "retcode": 0, "message": "OK", "data": data }))
+    }
+
+    pub fn json(body: Value) -> Self {
+        Self::raw(200, Bytes::from(body.to_string()))
+    }
+
+    pub fn raw(status: u16, body: impl Into<Bytes>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            chunked: false,
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self::raw(status, Bytes::from_static(b"mock failure"))
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn chunked(mut self) -> Self {
+        self.chunked = true;
+        self
+    }
+}
+
+type Handler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+pub struct MockApi {
+    pub addr: SocketAddr,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockApi {
+    /// Starts the server on its own thread and runtime so it outlives the
+    /// per-test runtimes of `#[tokio::test]`.
+    pub fn start(handler: impl Fn(&MockRequest) -> MockResponse + Send + Sync + 'static) -> Self {
+        let handler: Arc<Handler> = Arc::new(handler);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let (addr_tx, addr_rx) = std::sync::mpsc::channel();
+        let server_requests = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("mock runtime");
+            runtime.block_on(async move {
+                let listener = TcpListener::bind("127.0.0.1:0").await.expect("mock bind");
+                addr_tx
+                    .send(listener.local_addr().expect("mock addr"))
+                    .expect("mock addr send");
+                loop {
+                    let Ok((stream, _)) = listener.accept().await else {
+                        continue;
+                    };
+                    let handler = Arc::clone(&handler);
+                    let requests = Arc::clone(&server_requests);
+                    tokio::spawn(async move {
+                        let service = service_fn(move |req| {
+                            handle(Arc::clone(&handler), Arc::clone(&requests), req)
+                        });
+                        let _ = http1::Builder::new()
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await;
+                    });
+                }
+            });
+        });
+        let addr = addr_rx.recv().expect("mock server did not start");
+        Self { addr, requests }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn handle(
+    handler: Arc<Handler>,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    req: Request<Incoming>,
+) -> Result<Response<BoxBody<Bytes, Infallible>>, Infallible> {
+    let method = req.method().to_string();
+    let endpoint = req
+        .uri()
+        .path()
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let query = req.uri().query().map(parse_query).unwrap_or_default();
+    let lang = req
+        .headers()
+        .get(LANGUAGE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body_bytes = req
+        .into_body()
+        .collect()
+        .await
+        .map(|body| body.to_bytes())
+        .unwrap_or_default();
+    let body = serde_json::from_slice(&body_bytes).unwrap_or(Value::Null);
+    let request = MockRequest {
+        method,
+        endpoint,
+        lang,
+        query,
+        body,
+    };
+    requests.lock().unwrap().push(request.clone());
+
+    let mock = handler(&request);
+    let body = if mock.chunked {
+        let chunks: Vec<Result<Frame<Bytes>, Infallible>> = mock
+            .body
+            .chunks(16 * 1024)
+            .map(|chunk| Ok(Frame::data(Bytes::copy_from_slice(chunk))))
+            .collect();
+        StreamBody::new(futures::stream::iter(chunks)).boxed()
+    } else {
+        Full::new(mock.body).boxed()
+    };
+    let mut response = Response::new(body);
+    *response.status_mut() = StatusCode::from_u16(mock.status).expect("mock status");
+    for (name, value) in mock.headers {
+        response.headers_mut().insert(
+            hyper::header::HeaderName::from_bytes(name.as_bytes()).expect("mock header name"),
+            value.parse().expect("mock header value"),
+        );
+    }
+    Ok(response)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match u8::from_str_radix(&raw[i + 1..i + 3], 16) {
+                Ok(byte) => {
+                    out.push(byte);
+                    i += 3;
+                }
+                Err(_) => {
+                    out.push(b'%');
+                    i += 1;
+                }
+            },
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A minimal detail page with one rich-text module.
+pub fn detail_page(id: i64, name: &str, menu_id: i64) -> Value {
+    json!({
+        "page": {
+            "id": id.to_string(),
+            "name": name,
+            "desc": format!("{} description", name),
+            "icon_url": format!("https://example.test/icons/{}.png", id),
+            "header_img_url": "",
+            "menu_id": menu_id.to_string(),
+            "menu_name": null,
+            "version": "1",
+            "filter_values": {},
+            "modules": [
+                {
+                    "name": "Description",
+                    "id": "1",
+                    "is_poped": false,
+                    "components": [
+                        {
+                            "component_id": "customize",
+                            "layout": "",
+                            "data": json!({
+                                "list": [{ "title": "", "content": format!("<p>About {}.</p>", name) }]
+                            }).to_string()
+                        }
+                    ]
+                }
+            ]
+        }
+    })
+}
+
+/// Navigation data listing `menus` as (menu id, name).
+pub fn nav(menus: &[(i64, &str)]) -> Value {
+    let nav: Vec<Value> = menus
+        .iter()
+        .map(|(menu_id, name)| {
+            json!({
+                "menu": { "menu_id": menu_id.to_string() },
+                "name": name,
+                "icon_url": format!("https://example.test/menus/{}.png", menu_id)
+            })
+        })
+        .collect();
+    json!({ "nav": nav })
+}
+
+/// One list page holding `entries` as (entry id, name).
+pub fn list(entries: &[(i64, &str)]) -> Value {
+    let list: Vec<Value> = entries
+        .iter()
+        .map(|(id, name)| {
+            json!({
+                "entry_page_id": id.to_string(),
+                "name": name,
+                "icon_url": format!("https://example.test/icons/{}.png", id),
+                "display_field": {},
+                "filter_values": {}
+            })
+        })
+        .collect();
+    json!({ "total": entries.len().to_string(), "list": list })
+}
+
+/// Bulk data holding `entries` as (entry id, name).
+pub fn bulk(entries: &[(i64, &str)]) -> Value {
+    let pages: Vec<Value> = entries
+        .iter()
+        .map(|(id, name)| {
+            json!({
+                "id": id.to_string(),
+                "name": name,
+                "desc": "",
+                "icon_url": format!("https://example.test/icons/{}.png", id)
+            })
+        })
+        .collect();
+    json!({ "entry_pages": pages })
+}
@@ -0,0 +1,242 @@
+//! Full `processor::run` passes against the mock API, written into a tempdir.
+//!
+//! Languages pick the scenario: `en-us` is clean, `ja-jp` fails one list menu
+//! with HTTP 500, `ko-kr` gets a 404 for one detail page and `zh-tw` fails its
+//! navigation request with HTTP 500.
+
+mod common;
+
+use common::{MockApi, MockRequest, MockResponse};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::OnceLock;
+use tokio::sync::Mutex;
+use wiki_update::config;
+use wiki_update::core::{pipeline, processor, verify};
+use wiki_update::model::common::LangCode;
+
+const CHARACTER_MENU: i64 = 2;
+const WEAPON_MENU: i64 = 4;
+const CHARACTERS: [(i64, &str); 2] = [(1001, "Amber"), (1002, "Kaeya")];
+const WEAPONS: [(i64, &str); 1] = [(2001, "Dull Blade")];
+
+// Runs share process-wide state (stats, failed-id list), so they go one at a time.
+static RUN_LOCK: Mutex<()> = Mutex::const_new(());
+static MOCK: OnceLock<MockApi> = OnceLock::new();
+
+fn mock_api() -> &'static MockApi {
+    MOCK.get_or_init(|| {
+        let api = MockApi::start(route);
+        config::set_api_base_url(api.base_url()).unwrap();
+        api
+    })
+}
+
+fn route(req: &MockRequest) -> MockResponse {
+    match req.endpoint.as_str() {
+        "navigation" if req.lang == "zh-tw" => MockResponse::status(500),
+        "navigation" => MockResponse::ok(common::nav(&[
+            (CHARACTER_MENU, "Characters"),
+            (WEAPON_MENU, "Weapons"),
+        ])),
+        "get_entry_page_list" => {
+            let menu_id = req.body["menu_id"].as_i64().unwrap_or_default();
+            let page_num = req.body["page_num"].as_i64().unwrap_or(1);
+            if req.lang == "ja-jp" && menu_id == WEAPON_MENU {
+                return MockResponse::status(500);
+            }
+            let entries: &[(i64, &str)] = match (menu_id, page_num) {
+                (CHARACTER_MENU, 1) => &CHARACTERS,
+                (WEAPON_MENU, 1) => &WEAPONS,
+                _ => &[],
+            };
+            MockResponse::ok(common::list(entries))
+        }
+        "entry_page" => {
+            let id = req.query_i64("entry_page_id").unwrap_or_default();
+            if req.lang == "ko-kr" && id == 1002 {
+                return MockResponse::status(404);
+            }
+            let (menu_id, name) = entry(id);
+            MockResponse::ok(common::detail_page(id, name, menu_id))
+        }
+        "entry_pages" => {
+            let pages: Vec<(i64, &str)> = req
+                .query_ids("str_entry_page_ids")
+                .into_iter()
+                .map(|id| (id, entry(id).1))
+                .collect();
+            MockResponse::ok(common::bulk(&pages))
+        }
+        "calendar" => MockResponse::ok(json!({
+            "calendar": [{ "drop_day": [1, 4], "obtain_method": "Domain" }],
+            "op": []
+        })),
+        _ => MockResponse::status(404),
+    }
+}
+
+fn entry(id: i64) -> (i64, &'static str) {
+    let characters = CHARACTERS.iter().map(|entry| (CHARACTER_MENU, entry));
+    let weapons = WEAPONS.iter().map(|entry| (WEAPON_MENU, entry));
+    characters
+        .chain(weapons)
+        .find(|(_, (entry_id, _))| *entry_id == id)
+        .map_or((0, "Unknown"), |(menu_id, (_, name))| (menu_id, *name))
+}
+
+fn lang_codes(langs: &[&str]) -> Vec<LangCode> {
+    langs
+        .iter()
+        .map(|lang| LangCode::parse(lang).unwrap())
+        .collect()
+}
+
+async fn run_into(dir: &Path, langs: &[&str]) -> i32 {
+    mock_api();
+    processor::run(lang_codes(langs), dir.to_path_buf())
+        .await
+        .unwrap()
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+fn read_json(path: &Path) -> Value {
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+    serde_json::from_str(&text).unwrap()
+}
+
+#[tokio::test]
+async fn clean_run_writes_every_directory() {
+    let _guard = RUN_LOCK.lock().await;
+    let out = tempfile::tempdir().unwrap();
+    let exit_code = run_into(out.path(), &["en-us"]).await;
+    assert_eq!(exit_code, 0);
+
+    let out = out.path();
+    assert_eq!(file_names(&out.join("navigation")), ["en-us.json"]);
+    assert_eq!(
+        file_names(&out.join("list/en-us")),
+        ["2.json", "4.json", "index.json"]
+    );
+    assert_eq!(
+        file_names(&out.join("detail/en-us")),
+        ["1001.json", "1002.json", "2001.json", "index.json"]
+    );
+    assert_eq!(file_names(&out.join("bulk")), ["en-us.json"]);
+    assert_eq!(file_names(&out.join("calendar")), ["en-us.json"]);
+
+    let detail = read_json(&out.join("detail/en-us/1001.json"));
+    assert_eq!(detail["epId"], 1001);
+    assert_eq!(detail["name"], "Amber");
+    assert_eq!(detail["desc"], "Amber description");
+    assert_eq!(detail["menuId"], CHARACTER_MENU);
+    assert_eq!(detail["menuName"], "Characters");
+    assert_eq!(detail["iconUrl"], "https://example.test/icons/1001.png");
+    assert_eq!(detail["components"]["customize"][0]["type"], "RichText");
+
+    let history = read_json(&out.join("reports/run_report_history.json"));
+    assert_eq!(history["runs"].as_array().unwrap().len(), 1);
+    assert_eq!(history["runs"][0]["listItemsByLang"], json!({ "en-us": 3 }));
+
+    let manifest = read_json(&out.join("reports/list_manifest.json"));
+    assert_eq!(
+        manifest,
+        json!({
+            "en-us": {
+                "2": { "menuName": "Characters", "totalItems": 2 },
+                "4": { "menuName": "Weapons", "totalItems": 1 }
+            }
+        })
+    );
+
+    let report = verify::verify_output_dir(out).await.unwrap();
+    assert!(report.is_ok(), "{:?}", report.problems);
+}
+
+#[tokio::test]
+async fn list_menu_500_skips_its_details() {
+    let _guard = RUN_LOCK.lock().await;
+    let out = tempfile::tempdir().unwrap();
+    let exit_code = run_into(out.path(), &["ja-jp"]).await;
+    assert_eq!(exit_code, 0);
+
+    let out = out.path();
+    assert_eq!(
+        file_names(&out.join("list/ja-jp")),
+        ["2.json", "index.json"]
+    );
+    let manifest = read_json(&out.join("reports/list_manifest.json"));
+    assert!(manifest["ja-jp"].get("4").is_none());
+    assert_eq!(
+        file_names(&out.join("detail/ja-jp")),
+        ["1001.json", "1002.json", "index.json"]
+    );
+}
+
+#[tokio::test]
+async fn detail_404_is_skipped() {
+    let _guard = RUN_LOCK.lock().await;
+    let out = tempfile::tempdir().unwrap();
+    let exit_code = run_into(out.path(), &["ko-kr"]).await;
+    assert_eq!(exit_code, 0);
+
+    let out = out.path();
+    assert_eq!(
+        file_names(&out.join("detail/ko-kr")),
+        ["1001.json", "2001.json", "index.json"]
+    );
+    let index = read_json(&out.join("detail/ko-kr/index.json"));
+    let indexed: Vec<&Value> = index["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| &entry["epId"])
+        .collect();
+    assert_eq!(indexed, [1001, 2001]);
+}
+
+#[tokio::test]
+async fn navigation_500_fails_the_run() {
+    let _guard = RUN_LOCK.lock().await;
+    let out = tempfile::tempdir().unwrap();
+    let exit_code = run_into(out.path(), &["en-us", "zh-tw"]).await;
+    assert_eq!(exit_code, 1);
+
+    let out = out.path();
+    assert_eq!(file_names(&out.join("navigation")), ["en-us.json"]);
+    assert!(file_names(&out.join("list")).contains(&"en-us".to_string()));
+    assert!(!out.join("list/zh-tw").exists());
+}
+
+#[tokio::test]
+async fn pipeline_runs_the_consistency_gate() {
+    let _guard = RUN_LOCK.lock().await;
+    mock_api();
+    let out = tempfile::tempdir().unwrap();
+
+    // ja-jp loses its weapon list, which the gate reports as a missing menu.
+    config::set_abort_on_consistency_warning(true);
+    let exit_code = pipeline::run(lang_codes(&["ja-jp"]), out.path().to_path_buf()).await;
+    config::set_abort_on_consistency_warning(false);
+    assert_eq!(exit_code.unwrap(), 1);
+    assert!(!out.path().join("detail/ja-jp").exists());
+
+    let exit_code = pipeline::run(lang_codes(&["ja-jp"]), out.path().to_path_buf()).await;
+    assert_eq!(exit_code.unwrap(), 0);
+    assert_eq!(
+        file_names(&out.path().join("detail/ja-jp")),
+        ["1001.json", "1002.json", "index.json"]
+    );
+}
@@ -0,0 +1,89 @@
+//! `fetch_and_transform` against the mock API, without an output directory.
+
+mod common;
+
+use common::{MockApi, MockRequest, MockResponse};
+use serde_json::json;
+use wiki_update::config;
+use wiki_update::model::common::LangCode;
+use wiki_update::{fetch_and_transform, FetchOptions};
+
+const CHARACTER_MENU: i64 = 2;
+const CHARACTERS: [(i64, &str); 2] = [(1001, "Amber"), (1002, "Kaeya")];
+
+fn route(req: &MockRequest) -> MockResponse {
+    match req.endpoint.as_str() {
+        "navigation" => MockResponse::ok(common::nav(&[(CHARACTER_MENU, "Characters")])),
+        "get_entry_page_list" => {
+            let entries: &[(i64, &str)] = match req.body["page_num"].as_i64().unwrap_or(1) {
+                1 => &CHARACTERS,
+                _ => &[],
+            };
+            MockResponse::ok(common::list(entries))
+        }
+        "entry_page" => {
+            let id = req.query_i64("entry_page_id").unwrap_or_default();
+            MockResponse::ok(common::detail_page(id, name(id), CHARACTER_MENU))
+        }
+        "entry_pages" => {
+            let pages: Vec<(i64, &str)> = req
+                .query_ids("str_entry_page_ids")
+                .into_iter()
+                .map(|id| (id, name(id)))
+                .collect();
+            MockResponse::ok(common::bulk(&pages))
+        }
+        "calendar" => MockResponse::ok(json!({
+            "calendar": [{ "drop_day": [1, 4], "obtain_method": "Domain" }],
+            "op": []
+        })),
+        _ => MockResponse::status(404),
+    }
+}
+
+fn name(id: i64) -> &'static str {
+    CHARACTERS
+        .iter()
+        .find(|(entry_id, _)| *entry_id == id)
+        .map_or("Unknown", |(_, name)| name)
+}
+
+#[tokio::test]
+async fn returns_transformed_data_without_touching_the_filesystem() {
+    let api = MockApi::start(route);
+    config::set_api_base_url(api.base_url()).unwrap();
+    let workdir = tempfile::tempdir().unwrap();
+    std::env::set_current_dir(workdir.path()).unwrap();
+
+    let langs = vec![LangCode::parse("en-us").unwrap()];
+    let (data, stats) = fetch_and_transform(&langs, &FetchOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(data.navigation["en-us"].len(), 1);
+    assert_eq!(data.navigation["en-us"][0].menu_id, CHARACTER_MENU);
+
+    let lists = &data.lists["en-us"];
+    assert_eq!(lists.len(), 1);
+    assert_eq!(lists[0].menu_id, CHARACTER_MENU);
+    assert_eq!(lists[0].list.len(), 2);
+
+    let mut details: Vec<(i64, &str)> = data.details["en-us"]
+        .iter()
+        .map(|page| (page.id, page.name.as_deref().unwrap_or_default()))
+        .collect();
+    details.sort_unstable();
+    assert_eq!(details, CHARACTERS);
+
+    assert!(data.calendars.contains_key("en-us"));
+    assert_eq!(data.bulk["en-us"].len(), 2);
+    assert_eq!(stats["Detail Fetch"].ok, 2);
+    assert_eq!(stats["Detail Fetch"].fail, 0);
+    assert_eq!(stats["Save Files"].total_tasks, 0);
+
+    let report = data.run_report.expect("run report entry");
+    assert_eq!(report.list_items_by_lang["en-us"], 2);
+
+    let written: Vec<_> = std::fs::read_dir(workdir.path()).unwrap().collect();
+    assert!(written.is_empty(), "unexpected files: {:?}", written);
+}
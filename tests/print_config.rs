@@ -0,0 +1,77 @@
+//! `--print-config` through the real binary: flags show up in the JSON and the
+//! command exits before touching the network.
+
+mod common;
+
+use common::{MockApi, MockResponse};
+use serde_json::{json, Value};
+use std::process::Command;
+
+fn print_config(api: &MockApi, args: &[&str]) -> Value {
+    let out_dir = tempfile::tempdir().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_wiki_update"))
+        .arg("--print-config")
+        .args(["--api-base-url", &api.base_url()])
+        .args(["--out-dir", out_dir.path().to_str().unwrap()])
+        .args(args)
+        .output()
+        .expect("run wiki_update");
+    assert!(
+        output.status.success(),
+        "exit {:?}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        std::fs::read_dir(out_dir.path()).unwrap().next().is_none(),
+        "--print-config wrote into the output directory"
+    );
+    serde_json::from_slice(&output.stdout).expect("stdout is one JSON document")
+}
+
+#[test]
+fn flag_overrides_appear_in_the_effective_config() {
+    let api = MockApi::start(|_| MockResponse::status(500));
+    let config = print_config(
+        &api,
+        &[
+            "-l",
+            "en-us",
+            "ja-jp",
+            "--quick",
+            "--calendar",
+            "--page-size",
+            "20",
+            "--strict-language",
+            "--max-runtime",
+            "90",
+        ],
+    );
+
+    assert_eq!(config["apiBaseUrl"], api.base_url());
+    assert_eq!(config["languages"], json!(["en-us", "ja-jp"]));
+    assert_eq!(config["mode"], "staged");
+    assert_eq!(config["menuIds"], json!([2, 4]));
+    assert_eq!(config["concurrency"]["pageSize"], 20);
+    assert_eq!(config["network"]["maxRuntimeSecs"], 90);
+    assert_eq!(config["features"]["skipCalendar"], false);
+    assert_eq!(config["features"]["bulkFallback"], false);
+    assert_eq!(config["features"]["skipMergedSchema"], true);
+    assert_eq!(config["features"]["strictLanguage"], true);
+
+    assert!(api.requests().is_empty(), "{:?}", api.requests());
+}
+
+#[test]
+fn defaults_without_overrides() {
+    let api = MockApi::start(|_| MockResponse::status(500));
+    let config = print_config(&api, &["-l", "en-us"]);
+
+    assert_eq!(config["menuIds"], Value::Null);
+    assert_eq!(config["features"]["skipCalendar"], false);
+    assert_eq!(config["features"]["bulkFallback"], true);
+    assert_eq!(config["features"]["strictLanguage"], false);
+    assert_eq!(config["network"]["maxRuntimeSecs"], Value::Null);
+
+    assert!(api.requests().is_empty(), "{:?}", api.requests());
+}
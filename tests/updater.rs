@@ -0,0 +1,130 @@
+//! Two `WikiUpdater` runs in one process, each against its own mock API with
+//! its own settings.
+
+mod common;
+
+use common::{MockApi, MockRequest, MockResponse};
+use serde_json::json;
+use std::path::Path;
+use wiki_update::config::{self, RuntimeSettings};
+use wiki_update::model::common::LangCode;
+use wiki_update::WikiUpdater;
+
+const CHARACTER_MENU: i64 = 2;
+const WEAPON_MENU: i64 = 4;
+
+fn route(req: &MockRequest) -> MockResponse {
+    match req.endpoint.as_str() {
+        "navigation" => MockResponse::ok(common::nav(&[
+            (CHARACTER_MENU, "Characters"),
+            (WEAPON_MENU, "Weapons"),
+        ])),
+        "get_entry_page_list" => {
+            let entries: &[(i64, &str)] = match (
+                req.body["menu_id"].as_i64().unwrap_or_default(),
+                req.body["page_num"].as_i64().unwrap_or(1),
+            ) {
+                (CHARACTER_MENU, 1) => &[(1001, "Amber")],
+                (WEAPON_MENU, 1) => &[(2001, "Dull Blade")],
+                _ => &[],
+            };
+            MockResponse::ok(common::list(entries))
+        }
+        "entry_page" => {
+            let id = req.query_i64("entry_page_id").unwrap_or_default();
+            let menu_id = if id == 1001 {
+                CHARACTER_MENU
+            } else {
+                WEAPON_MENU
+            };
+            MockResponse::ok(common::detail_page(id, "Entry", menu_id))
+        }
+        "entry_pages" => {
+            let pages: Vec<(i64, &str)> = req
+                .query_ids("str_entry_page_ids")
+                .into_iter()
+                .map(|id| (id, "Entry"))
+                .collect();
+            MockResponse::ok(common::bulk(&pages))
+        }
+        "calendar" => MockResponse::ok(json!({
+            "calendar": [{ "drop_day": [1, 4], "obtain_method": "Domain" }],
+            "op": []
+        })),
+        _ => MockResponse::status(404),
+    }
+}
+
+fn list_files(out: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(out.join("list/en-us"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+fn requested_menus(api: &MockApi) -> Vec<i64> {
+    let mut menus: Vec<i64> = api
+        .requests()
+        .iter()
+        .filter(|req| req.endpoint == "get_entry_page_list")
+        .filter_map(|req| req.body["menu_id"].as_i64())
+        .collect();
+    menus.dedup();
+    menus
+}
+
+#[tokio::test]
+async fn consecutive_updaters_keep_their_own_settings() {
+    // Process-wide settings that must not leak into either updater. They can
+    // only be published once.
+    let published = RuntimeSettings {
+        menu_ids: Some(vec![99]),
+        ..RuntimeSettings::default()
+    };
+    published.apply().unwrap();
+    let changed = RuntimeSettings {
+        menu_ids: Some(vec![98]),
+        ..RuntimeSettings::default()
+    };
+    assert!(changed.apply().is_err());
+    config::set_api_base_url("http://127.0.0.1:1/".to_string()).unwrap();
+    assert!(config::set_api_base_url("http://127.0.0.1:2".to_string()).is_err());
+
+    let first_api = MockApi::start(route);
+    let first_out = tempfile::tempdir().unwrap();
+    WikiUpdater::new(first_out.path())
+        .languages(vec![LangCode::parse("en-us").unwrap()])
+        .settings(RuntimeSettings {
+            menu_ids: Some(vec![CHARACTER_MENU]),
+            skip_calendar: true,
+            ..RuntimeSettings::default()
+        })
+        .base_url(first_api.base_url())
+        .run()
+        .await
+        .unwrap();
+
+    let second_api = MockApi::start(route);
+    let second_out = tempfile::tempdir().unwrap();
+    WikiUpdater::new(second_out.path())
+        .languages(vec![LangCode::parse("en-us").unwrap()])
+        .settings(RuntimeSettings {
+            menu_ids: Some(vec![WEAPON_MENU]),
+            ..RuntimeSettings::default()
+        })
+        .base_url(second_api.base_url())
+        .run()
+        .await
+        .unwrap();
+
+    assert_eq!(list_files(first_out.path()), ["2.json", "index.json"]);
+    assert!(!first_out.path().join("calendar/en-us.json").exists());
+    assert_eq!(requested_menus(&first_api), [CHARACTER_MENU]);
+
+    assert_eq!(list_files(second_out.path()), ["4.json", "index.json"]);
+    assert!(second_out.path().join("calendar/en-us.json").exists());
+    assert_eq!(requested_menus(&second_api), [WEAPON_MENU]);
+    assert_eq!(RuntimeSettings::current(), published);
+}